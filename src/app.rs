@@ -1,19 +1,20 @@
 use entropy_engine::core::pipeline::ExportPipeline;
-use entropy_engine::core::editor::WindowSize;
+use entropy_engine::core::editor::{Editor, PointLight, DirectionalLight, WindowSize};
 use entropy_engine::helpers::load_project::place_project;
-use entropy_engine::helpers::saved_data::{ComponentData, SavedState, ComponentKind, CollectableType, GenericProperties, CollectableProperties, LightProperties, NPCProperties, AttackStats, CharacterStats};
+use entropy_engine::helpers::saved_data::{ComponentData, SavedState, ComponentKind, CollectableType, GenericProperties, CollectableProperties, LightProperties, NPCProperties, AttackStats, CharacterStats, PostProcessConfig, TonemapCurve, ColorGradeConfig, DayNightCycleConfig, WeatherConfig, CloudConfig, AmbientOcclusionConfig, FogConfig, ModelProperties, LandscapeData, TriggerProperties, TriggerShape, StatData, InstanceTransform, InstancedModelProperties, DirectionalLightProperties, AmbientLightConfig, GroupProperties, LandscapeProperties, LandscapeTextureBlend, ParticleProperties, AudioSourceProperties, PlayerSpawn, PlayerConfig, InventoryConfig, InventoryItem, ForestProperties, ProceduralTreeProperties};
 use entropy_engine::helpers::timelines::SavedTimelineStateConfig;
 use entropy_engine::game_behaviors::stateful::{BehaviorConfig, CombatType};
 use js_sys::Date;
 use leptos::html::Canvas;
 use leptos::task::spawn_local;
 use leptos::{prelude::*};
-use leptos_use::use_raf_fn;
+use leptos_use::{signal_debounced, use_preferred_dark, use_raf_fn, use_resize_observer};
 use leptos_use::utils::Pausable;
 use phosphor_leptos::{CHAT, CHATS, GAME_CONTROLLER, Icon, IconWeight, VIDEO};
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -21,12 +22,11 @@ use leptos::logging::log;
 use wasm_bindgen_futures::spawn_local as wasm_spawn_local;
 use entropy_engine::helpers::load_project::load_project;
 use leptos::web_sys;
-use entropy_engine::handlers::{EntropyPosition, handle_key_press, handle_mouse_move, handle_mouse_move_on_shift, handle_add_model, handle_add_collectable, handle_add_water_plane, handle_add_npc};
+use entropy_engine::handlers::{EntropyPosition, handle_key_press, handle_mouse_move, handle_mouse_move_on_shift, handle_add_model, handle_add_collectable, handle_add_water_plane, handle_add_npc, handle_set_model_material, handle_add_trigger_volume, handle_add_instanced_model, handle_add_audio_source, handle_set_player_spawn, handle_add_forest};
 use entropy_engine::water_plane::config::WaterConfig;
 use entropy_engine::procedural_grass::grass::GrassConfig;
 use entropy_engine::shape_primitives::{Cube::Cube, Sphere::Sphere};
-use entropy_engine::procedural_heightmaps::heightmap_generation::{HeightmapGenerator, TerrainFeature, FeatureType, FalloffType};
-use entropy_engine::helpers::landscapes::generate_landscape_data;
+use entropy_engine::procedural_heightmaps::heightmap_generation::{HeightmapGenerator, TerrainFeature, FeatureType};
 use std::time::{Duration, SystemTime};
 use gloo_net::http::Request;
 use web_sys::FormData;
@@ -34,6 +34,24 @@ use nalgebra::{Isometry3, Translation3, UnitQuaternion, Vector3};
 
 use crate::components::component_browser::ComponentPropertiesEditor;
 use crate::components::assets_browser::AssetsBrowser;
+use crate::components::chat_message::ChatMessageItem;
+use crate::components::confirm_dialog::{confirm_action, ConfirmDialog, ConfirmKind};
+use crate::storage;
+use crate::download::download_text;
+use crate::project_sync;
+use crate::offline_cache::{self, CachedProject};
+use crate::erosion::{erode, ErosionSettings};
+use crate::property_fields::*;
+use crate::terrain_edit::{self, HeightField};
+use crate::day_night;
+use crate::weather;
+use crate::scene_diff;
+use crate::forest;
+use crate::camera_move;
+use crate::orbit_camera;
+use crate::selection_bounds;
+use crate::light_gizmos;
+use crate::tool_result::ToolResult;
 
 pub fn get_api_url() -> String {
     let window = web_sys::window().unwrap();
@@ -47,20 +65,171 @@ pub fn get_api_url() -> String {
     }
 }
 
-async fn save_project(project_id: &str, saved_state: &SavedState) -> Result<(), String> {
+/// HTTP status the server answers with when `expectedVersion` no longer matches its copy.
+const SAVE_CONFLICT_STATUS: u16 = 409;
+
+/// Saves the project, one request at a time per project. While a save is in flight, this state waits
+/// for it and goes out next, replacing anything older that was waiting, and the call returns straight away.
+pub async fn save_project(project_id: &str, saved_state: &SavedState) -> Result<(), String> {
+    if !project_sync::queue_save(project_id, saved_state.clone()) {
+        return Ok(());
+    }
+
+    while let Some(saved_state) = project_sync::next_save(project_id) {
+        if let Err(e) = send_save(project_id, saved_state).await {
+            // Whatever was waiting stays in the offline cache, it gets flushed once we're back online
+            if let Some(saved_state) = project_sync::abandon_saves(project_id) {
+                cache_unsynced_save(project_id, saved_state).await;
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Caches a save that hasn't reached the server, based on the last version the server acknowledged.
+async fn cache_unsynced_save(project_id: &str, mut saved_state: SavedState) -> u64 {
+    let version = project_sync::acknowledged_version(project_id) + 1;
+    saved_state.version = Some(version);
+    let _ = offline_cache::put(&CachedProject {
+        project_id: project_id.to_string(),
+        saved_state,
+        version,
+        synced: false,
+    }).await;
+    version
+}
+
+async fn send_save(project_id: &str, mut saved_state: SavedState) -> Result<(), String> {
     let url = format!("{}/api/projects/{}", get_api_url(), project_id);
-    let body = serde_json::json!({ "savedData": saved_state });
+    let expected_version = project_sync::acknowledged_version(project_id);
+
+    // Cached first so the edit isn't lost if the request fails
+    let next_version = cache_unsynced_save(project_id, saved_state.clone()).await;
+    saved_state.version = Some(next_version);
+
+    let body = serde_json::json!({ "savedData": saved_state, "expectedVersion": expected_version });
     
-    Request::patch(&url)
+    let response = Request::patch(&url)
         .json(&body)
         .map_err(|e| e.to_string())?
         .send()
         .await
         .map_err(|e| e.to_string())?;
+
+    if response.status() == SAVE_CONFLICT_STATUS {
+        project_sync::report_conflict(project_id);
+        return Err("Project was changed elsewhere".to_string());
+    }
+    if !response.ok() {
+        return Err(format!("Save failed with status {}", response.status()));
+    }
+
+    project_sync::record_version(project_id, next_version);
+    offline_cache::mark_synced(project_id, next_version).await;
+    project_sync::record_base(project_id, &saved_state);
         
     Ok(())
 }
 
+/// Pushes cached edits that never reached the server, oldest base version first.
+async fn flush_unsynced_saves() {
+    let mut entries = offline_cache::unsynced().await;
+    entries.sort_by_key(|entry| entry.version);
+
+    for entry in entries {
+        log!("Flushing unsynced save for project {}", entry.project_id);
+        // The cached state was built on the version just before it
+        project_sync::record_version(&entry.project_id, entry.version.saturating_sub(1));
+        if let Err(e) = save_project(&entry.project_id, &entry.saved_state).await {
+            log!("Flushing project {} failed: {}", entry.project_id, e);
+        }
+    }
+}
+
+/// HTTP status the server answers with when a project with the same name already exists.
+const PROJECT_NAME_TAKEN_STATUS: u16 = 409;
+
+/// Saved data for a brand new project, a single level with nothing in it.
+fn empty_saved_state() -> SavedState {
+    let mut saved_state = SavedState::default();
+    saved_state.levels = Some(vec![Default::default()]);
+    if let Some(level) = saved_state.levels.as_mut().and_then(|levels| levels.get_mut(0)) {
+        level.components = Some(Vec::new());
+    }
+    saved_state
+}
+
+async fn create_project(name: &str) -> Result<ProjectInfo, String> {
+    let body = serde_json::json!({ "name": name, "savedData": empty_saved_state() });
+    let response = Request::post(&format!("{}/api/projects", get_api_url()))
+        .json(&body)
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status() == PROJECT_NAME_TAKEN_STATUS {
+        return Err(format!("A project named \"{}\" already exists", name));
+    }
+    if !response.ok() {
+        return Err(format!("Couldn't create the project (status {})", response.status()));
+    }
+
+    response.json::<ProjectInfo>().await.map_err(|e| e.to_string())
+}
+
+/// Deletes `message_id` and every message after it in the session.
+async fn delete_messages_from(session_id: &str, message_id: &str) -> Result<(), String> {
+    let url = format!("{}/api/sessions/{}/messages/{}", get_api_url(), session_id, message_id);
+    let response = Request::delete(&url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.ok() {
+        Ok(())
+    } else {
+        Err(format!("Delete failed with status {}", response.status()))
+    }
+}
+
+/// Saves a session's title, shown in the session list.
+async fn save_session_title(session_id: &str, title: &str) -> Result<(), String> {
+    let url = format!("{}/api/sessions/{}", get_api_url(), session_id);
+    let response = Request::patch(&url)
+        .json(&serde_json::json!({ "title": title }))
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.ok() {
+        Ok(())
+    } else {
+        Err(format!("Saving the title failed with status {}", response.status()))
+    }
+}
+
+/// Fetches the server copy of a project and records its version as the new base for saves.
+async fn fetch_project(project_id: &str) -> Result<Project, String> {
+    let project: Project = Request::get(&format!("{}/api/projects/{}", get_api_url(), project_id))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let version = project.saved_data.as_ref().and_then(|saved_state| saved_state.version).unwrap_or(0);
+    project_sync::record_version(&project.id, version);
+    if let Some(saved_state) = project.saved_data.as_ref() {
+        project_sync::record_base(&project.id, saved_state);
+    }
+
+    Ok(project)
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Project {
     pub id: String,
@@ -77,6 +246,37 @@ pub struct ProjectInfo {
     pub id: String,
     pub name: String,
     pub path: String,
+    // Optional so older servers that don't send them still parse
+    #[serde(default, rename = "createdAt")]
+    pub created_at: Option<String>,
+    #[serde(default, rename = "updatedAt")]
+    pub updated_at: Option<String>,
+    #[serde(default, alias = "type")]
+    pub kind: Option<String>,
+}
+
+impl ProjectInfo {
+    /// Milliseconds since the epoch of the last update (or creation), if the server sent a parseable date.
+    fn last_activity_ms(&self) -> Option<f64> {
+        self.updated_at.as_ref()
+            .or(self.created_at.as_ref())
+            .map(|date| Date::parse(date))
+            .filter(|ms| !ms.is_nan())
+    }
+}
+
+/// "just now", "5 minutes ago", "2 days ago", ... for a past timestamp.
+fn relative_time(then_ms: f64, now_ms: f64) -> String {
+    let seconds = ((now_ms - then_ms) / 1000.0).max(0.0) as u64;
+    let (value, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86_399 => (seconds / 3600, "hour"),
+        86_400..=2_591_999 => (seconds / 86_400, "day"),
+        2_592_000..=31_535_999 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    format!("{} {}{} ago", value, unit, if value == 1 { "" } else { "s" })
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -84,6 +284,9 @@ pub struct ChatSession {
     pub id: String,
     #[serde(rename = "projectId")]
     pub project_id: String,
+    // Set after the first exchange, or by the user renaming the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -97,6 +300,27 @@ pub struct ChatMessage {
     pub tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
+    // Only reported by some backends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+/// Tokens billed for one response. Accepts both camelCase and the snake_case most providers use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    #[serde(default, alias = "prompt_tokens")]
+    pub prompt_tokens: u32,
+    #[serde(default, alias = "completion_tokens")]
+    pub completion_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u32 {
+        self.prompt_tokens + self.completion_tokens
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -118,1534 +342,7418 @@ pub struct OpenChatResponse {
     project: Project, session: ChatSession
 }
 
-async fn execute_tool_call(
-    tool_call: &ToolCall,
-    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
-    project_id: String,
-    selected_project: ReadSignal<Option<Project>>,
-) -> String {
-    log!("Executing tool call: {:?}", tool_call.function.name);
+/// What a file dropped onto the chat should be stored as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DroppedAssetKind {
+    Model,
+    Texture,
+    Heightmap,
+}
 
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct TransformObjectArgs {
-        component_id: String,
-        translation: Option<[f32; 3]>,
-        rotation: Option<[f32; 3]>,
-        scale: Option<[f32; 3]>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    // #[serde(rename_all = "camelCase")]
-    struct ConfigureWaterArgs {
-        #[serde(rename = "componentId")]
-        component_id: Option<String>,
-        shallow_color: Option<[f32; 3]>,
-        medium_color: Option<[f32; 3]>,
-        deep_color: Option<[f32; 3]>,
-        ripple_amplitude_multiplier: Option<f32>,
-        ripple_freq: Option<f32>,
-        ripple_speed: Option<f32>,
-        shoreline_foam_range: Option<f32>,
-        crest_foam_min: Option<f32>,
-        crest_foam_max: Option<f32>,
-        sparkle_intensity: Option<f32>,
-        sparkle_threshold: Option<f32>,
-        subsurface_multiplier: Option<f32>,
-        fresnel_power: Option<f32>,
-        fresnel_multiplier: Option<f32>,
-
-        // Wave 1 - primary wave
-        pub wave1_amplitude: Option<f32>,
-        pub wave1_frequency: Option<f32>,
-        pub wave1_speed: Option<f32>,
-        pub wave1_steepness: Option<f32>,
-        pub wave1_direction: Option<[f32; 2]>,
-
-        // Wave 2 - secondary wave
-        pub wave2_amplitude: Option<f32>,
-        pub wave2_frequency: Option<f32>,
-        pub wave2_speed: Option<f32>,
-        pub wave2_steepness: Option<f32>,
-        pub wave2_direction: Option<[f32; 2]>,
-
-        // Wave 3 - tertiary wave
-        pub wave3_amplitude: Option<f32>,
-        pub wave3_frequency: Option<f32>,
-        pub wave3_speed: Option<f32>,
-        pub wave3_steepness: Option<f32>,
-        pub wave3_direction: Option<[f32; 2]>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct ConfigureGrassArgs {
-        #[serde(rename = "componentId")]
-        component_id: Option<String>,
-        wind_strength: Option<f32>,
-        wind_speed: Option<f32>,
-        blade_height: Option<f32>,
-        blade_width: Option<f32>,
-        blade_density: Option<f32>, // Changing to f32 to match tool definition, will cast to u32
-        render_distance: Option<f32>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct SpawnPrimitiveArgs {
-        r#type: String,
-        position: [f32; 3],
-        scale: Option<[f32; 3]>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct ConfigureSkyArgs {
-        #[serde(rename = "componentId")]
-        component_id: Option<String>,
-        horizon_color: Option<[f32; 3]>,
-        zenith_color: Option<[f32; 3]>,
-        sun_direction: Option<[f32; 3]>,
-        sun_color: Option<[f32; 3]>,
-        sun_intensity: Option<f32>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct ConfigureTreesArgs {
-        #[serde(rename = "componentId")]
-        component_id: Option<String>,
-        seed: Option<u32>,
-        trunk_height: Option<f32>,
-        trunk_radius: Option<f32>,
-        branch_levels: Option<u32>,
-        foliage_radius: Option<f32>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct SpawnModelArgs {
-        #[serde(rename = "assetId")]
-        asset_id: String,
-        position: Option<[f32; 3]>,
-        rotation: Option<[f32; 3]>,
-        scale: Option<[f32; 3]>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct SpawnPointLightArgs {
-        position: [f32; 3],
-        color: Option<[f32; 3]>,
-        intensity: Option<f32>,
-        radius: Option<f32>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct SpawnCollectableArgs {
-        #[serde(rename = "assetId")]
-        asset_id: String,
-        r#type: String, // "Item", "MeleeWeapon", etc.
-        position: Option<[f32; 3]>,
-        rotation: Option<[f32; 3]>,
-        scale: Option<[f32; 3]>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct SpawnNPCArgs {
-        #[serde(rename = "assetId")]
-        asset_id: String,
-        position: Option<[f32; 3]>,
-        rotation: Option<[f32; 3]>,
-        scale: Option<[f32; 3]>,
-        aggressiveness: Option<f32>,
-        combat_type: Option<String>,
-        wander_radius: Option<f32>,
-        wander_speed: Option<f32>,
-        detection_radius: Option<f32>,
-        damage: Option<f32>,
-        health: Option<f32>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct SaveScriptArgs {
-        filename: String,
-        content: String,
-        componentId: Option<String>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct TerrainFeatureArgs {
-        r#type: String,
-        center: [f64; 2],
-        radius: f64,
-        intensity: f64,
-        falloff: String,
-        flat_top: Option<f64>,
-        transition: Option<f64>,
-    }
-
-    #[derive(Debug, Clone, Serialize, Deserialize)]
-    struct GenerateHeightmapArgs {
-        #[serde(rename = "componentId")]
-        component_id: Option<String>,
-        seed: Option<u32>,
-        scale: Option<f64>,
-        persistence: Option<f64>,
-        lacunarity: Option<f64>,
-        features: Option<Vec<TerrainFeatureArgs>>,
+impl DroppedAssetKind {
+    fn from_value(value: &str) -> Self {
+        match value {
+            "texture" => DroppedAssetKind::Texture,
+            "heightmap" => DroppedAssetKind::Heightmap,
+            _ => DroppedAssetKind::Model,
+        }
     }
 
-    let mut saved_state_clone = None;
-
-    if tool_call.function.name == "transformObject" {
-        let args: Result<TransformObjectArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        // Update SavedState
-                        if let Some(saved_state) = editor.saved_state.as_mut() {
-                            if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                if let Some(components) = level.components.as_mut() {
-                                    if let Some(component) = components.iter_mut().find(|c| c.id == args.component_id) {
-                                        if let Some(translation) = args.translation {
-                                            component.generic_properties.position = translation;
-                                        }
-                                        if let Some(rotation) = args.rotation {
-                                            component.generic_properties.rotation = rotation;
-                                        }
-                                        if let Some(scale) = args.scale {
-                                            component.generic_properties.scale = scale;
-                                        }
-                                    }
-                                }
-                            }
-                            saved_state_clone = Some(saved_state.clone());
-                        }
-
-                        // Update RendererState
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                            if let Some(model) = renderer_state.models.iter_mut().find(|m| m.id == args.component_id) {
-                                for mesh in model.meshes.iter_mut() {
-                                    if let Some(translation) = args.translation {
-                                        mesh.transform.update_position(translation);
-                                    }
-                                    if let Some(rotation) = args.rotation {
-                                        mesh.transform.update_rotation(rotation);
-                                    }
-                                    if let Some(scale) = args.scale {
-                                        mesh.transform.update_scale(scale);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    fn label(&self) -> &'static str {
+        match self {
+            DroppedAssetKind::Model => "model",
+            DroppedAssetKind::Texture => "texture",
+            DroppedAssetKind::Heightmap => "heightmap",
         }
-    } else if tool_call.function.name == "configureWater" {
-        log!("Configuring water plane...");
-        let args: Result<ConfigureWaterArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                            
-                            // Check if we have any water planes
-                            if renderer_state.water_planes.is_empty() {
-                                // Try to create one if we have a landscape
-                                if let Some(landscape) = renderer_state.landscapes.first() {
-                                     let landscape_id = landscape.id.clone();
-                                     let device = &editor.gpu_resources.as_ref().unwrap().device;
-                                     let camera_binding = editor.camera_binding.as_ref().unwrap(); 
-                                     let surface_format = wgpu::TextureFormat::Rgba8Unorm; // Matching ProjectCanvas
-                                     
-                                     handle_add_water_plane(
-                                        renderer_state, 
-                                        device, 
-                                        &camera_binding.bind_group_layout, 
-                                        surface_format, 
-                                        landscape_id.clone(), 
-                                        Some(WaterConfig::default()), 
-                                        Some(landscape_id.clone())
-                                    );
-                                     log!("Water plane created for landscape {}", landscape_id);
-                                }
-                            }
+    }
 
-                            // Now configure the first water plane (assuming single water plane support for now)
-                            if let Some(water_plane) = renderer_state.water_planes.get_mut(0) {
-                                let mut current_config = water_plane.config; // Get current config
+    fn accepts(&self, file_name: &str) -> bool {
+        let extension = file_name.rsplit('.').next().unwrap_or_default().to_lowercase();
+        match self {
+            DroppedAssetKind::Model => matches!(extension.as_str(), "glb" | "gltf"),
+            DroppedAssetKind::Texture | DroppedAssetKind::Heightmap => matches!(extension.as_str(), "png" | "jpg" | "jpeg"),
+        }
+    }
+}
 
-                                log!("Configuring water plane still... {:?}", args);
+/// Uploads a file dropped onto the chat through the matching asset endpoint and records it in `SavedState`.
+/// Returns a note for the transcript describing what was added.
+async fn add_dropped_asset(
+    kind: DroppedAssetKind,
+    file: web_sys::File,
+    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
+    selected_project: ReadSignal<Option<Project>>,
+) -> String {
+    let file_name = file.name();
+    if !kind.accepts(&file_name) {
+        return format!("Couldn't add {}: unsupported file type for a {}.", file_name, kind.label());
+    }
 
-                                if let Some(color) = args.shallow_color {
-                                    current_config.shallow_color = [color[0], color[1], color[2], 1.0];
-                                }
-                                if let Some(color) = args.medium_color {
-                                    current_config.medium_color = [color[0], color[1], color[2], 1.0];
-                                }
-                                if let Some(color) = args.deep_color {
-                                    current_config.deep_color = [color[0], color[1], color[2], 1.0];
-                                }
-                                if let Some(val) = args.ripple_amplitude_multiplier {
-                                    current_config.ripple_amplitude_multiplier = val;
-                                }
-                                if let Some(val) = args.ripple_freq {
-                                    current_config.ripple_freq = val;
-                                }
-                                if let Some(val) = args.ripple_speed {
-                                    current_config.ripple_speed = val;
-                                }
-                                if let Some(val) = args.shoreline_foam_range {
-                                    current_config.shoreline_foam_range = val;
-                                }
-                                if let Some(val) = args.crest_foam_min {
-                                    current_config.crest_foam_min = val;
-                                }
-                                if let Some(val) = args.crest_foam_max {
-                                    current_config.crest_foam_max = val;
-                                }
-                                if let Some(val) = args.sparkle_intensity {
-                                    current_config.sparkle_intensity = val;
-                                }
-                                if let Some(val) = args.sparkle_threshold {
-                                    current_config.sparkle_threshold = val;
-                                }
-                                if let Some(val) = args.subsurface_multiplier {
-                                    current_config.subsurface_multiplier = val;
-                                }
-                                if let Some(val) = args.fresnel_power {
-                                    current_config.fresnel_power = val;
-                                }
-                                if let Some(val) = args.fresnel_multiplier {
-                                    current_config.fresnel_multiplier = val;
-                                }
+    let project = match selected_project.get_untracked() {
+        Some(project) => project,
+        None => return format!("Couldn't add {}: no project is open.", file_name),
+    };
 
-                                if let Some(val) = args.wave1_amplitude {
-                                    current_config.wave1_amplitude = val;
-                                }
-                                if let Some(val) = args.wave1_frequency {
-                                    current_config.wave1_frequency = val;
-                                }
-                                if let Some(val) = args.wave1_speed {
-                                    current_config.wave1_speed = val;
-                                }
-                                if let Some(val) = args.wave1_steepness {
-                                    current_config.wave1_steepness = val;
-                                }
-                                if let Some(val) = args.wave1_direction {
-                                    current_config.wave1_direction = val;
-                                }
-                                
-                                if let Some(val) = args.wave2_amplitude {
-                                    current_config.wave2_amplitude = val;
-                                }
-                                if let Some(val) = args.wave2_frequency {
-                                    current_config.wave2_frequency = val;
-                                }
-                                if let Some(val) = args.wave2_speed {
-                                    current_config.wave2_speed = val;
-                                }
-                                if let Some(val) = args.wave2_steepness {
-                                    current_config.wave2_steepness = val;
-                                }
-                                if let Some(val) = args.wave2_direction {
-                                    current_config.wave2_direction = val;
-                                }
+    let asset_id = Uuid::new_v4().to_string();
+    let form_data = FormData::new().unwrap();
+    form_data.append_with_str("projectPath", &project.path).unwrap();
+    form_data.append_with_str("filename", &file_name).unwrap();
+    let endpoint = match kind {
+        DroppedAssetKind::Model => "upload-model",
+        DroppedAssetKind::Texture => "upload-texture",
+        DroppedAssetKind::Heightmap => {
+            form_data.append_with_str("landscapeAssetId", &asset_id).unwrap();
+            form_data.append_with_str("type", "heightmap").unwrap();
+            "upload-landscape-map"
+        }
+    };
+    form_data.append_with_blob("file", &file).unwrap();
 
-                                if let Some(val) = args.wave3_amplitude {
-                                    current_config.wave3_amplitude = val;
-                                }
-                                if let Some(val) = args.wave3_frequency {
-                                    current_config.wave3_frequency = val;
-                                }
-                                if let Some(val) = args.wave3_speed {
-                                    current_config.wave3_speed = val;
-                                }
-                                if let Some(val) = args.wave3_steepness {
-                                    current_config.wave3_steepness = val;
-                                }
-                                if let Some(val) = args.wave3_direction {
-                                    current_config.wave3_direction = val;
-                                }
+    let url = format!("{}/api/{}", get_api_url(), endpoint);
+    let res = Request::post(&url)
+        .body(form_data)
+        .expect("Couldn't make post body")
+        .send()
+        .await;
 
-                                // water_plane.config = current_config;
-                                water_plane.update_config(&editor.gpu_resources.as_ref().expect("Couldn't get gpu resources").queue, current_config);
+    if !matches!(&res, Ok(resp) if resp.ok()) {
+        return format!("Upload of {} failed.", file_name);
+    }
 
-                                log!("Water plane configured {:?}", water_plane.config);
+    let new_file = entropy_engine::helpers::saved_data::File {
+        id: asset_id.clone(),
+        fileName: file_name.clone(),
+        cloudfrontUrl: "".to_string(), // Local only for now
+        normalFilePath: "".to_string(),
+    };
 
-                                if let Some(saved_state) = editor.saved_state.as_mut() {
-                                    saved_state_clone = Some(saved_state.clone());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    } else if tool_call.function.name == "configureSky" {
-        log!("Configuring sky...");
-        let args: Result<ConfigureSkyArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        if let Some(saved_state) = editor.saved_state.as_mut() {
-                            if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                if level.procedural_sky.is_none() {
-                                    level.procedural_sky = Some(entropy_engine::helpers::saved_data::ProceduralSkyConfig::default());
-                                }
-                                if let Some(sky) = level.procedural_sky.as_mut() {
-                                    if let Some(color) = args.horizon_color { sky.horizon_color = color; }
-                                    if let Some(color) = args.zenith_color { sky.zenith_color = color; }
-                                    if let Some(dir) = args.sun_direction { sky.sun_direction = dir; }
-                                    if let Some(color) = args.sun_color { sky.sun_color = color; }
-                                    if let Some(intensity) = args.sun_intensity { sky.sun_intensity = intensity; }
-                                }
-                            }
-                            saved_state_clone = Some(saved_state.clone());
+    // Update SavedState
+    let mut saved_state_clone = None;
+    if let Some(pipeline_arc_val) = pipeline_store.get_untracked() {
+        if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
+            let mut pipeline = pipeline_arc.borrow_mut();
+            if let Some(editor) = pipeline.export_editor.as_mut() {
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    match kind {
+                        DroppedAssetKind::Model => saved_state.models.push(new_file),
+                        DroppedAssetKind::Texture => saved_state.textures.get_or_insert_with(Vec::new).push(new_file),
+                        DroppedAssetKind::Heightmap => {
+                            saved_state.landscapes.get_or_insert_with(Vec::new).push(LandscapeData {
+                                id: asset_id.clone(),
+                                heightmap: Some(entropy_engine::helpers::saved_data::File {
+                                    id: Uuid::new_v4().to_string(),
+                                    ..new_file
+                                }),
+                                rockmap: None,
+                                soil: None,
+                            });
                         }
                     }
+                    saved_state_clone = Some(saved_state.clone());
                 }
             }
         }
-    } else if tool_call.function.name == "configureTrees" {
-        log!("Configuring trees...");
-        let args: Result<ConfigureTreesArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        let mut new_tree_props = None;
-                        
-                        // Update SavedState
-                        if let Some(saved_state) = editor.saved_state.as_mut() {
-                            if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                if let Some(components) = level.components.as_mut() {
-                                    
-                                    let mut found = false;
-                                    for component in components.iter_mut() {
-                                        if component.kind == Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralTree) {
-                                            if let Some(target_id) = &args.component_id {
-                                                if &component.id != target_id {
-                                                    continue;
-                                                }
-                                            }
-                                            
-                                            if component.procedural_tree_properties.is_none() {
-                                                component.procedural_tree_properties = Some(entropy_engine::helpers::saved_data::ProceduralTreeProperties::default());
-                                            }
-                                            if let Some(props) = component.procedural_tree_properties.as_mut() {
-                                                if let Some(val) = args.seed { props.seed = val; }
-                                                if let Some(val) = args.trunk_height { props.trunk_height = val; }
-                                                if let Some(val) = args.trunk_radius { props.trunk_radius = val; }
-                                                if let Some(val) = args.branch_levels { props.branch_levels = val; }
-                                                if let Some(val) = args.foliage_radius { props.foliage_radius = val; }
-                                                new_tree_props = Some(props.clone());
-                                            }
-                                            found = true;
-                                            break; 
-                                        }
-                                    }
-                                    
-                                    if !found && args.component_id.is_none() {
-                                        let props = entropy_engine::helpers::saved_data::ProceduralTreeProperties {
-                                            seed: args.seed.unwrap_or(0),
-                                            trunk_height: args.trunk_height.unwrap_or(3.5),
-                                            trunk_radius: args.trunk_radius.unwrap_or(0.25),
-                                            branch_levels: args.branch_levels.unwrap_or(4),
-                                            foliage_radius: args.foliage_radius.unwrap_or(0.5),
-                                        };
-                                        
-                                        let new_component = ComponentData {
-                                            id: Uuid::new_v4().to_string(),
-                                            kind: Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralTree),
-                                            asset_id: "".to_string(),
-                                            procedural_tree_properties: Some(props.clone()),
-                                            ..Default::default()
-                                        };
-                                        components.push(new_component);
-                                        new_tree_props = Some(props);
-                                        log!("Created new tree component in saved state.");
-                                    }
-                                }
-                            }
-                            saved_state_clone = Some(saved_state.clone());
-                        }
+    }
 
-                        // Update RendererState (live update)
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                            if let Some(new_props) = new_tree_props {
-                                // For now, update ALL trees since we don't have ID mapping easily accessible in renderer_state yet
-                                // Or assume single tree system per level
-                                for trees in &mut renderer_state.procedural_trees {
-                                    let device = &editor.gpu_resources.as_ref().unwrap().device;
-                                    trees.regenerate(device, new_props.clone());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    if let Some(saved_state) = saved_state_clone {
+        let _ = save_project(&project.id, &saved_state).await;
+    }
+
+    format!("Added {} {} (asset id {}).", kind.label(), file_name, asset_id)
+}
+
+/// Appends the local-only messages that the server copy doesn't already contain, keeping remote order.
+fn merge_messages(mut remote: Vec<ChatMessage>, local: &[ChatMessage]) -> Vec<ChatMessage> {
+    for message in local {
+        if !remote.iter().any(|m| m.id == message.id) {
+            remote.push(message.clone());
         }
-    } else if tool_call.function.name == "spawnModel" {
-        log!("Spawning model...");
-        let args: Result<SpawnModelArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        // let project_id = editor.project_id.clone();
-                        let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
-                        let mut asset_file_name = String::new();
-                        
-                        // Find asset filename in SavedState
-                        if let Some(saved_state) = editor.saved_state.as_ref() {
-                            if let Some(model) = saved_state.models.iter().find(|m| m.id == args.asset_id) {
-                                asset_file_name = model.fileName.clone();
-                            }
-                        }
+    }
+    remote
+}
 
-                        if !asset_file_name.is_empty() {
-                            let component_id = Uuid::new_v4().to_string();
-                            let pos = args.position.unwrap_or([0.0, 0.0, 0.0]);
-                            let rot = args.rotation.unwrap_or([0.0, 0.0, 0.0]);
-                            let scale = args.scale.unwrap_or([1.0, 1.0, 1.0]);
+/// Renders the conversation as a Markdown build log. Tool calls and their results are folded
+/// into `<details>` blocks so the log reads as the back-and-forth.
+fn chat_to_markdown(project_name: &str, messages: &[ChatMessage]) -> String {
+    let mut markdown = format!("# Chat with {}\n\n", project_name);
 
-                            let model_position = Translation3::new(pos[0], pos[1], pos[2]);
-                            let model_rotation = UnitQuaternion::from_euler_angles(
-                                rot[0].to_radians(), rot[1].to_radians(), rot[2].to_radians()
-                            );
-                            let model_iso = Isometry3::from_parts(model_position, model_rotation);
-                            let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
+    for message in messages {
+        // Tool results are shown with the call that produced them
+        if message.role == "tool" {
+            continue;
+        }
 
-                            let renderer_state = editor.renderer_state.as_mut().unwrap();
-                            let gpu_resources = editor.gpu_resources.as_ref().unwrap();
-                            let camera = editor.camera.as_ref().unwrap();
-
-                            handle_add_model(
-                                renderer_state,
-                                &gpu_resources.device,
-                                &gpu_resources.queue,
-                                project_id,
-                                args.asset_id.clone(),
-                                component_id.clone(),
-                                asset_file_name,
-                                model_iso,
-                                model_scale,
-                                camera,
-                                None // Script state
-                            ).await;
-
-                            // Update SavedState
-                            if let Some(saved_state) = editor.saved_state.as_mut() {
-                                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                    let new_component = ComponentData {
-                                        id: component_id,
-                                        kind: Some(ComponentKind::Model),
-                                        asset_id: args.asset_id,
-                                        generic_properties: GenericProperties {
-                                            name: "New Model".to_string(),
-                                            position: pos,
-                                            rotation: rot,
-                                            scale: scale,
-                                        },
-                                        ..Default::default()
-                                    };
-                                    
-                                    if let Some(components) = level.components.as_mut() {
-                                        components.push(new_component);
-                                    } else {
-                                        level.components = Some(vec![new_component]);
-                                    }
-                                }
-                                saved_state_clone = Some(saved_state.clone());
-                            }
-                        } else {
-                            log!("Asset not found: {}", args.asset_id);
-                        }
-                    }
-                }
-            }
+        markdown.push_str(&format!("## {}", message.role));
+        if let Some(created_at) = message.created_at.as_ref() {
+            markdown.push_str(&format!(" · {}", created_at));
         }
-    } else if tool_call.function.name == "spawnPointLight" {
-        log!("Spawning point light...");
-        let args: Result<SpawnPointLightArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        let component_id = Uuid::new_v4().to_string();
-                        let color = args.color.unwrap_or([1.0, 1.0, 1.0]);
-                        let intensity = args.intensity.unwrap_or(1.0);
-                        let radius = args.radius.unwrap_or(10.0);
+        markdown.push_str("\n\n");
 
-                        // Update RendererState
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                            renderer_state.point_lights.push(entropy_engine::core::editor::PointLight {
-                                position: args.position,
-                                _padding1: 0,
-                                color,
-                                _padding2: 0,
-                                intensity,
-                                max_distance: radius, // Using radius as max_distance
-                                _padding3: [0; 2],
-                            });
-                        }
+        if let Some(content) = message.content.as_ref().filter(|c| !c.trim().is_empty()) {
+            markdown.push_str(content.trim());
+            markdown.push_str("\n\n");
+        }
 
-                        // Update SavedState
-                        if let Some(saved_state) = editor.saved_state.as_mut() {
-                            if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                let new_component = ComponentData {
-                                    id: component_id,
-                                    kind: Some(ComponentKind::PointLight),
-                                    asset_id: "".to_string(),
-                                    generic_properties: GenericProperties {
-                                        name: "New Light".to_string(),
-                                        position: args.position,
-                                        ..Default::default()
-                                    },
-                                    light_properties: Some(LightProperties {
-                                        color: [color[0], color[1], color[2], 1.0],
-                                        intensity,
-                                    }),
-                                    ..Default::default()
-                                };
-                                
-                                if let Some(components) = level.components.as_mut() {
-                                    components.push(new_component);
-                                } else {
-                                    level.components = Some(vec![new_component]);
-                                }
-                            }
-                            saved_state_clone = Some(saved_state.clone());
-                        }
-                    }
+        if let Some(tool_calls) = message.tool_calls.as_ref().filter(|calls| !calls.is_empty()) {
+            markdown.push_str(&format!("<details>\n<summary>{} tool call(s)</summary>\n\n", tool_calls.len()));
+            for tool_call in tool_calls {
+                markdown.push_str(&format!("- `{}` `{}`\n", tool_call.function.name, tool_call.function.arguments));
+                let result = messages.iter()
+                    .find(|m| m.role == "tool" && m.tool_call_id.as_deref() == Some(tool_call.id.as_str()))
+                    .and_then(|m| m.content.as_ref());
+                if let Some(result) = result {
+                    markdown.push_str(&format!("  - Result: {}\n", result.trim()));
                 }
             }
+            markdown.push_str("\n</details>\n\n");
         }
-    } else if tool_call.function.name == "spawnCollectable" {
-        log!("Spawning collectable...");
-        let args: Result<SpawnCollectableArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        // let project_id = editor.project_id.clone();
-                        let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
-                        let mut asset_file_name = String::new();
-                        let mut stat_data = None;
-
-                        // Find asset and default stat in SavedState
-                        if let Some(saved_state) = editor.saved_state.as_ref() {
-                            if let Some(model) = saved_state.models.iter().find(|m| m.id == args.asset_id) {
-                                asset_file_name = model.fileName.clone();
-                            }
-                            if let Some(stats) = &saved_state.stats {
-                                if !stats.is_empty() {
-                                    stat_data = Some(stats[0].clone()); // Pick first stat for now
-                                }
-                            }
-                        }
+    }
 
-                        if !asset_file_name.is_empty() && stat_data.is_some() {
-                            let component_id = Uuid::new_v4().to_string();
-                            let pos = args.position.unwrap_or([0.0, 0.0, 0.0]);
-                            let rot = args.rotation.unwrap_or([0.0, 0.0, 0.0]);
-                            let scale = args.scale.unwrap_or([1.0, 1.0, 1.0]);
-
-                            let model_position = Translation3::new(pos[0], pos[1], pos[2]);
-                            let model_rotation = UnitQuaternion::from_euler_angles(
-                                rot[0].to_radians(), rot[1].to_radians(), rot[2].to_radians()
-                            );
-                            let model_iso = Isometry3::from_parts(model_position, model_rotation);
-                            let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
-
-                            let collectable_type = match args.r#type.as_str() {
-                                "MeleeWeapon" => CollectableType::MeleeWeapon,
-                                "RangedWeapon" => CollectableType::RangedWeapon,
-                                "Armor" => CollectableType::Armor,
-                                _ => CollectableType::Item,
-                            };
+    markdown
+}
 
-                            let related_stat = stat_data.unwrap(); // Verified safe above
+/// Builds a `PointLight` for the GPU light buffer, keeping its layout padding out of tool code.
+fn new_point_light(id: String, position: [f32; 3], color: [f32; 3], intensity: f32, max_distance: f32) -> PointLight {
+    PointLight {
+        id,
+        position,
+        _padding1: 0,
+        color,
+        _padding2: 0,
+        intensity,
+        max_distance,
+        _padding3: [0; 2],
+    }
+}
 
-                            let collectable_properties = CollectableProperties {
-                                model_id: Some(component_id.clone()), // Use same ID for model part
-                                collectable_type: Some(collectable_type.clone()),
-                                stat_id: Some(related_stat.id.clone()),
-                            };
+/// Removes a point light component's renderer light, leaving the others where they are.
+fn remove_point_light(point_lights: &mut Vec<PointLight>, component_id: &str) -> Option<PointLight> {
+    let index = point_lights.iter().position(|light| light.id == component_id)?;
+    Some(point_lights.remove(index))
+}
 
-                            let renderer_state = editor.renderer_state.as_mut().unwrap();
-                            let gpu_resources = editor.gpu_resources.as_ref().unwrap();
-                            let camera = editor.camera.as_ref().unwrap();
-
-                            handle_add_collectable(
-                                renderer_state,
-                                &gpu_resources.device,
-                                &gpu_resources.queue,
-                                project_id,
-                                args.asset_id.clone(),
-                                component_id.clone(),
-                                asset_file_name,
-                                model_iso,
-                                model_scale,
-                                camera,
-                                &collectable_properties,
-                                &related_stat,
-                                false, // Don't hide
-                                None // Script state
-                            ).await;
-
-                            // Update SavedState
-                            if let Some(saved_state) = editor.saved_state.as_mut() {
-                                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                    let new_component = ComponentData {
-                                        id: component_id,
-                                        kind: Some(ComponentKind::Collectable),
-                                        asset_id: args.asset_id,
-                                        generic_properties: GenericProperties {
-                                            name: "New Collectable".to_string(),
-                                            position: pos,
-                                            rotation: rot,
-                                            scale: scale,
-                                        },
-                                        collectable_properties: Some(collectable_properties),
-                                        ..Default::default()
-                                    };
-                                    
-                                    if let Some(components) = level.components.as_mut() {
-                                        components.push(new_component);
-                                    } else {
-                                        level.components = Some(vec![new_component]);
-                                    }
-                                }
-                                saved_state_clone = Some(saved_state.clone());
-                            }
-                        } else {
-                            log!("Asset or Stats not found for collectable. AssetId: {}", args.asset_id);
+/// LOD switch distances scaled from a model's bounding radius, so small props drop detail sooner than buildings.
+/// The last distance is where the model stops rendering.
+fn default_lod_distances(bounding_radius: f32) -> Vec<f32> {
+    let radius = bounding_radius.max(0.5);
+    vec![radius * 20.0, radius * 60.0, radius * 150.0]
+}
+
+/// A directional light points straight down at zero rotation; its component rotation (degrees) turns it from there.
+fn light_direction_from_rotation(rotation: [f32; 3]) -> [f32; 3] {
+    let rotation = UnitQuaternion::from_euler_angles(
+        rotation[0].to_radians(), rotation[1].to_radians(), rotation[2].to_radians()
+    );
+    let direction = rotation * Vector3::new(0.0, -1.0, 0.0);
+    [direction.x, direction.y, direction.z]
+}
+
+/// Inverse of `light_direction_from_rotation`, so a requested direction can be stored as a rotation.
+fn light_rotation_from_direction(direction: [f32; 3]) -> [f32; 3] {
+    let direction = Vector3::new(direction[0], direction[1], direction[2]);
+    let rotation = UnitQuaternion::rotation_between(&Vector3::new(0.0, -1.0, 0.0), &direction)
+        // Only fails for the exact opposite direction
+        .unwrap_or_else(|| UnitQuaternion::from_euler_angles(std::f32::consts::PI, 0.0, 0.0));
+    let (roll, pitch, yaw) = rotation.euler_angles();
+    [roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees()]
+}
+
+/// Reflects a position across the plane `axis = plane`, and flips the two rotation angles
+/// that change handedness under that reflection so the copy faces the mirrored way.
+fn mirror_transform(position: [f32; 3], rotation: [f32; 3], axis: usize, plane: f32) -> ([f32; 3], [f32; 3]) {
+    let mut mirrored_position = position;
+    mirrored_position[axis] = 2.0 * plane - position[axis];
+
+    let mut mirrored_rotation = rotation;
+    for (i, angle) in mirrored_rotation.iter_mut().enumerate() {
+        if i != axis {
+            *angle = -*angle;
+        }
+    }
+
+    (mirrored_position, mirrored_rotation)
+}
+
+/// Rounds each value to the nearest multiple of `increment`; no increment (or a non-positive one) leaves them as is.
+fn snap_to_increment(values: [f32; 3], increment: Option<f32>) -> [f32; 3] {
+    match increment {
+        Some(increment) if increment > 0.0 => values.map(|v| (v / increment).round() * increment),
+        _ => values,
+    }
+}
+
+/// Smallest and largest heightmap edge we generate or accept from an imported image.
+const MIN_HEIGHTMAP_SIZE: u32 = 64;
+const MAX_HEIGHTMAP_SIZE: u32 = 4096;
+const DEFAULT_HEIGHTMAP_SIZE: u32 = 1024;
+
+fn validate_heightmap_size(width: u32, height: u32) -> Result<(), String> {
+    let range = MIN_HEIGHTMAP_SIZE..=MAX_HEIGHTMAP_SIZE;
+    if !range.contains(&width) || !range.contains(&height) {
+        return Err(format!(
+            "Heightmap sides must be between {} and {} pixels, got {}x{}",
+            MIN_HEIGHTMAP_SIZE, MAX_HEIGHTMAP_SIZE, width, height
+        ));
+    }
+    Ok(())
+}
+/// How far apart the color channels of an imported pixel may be and still count as gray.
+const GRAYSCALE_TOLERANCE: u8 = 8;
+
+/// Decodes an imported heightmap, checking its size and that it's actually grayscale
+/// (a color image would silently turn into nonsense terrain).
+fn decode_heightmap_image(bytes: &[u8]) -> Result<image::ImageBuffer<image::Luma<u16>, Vec<u16>>, String> {
+    let img = image::load_from_memory(bytes).map_err(|e| format!("Couldn't decode heightmap image: {}", e))?;
+
+    validate_heightmap_size(img.width(), img.height())?;
+
+    if img.color().has_color() {
+        let is_gray = img.to_rgb8().pixels().all(|p| {
+            let (min, max) = (p.0.iter().min().unwrap(), p.0.iter().max().unwrap());
+            max - min <= GRAYSCALE_TOLERANCE
+        });
+        if !is_gray {
+            return Err("Heightmap image must be grayscale".to_string());
+        }
+    }
+
+    Ok(img.to_luma16())
+}
+
+async fn fetch_heightmap_image(url: &str) -> Result<image::ImageBuffer<image::Luma<u16>, Vec<u16>>, String> {
+    let response = Request::get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Couldn't fetch heightmap image: {}", e))?;
+    if !response.ok() {
+        return Err(format!("Couldn't fetch heightmap image: HTTP {}", response.status()));
+    }
+    let bytes = response.binary().await.map_err(|e| e.to_string())?;
+
+    decode_heightmap_image(&bytes)
+}
+
+/// Where a group child ends up when its group goes from `previous` to `current`: the child's offset from
+/// the group pivot is scaled and rotated along with the group, and its own rotation and scale change by the same amount.
+fn group_child_transform(previous: &GenericProperties, current: &GenericProperties, child: &GenericProperties) -> GenericProperties {
+    let to_quaternion = |r: [f32; 3]| UnitQuaternion::from_euler_angles(r[0].to_radians(), r[1].to_radians(), r[2].to_radians());
+
+    let delta_rotation = to_quaternion(current.rotation) * to_quaternion(previous.rotation).inverse();
+    let scale_ratio = Vector3::from_fn(|i, _| {
+        if previous.scale[i].abs() > f32::EPSILON { current.scale[i] / previous.scale[i] } else { 1.0 }
+    });
+
+    let offset = Vector3::from(child.position) - Vector3::from(previous.position);
+    let position = Vector3::from(current.position) + delta_rotation * offset.component_mul(&scale_ratio);
+    let (roll, pitch, yaw) = (delta_rotation * to_quaternion(child.rotation)).euler_angles();
+
+    GenericProperties {
+        position: [position.x, position.y, position.z],
+        rotation: [roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees()],
+        scale: [child.scale[0] * scale_ratio.x, child.scale[1] * scale_ratio.y, child.scale[2] * scale_ratio.z],
+        ..child.clone()
+    }
+}
+
+/// Refusal for tools that would edit a locked component, `None` when it's unlocked, missing, or `force` is set.
+fn locked_component_error(saved_state: &SavedState, component_id: &str, force: bool) -> Option<ToolResult> {
+    if force {
+        return None;
+    }
+
+    saved_state.levels.as_ref()
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_ref())
+        .and_then(|components| components.iter().find(|c| c.id == component_id && c.locked))
+        .map(|component| ToolResult::error(format!(
+            "{} is locked. Unlock it first, or pass force: true to edit it anyway",
+            component.generic_properties.name
+        )))
+}
+
+/// Picks the stat for a new collectable: the requested id if given, otherwise the first stat
+/// whose data fits the collectable type (attack stats for weapons, defense for armor).
+fn select_collectable_stat(stats: &[StatData], collectable_type: &CollectableType, stat_id: Option<&str>) -> Option<StatData> {
+    if let Some(stat_id) = stat_id {
+        return stats.iter().find(|stat| stat.id == stat_id).cloned();
+    }
+
+    stats.iter().find(|stat| match collectable_type {
+        CollectableType::MeleeWeapon | CollectableType::RangedWeapon => stat.attack.is_some(),
+        CollectableType::Armor => stat.defense.is_some(),
+        _ => stat.attack.is_none() && stat.defense.is_none(),
+    }).cloned()
+}
+
+/// Tonemap curve for a tool's label, `None` when it isn't one of "None", "Reinhard", "ACES" or "Filmic".
+fn tonemap_from_label(label: &str) -> Option<TonemapCurve> {
+    match label.to_lowercase().as_str() {
+        "none" => Some(TonemapCurve::None),
+        "reinhard" => Some(TonemapCurve::Reinhard),
+        "aces" => Some(TonemapCurve::Aces),
+        "filmic" => Some(TonemapCurve::Filmic),
+        _ => None,
+    }
+}
+
+/// A tool's optional `tonemapping` argument, an error naming the valid labels when it isn't one of them.
+fn tonemap_arg(label: Option<&str>) -> Result<Option<TonemapCurve>, String> {
+    label.map(|label| {
+        tonemap_from_label(label).ok_or_else(|| format!("Unknown tonemapping {}, expected None, Reinhard, ACES or Filmic", label))
+    }).transpose()
+}
+
+/// Starting point for `configureSky`'s `preset`, fine-tuned by any colors passed alongside it.
+struct SkyPreset {
+    horizon_color: [f32; 3],
+    zenith_color: [f32; 3],
+    sun_direction: [f32; 3],
+    sun_color: [f32; 3],
+    sun_intensity: f32,
+}
+
+const SKY_PRESETS: &[(&str, SkyPreset)] = &[
+    ("clear day", SkyPreset {
+        horizon_color: [0.7, 0.85, 1.0],
+        zenith_color: [0.2, 0.45, 0.9],
+        sun_direction: [0.3, 0.8, 0.3],
+        sun_color: [1.0, 0.97, 0.9],
+        sun_intensity: 1.0,
+    }),
+    ("sunset", SkyPreset {
+        horizon_color: [0.95, 0.45, 0.2],
+        zenith_color: [0.25, 0.2, 0.45],
+        sun_direction: [0.9, 0.1, 0.3],
+        sun_color: [1.0, 0.5, 0.25],
+        sun_intensity: 0.7,
+    }),
+    ("overcast", SkyPreset {
+        horizon_color: [0.6, 0.62, 0.65],
+        zenith_color: [0.45, 0.47, 0.5],
+        sun_direction: [0.3, 0.8, 0.3],
+        sun_color: [0.8, 0.8, 0.8],
+        sun_intensity: 0.3,
+    }),
+    // The "sun" is a faint bluish moon
+    ("night", SkyPreset {
+        horizon_color: [0.03, 0.04, 0.09],
+        zenith_color: [0.0, 0.0, 0.02],
+        sun_direction: [-0.3, 0.6, 0.3],
+        sun_color: [0.6, 0.7, 1.0],
+        sun_intensity: 0.05,
+    }),
+    ("alien", SkyPreset {
+        horizon_color: [0.3, 0.9, 0.5],
+        zenith_color: [0.35, 0.1, 0.5],
+        sun_direction: [0.5, 0.5, -0.4],
+        sun_color: [1.0, 0.3, 0.8],
+        sun_intensity: 1.2,
+    }),
+];
+
+/// Sky preset for a tool's label, ignoring case and accepting "clear_day" or "clear-day" for "clear day".
+fn sky_preset(label: &str) -> Option<&'static SkyPreset> {
+    let label = label.to_lowercase().replace(['_', '-'], " ");
+    SKY_PRESETS.iter().find(|(name, _)| *name == label.trim()).map(|(_, preset)| preset)
+}
+
+/// Checks a tool call's arguments parse as `T`. The raw arguments are logged when they don't, for debugging.
+fn check_tool_args<T: serde::de::DeserializeOwned>(tool_call: &ToolCall) -> Result<(), String> {
+    serde_json::from_str::<T>(&tool_call.function.arguments)
+        .map(|_| ())
+        .map_err(|e| {
+            log!("Invalid arguments for {}: {}", tool_call.function.name, tool_call.function.arguments);
+            format!("Invalid arguments for {}: {}", tool_call.function.name, e)
+        })
+}
+
+/// Posts a tool call's result to the session as a `tool` message, so the model sees what went wrong.
+async fn report_tool_result(session_id: &str, tool_call_id: &str, result: &ToolResult) {
+    let body = serde_json::json!({
+        "role": "tool",
+        "content": serde_json::to_string(result).unwrap_or_default(),
+        "toolCallId": tool_call_id,
+    });
+    let url = format!("{}/api/sessions/{}/messages", get_api_url(), session_id);
+    if let Ok(request) = Request::post(&url).json(&body) {
+        if let Err(e) = request.send().await {
+            log!("Couldn't report tool result: {}", e);
+        }
+    }
+}
+
+/// Tool error while the project is still loading into the preview.
+const EDITOR_NOT_READY: &str = "The editor isn't ready yet, wait for the project to finish loading";
+
+/// Runs `f` against the project's editor, or reports that the editor isn't ready yet when the
+/// pipeline is still loading.
+async fn with_editor_mut(
+    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
+    f: impl AsyncFnOnce(&mut Editor) -> ToolResult,
+) -> ToolResult {
+    let Some(pipeline_arc) = pipeline_store.get().flatten() else {
+        return ToolResult::error(EDITOR_NOT_READY);
+    };
+    let mut pipeline = pipeline_arc.borrow_mut();
+    let Some(editor) = pipeline.export_editor.as_mut() else {
+        return ToolResult::error(EDITOR_NOT_READY);
+    };
+
+    f(editor).await
+}
+
+/// What every tool handler gets to work with.
+struct ToolContext {
+    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
+    project_id: String,
+    selected_project: ReadSignal<Option<Project>>,
+    /// What a slow tool is doing and how far along it is (0-1), shown in place of the typing indicator.
+    set_progress: WriteSignal<Option<(String, f32)>>,
+}
+
+impl ToolContext {
+    /// Saves the project when the handler changed the scene, then hands back its result.
+    fn finish(&self, saved_state: Option<SavedState>, tool_result: ToolResult) -> ToolResult {
+        if let Some(saved_state) = saved_state {
+            let project_id = self.project_id.clone();
+            spawn_local(async move {
+                let _ = save_project(&project_id, &saved_state).await;
+            });
+        }
+
+        tool_result
+    }
+}
+
+type ToolFuture<'a> = std::pin::Pin<Box<dyn std::future::Future<Output = ToolResult> + 'a>>;
+
+/// A tool the model can call: its name, a check that its arguments parse, and the handler that runs it.
+struct ToolHandler {
+    name: &'static str,
+    check_args: fn(&ToolCall) -> Result<(), String>,
+    run: for<'a> fn(&'a ToolContext, &'a ToolCall) -> ToolFuture<'a>,
+}
+
+static TOOL_HANDLERS: &[ToolHandler] = &[
+    ToolHandler { name: "transformObject", check_args: check_tool_args::<TransformObjectArgs>, run: |ctx, tool_call| Box::pin(transform_object(ctx, tool_call)) },
+    ToolHandler { name: "configureWater", check_args: check_tool_args::<ConfigureWaterArgs>, run: |ctx, tool_call| Box::pin(configure_water(ctx, tool_call)) },
+    ToolHandler { name: "spawnWaterPlane", check_args: check_tool_args::<SpawnWaterPlaneArgs>, run: |ctx, tool_call| Box::pin(spawn_water_plane(ctx, tool_call)) },
+    ToolHandler { name: "removeWaterPlane", check_args: check_tool_args::<RemoveWaterPlaneArgs>, run: |ctx, tool_call| Box::pin(remove_water_plane(ctx, tool_call)) },
+    ToolHandler { name: "configureSky", check_args: check_tool_args::<ConfigureSkyArgs>, run: |ctx, tool_call| Box::pin(configure_sky(ctx, tool_call)) },
+    ToolHandler { name: "configureDayNightCycle", check_args: check_tool_args::<ConfigureDayNightCycleArgs>, run: |ctx, tool_call| Box::pin(configure_day_night_cycle(ctx, tool_call)) },
+    ToolHandler { name: "configureWeather", check_args: check_tool_args::<ConfigureWeatherArgs>, run: |ctx, tool_call| Box::pin(configure_weather(ctx, tool_call)) },
+    ToolHandler { name: "configureClouds", check_args: check_tool_args::<ConfigureCloudsArgs>, run: |ctx, tool_call| Box::pin(configure_clouds(ctx, tool_call)) },
+    ToolHandler { name: "configureAO", check_args: check_tool_args::<ConfigureAOArgs>, run: |ctx, tool_call| Box::pin(configure_ao(ctx, tool_call)) },
+    ToolHandler { name: "configureFog", check_args: check_tool_args::<ConfigureFogArgs>, run: |ctx, tool_call| Box::pin(configure_fog(ctx, tool_call)) },
+    ToolHandler { name: "configurePostProcessing", check_args: check_tool_args::<ConfigurePostProcessingArgs>, run: |ctx, tool_call| Box::pin(configure_post_processing(ctx, tool_call)) },
+    ToolHandler { name: "configureColorGrade", check_args: check_tool_args::<ConfigureColorGradeArgs>, run: |ctx, tool_call| Box::pin(configure_color_grade(ctx, tool_call)) },
+    ToolHandler { name: "setAmbientLight", check_args: check_tool_args::<SetAmbientLightArgs>, run: |ctx, tool_call| Box::pin(set_ambient_light(ctx, tool_call)) },
+    ToolHandler { name: "configureLandscapeTextures", check_args: check_tool_args::<ConfigureLandscapeTexturesArgs>, run: |ctx, tool_call| Box::pin(configure_landscape_textures(ctx, tool_call)) },
+    ToolHandler { name: "configureParticle", check_args: check_tool_args::<ConfigureParticleArgs>, run: |ctx, tool_call| Box::pin(configure_particle(ctx, tool_call)) },
+    ToolHandler { name: "configureTrees", check_args: check_tool_args::<ConfigureTreesArgs>, run: |ctx, tool_call| Box::pin(configure_trees(ctx, tool_call)) },
+    ToolHandler { name: "spawnForest", check_args: check_tool_args::<SpawnForestArgs>, run: |ctx, tool_call| Box::pin(spawn_forest(ctx, tool_call)) },
+    ToolHandler { name: "spawnModel", check_args: check_tool_args::<SpawnModelArgs>, run: |ctx, tool_call| Box::pin(spawn_model(ctx, tool_call)) },
+    ToolHandler { name: "instanceModel", check_args: check_tool_args::<InstanceModelArgs>, run: |ctx, tool_call| Box::pin(instance_model(ctx, tool_call)) },
+    ToolHandler { name: "configureLOD", check_args: check_tool_args::<ConfigureLODArgs>, run: |ctx, tool_call| Box::pin(configure_lod(ctx, tool_call)) },
+    ToolHandler { name: "mirrorObject", check_args: check_tool_args::<MirrorObjectArgs>, run: |ctx, tool_call| Box::pin(mirror_object(ctx, tool_call)) },
+    ToolHandler { name: "duplicateObject", check_args: check_tool_args::<DuplicateObjectArgs>, run: |ctx, tool_call| Box::pin(duplicate_object(ctx, tool_call)) },
+    ToolHandler { name: "deleteObject", check_args: check_tool_args::<DeleteObjectArgs>, run: |ctx, tool_call| Box::pin(delete_object(ctx, tool_call)) },
+    ToolHandler { name: "setMaterial", check_args: check_tool_args::<SetMaterialArgs>, run: |ctx, tool_call| Box::pin(set_material(ctx, tool_call)) },
+    ToolHandler { name: "spawnPointLight", check_args: check_tool_args::<SpawnPointLightArgs>, run: |ctx, tool_call| Box::pin(spawn_point_light(ctx, tool_call)) },
+    ToolHandler { name: "spawnDirectionalLight", check_args: check_tool_args::<SpawnDirectionalLightArgs>, run: |ctx, tool_call| Box::pin(spawn_directional_light(ctx, tool_call)) },
+    ToolHandler { name: "spawnCollectable", check_args: check_tool_args::<SpawnCollectableArgs>, run: |ctx, tool_call| Box::pin(spawn_collectable(ctx, tool_call)) },
+    ToolHandler { name: "setWeaponStats", check_args: check_tool_args::<SetWeaponStatsArgs>, run: |ctx, tool_call| Box::pin(set_weapon_stats(ctx, tool_call)) },
+    ToolHandler { name: "configureGrass", check_args: check_tool_args::<ConfigureGrassArgs>, run: |ctx, tool_call| Box::pin(configure_grass(ctx, tool_call)) },
+    ToolHandler { name: "spawnPrimitive", check_args: check_tool_args::<SpawnPrimitiveArgs>, run: |ctx, tool_call| Box::pin(spawn_primitive(ctx, tool_call)) },
+    ToolHandler { name: "spawnNPC", check_args: check_tool_args::<SpawnNPCArgs>, run: |ctx, tool_call| Box::pin(spawn_npc(ctx, tool_call)) },
+    ToolHandler { name: "spawnTrigger", check_args: check_tool_args::<SpawnTriggerArgs>, run: |ctx, tool_call| Box::pin(spawn_trigger(ctx, tool_call)) },
+    ToolHandler { name: "spawnAudioSource", check_args: check_tool_args::<SpawnAudioSourceArgs>, run: |ctx, tool_call| Box::pin(spawn_audio_source(ctx, tool_call)) },
+    ToolHandler { name: "setPlayerSpawn", check_args: check_tool_args::<SetPlayerSpawnArgs>, run: |ctx, tool_call| Box::pin(set_player_spawn(ctx, tool_call)) },
+    ToolHandler { name: "configurePlayerStats", check_args: check_tool_args::<ConfigurePlayerStatsArgs>, run: |ctx, tool_call| Box::pin(configure_player_stats(ctx, tool_call)) },
+    ToolHandler { name: "addInventoryItem", check_args: check_tool_args::<AddInventoryItemArgs>, run: |ctx, tool_call| Box::pin(add_inventory_item(ctx, tool_call)) },
+    ToolHandler { name: "conformToTerrain", check_args: check_tool_args::<ConformToTerrainArgs>, run: |ctx, tool_call| Box::pin(conform_to_terrain(ctx, tool_call)) },
+    ToolHandler { name: "renameComponent", check_args: check_tool_args::<RenameComponentArgs>, run: |ctx, tool_call| Box::pin(rename_component(ctx, tool_call)) },
+    ToolHandler { name: "groupComponents", check_args: check_tool_args::<GroupComponentsArgs>, run: |ctx, tool_call| Box::pin(group_components(ctx, tool_call)) },
+    ToolHandler { name: "ungroupComponents", check_args: check_tool_args::<UngroupComponentsArgs>, run: |ctx, tool_call| Box::pin(ungroup_components(ctx, tool_call)) },
+    ToolHandler { name: "lockComponent", check_args: check_tool_args::<LockComponentArgs>, run: |ctx, tool_call| Box::pin(lock_component(ctx, tool_call)) },
+    ToolHandler { name: "frameScene", check_args: check_tool_args::<FrameSceneArgs>, run: |ctx, tool_call| Box::pin(frame_scene_tool(ctx, tool_call)) },
+    ToolHandler { name: "measureDistance", check_args: check_tool_args::<MeasureDistanceArgs>, run: |ctx, tool_call| Box::pin(measure_distance(ctx, tool_call)) },
+    ToolHandler { name: "saveScript", check_args: check_tool_args::<SaveScriptArgs>, run: |ctx, tool_call| Box::pin(save_script(ctx, tool_call)) },
+    ToolHandler { name: "generateHeightmap", check_args: check_tool_args::<GenerateHeightmapArgs>, run: |ctx, tool_call| Box::pin(generate_heightmap(ctx, tool_call)) },
+    ToolHandler { name: "flattenTerrain", check_args: check_tool_args::<FlattenTerrainArgs>, run: |ctx, tool_call| Box::pin(flatten_terrain(ctx, tool_call)) },
+];
+
+/// The handler for a tool call, or the error result to send back when the tool is unknown or its arguments don't parse.
+fn resolve_tool_call(tool_call: &ToolCall) -> Result<&'static ToolHandler, ToolResult> {
+    let Some(handler) = TOOL_HANDLERS.iter().find(|handler| handler.name == tool_call.function.name) else {
+        return Err(ToolResult::error(format!("Unknown tool {}", tool_call.function.name)));
+    };
+    // Bad arguments come back as an error the model can act on, instead of the handler quietly doing nothing
+    (handler.check_args)(tool_call).map_err(ToolResult::error)?;
+    Ok(handler)
+}
+
+async fn execute_tool_call(
+    tool_call: &ToolCall,
+    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
+    project_id: String,
+    selected_project: ReadSignal<Option<Project>>,
+    set_progress: WriteSignal<Option<(String, f32)>>,
+) -> ToolResult {
+    log!("Executing tool call: {:?}", tool_call.function.name);
+
+    let handler = match resolve_tool_call(tool_call) {
+        Ok(handler) => handler,
+        Err(tool_result) => return tool_result,
+    };
+
+    let ctx = ToolContext { pipeline_store, project_id, selected_project, set_progress };
+    let tool_result = (handler.run)(&ctx, tool_call).await;
+    set_progress.set(None);
+    tool_result
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransformObjectArgs {
+    component_id: String,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 3]>,
+    scale: Option<[f32; 3]>,
+    snap: Option<f32>, // grid increment for the translation
+    rotation_snap: Option<f32>, // increment in degrees for the rotation
+    force: Option<bool>, // move it even if it's locked
+}
+
+async fn transform_object(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    let args: Result<TransformObjectArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let translation = args.translation.map(|t| snap_to_increment(t, args.snap));
+        let rotation = args.rotation.map(|r| snap_to_increment(r, args.rotation_snap));
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            // Every component whose transform changed, with its resulting transform
+            let mut moved: Vec<(String, GenericProperties)> = Vec::new();
+
+            let force = args.force.unwrap_or(false);
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(error) = locked_component_error(saved_state, &args.component_id, force) {
+                    return error;
+                }
+                let exists = saved_state.levels.as_ref()
+                    .and_then(|levels| levels.get(0))
+                    .and_then(|level| level.components.as_ref())
+                    .is_some_and(|components| components.iter().any(|c| c.id == args.component_id));
+                if !exists {
+                    return ToolResult::error(format!("Component {} not found", args.component_id));
+                }
+
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if let Some(components) = level.components.as_mut() {
+                        let target = components.iter_mut().find(|c| c.id == args.component_id).map(|component| {
+                            let previous = component.generic_properties.clone();
+                            if let Some(translation) = translation {
+                                component.generic_properties.position = translation;
+                            }
+                            if let Some(rotation) = rotation {
+                                component.generic_properties.rotation = rotation;
+                            }
+                            if let Some(scale) = args.scale {
+                                component.generic_properties.scale = scale;
+                            }
+                            let child_ids = match (&component.kind, &component.group_properties) {
+                                (Some(ComponentKind::Group), Some(group)) => group.child_ids.clone(),
+                                _ => Vec::new(),
+                            };
+                            (previous, component.generic_properties.clone(), child_ids)
+                        });
+
+                        if let Some((previous, current, child_ids)) = target {
+                            // Children of a group follow it, keeping their offsets from the group pivot.
+                            // Locked children stay put unless forced
+                            for child in components.iter_mut().filter(|c| child_ids.contains(&c.id) && (force || !c.locked)) {
+                                child.generic_properties = group_child_transform(&previous, &current, &child.generic_properties);
+                                moved.push((child.id.clone(), child.generic_properties.clone()));
+                            }
+                            moved.push((args.component_id.clone(), current));
+                        }
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Update RendererState
+            for (component_id, transform) in moved.iter() {
+                // Also moves point lights, found by component so lights sharing a position stay apart
+                apply_component_position(editor, component_id, transform.position);
+            }
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                for (component_id, transform) in moved.iter() {
+                    if let Some(model) = renderer_state.models.iter_mut().find(|m| &m.id == component_id) {
+                        for mesh in model.meshes.iter_mut() {
+                            mesh.transform.update_position(transform.position);
+                            mesh.transform.update_rotation(transform.rotation);
+                            mesh.transform.update_scale(transform.scale);
+                        }
+                    }
+
+                    // Directional lights have no mesh, their rotation is the light direction
+                    if let Some(light) = renderer_state.directional_lights.iter_mut().find(|l| &l.id == component_id) {
+                        light.direction = light_direction_from_rotation(transform.rotation);
+                    }
+                }
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// #[serde(rename_all = "camelCase")]
+struct ConfigureWaterArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>,
+    shallow_color: Option<[f32; 3]>,
+    medium_color: Option<[f32; 3]>,
+    deep_color: Option<[f32; 3]>,
+    ripple_amplitude_multiplier: Option<f32>,
+    ripple_freq: Option<f32>,
+    ripple_speed: Option<f32>,
+    shoreline_foam_range: Option<f32>,
+    crest_foam_min: Option<f32>,
+    crest_foam_max: Option<f32>,
+    sparkle_intensity: Option<f32>,
+    sparkle_threshold: Option<f32>,
+    subsurface_multiplier: Option<f32>,
+    fresnel_power: Option<f32>,
+    fresnel_multiplier: Option<f32>,
+
+    // Wave 1 - primary wave
+    pub wave1_amplitude: Option<f32>,
+    pub wave1_frequency: Option<f32>,
+    pub wave1_speed: Option<f32>,
+    pub wave1_steepness: Option<f32>,
+    pub wave1_direction: Option<[f32; 2]>,
+
+    // Wave 2 - secondary wave
+    pub wave2_amplitude: Option<f32>,
+    pub wave2_frequency: Option<f32>,
+    pub wave2_speed: Option<f32>,
+    pub wave2_steepness: Option<f32>,
+    pub wave2_direction: Option<[f32; 2]>,
+
+    // Wave 3 - tertiary wave
+    pub wave3_amplitude: Option<f32>,
+    pub wave3_frequency: Option<f32>,
+    pub wave3_speed: Option<f32>,
+    pub wave3_steepness: Option<f32>,
+    pub wave3_direction: Option<[f32; 2]>,
+
+    // Surface optics
+    reflection_strength: Option<f32>, // 0-1, 1 is a mirror
+    refraction_strength: Option<f32>, // 0-1, how much the view through the water is distorted
+    transparency: Option<f32>, // 0-1, 0 is opaque
+    depth_fade: Option<f32>, // world units until the bottom fades out, lower is murkier
+}
+
+async fn configure_water(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring water plane...");
+    let args: Result<ConfigureWaterArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let mut notes = Vec::new();
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            if let Some(component_id) = args.component_id.as_ref() {
+                let is_water_plane = editor.saved_state.as_ref()
+                    .and_then(|saved_state| saved_state.levels.as_ref())
+                    .and_then(|levels| levels.get(0))
+                    .and_then(|level| level.components.as_ref())
+                    .is_some_and(|components| components.iter().any(|c| &c.id == component_id && c.kind == Some(ComponentKind::WaterPlane)));
+                let is_rendered = editor.renderer_state.as_ref()
+                    .is_some_and(|renderer_state| renderer_state.water_planes.iter().any(|w| &w.id == component_id));
+                if !is_water_plane || !is_rendered {
+                    return ToolResult::error(format!("Water plane {} not found", component_id));
+                }
+            }
+
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+
+                // Check if we have any water planes
+                if renderer_state.water_planes.is_empty() {
+                    // Try to create one if we have a landscape
+                    if let Some(landscape) = renderer_state.landscapes.first() {
+                         let landscape_id = landscape.id.clone();
+                         let device = &editor.gpu_resources.as_ref().unwrap().device;
+                         let camera_binding = editor.camera_binding.as_ref().unwrap(); 
+                         let surface_format = editor.surface_format.expect("Surface format is set when the preview is configured");
+
+                         handle_add_water_plane(
+                            renderer_state, 
+                            device, 
+                            &camera_binding.bind_group_layout, 
+                            surface_format, 
+                            landscape_id.clone(), 
+                            Some(WaterConfig::default()), 
+                            Some(landscape_id.clone())
+                        );
+                         log!("Water plane created for landscape {}", landscape_id);
+                    }
+                }
+
+                // The requested plane, or the first one when no componentId is given
+                let water_plane = match args.component_id.as_ref() {
+                    Some(component_id) => renderer_state.water_planes.iter_mut().find(|w| &w.id == component_id),
+                    None => renderer_state.water_planes.first_mut(),
+                };
+                if let Some(water_plane) = water_plane {
+                    let mut current_config = water_plane.config; // Get current config
+
+                    log!("Configuring water plane still... {:?}", args);
+
+                    if let Some(color) = args.shallow_color.and_then(|c| COLOR_CHANNEL.check_each("shallow_color", c, &mut notes)) {
+                        current_config.shallow_color = [color[0], color[1], color[2], 1.0];
+                    }
+                    if let Some(color) = args.medium_color.and_then(|c| COLOR_CHANNEL.check_each("medium_color", c, &mut notes)) {
+                        current_config.medium_color = [color[0], color[1], color[2], 1.0];
+                    }
+                    if let Some(color) = args.deep_color.and_then(|c| COLOR_CHANNEL.check_each("deep_color", c, &mut notes)) {
+                        current_config.deep_color = [color[0], color[1], color[2], 1.0];
+                    }
+                    if let Some(val) = args.ripple_amplitude_multiplier.and_then(|v| WATER_RIPPLE_AMPLITUDE.check("ripple_amplitude_multiplier", v, &mut notes)) {
+                        current_config.ripple_amplitude_multiplier = val;
+                    }
+                    if let Some(val) = args.ripple_freq.and_then(|v| WATER_RIPPLE_FREQ.check("ripple_freq", v, &mut notes)) {
+                        current_config.ripple_freq = val;
+                    }
+                    if let Some(val) = args.ripple_speed.and_then(|v| WATER_RIPPLE_SPEED.check("ripple_speed", v, &mut notes)) {
+                        current_config.ripple_speed = val;
+                    }
+                    if let Some(val) = args.shoreline_foam_range.and_then(|v| WATER_SHORELINE_FOAM_RANGE.check("shoreline_foam_range", v, &mut notes)) {
+                        current_config.shoreline_foam_range = val;
+                    }
+                    if let Some(val) = args.crest_foam_min.and_then(|v| WATER_CREST_FOAM_MIN.check("crest_foam_min", v, &mut notes)) {
+                        current_config.crest_foam_min = val;
+                    }
+                    if let Some(val) = args.crest_foam_max.and_then(|v| WATER_CREST_FOAM_MAX.check("crest_foam_max", v, &mut notes)) {
+                        current_config.crest_foam_max = val;
+                    }
+                    order_range(
+                        "crest_foam",
+                        &mut current_config.crest_foam_min,
+                        &mut current_config.crest_foam_max,
+                        args.crest_foam_max.is_some(),
+                        &mut notes,
+                    );
+                    if let Some(val) = args.sparkle_intensity.and_then(|v| WATER_SPARKLE_INTENSITY.check("sparkle_intensity", v, &mut notes)) {
+                        current_config.sparkle_intensity = val;
+                    }
+                    if let Some(val) = args.sparkle_threshold.and_then(|v| WATER_SPARKLE_THRESHOLD.check("sparkle_threshold", v, &mut notes)) {
+                        current_config.sparkle_threshold = val;
+                    }
+                    if let Some(val) = args.subsurface_multiplier.and_then(|v| WATER_SUBSURFACE_MULTIPLIER.check("subsurface_multiplier", v, &mut notes)) {
+                        current_config.subsurface_multiplier = val;
+                    }
+                    if let Some(val) = args.fresnel_power.and_then(|v| WATER_FRESNEL_POWER.check("fresnel_power", v, &mut notes)) {
+                        current_config.fresnel_power = val;
+                    }
+                    if let Some(val) = args.fresnel_multiplier.and_then(|v| WATER_FRESNEL_MULTIPLIER.check("fresnel_multiplier", v, &mut notes)) {
+                        current_config.fresnel_multiplier = val;
+                    }
+
+                    if let Some(val) = args.wave1_amplitude.and_then(|v| WATER_WAVE_AMPLITUDE.check("wave1_amplitude", v, &mut notes)) {
+                        current_config.wave1_amplitude = val;
+                    }
+                    if let Some(val) = args.wave1_frequency.and_then(|v| WATER_WAVE_FREQUENCY.check("wave1_frequency", v, &mut notes)) {
+                        current_config.wave1_frequency = val;
+                    }
+                    if let Some(val) = args.wave1_speed.and_then(|v| WATER_WAVE_SPEED.check("wave1_speed", v, &mut notes)) {
+                        current_config.wave1_speed = val;
+                    }
+                    if let Some(val) = args.wave1_steepness.and_then(|v| WATER_WAVE_STEEPNESS.check("wave1_steepness", v, &mut notes)) {
+                        current_config.wave1_steepness = val;
+                    }
+                    if let Some(val) = args.wave1_direction.and_then(|v| WATER_WAVE_DIRECTION.check_each("wave1_direction", v, &mut notes)) {
+                        current_config.wave1_direction = val;
+                    }
+
+                    if let Some(val) = args.wave2_amplitude.and_then(|v| WATER_WAVE_AMPLITUDE.check("wave2_amplitude", v, &mut notes)) {
+                        current_config.wave2_amplitude = val;
+                    }
+                    if let Some(val) = args.wave2_frequency.and_then(|v| WATER_WAVE_FREQUENCY.check("wave2_frequency", v, &mut notes)) {
+                        current_config.wave2_frequency = val;
+                    }
+                    if let Some(val) = args.wave2_speed.and_then(|v| WATER_WAVE_SPEED.check("wave2_speed", v, &mut notes)) {
+                        current_config.wave2_speed = val;
+                    }
+                    if let Some(val) = args.wave2_steepness.and_then(|v| WATER_WAVE_STEEPNESS.check("wave2_steepness", v, &mut notes)) {
+                        current_config.wave2_steepness = val;
+                    }
+                    if let Some(val) = args.wave2_direction.and_then(|v| WATER_WAVE_DIRECTION.check_each("wave2_direction", v, &mut notes)) {
+                        current_config.wave2_direction = val;
+                    }
+
+                    if let Some(val) = args.wave3_amplitude.and_then(|v| WATER_WAVE_AMPLITUDE.check("wave3_amplitude", v, &mut notes)) {
+                        current_config.wave3_amplitude = val;
+                    }
+                    if let Some(val) = args.wave3_frequency.and_then(|v| WATER_WAVE_FREQUENCY.check("wave3_frequency", v, &mut notes)) {
+                        current_config.wave3_frequency = val;
+                    }
+                    if let Some(val) = args.wave3_speed.and_then(|v| WATER_WAVE_SPEED.check("wave3_speed", v, &mut notes)) {
+                        current_config.wave3_speed = val;
+                    }
+                    if let Some(val) = args.wave3_steepness.and_then(|v| WATER_WAVE_STEEPNESS.check("wave3_steepness", v, &mut notes)) {
+                        current_config.wave3_steepness = val;
+                    }
+                    if let Some(val) = args.wave3_direction.and_then(|v| WATER_WAVE_DIRECTION.check_each("wave3_direction", v, &mut notes)) {
+                        current_config.wave3_direction = val;
+                    }
+
+                    if let Some(val) = args.reflection_strength.and_then(|v| WATER_REFLECTION_STRENGTH.check("reflection_strength", v, &mut notes)) {
+                        current_config.reflection_strength = val;
+                    }
+                    if let Some(val) = args.refraction_strength.and_then(|v| WATER_REFRACTION_STRENGTH.check("refraction_strength", v, &mut notes)) {
+                        current_config.refraction_strength = val;
+                    }
+                    if let Some(val) = args.transparency.and_then(|v| WATER_TRANSPARENCY.check("transparency", v, &mut notes)) {
+                        current_config.transparency = val;
+                    }
+                    if let Some(val) = args.depth_fade.and_then(|v| WATER_DEPTH_FADE.check("depth_fade", v, &mut notes)) {
+                        current_config.depth_fade = val;
+                    }
+
+                    // water_plane.config = current_config;
+                    water_plane.update_config(&editor.gpu_resources.as_ref().expect("Couldn't get gpu resources").queue, current_config);
+
+                    log!("Water plane configured {:?}", water_plane.config);
+
+                    if let Some(message) = notes_message(&notes) {
+                        tool_result = ToolResult::ok().with_message(message);
+                    }
+
+                    // Update SavedState, so the plane keeps its look after a reload
+                    let water_plane_id = water_plane.id.clone();
+                    if let Some(saved_state) = editor.saved_state.as_mut() {
+                        let component = saved_state.levels.as_mut()
+                            .and_then(|levels| levels.get_mut(0))
+                            .and_then(|level| level.components.as_mut())
+                            .and_then(|components| components.iter_mut().find(|c| c.id == water_plane_id));
+                        if let Some(component) = component {
+                            component.water_properties = Some(current_config);
+                        }
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                }
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnWaterPlaneArgs {
+    position: [f32; 3], // center of the plane, Y is the water level
+    width: f32, // meters along X
+    length: f32, // meters along Z
+    #[serde(rename = "shallowColor")]
+    shallow_color: Option<[f32; 3]>,
+    #[serde(rename = "deepColor")]
+    deep_color: Option<[f32; 3]>,
+    transparency: Option<f32>, // 0-1, 0 is opaque
+}
+
+async fn spawn_water_plane(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning water plane...");
+    let args: Result<SpawnWaterPlaneArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let mut notes = Vec::new();
+        let (Some(width), Some(length)) = (
+            WATER_PLANE_SIZE.check("width", args.width, &mut notes),
+            WATER_PLANE_SIZE.check("length", args.length, &mut notes),
+        ) else {
+            return ToolResult::error(notes_message(&notes).unwrap_or_default());
+        };
+
+        let mut config = WaterConfig::default();
+        if let Some(color) = args.shallow_color.and_then(|c| COLOR_CHANNEL.check_each("shallowColor", c, &mut notes)) {
+            config.shallow_color = [color[0], color[1], color[2], 1.0];
+        }
+        if let Some(color) = args.deep_color.and_then(|c| COLOR_CHANNEL.check_each("deepColor", c, &mut notes)) {
+            config.deep_color = [color[0], color[1], color[2], 1.0];
+        }
+        if let Some(val) = args.transparency.and_then(|v| WATER_TRANSPARENCY.check("transparency", v, &mut notes)) {
+            config.transparency = val;
+        }
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let component_id = Uuid::new_v4().to_string();
+            // The plane is a unit square scaled to size, the same way the component's transform is saved
+            let scale = [width, 1.0, length];
+
+            let (Some(renderer_state), Some(gpu_resources), Some(camera_binding)) =
+                (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref(), editor.camera_binding.as_ref())
+            else {
+                return ToolResult::error("Renderer state not found");
+            };
+            let surface_format = editor.surface_format.expect("Surface format is set when the preview is configured");
+
+            // No landscape to follow, the plane stays where it's put
+            handle_add_water_plane(
+                renderer_state,
+                &gpu_resources.device,
+                &camera_binding.bind_group_layout,
+                surface_format,
+                component_id.clone(),
+                Some(config),
+                None,
+            );
+            if let Some(water_plane) = renderer_state.water_planes.iter_mut().find(|w| w.id == component_id) {
+                water_plane.transform.update_position(args.position);
+                water_plane.transform.update_scale(scale);
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id.clone(),
+                        kind: Some(ComponentKind::WaterPlane),
+                        asset_id: "".to_string(),
+                        generic_properties: GenericProperties {
+                            name: "Water".to_string(),
+                            position: args.position,
+                            rotation: [0.0, 0.0, 0.0],
+                            scale,
+                        },
+                        water_properties: Some(config),
+                        ..Default::default()
+                    };
+                    level.components.get_or_insert_with(Vec::new).push(new_component);
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "componentId": component_id,
+                "position": args.position,
+                "width": width,
+                "length": length,
+            }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(message),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoveWaterPlaneArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>,
+}
+
+async fn remove_water_plane(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Removing water plane...");
+    let args: Result<RemoveWaterPlaneArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut removed = false;
+
+            // Update RendererState, dropping the plane releases its GPU buffers
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                let index = match &args.component_id {
+                    Some(target_id) => renderer_state.water_planes.iter().position(|w| &w.id == target_id),
+                    None => if renderer_state.water_planes.is_empty() { None } else { Some(0) },
+                };
+                if let Some(index) = index {
+                    renderer_state.water_planes.remove(index);
+                    removed = true;
+                }
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if let Some(components) = level.components.as_mut() {
+                        let index = components.iter().position(|c| {
+                            c.kind == Some(ComponentKind::WaterPlane)
+                                && args.component_id.as_ref().map_or(true, |target_id| &c.id == target_id)
+                        });
+                        if let Some(index) = index {
+                            components.remove(index);
+                            removed = true;
+                        }
+                    }
+                }
+                if removed {
+                    saved_state_clone = Some(saved_state.clone());
+                }
+            }
+
+            ToolResult::ok()
+                .with_message(if removed { "Water plane removed" } else { "No water plane to remove" })
+                .with_data(serde_json::json!({ "removed": removed }))
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureSkyArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>,
+    horizon_color: Option<[f32; 3]>,
+    zenith_color: Option<[f32; 3]>,
+    sun_direction: Option<[f32; 3]>,
+    sun_color: Option<[f32; 3]>,
+    sun_intensity: Option<f32>,
+    preset: Option<String>, // "clear day", "sunset", "overcast", "night" or "alien", the fields above override it
+}
+
+async fn configure_sky(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring sky...");
+    let args: Result<ConfigureSkyArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let preset = match args.preset.as_deref() {
+            Some(label) => match sky_preset(label) {
+                Some(preset) => Some(preset),
+                None => return ToolResult::error(format!(
+                    "Unknown sky preset {}, expected {}",
+                    label,
+                    SKY_PRESETS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+                )),
+            },
+            None => None,
+        };
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if level.procedural_sky.is_none() {
+                        level.procedural_sky = Some(entropy_engine::helpers::saved_data::ProceduralSkyConfig::default());
+                    }
+                    if let Some(sky) = level.procedural_sky.as_mut() {
+                        if let Some(preset) = preset {
+                            sky.horizon_color = preset.horizon_color;
+                            sky.zenith_color = preset.zenith_color;
+                            sky.sun_direction = preset.sun_direction;
+                            sky.sun_color = preset.sun_color;
+                            sky.sun_intensity = preset.sun_intensity;
+                        }
+                        if let Some(color) = args.horizon_color { sky.horizon_color = color; }
+                        if let Some(color) = args.zenith_color { sky.zenith_color = color; }
+                        if let Some(dir) = args.sun_direction { sky.sun_direction = dir; }
+                        if let Some(color) = args.sun_color { sky.sun_color = color; }
+                        if let Some(intensity) = args.sun_intensity { sky.sun_intensity = intensity; }
+
+                        tool_result = ToolResult::ok().with_data(serde_json::json!({
+                            "preset": args.preset,
+                            "horizon_color": sky.horizon_color,
+                            "zenith_color": sky.zenith_color,
+                            "sun_direction": sky.sun_direction,
+                            "sun_color": sky.sun_color,
+                            "sun_intensity": sky.sun_intensity,
+                        }));
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureDayNightCycleArgs {
+    enabled: Option<bool>,
+    #[serde(rename = "cycleLength")]
+    cycle_length: Option<f32>, // seconds for a full day
+    #[serde(rename = "startTime")]
+    start_time: Option<f32>, // hour 0-24 the cycle starts from
+    #[serde(rename = "runInPlayMode")]
+    run_in_play_mode: Option<bool>,
+    #[serde(rename = "pauseAt")]
+    pause_at: Option<f32>, // hour 0-24 to hold, e.g. for screenshots
+    resume: Option<bool>, // clears pauseAt
+}
+
+async fn configure_day_night_cycle(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring day/night cycle...");
+    let args: Result<ConfigureDayNightCycleArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let mut notes = Vec::new();
+            let mut cycle = None;
+
+            // Update SavedState, the preview picks it up on the next frame
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let config = level.day_night_cycle.get_or_insert_with(|| DayNightCycleConfig {
+                        enabled: true,
+                        cycle_length: day_night::DEFAULT_CYCLE_LENGTH,
+                        start_time: day_night::DEFAULT_START_TIME,
+                        ..Default::default()
+                    });
+                    if let Some(enabled) = args.enabled { config.enabled = enabled; }
+                    if let Some(length) = args.cycle_length.and_then(|v| DAY_LENGTH.check("cycleLength", v, &mut notes)) { config.cycle_length = length; }
+                    if let Some(hour) = args.start_time.and_then(|v| TIME_OF_DAY.check("startTime", v, &mut notes)) { config.start_time = hour; }
+                    if let Some(run) = args.run_in_play_mode { config.run_in_play_mode = run; }
+                    if args.resume.unwrap_or(false) { config.paused_at = None; }
+                    if let Some(hour) = args.pause_at.and_then(|v| TIME_OF_DAY.check("pauseAt", v, &mut notes)) { config.paused_at = Some(hour); }
+                    cycle = Some(config.clone());
+                    // Runs from its start time again, whether it was just switched on or retimed
+                    day_night::restart();
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Switching the cycle off puts the level's own sky back
+            if cycle.as_ref().is_some_and(|config| !config.enabled) {
+                let sky = editor.saved_state.as_ref()
+                    .and_then(|saved_state| saved_state.levels.as_ref())
+                    .and_then(|levels| levels.get(0))
+                    .and_then(|level| level.procedural_sky.clone())
+                    .unwrap_or_default();
+                if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                    renderer_state.update_procedural_sky(&gpu_resources.queue, &sky);
+                }
+            }
+
+            if let Some(config) = cycle {
+                tool_result = ToolResult::ok().with_data(serde_json::json!({
+                    "enabled": config.enabled,
+                    "cycleLength": config.cycle_length,
+                    "startTime": config.start_time,
+                    "runInPlayMode": config.run_in_play_mode,
+                    "pausedAt": config.paused_at,
+                }));
+                if let Some(message) = notes_message(&notes) {
+                    tool_result = tool_result.with_message(message);
+                }
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureWeatherArgs {
+    r#type: Option<String>, // "none", "rain" or "snow"
+    intensity: Option<f32>, // 0-1
+    #[serde(rename = "windCoupling")]
+    wind_coupling: Option<f32>, // 0-1, how much the grass wind pushes the particles
+    #[serde(rename = "darkenSky")]
+    darken_sky: Option<bool>,
+}
+
+async fn configure_weather(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring weather...");
+    let args: Result<ConfigureWeatherArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let kind = match args.r#type.as_deref() {
+            Some(label) => match weather::kind_from_label(label) {
+                Some(kind) => Some(kind),
+                None => return ToolResult::error(format!("Unknown weather type {}, expected none, rain or snow", label)),
+            },
+            None => None,
+        };
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let mut notes = Vec::new();
+            let mut updated = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let config = level.weather.get_or_insert_with(WeatherConfig::default);
+                    if let Some(kind) = kind { config.kind = kind; }
+                    if let Some(val) = args.intensity.and_then(|v| WEATHER_INTENSITY.check("intensity", v, &mut notes)) { config.intensity = val; }
+                    if let Some(val) = args.wind_coupling.and_then(|v| WEATHER_WIND_COUPLING.check("windCoupling", v, &mut notes)) { config.wind_coupling = val; }
+                    if let Some(val) = args.darken_sky { config.darken_sky = val; }
+                    updated = Some(config.clone());
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Update RendererState (live)
+            weather::apply(editor);
+
+            if let Some(config) = updated {
+                tool_result = ToolResult::ok().with_data(serde_json::json!({
+                    "type": format!("{:?}", config.kind),
+                    "intensity": config.intensity,
+                    "windCoupling": config.wind_coupling,
+                    "darkenSky": config.darken_sky,
+                }));
+                if let Some(message) = notes_message(&notes) {
+                    tool_result = tool_result.with_message(message);
+                }
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+/// The level config for clouds, AO or fog, created on the first configure. A new effect starts switched on,
+/// since asking to configure it means wanting to see it; after that only an explicit `enabled` changes it.
+fn configure_level_effect<T: Default>(slot: &mut Option<T>, enabled: Option<bool>, enabled_field: fn(&mut T) -> &mut bool) -> &mut T {
+    let first = slot.is_none();
+    let config = slot.get_or_insert_with(T::default);
+    if let Some(enabled) = enabled.or(first.then_some(true)) {
+        *enabled_field(config) = enabled;
+    }
+    config
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureCloudsArgs {
+    enabled: Option<bool>, // turning them on is the default when other settings are given
+    coverage: Option<f32>, // 0-1 share of the sky
+    density: Option<f32>, // 0-1, thin wisps to heavy overcast
+    altitude: Option<f32>, // meters
+    speed: Option<f32>, // meters per second drift
+}
+
+async fn configure_clouds(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring clouds...");
+    let args: Result<ConfigureCloudsArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let mut notes = Vec::new();
+            let mut updated = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let config = configure_level_effect(&mut level.clouds, args.enabled, |c| &mut c.enabled);
+                    if let Some(val) = args.coverage.and_then(|v| CLOUD_COVERAGE.check("coverage", v, &mut notes)) { config.coverage = val; }
+                    if let Some(val) = args.density.and_then(|v| CLOUD_DENSITY.check("density", v, &mut notes)) { config.density = val; }
+                    if let Some(val) = args.altitude.and_then(|v| CLOUD_ALTITUDE.check("altitude", v, &mut notes)) { config.altitude = val; }
+                    if let Some(val) = args.speed.and_then(|v| CLOUD_SPEED.check("speed", v, &mut notes)) { config.speed = val; }
+                    updated = Some(config.clone());
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Update RendererState (live)
+            if let Some(config) = updated.as_ref() {
+                if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                    renderer_state.update_clouds(&gpu_resources.device, &gpu_resources.queue, config);
+                }
+            }
+
+            if let Some(config) = updated {
+                let mut messages: Vec<String> = notes_message(&notes).into_iter().collect();
+                if config.enabled {
+                    messages.push("Clouds are raymarched every frame and can cost several milliseconds on slower GPUs, turn them off if the preview stutters.".to_string());
+                }
+                tool_result = ToolResult::ok().with_data(serde_json::json!({
+                    "enabled": config.enabled,
+                    "coverage": config.coverage,
+                    "density": config.density,
+                    "altitude": config.altitude,
+                    "speed": config.speed,
+                }));
+                if !messages.is_empty() {
+                    tool_result = tool_result.with_message(messages.join(" "));
+                }
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureAOArgs {
+    enabled: Option<bool>, // turning it on is the default when other settings are given
+    radius: Option<f32>, // meters sampled around each pixel
+    intensity: Option<f32>, // how dark creases get
+}
+
+async fn configure_ao(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring ambient occlusion...");
+    let args: Result<ConfigureAOArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let mut updated = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let config = configure_level_effect(&mut level.ambient_occlusion, args.enabled, |c| &mut c.enabled);
+                    if let Some(val) = args.radius.and_then(|v| AO_RADIUS.check("radius", v, &mut notes)) { config.radius = val; }
+                    if let Some(val) = args.intensity.and_then(|v| AO_INTENSITY.check("intensity", v, &mut notes)) { config.intensity = val; }
+                    updated = Some(config.clone());
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let Some(config) = updated else {
+                return ToolResult::error("No level to configure");
+            };
+
+            // Update RendererState (live)
+            if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                renderer_state.update_ambient_occlusion(&gpu_resources.device, &gpu_resources.queue, &config);
+            }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "enabled": config.enabled,
+                "radius": config.radius,
+                "intensity": config.intensity,
+            }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(message),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureFogArgs {
+    enabled: Option<bool>, // turning it on is the default when other settings are given
+    color: Option<[f32; 3]>,
+    #[serde(rename = "distanceNear")]
+    distance_near: Option<f32>, // meters where distance haze starts
+    #[serde(rename = "distanceFar")]
+    distance_far: Option<f32>, // meters where it's fully fogged
+    #[serde(rename = "distanceDensity")]
+    distance_density: Option<f32>, // 0-1, strength of the haze at the far distance
+    #[serde(rename = "groundCeiling")]
+    ground_ceiling: Option<f32>, // world height the ground fog thins out to nothing at
+    #[serde(rename = "groundDensity")]
+    ground_density: Option<f32>, // 0-1, 0 turns ground fog off
+}
+
+async fn configure_fog(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring fog...");
+    let args: Result<ConfigureFogArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let mut updated = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let config = configure_level_effect(&mut level.fog, args.enabled, |c| &mut c.enabled);
+                    if let Some(color) = args.color.and_then(|c| COLOR_CHANNEL.check_each("color", c, &mut notes)) { config.color = color; }
+                    if let Some(val) = args.distance_near.and_then(|v| FOG_DISTANCE.check("distanceNear", v, &mut notes)) { config.distance_near = val; }
+                    if let Some(val) = args.distance_far.and_then(|v| FOG_DISTANCE.check("distanceFar", v, &mut notes)) { config.distance_far = val; }
+                    if let Some(val) = args.distance_density.and_then(|v| FOG_DENSITY.check("distanceDensity", v, &mut notes)) { config.distance_density = val; }
+                    if let Some(val) = args.ground_ceiling.and_then(|v| FOG_CEILING.check("groundCeiling", v, &mut notes)) { config.ground_ceiling = val; }
+                    if let Some(val) = args.ground_density.and_then(|v| FOG_DENSITY.check("groundDensity", v, &mut notes)) { config.ground_density = val; }
+                    // Keep the haze band the right way round
+                    if config.distance_far < config.distance_near {
+                        std::mem::swap(&mut config.distance_near, &mut config.distance_far);
+                    }
+                    updated = Some(config.clone());
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let Some(config) = updated else {
+                return ToolResult::error("No level to configure");
+            };
+
+            // Update RendererState (live)
+            if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                renderer_state.update_fog(&gpu_resources.queue, &config);
+            }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "enabled": config.enabled,
+                "color": config.color,
+                "distanceNear": config.distance_near,
+                "distanceFar": config.distance_far,
+                "distanceDensity": config.distance_density,
+                "groundCeiling": config.ground_ceiling,
+                "groundDensity": config.ground_density,
+            }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(message),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigurePostProcessingArgs {
+    bloom_intensity: Option<f32>,
+    bloom_threshold: Option<f32>,
+    exposure: Option<f32>,
+    tonemapping: Option<String>, // "None", "Reinhard", "ACES", "Filmic"
+    vignette: Option<f32>,
+}
+
+async fn configure_post_processing(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring post processing...");
+    let args: Result<ConfigurePostProcessingArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let tonemapping = match tonemap_arg(args.tonemapping.as_deref()) {
+            Ok(curve) => curve,
+            Err(e) => return ToolResult::error(e),
+        };
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let mut new_config = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if level.post_processing.is_none() {
+                        level.post_processing = Some(PostProcessConfig::default());
+                    }
+                    if let Some(config) = level.post_processing.as_mut() {
+                        if let Some(val) = args.bloom_intensity.and_then(|v| BLOOM_INTENSITY.check("bloom_intensity", v, &mut notes)) { config.bloom_intensity = val; }
+                        if let Some(val) = args.bloom_threshold.and_then(|v| BLOOM_THRESHOLD.check("bloom_threshold", v, &mut notes)) { config.bloom_threshold = val; }
+                        if let Some(val) = args.exposure.and_then(|v| EXPOSURE.check("exposure", v, &mut notes)) { config.exposure = val; }
+                        if let Some(val) = args.vignette.and_then(|v| VIGNETTE.check("vignette", v, &mut notes)) { config.vignette = val; }
+                        if let Some(curve) = tonemapping { config.tonemapping = curve; }
+                        new_config = Some(config.clone());
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let Some(config) = new_config else {
+                return ToolResult::error("No level to configure");
+            };
+
+            // Update RendererState (live update)
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                let queue = &editor.gpu_resources.as_ref().unwrap().queue;
+                renderer_state.update_post_processing(queue, &config);
+            }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "bloom_intensity": config.bloom_intensity,
+                "bloom_threshold": config.bloom_threshold,
+                "exposure": config.exposure,
+                "tonemapping": format!("{:?}", config.tonemapping),
+                "vignette": config.vignette,
+            }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(message),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureColorGradeArgs {
+    exposure: Option<f32>, // multiplier before tonemapping, shared with post processing
+    contrast: Option<f32>, // 1 is neutral
+    saturation: Option<f32>, // 1 is neutral, 0 is grayscale
+    temperature: Option<f32>, // -1 cool to 1 warm
+    tint: Option<f32>, // -1 green to 1 magenta
+    tonemapping: Option<String>, // "None", "Reinhard", "ACES", "Filmic"
+}
+
+async fn configure_color_grade(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring color grade...");
+    let args: Result<ConfigureColorGradeArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let tonemapping = match tonemap_arg(args.tonemapping.as_deref()) {
+            Ok(curve) => curve,
+            Err(e) => return ToolResult::error(e),
+        };
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let mut updated = None;
+
+            // Update SavedState, exposure and the tonemap curve live with the rest of the post processing
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let post = level.post_processing.get_or_insert_with(PostProcessConfig::default);
+                    if let Some(val) = args.exposure.and_then(|v| EXPOSURE.check("exposure", v, &mut notes)) { post.exposure = val; }
+                    if let Some(curve) = tonemapping { post.tonemapping = curve; }
+                    let post = post.clone();
+
+                    let grade = level.color_grade.get_or_insert_with(ColorGradeConfig::default);
+                    if let Some(val) = args.contrast.and_then(|v| CONTRAST.check("contrast", v, &mut notes)) { grade.contrast = val; }
+                    if let Some(val) = args.saturation.and_then(|v| SATURATION.check("saturation", v, &mut notes)) { grade.saturation = val; }
+                    if let Some(val) = args.temperature.and_then(|v| COLOR_TEMPERATURE.check("temperature", v, &mut notes)) { grade.temperature = val; }
+                    if let Some(val) = args.tint.and_then(|v| COLOR_TINT.check("tint", v, &mut notes)) { grade.tint = val; }
+                    updated = Some((post, grade.clone()));
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let Some((post, grade)) = updated else {
+                return ToolResult::error("No level to configure");
+            };
+
+            // Update RendererState (live), the grade is the last pass after tonemapping
+            if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                renderer_state.update_post_processing(&gpu_resources.queue, &post);
+                renderer_state.update_color_grade(&gpu_resources.queue, &grade);
+            }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "exposure": post.exposure,
+                "tonemapping": format!("{:?}", post.tonemapping),
+                "contrast": grade.contrast,
+                "saturation": grade.saturation,
+                "temperature": grade.temperature,
+                "tint": grade.tint,
+            }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(message),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetAmbientLightArgs {
+    color: Option<[f32; 3]>,
+    intensity: Option<f32>,
+}
+
+async fn set_ambient_light(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Setting ambient light...");
+    let args: Result<SetAmbientLightArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let mut new_config = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if level.ambient_light.is_none() {
+                        level.ambient_light = Some(AmbientLightConfig::default());
+                    }
+                    if let Some(config) = level.ambient_light.as_mut() {
+                        if let Some(color) = args.color.and_then(|c| COLOR_CHANNEL.check_each("color", c, &mut notes)) { config.color = color; }
+                        if let Some(val) = args.intensity.and_then(|v| AMBIENT_LIGHT_INTENSITY.check("intensity", v, &mut notes)) { config.intensity = val; }
+                        new_config = Some(config.clone());
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Update RendererState (live update), added on top of the sun and point lights
+            if let Some(config) = new_config {
+                if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                    let queue = &editor.gpu_resources.as_ref().unwrap().queue;
+                    renderer_state.update_ambient_light(queue, &config);
+                }
+            }
+            match notes_message(&notes) {
+                Some(message) => ToolResult::ok().with_message(message),
+                None => ToolResult::ok(),
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureLandscapeTexturesArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>, // first landscape when omitted
+    rock_slope_start: Option<f32>, // degrees where rock starts showing
+    rock_slope_end: Option<f32>, // degrees where it's fully rock
+    soil_height_max: Option<f32>, // world height below which soil blends in
+    tiling_scale: Option<f32>,
+    blend_sharpness: Option<f32>,
+}
+
+async fn configure_landscape_textures(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring landscape textures...");
+    let args: Result<ConfigureLandscapeTexturesArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let mut updated = None;
+
+            let mut notes = Vec::new();
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                let component = saved_state.levels.as_mut()
+                    .and_then(|l| l.get_mut(0))
+                    .and_then(|level| level.components.as_mut())
+                    .and_then(|components| components.iter_mut().find(|c| {
+                        c.kind == Some(ComponentKind::Landscape)
+                            && args.component_id.as_ref().map_or(true, |id| &c.id == id)
+                    }));
+
+                if let Some(component) = component {
+                    let properties = component.landscape_properties.get_or_insert_with(LandscapeProperties::default);
+                    let blend = properties.texture_blend.get_or_insert_with(LandscapeTextureBlend::default);
+
+                    if let Some(val) = args.rock_slope_start.and_then(|v| SLOPE_ANGLE.check("rock_slope_start", v, &mut notes)) {
+                        blend.rock_slope_start = val;
+                    }
+                    if let Some(val) = args.rock_slope_end.and_then(|v| SLOPE_ANGLE.check("rock_slope_end", v, &mut notes)) {
+                        blend.rock_slope_end = val;
+                    }
+                    // Keep the slope band the right way round
+                    order_range(
+                        "rock_slope",
+                        &mut blend.rock_slope_start,
+                        &mut blend.rock_slope_end,
+                        args.rock_slope_end.is_some(),
+                        &mut notes,
+                    );
+                    if let Some(val) = args.soil_height_max.and_then(|v| TERRAIN_HEIGHT.check("soil_height_max", v, &mut notes)) {
+                        blend.soil_height_max = val;
+                    }
+                    if let Some(val) = args.tiling_scale.and_then(|v| LANDSCAPE_TILING_SCALE.check("tiling_scale", v, &mut notes)) {
+                        blend.tiling_scale = val;
+                    }
+                    if let Some(val) = args.blend_sharpness.and_then(|v| LANDSCAPE_BLEND_SHARPNESS.check("blend_sharpness", v, &mut notes)) {
+                        blend.blend_sharpness = val;
+                    }
+
+                    updated = Some((component.id.clone(), blend.clone()));
+                    saved_state_clone = Some(saved_state.clone());
+                }
+            }
+
+            match updated {
+                Some((component_id, blend)) => {
+                    // Update RendererState (live update)
+                    if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                        let queue = &editor.gpu_resources.as_ref().unwrap().queue;
+                        if let Some(landscape) = renderer_state.landscapes.iter_mut().find(|l| l.id == component_id) {
+                            landscape.update_texture_blend(queue, &blend);
+                        }
+                    }
+                    let result = ToolResult::ok().with_data(serde_json::json!({ "componentId": component_id }));
+                    tool_result = match notes_message(&notes) {
+                        Some(message) => result.with_message(message),
+                        None => result,
+                    };
+                }
+                None => {
+                    tool_result = match &args.component_id {
+                        Some(id) => ToolResult::error(format!("Landscape {} not found", id)),
+                        None => ToolResult::error("No landscape found to configure"),
+                    };
+                }
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureParticleArgs {
+    #[serde(rename = "componentId")]
+    component_id: String,
+    emission_rate: Option<f32>, // particles per second
+    lifetime: Option<f32>, // seconds
+    start_color: Option<[f32; 4]>,
+    end_color: Option<[f32; 4]>,
+    size_curve: Option<Vec<f32>>, // sizes sampled evenly over each particle's life
+    gravity: Option<[f32; 3]>,
+}
+
+async fn configure_particle(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring particle emitter...");
+    let args: Result<ConfigureParticleArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut updated = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                let component = saved_state.levels.as_mut()
+                    .and_then(|l| l.get_mut(0))
+                    .and_then(|level| level.components.as_mut())
+                    .and_then(|components| components.iter_mut().find(|c| c.id == args.component_id));
+
+                match component {
+                    Some(component) if component.kind == Some(ComponentKind::ParticleEffect) => {
+                        let properties = component.particle_properties.get_or_insert_with(ParticleProperties::default);
+
+                        if let Some(val) = args.emission_rate { properties.emission_rate = val.max(0.0); }
+                        if let Some(val) = args.lifetime { properties.lifetime = val.max(0.01); }
+                        if let Some(val) = args.start_color { properties.start_color = val; }
+                        if let Some(val) = args.end_color { properties.end_color = val; }
+                        if let Some(val) = args.size_curve.filter(|curve| !curve.is_empty()) {
+                            properties.size_curve = val.into_iter().map(|size| size.max(0.0)).collect();
+                        }
+                        if let Some(val) = args.gravity { properties.gravity = val; }
+
+                        updated = Some(properties.clone());
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                    Some(_) => {
+                        return ToolResult::error(format!("{} is not a particle effect", args.component_id));
+                    }
+                    None => {
+                        return ToolResult::error(format!("Component {} not found", args.component_id));
+                    }
+                }
+            }
+
+            // Update RendererState (live update)
+            if let Some(properties) = updated {
+                if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                    let queue = &editor.gpu_resources.as_ref().unwrap().queue;
+                    if let Some(emitter) = renderer_state.particle_emitters.iter_mut().find(|e| e.id == args.component_id) {
+                        emitter.update_config(queue, &properties);
+                    }
+                }
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureTreesArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>,
+    seed: Option<u32>,
+    trunk_height: Option<f32>,
+    trunk_radius: Option<f32>,
+    branch_levels: Option<u32>,
+    foliage_radius: Option<f32>,
+}
+
+async fn configure_trees(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring trees...");
+    let args: Result<ConfigureTreesArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut new_tree_props = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if let Some(components) = level.components.as_mut() {
+
+                        let mut found = false;
+                        for component in components.iter_mut() {
+                            if component.kind == Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralTree) {
+                                if let Some(target_id) = &args.component_id {
+                                    if &component.id != target_id {
+                                        continue;
+                                    }
+                                }
+
+                                if component.procedural_tree_properties.is_none() {
+                                    component.procedural_tree_properties = Some(entropy_engine::helpers::saved_data::ProceduralTreeProperties::default());
+                                }
+                                if let Some(props) = component.procedural_tree_properties.as_mut() {
+                                    if let Some(val) = args.seed { props.seed = val; }
+                                    if let Some(val) = args.trunk_height { props.trunk_height = val; }
+                                    if let Some(val) = args.trunk_radius { props.trunk_radius = val; }
+                                    if let Some(val) = args.branch_levels { props.branch_levels = val; }
+                                    if let Some(val) = args.foliage_radius { props.foliage_radius = val; }
+                                    new_tree_props = Some(props.clone());
+                                }
+                                found = true;
+                                break; 
+                            }
+                        }
+
+                        if !found && args.component_id.is_none() {
+                            let props = entropy_engine::helpers::saved_data::ProceduralTreeProperties {
+                                seed: args.seed.unwrap_or(0),
+                                trunk_height: args.trunk_height.unwrap_or(3.5),
+                                trunk_radius: args.trunk_radius.unwrap_or(0.25),
+                                branch_levels: args.branch_levels.unwrap_or(4),
+                                foliage_radius: args.foliage_radius.unwrap_or(0.5),
+                            };
+
+                            let new_component = ComponentData {
+                                id: Uuid::new_v4().to_string(),
+                                kind: Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralTree),
+                                asset_id: "".to_string(),
+                                procedural_tree_properties: Some(props.clone()),
+                                ..Default::default()
+                            };
+                            components.push(new_component);
+                            new_tree_props = Some(props);
+                            log!("Created new tree component in saved state.");
+                        }
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Update RendererState (live update)
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                if let Some(new_props) = new_tree_props {
+                    // For now, update ALL trees since we don't have ID mapping easily accessible in renderer_state yet
+                    // Or assume single tree system per level
+                    for trees in &mut renderer_state.procedural_trees {
+                        let device = &editor.gpu_resources.as_ref().unwrap().device;
+                        trees.regenerate(device, new_props.clone());
+                    }
+                }
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpawnForestArgs {
+    component_id: Option<String>, // existing forest to regenerate, unset fields keep their values
+    center: Option<[f32; 3]>,
+    radius: Option<f32>,
+    count: Option<u32>,
+    seed: Option<u32>,
+    size_variance: Option<f32>, // 0-1, how much tree sizes vary around 1
+    snap_to_terrain: Option<bool>, // defaults to true
+    // Tree shape, as in configureTrees
+    trunk_height: Option<f32>,
+    trunk_radius: Option<f32>,
+    branch_levels: Option<u32>,
+    foliage_radius: Option<f32>,
+}
+
+async fn spawn_forest(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning forest...");
+    let args: Result<SpawnForestArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let mut notes = Vec::new();
+        let radius = args.radius.and_then(|v| FOREST_RADIUS.check("radius", v, &mut notes));
+        let count = args.count.and_then(|v| FOREST_TREE_COUNT.check("count", v as f32, &mut notes)).map(|v| v.round() as u32);
+        let size_variance = args.size_variance.and_then(|v| FOREST_SIZE_VARIANCE.check("sizeVariance", v, &mut notes));
+        let trunk_height = args.trunk_height.and_then(|v| TREE_TRUNK_HEIGHT.check("trunkHeight", v, &mut notes));
+        let trunk_radius = args.trunk_radius.and_then(|v| TREE_TRUNK_RADIUS.check("trunkRadius", v, &mut notes));
+        let branch_levels = args.branch_levels.and_then(|v| TREE_BRANCH_LEVELS.check("branchLevels", v as f32, &mut notes)).map(|v| v.round() as u32);
+        let foliage_radius = args.foliage_radius.and_then(|v| TREE_FOLIAGE_RADIUS.check("foliageRadius", v, &mut notes));
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let existing = match args.component_id.as_ref() {
+                Some(component_id) => {
+                    let component = editor.saved_state.as_ref()
+                        .and_then(|saved_state| saved_state.levels.as_ref())
+                        .and_then(|levels| levels.get(0))
+                        .and_then(|level| level.components.as_ref())
+                        .and_then(|components| components.iter().find(|c| &c.id == component_id && c.kind == Some(ComponentKind::Forest)))
+                        .cloned();
+                    match component {
+                        Some(component) => Some(component),
+                        None => return ToolResult::error(format!("Forest {} not found", component_id)),
+                    }
+                }
+                None => None,
+            };
+
+            let mut forest = existing.as_ref().and_then(|c| c.forest_properties.clone()).unwrap_or_else(|| ForestProperties {
+                radius: forest::DEFAULT_RADIUS,
+                count: forest::DEFAULT_TREE_COUNT,
+                size_variance: forest::DEFAULT_SIZE_VARIANCE,
+                snap_to_terrain: true,
+                ..Default::default()
+            });
+            if let Some(center) = args.center { forest.center = center; }
+            if let Some(radius) = radius { forest.radius = radius; }
+            if let Some(count) = count { forest.count = count; }
+            if let Some(seed) = args.seed { forest.seed = seed; }
+            if let Some(variance) = size_variance { forest.size_variance = variance; }
+            if let Some(snap) = args.snap_to_terrain { forest.snap_to_terrain = snap; }
+
+            let mut tree = existing.as_ref().and_then(|c| c.procedural_tree_properties.clone()).unwrap_or_else(|| ProceduralTreeProperties {
+                seed: 0,
+                trunk_height: 3.5,
+                trunk_radius: 0.25,
+                branch_levels: 4,
+                foliage_radius: 0.5,
+            });
+            if let Some(val) = trunk_height { tree.trunk_height = val; }
+            if let Some(val) = trunk_radius { tree.trunk_radius = val; }
+            if let Some(val) = branch_levels { tree.branch_levels = val; }
+            if let Some(val) = foliage_radius { tree.foliage_radius = val; }
+
+            let landscape = editor.renderer_state.as_ref().and_then(|renderer_state| renderer_state.landscapes.first());
+            forest.transforms = forest::scatter(&forest, |x, z| landscape.and_then(|landscape| landscape.get_height_at(x, z)));
+
+            let component_id = existing.as_ref().map(|c| c.id.clone()).unwrap_or_else(|| Uuid::new_v4().to_string());
+
+            // One tree mesh drawn once per transform. Regenerating replaces the old layout
+            if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                renderer_state.forests.retain(|f| f.id != component_id);
+                handle_add_forest(renderer_state, &gpu_resources.device, &gpu_resources.queue, component_id.clone(), &tree, &forest.transforms);
+            }
+
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let components = level.components.get_or_insert_with(Vec::new);
+                    match components.iter_mut().find(|c| c.id == component_id) {
+                        Some(component) => {
+                            component.generic_properties.position = forest.center;
+                            component.forest_properties = Some(forest.clone());
+                            component.procedural_tree_properties = Some(tree.clone());
+                        }
+                        None => components.push(ComponentData {
+                            id: component_id.clone(),
+                            kind: Some(ComponentKind::Forest),
+                            generic_properties: GenericProperties {
+                                name: "New Forest".to_string(),
+                                position: forest.center,
+                                rotation: [0.0, 0.0, 0.0],
+                                scale: [1.0, 1.0, 1.0],
+                            },
+                            forest_properties: Some(forest.clone()),
+                            procedural_tree_properties: Some(tree.clone()),
+                            ..Default::default()
+                        }),
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let result = ToolResult::ok()
+                .with_message(format!("Planted {} trees", forest.transforms.len()))
+                .with_data(serde_json::json!({
+                    "componentId": component_id,
+                    "treeCount": forest.transforms.len(),
+                    "center": forest.center,
+                    "radius": forest.radius,
+                    "seed": forest.seed,
+                    "sizeVariance": forest.size_variance,
+                    "snapToTerrain": forest.snap_to_terrain,
+                }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(format!("Planted {} trees. {}", forest.transforms.len(), message)),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnModelArgs {
+    #[serde(rename = "assetId")]
+    asset_id: String,
+    position: Option<[f32; 3]>,
+    rotation: Option<[f32; 3]>,
+    scale: Option<[f32; 3]>,
+}
+
+async fn spawn_model(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning model...");
+    let args: Result<SpawnModelArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        if args.asset_id.trim().is_empty() {
+            return ToolResult::error("assetId is required, pick a model from the project's assets");
+        }
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            // let project_id = editor.project_id.clone();
+            let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+            let pos = args.position.unwrap_or([0.0, 0.0, 0.0]);
+            let rot = args.rotation.unwrap_or([0.0, 0.0, 0.0]);
+            let scale = args.scale.unwrap_or([1.0, 1.0, 1.0]);
+
+            match spawn_model_component(editor, &project_id, &args.asset_id, pos, rot, scale).await {
+                Ok(component) => {
+                    saved_state_clone = editor.saved_state.clone();
+                    ToolResult::ok().with_data(serde_json::json!({ "componentId": component.id }))
+                }
+                Err(e) => ToolResult::error(e),
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstanceTransformArgs {
+    position: [f32; 3],
+    rotation: Option<[f32; 3]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstanceModelArgs {
+    #[serde(rename = "assetId")]
+    asset_id: Option<String>,
+    #[serde(rename = "componentId")]
+    component_id: Option<String>, // existing instanced model to add to / remove from
+    add: Option<Vec<InstanceTransformArgs>>,
+    remove: Option<Vec<usize>>, // instance indices
+    force: Option<bool>, // edit even if locked
+}
+
+async fn instance_model(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Instancing model...");
+    let args: Result<InstanceModelArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+            let added: Vec<InstanceTransform> = args.add.unwrap_or_default().into_iter().map(|t| InstanceTransform {
+                position: t.position,
+                rotation: t.rotation.unwrap_or([0.0, 0.0, 0.0]),
+                scale: t.scale.unwrap_or([1.0, 1.0, 1.0]),
+            }).collect();
+
+            if let Some(component_id) = args.component_id {
+                // Edit the transform list of an existing instanced model
+                if let Some(error) = editor.saved_state.as_ref()
+                    .and_then(|saved_state| locked_component_error(saved_state, &component_id, args.force.unwrap_or(false)))
+                {
+                    return error;
+                }
+                let mut transforms = None;
+
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                        if let Some(component) = level.components.as_mut().and_then(|c| c.iter_mut().find(|c| c.id == component_id && c.kind == Some(ComponentKind::InstancedModel))) {
+                            let properties = component.instanced_model_properties.get_or_insert_with(InstancedModelProperties::default);
+
+                            if let Some(mut remove) = args.remove {
+                                // Remove from the back so earlier indices stay valid
+                                remove.sort_unstable();
+                                remove.dedup();
+                                for index in remove.into_iter().rev() {
+                                    if index < properties.transforms.len() {
+                                        properties.transforms.remove(index);
+                                    }
+                                }
+                            }
+                            properties.transforms.extend(added);
+
+                            transforms = Some(properties.transforms.clone());
+                        }
+                    }
+                    if transforms.is_some() {
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                }
+
+                match transforms {
+                    Some(transforms) => {
+                        // Update RendererState
+                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                            let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+                            if let Some(instanced_model) = renderer_state.instanced_models.iter_mut().find(|m| m.id == component_id) {
+                                instanced_model.update_instances(&gpu_resources.device, &gpu_resources.queue, &transforms);
+                            }
+                        }
+
+                        tool_result = ToolResult::ok()
+                            .with_message(format!("Instanced model now has {} instances", transforms.len()))
+                            .with_data(serde_json::json!({ "componentId": component_id, "instanceCount": transforms.len() }));
+                    }
+                    None => {
+                        log!("Instanced model not found: {}", component_id);
+                        tool_result = ToolResult::error(format!("Instanced model {} not found", component_id));
+                    }
+                }
+            } else if let Some(asset_id) = args.asset_id {
+                let mut asset_file_name = String::new();
+
+                // Find asset filename in SavedState
+                if let Some(saved_state) = editor.saved_state.as_ref() {
+                    if let Some(model) = saved_state.models.iter().find(|m| m.id == asset_id) {
+                        asset_file_name = model.fileName.clone();
+                    }
+                }
+
+                if asset_file_name.is_empty() {
+                    log!("Asset not found: {}", asset_id);
+                    return ToolResult::error(format!("Model asset {} not found", asset_id));
+                }
+
+                let component_id = Uuid::new_v4().to_string();
+                let instance_count = added.len();
+
+                let renderer_state = editor.renderer_state.as_mut().unwrap();
+                let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+                let camera = editor.camera.as_ref().unwrap();
+
+                // One mesh, one instance buffer, one draw call
+                handle_add_instanced_model(
+                    renderer_state,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    project_id,
+                    asset_id.clone(),
+                    component_id.clone(),
+                    asset_file_name,
+                    &added,
+                    camera
+                ).await;
+
+                // Update SavedState
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                        let new_component = ComponentData {
+                            id: component_id.clone(),
+                            kind: Some(ComponentKind::InstancedModel),
+                            asset_id,
+                            generic_properties: GenericProperties {
+                                name: "New Instanced Model".to_string(),
+                                ..Default::default()
+                            },
+                            instanced_model_properties: Some(InstancedModelProperties {
+                                transforms: added,
+                            }),
+                            ..Default::default()
+                        };
+
+                        if let Some(components) = level.components.as_mut() {
+                            components.push(new_component);
+                        } else {
+                            level.components = Some(vec![new_component]);
+                        }
+                    }
+                    saved_state_clone = Some(saved_state.clone());
+                }
+
+                tool_result = ToolResult::ok()
+                    .with_message(format!("Created instanced model with {} instances", instance_count))
+                    .with_data(serde_json::json!({ "componentId": component_id, "instanceCount": instance_count }));
+            } else {
+                tool_result = ToolResult::error("Pass an assetId to create an instanced model or a componentId to edit one");
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureLODArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>, // all models when omitted
+    distances: Option<Vec<f32>>, // ascending; beyond the last one the model is culled
+    force: Option<bool>, // edit even if locked
+}
+
+async fn configure_lod(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring LOD...");
+    let args: Result<ConfigureLODArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let force = args.force.unwrap_or(false);
+            if let Some(error) = editor.saved_state.as_ref().zip(args.component_id.as_ref())
+                .and_then(|(saved_state, component_id)| locked_component_error(saved_state, component_id, force))
+            {
+                return error;
+            }
+            let requested = args.distances.map(|mut distances| {
+                distances.retain(|d| d.is_finite() && *d > 0.0);
+                distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                distances
+            });
+            let mut configured: Vec<(String, Vec<f32>)> = Vec::new();
+
+            if let (Some(saved_state), Some(renderer_state)) = (editor.saved_state.as_mut(), editor.renderer_state.as_mut()) {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if let Some(components) = level.components.as_mut() {
+                        for component in components.iter_mut() {
+                            // Locked models are left out of "all models"
+                            let targeted = match &args.component_id {
+                                Some(id) => &component.id == id,
+                                None => component.kind == Some(ComponentKind::Model) && (force || !component.locked),
+                            };
+                            if !targeted {
+                                continue;
+                            }
+
+                            let model = renderer_state.models.iter_mut().find(|m| m.id == component.id);
+                            let distances = match (&requested, &model) {
+                                (Some(distances), _) if !distances.is_empty() => distances.clone(),
+                                (_, Some(model)) => default_lod_distances(model.bounding_radius()),
+                                _ => continue,
+                            };
+
+                            // Update RendererState
+                            if let Some(model) = model {
+                                model.set_lod_distances(&distances);
+                            }
+
+                            let properties = component.model_properties.get_or_insert_with(ModelProperties::default);
+                            properties.lod_distances = Some(distances.clone());
+                            configured.push((component.id.clone(), distances));
+                        }
+                    }
+                }
+
+                if !configured.is_empty() {
+                    saved_state_clone = Some(saved_state.clone());
+                }
+            }
+
+            tool_result = if configured.is_empty() {
+                ToolResult::error(match &args.component_id {
+                    Some(id) => format!("Model {} not found", id),
+                    None => "No models to configure".to_string(),
+                })
+            } else {
+                ToolResult::ok()
+                    .with_message(format!("Configured LOD for {} models", configured.len()))
+                    .with_data(serde_json::json!({
+                        "models": configured.iter().map(|(id, distances)| serde_json::json!({ "componentId": id, "distances": distances })).collect::<Vec<_>>(),
+                    }))
+            };
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MirrorObjectArgs {
+    #[serde(rename = "componentId")]
+    component_id: String,
+    axis: String, // "x", "y" or "z"
+    plane: Option<f32>, // coordinate of the mirror plane along the axis
+}
+
+async fn mirror_object(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Mirroring object...");
+    let args: Result<MirrorObjectArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let axis = match args.axis.to_lowercase().as_str() {
+            "x" => 0,
+            "y" => 1,
+            "z" => 2,
+            _ => return ToolResult::error(format!("Unknown axis {}, expected x, y or z", args.axis)),
+        };
+        let plane = args.plane.unwrap_or(0.0);
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+
+            let source = editor.saved_state.as_ref()
+                .and_then(|saved_state| saved_state.levels.as_ref())
+                .and_then(|levels| levels.get(0))
+                .and_then(|level| level.components.as_ref())
+                .and_then(|components| components.iter().find(|c| c.id == args.component_id).cloned());
+
+            let source = match source {
+                Some(source) if source.kind == Some(ComponentKind::Model) => source,
+                Some(_) => return ToolResult::error("Only models can be mirrored"),
+                None => return ToolResult::error(format!("Component {} not found", args.component_id)),
+            };
+
+            let asset_file_name = editor.saved_state.as_ref()
+                .and_then(|saved_state| saved_state.models.iter().find(|m| m.id == source.asset_id))
+                .map(|model| model.fileName.clone());
+
+            let asset_file_name = match asset_file_name {
+                Some(name) => name,
+                None => return ToolResult::error(format!("Model asset {} not found", source.asset_id)),
+            };
+
+            let component_id = Uuid::new_v4().to_string();
+            let (pos, rot) = mirror_transform(
+                source.generic_properties.position,
+                source.generic_properties.rotation,
+                axis,
+                plane
+            );
+            let scale = source.generic_properties.scale;
+
+            let model_position = Translation3::new(pos[0], pos[1], pos[2]);
+            let model_rotation = UnitQuaternion::from_euler_angles(
+                rot[0].to_radians(), rot[1].to_radians(), rot[2].to_radians()
+            );
+            let model_iso = Isometry3::from_parts(model_position, model_rotation);
+            let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
+
+            let renderer_state = editor.renderer_state.as_mut().unwrap();
+            let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+            let camera = editor.camera.as_ref().unwrap();
+
+            handle_add_model(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                project_id,
+                source.asset_id.clone(),
+                component_id.clone(),
+                asset_file_name,
+                model_iso,
+                model_scale,
+                camera,
+                None // Script state
+            ).await;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id.clone(),
+                        generic_properties: GenericProperties {
+                            name: format!("{} (Mirrored)", source.generic_properties.name),
+                            position: pos,
+                            rotation: rot,
+                            scale,
+                        },
+                        ..source
+                    };
+
+                    if let Some(components) = level.components.as_mut() {
+                        components.push(new_component);
+                    } else {
+                        level.components = Some(vec![new_component]);
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            ToolResult::ok().with_data(serde_json::json!({ "componentId": component_id }))
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DuplicateObjectArgs {
+    #[serde(rename = "componentId")]
+    component_id: String,
+    offset: Option<[f32; 3]>, // from the original, defaults to 2 along X
+}
+
+async fn duplicate_object(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Duplicating object...");
+    let args: Result<DuplicateObjectArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+            let offset = args.offset.unwrap_or(DUPLICATE_OFFSET);
+
+            match duplicate_component(editor, &project_id, &args.component_id, offset).await {
+                Ok(copy) => {
+                    tool_result = ToolResult::ok().with_data(serde_json::json!({
+                        "componentId": copy.id,
+                        "name": copy.generic_properties.name,
+                        "position": copy.generic_properties.position,
+                    }));
+                    if let Some(saved_state) = editor.saved_state.as_ref() {
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                }
+                Err(e) => return ToolResult::error(e),
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DeleteObjectArgs {
+    #[serde(rename = "componentId")]
+    component_id: String,
+    force: Option<bool>, // delete even if locked
+}
+
+async fn delete_object(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Deleting object...");
+    let args: Result<DeleteObjectArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            if let Some(error) = editor.saved_state.as_ref()
+                .and_then(|saved_state| locked_component_error(saved_state, &args.component_id, args.force.unwrap_or(false)))
+            {
+                return error;
+            }
+
+            match delete_component(editor, &args.component_id) {
+                Ok(removed) => {
+                    tool_result = ToolResult::ok().with_message(format!("Deleted {}", removed.generic_properties.name));
+                    if let Some(saved_state) = editor.saved_state.as_ref() {
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                }
+                Err(e) => return ToolResult::error(e),
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetMaterialArgs {
+    component_id: String,
+    pbr_texture_id: String,
+    force: Option<bool>, // edit even if locked
+}
+
+async fn set_material(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Setting material...");
+    let args: Result<SetMaterialArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+            let mut pbr_data = None;
+            let mut is_model = false;
+
+            if let Some(error) = editor.saved_state.as_ref()
+                .and_then(|saved_state| locked_component_error(saved_state, &args.component_id, args.force.unwrap_or(false)))
+            {
+                return error;
+            }
+
+            // Validate both ids against SavedState
+            if let Some(saved_state) = editor.saved_state.as_ref() {
+                if let Some(pbr_textures) = saved_state.pbr_textures.as_ref() {
+                    pbr_data = pbr_textures.iter().find(|p| p.id == args.pbr_texture_id).cloned();
+                }
+                if let Some(components) = saved_state.levels.as_ref().and_then(|l| l.get(0)).and_then(|l| l.components.as_ref()) {
+                    is_model = components.iter().any(|c| c.id == args.component_id && c.kind == Some(ComponentKind::Model));
+                }
+            }
+
+            let pbr_data = match pbr_data {
+                Some(data) if is_model => data,
+                _ => {
+                    log!("Model component or PBR texture not found. ComponentId: {}, PbrTextureId: {}", args.component_id, args.pbr_texture_id);
+                    return ToolResult::error(format!("Model component {} or PBR texture {} not found", args.component_id, args.pbr_texture_id));
+                }
+            };
+
+            // Update RendererState
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+
+                handle_set_model_material(
+                    renderer_state,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    project_id,
+                    args.component_id.clone(),
+                    &pbr_data
+                ).await;
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    if let Some(components) = level.components.as_mut() {
+                        if let Some(component) = components.iter_mut().find(|c| c.id == args.component_id) {
+                            let properties = component.model_properties.get_or_insert_with(ModelProperties::default);
+                            properties.pbr_texture_id = Some(args.pbr_texture_id.clone());
+                        }
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnPointLightArgs {
+    position: [f32; 3],
+    color: Option<[f32; 3]>,
+    intensity: Option<f32>,
+    radius: Option<f32>,
+}
+
+async fn spawn_point_light(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning point light...");
+    let args: Result<SpawnPointLightArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let component_id = Uuid::new_v4().to_string();
+            let mut notes = Vec::new();
+            let color = args.color.and_then(|c| COLOR_CHANNEL.check_each("color", c, &mut notes)).unwrap_or([1.0, 1.0, 1.0]);
+            let intensity = args.intensity.and_then(|v| LIGHT_INTENSITY.check("intensity", v, &mut notes)).unwrap_or(1.0);
+            let radius = args.radius.and_then(|v| LIGHT_RADIUS.check("radius", v, &mut notes)).unwrap_or(10.0);
+            if let Some(message) = notes_message(&notes) {
+                tool_result = ToolResult::ok().with_message(message);
+            }
+
+            // Update RendererState
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                // Using radius as max_distance
+                renderer_state.point_lights.push(new_point_light(component_id.clone(), args.position, color, intensity, radius));
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id,
+                        kind: Some(ComponentKind::PointLight),
+                        asset_id: "".to_string(),
+                        generic_properties: GenericProperties {
+                            name: "New Light".to_string(),
+                            position: args.position,
+                            ..Default::default()
+                        },
+                        light_properties: Some(LightProperties {
+                            color: [color[0], color[1], color[2], 1.0],
+                            intensity,
+                        }),
+                        ..Default::default()
+                    };
+
+                    if let Some(components) = level.components.as_mut() {
+                        components.push(new_component);
+                    } else {
+                        level.components = Some(vec![new_component]);
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnDirectionalLightArgs {
+    name: Option<String>,
+    direction: Option<[f32; 3]>,
+    color: Option<[f32; 3]>,
+    intensity: Option<f32>,
+    cast_shadows: Option<bool>,
+}
+
+async fn spawn_directional_light(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning directional light...");
+    let args: Result<SpawnDirectionalLightArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let component_id = Uuid::new_v4().to_string();
+            let rotation = light_rotation_from_direction(args.direction.unwrap_or([-0.3, -1.0, -0.3]));
+            let mut notes = Vec::new();
+            let properties = DirectionalLightProperties {
+                color: args.color.and_then(|c| COLOR_CHANNEL.check_each("color", c, &mut notes)).unwrap_or([1.0, 1.0, 1.0]),
+                intensity: args.intensity.and_then(|v| DIRECTIONAL_LIGHT_INTENSITY.check("intensity", v, &mut notes)).unwrap_or(1.0),
+                cast_shadows: args.cast_shadows.unwrap_or(true),
+            };
+
+            // Update RendererState
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                renderer_state.directional_lights.push(DirectionalLight {
+                    id: component_id.clone(),
+                    direction: light_direction_from_rotation(rotation),
+                    color: properties.color,
+                    intensity: properties.intensity,
+                    cast_shadows: properties.cast_shadows,
+                });
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id.clone(),
+                        kind: Some(ComponentKind::DirectionalLight),
+                        asset_id: "".to_string(),
+                        generic_properties: GenericProperties {
+                            name: args.name.unwrap_or_else(|| "New Directional Light".to_string()),
+                            rotation,
+                            ..Default::default()
+                        },
+                        directional_light_properties: Some(properties),
+                        ..Default::default()
+                    };
+
+                    if let Some(components) = level.components.as_mut() {
+                        components.push(new_component);
+                    } else {
+                        level.components = Some(vec![new_component]);
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            tool_result = ToolResult::ok().with_data(serde_json::json!({ "componentId": component_id }));
+            if let Some(message) = notes_message(&notes) {
+                tool_result = tool_result.with_message(message);
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnCollectableArgs {
+    #[serde(rename = "assetId")]
+    asset_id: String,
+    r#type: String, // "Item", "MeleeWeapon", etc.
+    #[serde(rename = "statId")]
+    stat_id: Option<String>,
+    position: Option<[f32; 3]>,
+    rotation: Option<[f32; 3]>,
+    scale: Option<[f32; 3]>,
+}
+
+async fn spawn_collectable(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning collectable...");
+    let args: Result<SpawnCollectableArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            // let project_id = editor.project_id.clone();
+            let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+            let mut asset_file_name = String::new();
+            let mut stat_data = None;
+
+            let collectable_type = match args.r#type.as_str() {
+                "MeleeWeapon" => CollectableType::MeleeWeapon,
+                "RangedWeapon" => CollectableType::RangedWeapon,
+                "Armor" => CollectableType::Armor,
+                _ => CollectableType::Item,
+            };
+
+            // Find asset and a matching stat in SavedState
+            if let Some(saved_state) = editor.saved_state.as_ref() {
+                if let Some(model) = saved_state.models.iter().find(|m| m.id == args.asset_id) {
+                    asset_file_name = model.fileName.clone();
+                }
+                if let Some(stats) = &saved_state.stats {
+                    stat_data = select_collectable_stat(stats, &collectable_type, args.stat_id.as_deref());
+                }
+            }
+
+            if asset_file_name.is_empty() {
+                log!("Asset not found for collectable. AssetId: {}", args.asset_id);
+                return ToolResult::error(format!("Model asset {} not found", args.asset_id));
+            }
+
+            let related_stat = match stat_data {
+                Some(stat) => stat,
+                None => {
+                    log!("No matching stat for collectable. Type: {:?}, StatId: {:?}", collectable_type, args.stat_id);
+                    return ToolResult::error(match &args.stat_id {
+                        Some(stat_id) => format!("Stat {} not found", stat_id),
+                        None => format!("No stat matches a {:?} collectable, create one or pass a statId", collectable_type),
+                    });
+                }
+            };
+
+            let component_id = Uuid::new_v4().to_string();
+            let pos = args.position.unwrap_or([0.0, 0.0, 0.0]);
+            let rot = args.rotation.unwrap_or([0.0, 0.0, 0.0]);
+            let scale = args.scale.unwrap_or([1.0, 1.0, 1.0]);
+
+            let model_position = Translation3::new(pos[0], pos[1], pos[2]);
+            let model_rotation = UnitQuaternion::from_euler_angles(
+                rot[0].to_radians(), rot[1].to_radians(), rot[2].to_radians()
+            );
+            let model_iso = Isometry3::from_parts(model_position, model_rotation);
+            let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
+
+            tool_result = ToolResult::ok()
+                .with_message(format!("Using stat {} ({})", related_stat.name, related_stat.id))
+                .with_data(serde_json::json!({
+                    "componentId": component_id,
+                    "statId": related_stat.id,
+                    "statName": related_stat.name,
+                }));
+
+            let collectable_properties = CollectableProperties {
+                model_id: Some(component_id.clone()), // Use same ID for model part
+                collectable_type: Some(collectable_type.clone()),
+                stat_id: Some(related_stat.id.clone()),
+            };
+
+            let renderer_state = editor.renderer_state.as_mut().unwrap();
+            let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+            let camera = editor.camera.as_ref().unwrap();
+
+            handle_add_collectable(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                project_id,
+                args.asset_id.clone(),
+                component_id.clone(),
+                asset_file_name,
+                model_iso,
+                model_scale,
+                camera,
+                &collectable_properties,
+                &related_stat,
+                false, // Don't hide
+                None // Script state
+            ).await;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id,
+                        kind: Some(ComponentKind::Collectable),
+                        asset_id: args.asset_id,
+                        generic_properties: GenericProperties {
+                            name: "New Collectable".to_string(),
+                            position: pos,
+                            rotation: rot,
+                            scale: scale,
+                        },
+                        collectable_properties: Some(collectable_properties),
+                        ..Default::default()
+                    };
+
+                    if let Some(components) = level.components.as_mut() {
+                        components.push(new_component);
+                    } else {
+                        level.components = Some(vec![new_component]);
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetWeaponStatsArgs {
+    #[serde(rename = "componentId")]
+    component_id: String, // a weapon collectable
+    damage: Option<f32>,
+    range: Option<f32>, // meters
+    cooldown: Option<f32>, // seconds between attacks
+    weight: Option<f32>,
+}
+
+async fn set_weapon_stats(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Setting weapon stats...");
+    let args: Result<SetWeaponStatsArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let Some(saved_state) = editor.saved_state.as_mut() else {
+                return ToolResult::error("No project loaded");
+            };
+
+            let components = saved_state.levels.as_ref()
+                .and_then(|levels| levels.get(0))
+                .and_then(|level| level.components.as_ref());
+            let Some(component) = components.and_then(|components| components.iter().find(|c| c.id == args.component_id)) else {
+                return ToolResult::error(format!("Component {} not found", args.component_id));
+            };
+            let Some(collectable) = component.collectable_properties.as_ref().filter(|_| component.kind == Some(ComponentKind::Collectable)) else {
+                return ToolResult::error(format!("{} is not a collectable", component.generic_properties.name));
+            };
+            if !matches!(collectable.collectable_type, Some(CollectableType::MeleeWeapon | CollectableType::RangedWeapon)) {
+                return ToolResult::error(format!("{} is not a weapon", component.generic_properties.name));
+            }
+            let Some(stat_id) = collectable.stat_id.clone() else {
+                return ToolResult::error(format!("{} has no stat attached", component.generic_properties.name));
+            };
+            // Other collectables linked to the same stat change along with this one
+            let shared_with = components.map_or(0, |components| components.iter()
+                .filter(|c| c.id != args.component_id)
+                .filter(|c| c.collectable_properties.as_ref().is_some_and(|p| p.stat_id.as_deref() == Some(stat_id.as_str())))
+                .count());
+
+            let Some(stat) = saved_state.stats.as_mut().and_then(|stats| stats.iter_mut().find(|stat| stat.id == stat_id)) else {
+                return ToolResult::error(format!("Stat {} not found", stat_id));
+            };
+            let attack = stat.attack.get_or_insert_with(AttackStats::default);
+            if let Some(val) = args.damage.and_then(|v| WEAPON_DAMAGE.check("damage", v, &mut notes)) { attack.damage = val; }
+            if let Some(val) = args.range.and_then(|v| WEAPON_RANGE.check("range", v, &mut notes)) { attack.range = val; }
+            if let Some(val) = args.cooldown.and_then(|v| WEAPON_COOLDOWN.check("cooldown", v, &mut notes)) { attack.cooldown = val; }
+            if let Some(val) = args.weight.and_then(|v| WEAPON_WEIGHT.check("weight", v, &mut notes)) { stat.weight = val; }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "statId": stat.id,
+                "statName": stat.name,
+                "damage": stat.attack.as_ref().map(|a| a.damage),
+                "range": stat.attack.as_ref().map(|a| a.range),
+                "cooldown": stat.attack.as_ref().map(|a| a.cooldown),
+                "weight": stat.weight,
+            }));
+            saved_state_clone = Some(saved_state.clone());
+
+            let mut messages: Vec<String> = notes_message(&notes).into_iter().collect();
+            if shared_with > 0 {
+                messages.push(format!("{} other collectable(s) use the same stat and changed too.", shared_with));
+            }
+            if messages.is_empty() { result } else { result.with_message(messages.join(" ")) }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigureGrassArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>,
+    wind_strength: Option<f32>,
+    wind_speed: Option<f32>,
+    blade_height: Option<f32>,
+    blade_width: Option<f32>,
+    blade_density: Option<f32>, // Changing to f32 to match tool definition, will cast to u32
+    render_distance: Option<f32>,
+    density_falloff: Option<f32>, // 0-1, how much sparser grass gets towards the render distance
+    terrain_tint_strength: Option<f32>, // 0-1, how much blades take the ground's color, drier over rock and lusher near water
+}
+
+async fn configure_grass(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring grass...");
+    let args: Result<ConfigureGrassArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(mut args) = args {
+        // Validate once up front, the values go to both the live grass and saved data
+        let mut notes = Vec::new();
+        args.wind_strength = args.wind_strength.and_then(|v| GRASS_WIND_STRENGTH.check("wind_strength", v, &mut notes));
+        args.wind_speed = args.wind_speed.and_then(|v| GRASS_WIND_SPEED.check("wind_speed", v, &mut notes));
+        args.blade_height = args.blade_height.and_then(|v| GRASS_BLADE_HEIGHT.check("blade_height", v, &mut notes));
+        args.blade_width = args.blade_width.and_then(|v| GRASS_BLADE_WIDTH.check("blade_width", v, &mut notes));
+        args.blade_density = args.blade_density.and_then(|v| GRASS_BLADE_DENSITY.check("blade_density", v, &mut notes));
+        args.render_distance = args.render_distance.and_then(|v| GRASS_RENDER_DISTANCE.check("render_distance", v, &mut notes));
+        args.density_falloff = args.density_falloff.and_then(|v| GRASS_DENSITY_FALLOFF.check("density_falloff", v, &mut notes));
+        args.terrain_tint_strength = args.terrain_tint_strength.and_then(|v| GRASS_TERRAIN_TINT.check("terrain_tint_strength", v, &mut notes));
+
+         tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+
+             // Update RendererState (Live)
+             if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                  for grass in renderer_state.grasses.iter_mut() {
+                      if let Some(val) = args.wind_strength { grass.config.wind_strength = val; }
+                      if let Some(val) = args.wind_speed { grass.config.wind_speed = val; }
+                      if let Some(val) = args.blade_height { grass.config.blade_height = val; }
+                      if let Some(val) = args.blade_width { grass.config.blade_width = val; }
+                      if let Some(val) = args.blade_density { grass.config.blade_density = val; }
+                      if let Some(val) = args.render_distance { grass.config.render_distance = val; }
+                      if let Some(val) = args.density_falloff { grass.config.density_falloff = val; }
+                      if let Some(val) = args.terrain_tint_strength { grass.config.terrain_tint_strength = val; }
+                  }
+             }
+
+             // Update SavedState
+             if let Some(saved_state) = editor.saved_state.as_mut() {
+                 if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                     if let Some(components) = level.components.as_mut() {
+                         // Find existing grass
+                         let mut found = false;
+                         for component in components.iter_mut() {
+                             if component.kind == Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralGrass) {
+                                 if let Some(target_id) = &args.component_id {
+                                     if &component.id != target_id {
+                                         continue;
+                                     }
+                                 }
+
+                                 if component.procedural_grass_properties.is_none() {
+                                     component.procedural_grass_properties = Some(entropy_engine::helpers::saved_data::ProceduralGrassProperties::default());
+                                 }
+                                 if let Some(props) = component.procedural_grass_properties.as_mut() {
+                                     if let Some(val) = args.wind_strength { props.wind_strength = val; }
+                                     if let Some(val) = args.wind_speed { props.wind_speed = val; }
+                                     if let Some(val) = args.blade_height { props.blade_height = val; }
+                                     if let Some(val) = args.blade_width { props.blade_width = val; }
+                                     if let Some(val) = args.blade_density { props.blade_density = val as u32; }
+                                     if let Some(val) = args.render_distance { props.render_distance = val; }
+                                     if let Some(val) = args.density_falloff { props.density_falloff = val; }
+                                     if let Some(val) = args.terrain_tint_strength { props.terrain_tint_strength = val; }
+                                 }
+                                 found = true;
+                             }
+                         }
+
+                         if !found && args.component_id.is_none() {
+                             let new_grass_props = entropy_engine::helpers::saved_data::ProceduralGrassProperties {
+                                 wind_strength: args.wind_strength.unwrap_or(2.5),
+                                 wind_speed: args.wind_speed.unwrap_or(0.3),
+                                 blade_height: args.blade_height.unwrap_or(2.75),
+                                 blade_width: args.blade_width.unwrap_or(0.03),
+                                 blade_density: args.blade_density.unwrap_or(15.0) as u32,
+                                 render_distance: args.render_distance.unwrap_or(150.0),
+                                 density_falloff: args.density_falloff.unwrap_or(DEFAULT_GRASS_DENSITY_FALLOFF),
+                                 terrain_tint_strength: args.terrain_tint_strength.unwrap_or(DEFAULT_GRASS_TERRAIN_TINT),
+                                 grid_size: 10.0,
+                                 brownian_strength: 0.5,
+                             };
+
+                             let new_component = ComponentData {
+                                 id: Uuid::new_v4().to_string(),
+                                 kind: Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralGrass),
+                                 asset_id: "".to_string(),
+                                 procedural_grass_properties: Some(new_grass_props),
+                                 ..Default::default()
+                             };
+                             components.push(new_component);
+                             log!("Created new grass component in saved state.");
+                         }
+                     }
+                 }
+                 saved_state_clone = Some(saved_state.clone());
+             }
+
+             // Rain and snow drift with the grass wind
+             if args.wind_strength.is_some() {
+                 weather::apply(editor);
+             }
+
+             match notes_message(&notes) {
+                 Some(message) => ToolResult::ok().with_message(message),
+                 None => ToolResult::ok(),
+             }
+         }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnPrimitiveArgs {
+    r#type: String,
+    position: [f32; 3],
+    scale: Option<[f32; 3]>,
+}
+
+async fn spawn_primitive(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning primitive...");
+    let args: Result<SpawnPrimitiveArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let device = &editor.gpu_resources.as_ref().unwrap().device;
+            let queue = &editor.gpu_resources.as_ref().unwrap().queue;
+            let model_layout = editor.model_bind_group_layout.as_ref().unwrap();
+            let group_layout = editor.group_bind_group_layout.as_ref().unwrap();
+            let camera = editor.camera.as_ref().unwrap();
+
+            // We need access to texture render mode buffer which is in RendererState or Pipeline
+            // But access via RendererState is hard because we are borrowing pipeline/editor.
+            // However, Cube::new needs it.
+            // In pipeline.rs, `texture_render_mode_buffer` is passed to `RendererState`.
+            // But `editor.renderer_state` has it.
+            // `renderer_state.texture_render_mode_buffer`
+
+            let buffer = if let Some(rs) = &editor.renderer_state {
+                rs.texture_render_mode_buffer.clone()
+            } else {
+                // Fallback or error
+                log!("Renderer state not found");
+                return ToolResult::error("Renderer state not found");
+            };
+
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                match args.r#type.as_str() {
+                    "Cube" => {
+                        let mut cube = Cube::new(
+                            device,
+                            queue,
+                            model_layout,
+                            group_layout,
+                            &buffer,
+                            camera
+                        );
+                        cube.transform.update_position(args.position);
+                        if let Some(scale) = args.scale {
+                            cube.transform.update_scale(scale);
+                        }
+                        renderer_state.cubes.push(cube);
+                    },
+                    "Sphere" => {
+                        let mut sphere = Sphere::new(
+                            device,
+                            queue,
+                            model_layout,
+                            group_layout,
+                            &buffer,
+                            camera,
+                            1.0, // radius
+                            32, // sectors
+                            32, // stacks
+                            [1.0, 1.0, 1.0], // color
+                            false // debug_moving
+                        );
+                        sphere.transform.update_position(args.position);
+                        if let Some(scale) = args.scale {
+                            sphere.transform.update_scale(scale);
+                        }
+                        renderer_state.spheres.push(sphere);
+                    },
+                    other => return ToolResult::error(format!("Unknown primitive type {}, expected Cube or Sphere", other)),
+                }
+
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    saved_state_clone = Some(saved_state.clone());
+                }
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnNPCArgs {
+    #[serde(rename = "assetId")]
+    asset_id: String,
+    position: Option<[f32; 3]>,
+    rotation: Option<[f32; 3]>,
+    scale: Option<[f32; 3]>,
+    aggressiveness: Option<f32>,
+    combat_type: Option<String>,
+    wander_radius: Option<f32>,
+    wander_speed: Option<f32>,
+    detection_radius: Option<f32>,
+    damage: Option<f32>,
+    health: Option<f32>,
+}
+
+async fn spawn_npc(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning NPC...");
+    let args: Result<SpawnNPCArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+             let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
+             let mut asset_file_name = String::new();
+
+            // Find asset in SavedState
+            if let Some(saved_state) = editor.saved_state.as_ref() {
+                if let Some(model) = saved_state.models.iter().find(|m| m.id == args.asset_id) {
+                    asset_file_name = model.fileName.clone();
+                }
+            }
+
+            if !asset_file_name.is_empty() {
+                let component_id = Uuid::new_v4().to_string();
+                let pos = args.position.unwrap_or([0.0, 0.0, 0.0]);
+                let rot = args.rotation.unwrap_or([0.0, 0.0, 0.0]);
+                let scale = args.scale.unwrap_or([1.0, 1.0, 1.0]);
+
+                let model_position = Translation3::new(pos[0], pos[1], pos[2]);
+                let model_rotation = UnitQuaternion::from_euler_angles(
+                    rot[0].to_radians(), rot[1].to_radians(), rot[2].to_radians()
+                );
+                let model_iso = Isometry3::from_parts(model_position, model_rotation);
+                let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
+
+                let combat_type = match args.combat_type.as_deref() {
+                    Some("Ranged") => CombatType::Ranged,
+                    _ => CombatType::Melee,
+                };
+
+                let damage = args.damage.unwrap_or(10.0);
+                let attack_stats = Some(AttackStats {
+                    damage: damage,
+                    range: if combat_type == CombatType::Melee { 2.0 } else { 15.0 },
+                    cooldown: 1.5,
+                    wind_up_time: 0.5,
+                    recovery_time: 0.5,
+                });
+
+                let behavior_config = BehaviorConfig {
+                    aggressiveness: args.aggressiveness.unwrap_or(0.5),
+                    combat_type: combat_type,
+                    wander_radius: args.wander_radius.unwrap_or(10.0),
+                    wander_speed: args.wander_speed.unwrap_or(2.0),
+                    detection_radius: args.detection_radius.unwrap_or(15.0),
+                    melee_stats: if combat_type == CombatType::Melee { attack_stats } else { None },
+                    ranged_stats: if combat_type == CombatType::Ranged { attack_stats } else { None },
+                };
+
+                let renderer_state = editor.renderer_state.as_mut().unwrap();
+                let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+                let camera = editor.camera.as_ref().unwrap();
+
+                handle_add_npc(
+                    renderer_state,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    project_id,
+                    args.asset_id.clone(),
+                    component_id.clone(),
+                    asset_file_name,
+                    model_iso,
+                    model_scale,
+                    camera,
+                    None, // Script state
+                    behavior_config.clone()
+                ).await;
+
+                // Update SavedState
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                        let new_component = ComponentData {
+                            id: component_id,
+                            kind: Some(ComponentKind::NPC),
+                            asset_id: args.asset_id.clone(),
+                            generic_properties: GenericProperties {
+                                name: "New NPC".to_string(),
+                                position: pos,
+                                rotation: rot,
+                                scale: scale,
+                            },
+                            npc_properties: Some(NPCProperties {
+                                model_id: args.asset_id,
+                                behavior: behavior_config,
+                            }),
+                            ..Default::default()
+                        };
+
+                        if let Some(components) = level.components.as_mut() {
+                            components.push(new_component);
+                        } else {
+                            level.components = Some(vec![new_component]);
+                        }
+                    }
+                    saved_state_clone = Some(saved_state.clone());
+                }
+            } else {
+                log!("Asset not found for NPC: {}", args.asset_id);
+                return ToolResult::error(format!("Model asset {} not found", args.asset_id));
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnTriggerArgs {
+    name: Option<String>,
+    shape: Option<String>, // "Box" or "Sphere"
+    position: [f32; 3],
+    size: Option<[f32; 3]>,
+    on_enter_script: Option<String>,
+    on_enter_quest_id: Option<String>,
+}
+
+async fn spawn_trigger(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning trigger volume...");
+    let args: Result<SpawnTriggerArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let component_id = Uuid::new_v4().to_string();
+
+            let trigger_properties = TriggerProperties {
+                shape: match args.shape.as_deref() {
+                    Some("Sphere") => TriggerShape::Sphere,
+                    _ => TriggerShape::Box,
+                },
+                size: args.size.unwrap_or([2.0, 2.0, 2.0]),
+                on_enter_script: args.on_enter_script,
+                on_enter_quest_id: args.on_enter_quest_id,
+            };
+
+            // Update RendererState (debug volume, only drawn in edit mode)
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+                let camera = editor.camera.as_ref().unwrap();
+
+                handle_add_trigger_volume(
+                    renderer_state,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    component_id.clone(),
+                    args.position,
+                    &trigger_properties,
+                    camera
+                );
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id,
+                        kind: Some(ComponentKind::TriggerVolume),
+                        asset_id: "".to_string(),
+                        generic_properties: GenericProperties {
+                            name: args.name.unwrap_or_else(|| "New Trigger".to_string()),
+                            position: args.position,
+                            ..Default::default()
+                        },
+                        trigger_properties: Some(trigger_properties),
+                        ..Default::default()
+                    };
+
+                    if let Some(components) = level.components.as_mut() {
+                        components.push(new_component);
+                    } else {
+                        level.components = Some(vec![new_component]);
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpawnAudioSourceArgs {
+    name: Option<String>,
+    #[serde(rename = "audioId")]
+    audio_id: String, // entry in saved_state.audio
+    position: [f32; 3],
+    volume: Option<f32>, // 0-1
+    #[serde(rename = "loop")]
+    looping: Option<bool>,
+    spatial_radius: Option<f32>, // 0 for ambient, heard the same everywhere
+    autoplay: Option<bool>,
+}
+
+async fn spawn_audio_source(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Spawning audio source...");
+    let args: Result<SpawnAudioSourceArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let audio_exists = editor.saved_state.as_ref()
+                .and_then(|saved_state| saved_state.audio.as_ref())
+                .map(|audio| audio.iter().any(|clip| clip.id == args.audio_id))
+                .unwrap_or(false);
+            if !audio_exists {
+                return ToolResult::error(format!("Audio asset {} not found, upload it in the assets browser first", args.audio_id));
+            }
+
+            let component_id = Uuid::new_v4().to_string();
+
+            let audio_source_properties = AudioSourceProperties {
+                audio_id: args.audio_id,
+                volume: args.volume.unwrap_or(1.0).clamp(0.0, 1.0),
+                looping: args.looping.unwrap_or(true),
+                spatial_radius: args.spatial_radius.unwrap_or(20.0).max(0.0),
+                autoplay: args.autoplay.unwrap_or(true),
+            };
+
+            // Update RendererState (speaker gizmo, only drawn in edit mode)
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+                let camera = editor.camera.as_ref().unwrap();
+
+                handle_add_audio_source(
+                    renderer_state,
+                    &gpu_resources.device,
+                    &gpu_resources.queue,
+                    component_id.clone(),
+                    args.position,
+                    &audio_source_properties,
+                    camera
+                );
+            }
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let new_component = ComponentData {
+                        id: component_id.clone(),
+                        kind: Some(ComponentKind::AudioSource),
+                        asset_id: audio_source_properties.audio_id.clone(),
+                        generic_properties: GenericProperties {
+                            name: args.name.unwrap_or_else(|| "New Audio Source".to_string()),
+                            position: args.position,
+                            ..Default::default()
+                        },
+                        audio_source_properties: Some(audio_source_properties),
+                        ..Default::default()
+                    };
+
+                    if let Some(components) = level.components.as_mut() {
+                        components.push(new_component);
+                    } else {
+                        level.components = Some(vec![new_component]);
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            ToolResult::ok().with_data(serde_json::json!({ "componentId": component_id }))
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetPlayerSpawnArgs {
+    position: [f32; 3],
+    facing: Option<f32>, // yaw in degrees, 0 looks down -Z
+    #[serde(rename = "snapToTerrain")]
+    snap_to_terrain: Option<bool>,
+}
+
+async fn set_player_spawn(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Setting player spawn...");
+    let args: Result<SetPlayerSpawnArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let mut position = args.position;
+            if args.snap_to_terrain.unwrap_or(false) {
+                if let Some(height) = editor.renderer_state.as_ref()
+                    .and_then(|renderer_state| renderer_state.landscapes.first())
+                    .and_then(|landscape| landscape.get_height_at(position[0], position[2]))
+                {
+                    position[1] = height;
+                }
+            }
+
+            // One spawn per level, setting it again moves it
+            let spawn = PlayerSpawn {
+                position,
+                facing: args.facing.unwrap_or(0.0).rem_euclid(360.0),
+            };
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    level.player_spawn = Some(spawn.clone());
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            // Update RendererState (spawn gizmo, only drawn in edit mode)
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                let gpu_resources = editor.gpu_resources.as_ref().unwrap();
+                let camera = editor.camera.as_ref().unwrap();
+                handle_set_player_spawn(renderer_state, &gpu_resources.device, &gpu_resources.queue, &spawn, camera);
+            }
+
+            tool_result = ToolResult::ok().with_data(serde_json::json!({
+                "position": spawn.position,
+                "facing": spawn.facing,
+            }));
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigurePlayerStatsArgs {
+    health: Option<f32>,
+    #[serde(rename = "moveSpeed")]
+    move_speed: Option<f32>, // meters per second
+    #[serde(rename = "jumpHeight")]
+    jump_height: Option<f32>, // meters
+    #[serde(rename = "baseDamage")]
+    base_damage: Option<f32>, // unarmed, weapons add their own
+}
+
+async fn configure_player_stats(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Configuring player stats...");
+    let args: Result<ConfigurePlayerStatsArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut notes = Vec::new();
+            let mut updated = None;
+
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let config = level.player_config.get_or_insert_with(PlayerConfig::default);
+                    if let Some(val) = args.health.and_then(|v| PLAYER_HEALTH.check("health", v, &mut notes)) { config.health = val; }
+                    if let Some(val) = args.move_speed.and_then(|v| PLAYER_MOVE_SPEED.check("moveSpeed", v, &mut notes)) { config.move_speed = val; }
+                    if let Some(val) = args.jump_height.and_then(|v| PLAYER_JUMP_HEIGHT.check("jumpHeight", v, &mut notes)) { config.jump_height = val; }
+                    if let Some(val) = args.base_damage.and_then(|v| WEAPON_DAMAGE.check("baseDamage", v, &mut notes)) { config.base_damage = val; }
+                    updated = Some(config.clone());
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+
+            let Some(config) = updated else {
+                return ToolResult::error("No level to configure");
+            };
+
+            // Update RendererState, so a player already in play mode changes without restarting it
+            if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                renderer_state.update_player_config(&config);
+            }
+
+            let result = ToolResult::ok().with_data(serde_json::json!({
+                "health": config.health,
+                "moveSpeed": config.move_speed,
+                "jumpHeight": config.jump_height,
+                "baseDamage": config.base_damage,
+            }));
+            match notes_message(&notes) {
+                Some(message) => result.with_message(message),
+                None => result,
+            }
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AddInventoryItemArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>, // a collectable, its stat is the item
+    #[serde(rename = "statId")]
+    stat_id: Option<String>, // or the stat directly
+    quantity: Option<u32>, // defaults to 1
+    slots: Option<u32>, // resizes the inventory
+    stackable: Option<bool>, // whether repeat items share a slot
+}
+
+async fn add_inventory_item(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Adding inventory item...");
+    let args: Result<AddInventoryItemArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let Some(saved_state) = editor.saved_state.as_mut() else {
+                return ToolResult::error("No project loaded");
+            };
+
+            let stat_id = match (&args.component_id, &args.stat_id) {
+                (Some(component_id), _) => {
+                    let component = saved_state.levels.as_ref()
+                        .and_then(|levels| levels.get(0))
+                        .and_then(|level| level.components.as_ref())
+                        .and_then(|components| components.iter().find(|c| &c.id == component_id));
+                    match component.and_then(|c| c.collectable_properties.as_ref()) {
+                        Some(collectable) => match collectable.stat_id.clone() {
+                            Some(stat_id) => stat_id,
+                            None => return ToolResult::error(format!("Collectable {} has no stat attached", component_id)),
+                        },
+                        None if component.is_some() => return ToolResult::error(format!("{} is not a collectable", component_id)),
+                        None => return ToolResult::error(format!("Component {} not found", component_id)),
+                    }
+                }
+                (None, Some(stat_id)) => stat_id.clone(),
+                (None, None) => return ToolResult::error("Pass a collectable componentId or a statId"),
+            };
+            let Some(stat) = saved_state.stats.as_ref().and_then(|stats| stats.iter().find(|stat| stat.id == stat_id)) else {
+                return ToolResult::error(format!("Stat {} not found", stat_id));
+            };
+            let stat_name = stat.name.clone();
+
+            let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) else {
+                return ToolResult::error("No level to add to");
+            };
+            let inventory = level.inventory.get_or_insert_with(|| InventoryConfig {
+                slots: DEFAULT_INVENTORY_SLOTS,
+                stackable: true,
+                pickup_to_inventory: true,
+                items: Vec::new(),
+            });
+            if let Some(slots) = args.slots { inventory.slots = slots.max(1); }
+            if let Some(stackable) = args.stackable { inventory.stackable = stackable; }
+
+            let quantity = args.quantity.unwrap_or(1).max(1);
+            let existing = inventory.items.iter().position(|item| item.stat_id == stat_id);
+            match existing {
+                Some(index) if inventory.stackable => inventory.items[index].quantity += quantity,
+                _ => {
+                    // Unstackable items take a slot each
+                    let needed = if inventory.stackable { 1 } else { quantity };
+                    let used: u32 = inventory.items.iter().map(|item| if inventory.stackable { 1 } else { item.quantity }).sum();
+                    if used + needed > inventory.slots {
+                        return ToolResult::error(format!(
+                            "The inventory is full ({} of {} slots), raise slots first",
+                            used, inventory.slots
+                        ));
+                    }
+                    match existing {
+                        Some(index) => inventory.items[index].quantity += quantity,
+                        None => inventory.items.push(InventoryItem { stat_id: stat_id.clone(), quantity }),
+                    }
+                }
+            }
+
+            let result = ToolResult::ok()
+                .with_message(format!("Added {} × {} to the starting inventory", quantity, stat_name))
+                .with_data(serde_json::json!({
+                    "slots": inventory.slots,
+                    "stackable": inventory.stackable,
+                    "items": inventory.items.iter().map(|item| serde_json::json!({
+                        "statId": item.stat_id,
+                        "quantity": item.quantity,
+                    })).collect::<Vec<_>>(),
+                }));
+            saved_state_clone = Some(saved_state.clone());
+            result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConformToTerrainArgs {
+    #[serde(rename = "componentIds")]
+    component_ids: Option<Vec<String>>,
+    offset: Option<f32>,
+    force: Option<bool>, // move locked components too
+}
+
+async fn conform_to_terrain(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Conforming objects to terrain...");
+    let args: Result<ConformToTerrainArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            let offset = args.offset.unwrap_or(0.0);
+            let force = args.force.unwrap_or(false);
+            let mut adjusted: Vec<(String, [f32; 3])> = Vec::new();
+
+            if let Some(saved_state) = editor.saved_state.as_ref() {
+                for component_id in args.component_ids.iter().flatten() {
+                    if let Some(error) = locked_component_error(saved_state, component_id, force) {
+                        return error;
+                    }
+                }
+            }
+
+            if let (Some(saved_state), Some(renderer_state)) = (editor.saved_state.as_mut(), editor.renderer_state.as_ref()) {
+                if let Some(landscape) = renderer_state.landscapes.first() {
+                    if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                        if let Some(components) = level.components.as_mut() {
+                            for component in components.iter_mut() {
+                                let targeted = match &args.component_ids {
+                                    Some(ids) => ids.contains(&component.id),
+                                    None => matches!(component.kind, Some(ComponentKind::Model) | Some(ComponentKind::NPC) | Some(ComponentKind::Collectable))
+                                        && (force || !component.locked),
+                                };
+                                if !targeted {
+                                    continue;
+                                }
+
+                                let position = component.generic_properties.position;
+                                if let Some(ground) = landscape.get_height_at(position[0], position[2]) {
+                                    component.generic_properties.position[1] = ground + offset;
+                                    adjusted.push((component.id.clone(), component.generic_properties.position));
+                                }
+                            }
+                        }
+                    }
+                } else {
+                    tool_result = ToolResult::error("No landscape to conform to");
+                }
+
+                if !adjusted.is_empty() {
+                    saved_state_clone = Some(saved_state.clone());
+                }
+            }
+
+            // Update RendererState
+            for (component_id, position) in adjusted.iter() {
+                apply_component_position(editor, component_id, *position);
+            }
+
+            if tool_result.success {
+                tool_result = ToolResult::ok()
+                    .with_message(format!("Adjusted {} objects", adjusted.len()))
+                    .with_data(serde_json::json!({ "adjusted": adjusted.len() }));
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RenameComponentArgs {
+    #[serde(rename = "componentId")]
+    component_id: String,
+    name: String,
+}
+
+async fn rename_component(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Renaming component...");
+    let args: Result<RenameComponentArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let name = args.name.trim().to_string();
+        if name.is_empty() {
+            return ToolResult::error("Name can't be empty");
+        }
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                let component = saved_state.levels.as_mut()
+                    .and_then(|l| l.get_mut(0))
+                    .and_then(|level| level.components.as_mut())
+                    .and_then(|components| components.iter_mut().find(|c| c.id == args.component_id));
+
+                match component {
+                    Some(component) => {
+                        let previous = std::mem::replace(&mut component.generic_properties.name, name.clone());
+                        tool_result = ToolResult::ok().with_message(format!("Renamed {} to {}", previous, name));
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                    None => {
+                        tool_result = ToolResult::error(format!("Component {} not found", args.component_id));
+                    }
+                }
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GroupComponentsArgs {
+    #[serde(rename = "componentIds")]
+    component_ids: Vec<String>,
+    name: Option<String>,
+}
+
+async fn group_components(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Grouping components...");
+    let args: Result<GroupComponentsArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        if args.component_ids.is_empty() {
+            return ToolResult::error("Pass at least one componentId to group");
+        }
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                    let components = level.components.get_or_insert_with(Vec::new);
+
+                    let children: Vec<&ComponentData> = components.iter().filter(|c| args.component_ids.contains(&c.id)).collect();
+                    if let Some(missing) = args.component_ids.iter().find(|id| !children.iter().any(|c| &c.id == *id)) {
+                        return ToolResult::error(format!("Component {} not found", missing));
+                    }
+
+                    // The group pivot sits at the middle of its children
+                    let mut center = [0.0f32; 3];
+                    for child in children.iter() {
+                        for i in 0..3 {
+                            center[i] += child.generic_properties.position[i] / children.len() as f32;
+                        }
+                    }
+
+                    // A component belongs to at most one group
+                    for component in components.iter_mut() {
+                        if let Some(group) = component.group_properties.as_mut() {
+                            group.child_ids.retain(|id| !args.component_ids.contains(id));
+                        }
+                    }
+
+                    let component_id = Uuid::new_v4().to_string();
+                    components.push(ComponentData {
+                        id: component_id.clone(),
+                        kind: Some(ComponentKind::Group),
+                        asset_id: "".to_string(),
+                        generic_properties: GenericProperties {
+                            name: args.name.unwrap_or_else(|| "New Group".to_string()),
+                            position: center,
+                            ..Default::default()
+                        },
+                        group_properties: Some(GroupProperties {
+                            child_ids: args.component_ids.clone(),
+                        }),
+                        ..Default::default()
+                    });
+
+                    tool_result = ToolResult::ok()
+                        .with_message(format!("Grouped {} components", args.component_ids.len()))
+                        .with_data(serde_json::json!({ "componentId": component_id }));
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UngroupComponentsArgs {
+    #[serde(rename = "componentId")]
+    component_id: String, // the group
+}
+
+async fn ungroup_components(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Ungrouping components...");
+    let args: Result<UngroupComponentsArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            // Update SavedState, the children stay where they are
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                if let Some(components) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)).and_then(|l| l.components.as_mut()) {
+                    let before = components.len();
+                    components.retain(|c| !(c.id == args.component_id && c.kind == Some(ComponentKind::Group)));
+
+                    if components.len() == before {
+                        return ToolResult::error(format!("Group {} not found", args.component_id));
+                    }
+                }
+                saved_state_clone = Some(saved_state.clone());
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LockComponentArgs {
+    #[serde(rename = "componentId")]
+    component_id: String,
+    locked: Option<bool>, // defaults to true
+}
+
+async fn lock_component(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Locking component...");
+    let args: Result<LockComponentArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let locked = args.locked.unwrap_or(true);
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let mut tool_result = ToolResult::ok();
+            // Update SavedState
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                let component = saved_state.levels.as_mut()
+                    .and_then(|l| l.get_mut(0))
+                    .and_then(|level| level.components.as_mut())
+                    .and_then(|components| components.iter_mut().find(|c| c.id == args.component_id));
+
+                match component {
+                    Some(component) => {
+                        component.locked = locked;
+                        tool_result = ToolResult::ok().with_message(format!(
+                            "{} {}",
+                            if locked { "Locked" } else { "Unlocked" },
+                            component.generic_properties.name
+                        ));
+                        saved_state_clone = Some(saved_state.clone());
+                    }
+                    None => {
+                        return ToolResult::error(format!("Component {} not found", args.component_id));
+                    }
+                }
+            }
+
+            // Keep canvas picking in step
+            sync_locked_components(editor);
+            tool_result
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FrameSceneArgs {}
+
+async fn frame_scene_tool(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut tool_result = ToolResult::ok();
+
+    log!("Framing scene...");
+    let args: Result<FrameSceneArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if args.is_ok() {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            match frame_scene(editor) {
+                Some(framed) => ToolResult::ok().with_data(serde_json::json!({
+                    "center": framed.center,
+                    "radius": framed.radius,
+                    "cameraPosition": framed.camera_position,
+                })),
+                None => ToolResult::error("There's nothing in the scene to frame"),
+            }
+        }).await;
+    }
+
+    // Only the camera moves, nothing to save
+    ctx.finish(None, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MeasureDistanceArgs {
+    #[serde(rename = "fromComponentId")]
+    from_component_id: String,
+    #[serde(rename = "toComponentId")]
+    to_component_id: String,
+}
+
+async fn measure_distance(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let mut tool_result = ToolResult::ok();
+
+    log!("Measuring distance...");
+    let args: Result<MeasureDistanceArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            // Read-only, nothing to save
+            let components = editor.saved_state.as_ref()
+                .and_then(|saved_state| saved_state.levels.as_ref())
+                .and_then(|levels| levels.get(0))
+                .and_then(|level| level.components.as_ref());
+            let find = |id: &str| components.and_then(|components| components.iter().find(|c| c.id == id));
+
+            let (from, to) = match (find(&args.from_component_id), find(&args.to_component_id)) {
+                (Some(from), Some(to)) => (from, to),
+                (None, _) => return ToolResult::error(format!("Component {} not found", args.from_component_id)),
+                (_, None) => return ToolResult::error(format!("Component {} not found", args.to_component_id)),
+            };
+
+            let a = from.generic_properties.position;
+            let b = to.generic_properties.position;
+            let delta = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let distance = (delta[0] * delta[0] + delta[1] * delta[1] + delta[2] * delta[2]).sqrt();
+
+            ToolResult::ok()
+                .with_message(format!(
+                    "{} and {} are {:.2} units apart",
+                    from.generic_properties.name, to.generic_properties.name, distance
+                ))
+                .with_data(serde_json::json!({
+                    "distance": distance,
+                    "dx": delta[0],
+                    "dy": delta[1],
+                    "dz": delta[2],
+                }))
+        }).await;
+    }
+
+    tool_result
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SaveScriptArgs {
+    filename: String,
+    content: String,
+    componentId: Option<String>,
+}
+
+async fn save_script(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Saving script...");
+    let args: Result<SaveScriptArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        // Update the component script path if provided
+        if let Some(component_id) = &args.componentId {
+            tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+                // Update SavedState
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                        if let Some(components) = level.components.as_mut() {
+                            if let Some(component) = components.iter_mut().find(|c| c.id == *component_id) {
+                                let script_path = format!("scripts/{}", args.filename);
+                                component.rhai_script_path = Some(script_path);
+                            }
+                        }
+                    }
+                    saved_state_clone = Some(saved_state.clone());
+                }
+                ToolResult::ok()
+            }).await;
+        }
+        
+         // We need the project path. It's in `selected_project`.
+        let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
+        
+        if !project_path.is_empty() {
+            let url = format!("{}/api/save-script", get_api_url());
+            let body = serde_json::json!({
+                "projectPath": project_path,
+                "filename": args.filename,
+                "content": args.content
+            });
+            
+            spawn_local(async move {
+                let _ = Request::post(&url)
+                    .json(&body)
+                    .expect("Couldn't make post body")
+                    .send()
+                    .await;
+            });
+        }
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TerrainFeatureArgs {
+    r#type: String,
+    center: [f64; 2],
+    radius: f64,
+    intensity: f64,
+    falloff: String,
+    flat_top: Option<f64>,
+    transition: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ErosionArgs {
+    iterations: u32, // droplets to simulate, e.g. 50000
+    strength: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GenerateHeightmapArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>,
+    seed: Option<u32>,
+    scale: Option<f64>,
+    persistence: Option<f64>,
+    lacunarity: Option<f64>,
+    features: Option<Vec<TerrainFeatureArgs>>,
+    width: Option<u32>, // pixels, defaults to 1024
+    height: Option<u32>, // pixels, defaults to 1024
+    #[serde(rename = "imageUrl")]
+    image_url: Option<String>, // grayscale image used as the base instead of noise
+    erosion: Option<ErosionArgs>, // off by default, it's slow and runs on the main thread with a progress bar
+}
+
+async fn generate_heightmap(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut saved_state_clone = None;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Generating heightmap...");
+    let args: Result<GenerateHeightmapArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        // Fetched before borrowing the pipeline, the request can take a while
+        let base_heightmap = match args.image_url.as_deref() {
+            Some(url) => match fetch_heightmap_image(url).await {
+                Ok(img) => Some(img),
+                Err(e) => {
+                    log!("{}", e);
+                    return ToolResult::error(e);
+                }
+            },
+            None => None,
+        };
+
+        let (width, height) = match base_heightmap.as_ref() {
+            Some(img) => img.dimensions(),
+            None => (
+                args.width.unwrap_or(DEFAULT_HEIGHTMAP_SIZE),
+                args.height.unwrap_or(DEFAULT_HEIGHTMAP_SIZE),
+            ),
+        };
+        if let Err(e) = validate_heightmap_size(width, height) {
+            return ToolResult::error(e);
+        }
+        let mut generator = HeightmapGenerator::new(width, height)
+                                                    .with_scale(1024.0)
+                                                    .with_octaves(8)
+                                                    .with_persistence(0.5)
+                                                    .with_seed(42);
+
+        // An imported image replaces the noise, features still get layered on top
+        if let Some(base) = base_heightmap {
+            generator = generator.with_base_heightmap(base);
+        }
+        
+        if let Some(seed) = args.seed { generator = generator.with_seed(seed); }
+        if let Some(scale) = args.scale { generator = generator.with_scale(scale); }
+        if let Some(persistence) = args.persistence { generator = generator.with_persistence(persistence); }
+        if let Some(lacunarity) = args.lacunarity { generator = generator.with_lacunarity(lacunarity); }
+
+        if let Some(features) = args.features {
+            for f in features {
+                let f_type = match f.r#type.as_str() {
+                    "Mountain" => FeatureType::Mountain,
+                    "Valley" => FeatureType::Valley,
+                    "Plateau" => FeatureType::Plateau,
+                    "Ridge" => FeatureType::Ridge,
+                    _ => FeatureType::Mountain,
+                };
+                let falloff = terrain_edit::falloff_from_label(&f.falloff);
+                let mut feature = TerrainFeature::new(
+                    (f.center[0], f.center[1]),
+                    f.radius,
+                    f.intensity,
+                    falloff,
+                    f_type
+                );
+                if let Some(ft) = f.flat_top { feature = feature.with_flat_top(ft); }
+                if let Some(t) = f.transition { feature = feature.with_transition(t); }
+                generator.add_feature(feature);
+            }
+        }
+
+        let mut img = generator.generate();
+
+        // Also done before borrowing the pipeline since it yields to the browser between batches
+        if let Some(erosion) = args.erosion.as_ref().filter(|e| e.iterations > 0) {
+            let mut heights: Vec<f32> = img.pixels().map(|p| p.0[0] as f32 / 65535.0).collect();
+            erode(
+                &mut heights,
+                width as usize,
+                height as usize,
+                ErosionSettings {
+                    iterations: erosion.iterations,
+                    strength: erosion.strength.unwrap_or(1.0),
+                    seed: args.seed.unwrap_or(42),
+                },
+                |progress| ctx.set_progress.set(Some(("Eroding heightmap".to_string(), progress)))
+            ).await;
+            for (pixel, value) in img.pixels_mut().zip(heights) {
+                pixel.0[0] = (value * 65535.0) as u16;
+            }
+        }
+
+        tool_result = with_editor_mut(ctx.pipeline_store, async |editor| {
+            let existing_info = editor.saved_state.as_ref()
+                .and_then(|saved_state| terrain_edit::landscape_heightmap_info(saved_state, args.component_id.as_deref()));
+
+            let info = if let Some(info) = existing_info {
+                info
+            } else {
+                log!("Creating new landscape info.");
+                terrain_edit::LandscapeHeightmapInfo {
+                    component_id: Uuid::new_v4().to_string(),
+                    position: [0.0, 0.0, 0.0],
+                    asset_id: Uuid::new_v4().to_string(),
+                    file_name: format!("heightmap_{}.png", Uuid::new_v4()),
+                }
+            };
+
+            // Kept at full resolution for sculpting and export
+            let field = HeightField::from_image(&img);
+            terrain_edit::store(&info.asset_id, field.clone());
+
+            // Upload to API
+            // We need the project path. It's in `selected_project`.
+            let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
+            if !project_path.is_empty() {
+                // In the background, the pipeline stays borrowed for as long as this closure runs
+                let (info, field) = (info.clone(), field.clone());
+                spawn_local(async move {
+                    if let Err(e) = terrain_edit::upload(&project_path, &info, &field).await {
+                        log!("{}", e);
+                    }
+                });
+            }
+
+            // Update In-Memory
+            terrain_edit::rebuild_landscape(editor, &field, &info);
+            log!("Heightmap generated and loaded!");
+
+            if let Some(saved_state) = editor.saved_state.as_mut() {
+                saved_state_clone = Some(saved_state.clone());
+            }
+            ToolResult::ok()
+        }).await;
+    }
+
+    ctx.finish(saved_state_clone, tool_result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FlattenTerrainArgs {
+    #[serde(rename = "componentId")]
+    component_id: Option<String>, // the landscape, defaults to the first one
+    center: [f32; 2], // world X, Z
+    radius: f32, // meters
+    height: f32, // world height to level to, in meters above the landscape's base
+    falloff: Option<String>, // "Linear", "Smooth" or "Gaussian", defaults to "Smooth"
+    edge: Option<f32>, // 0-1 fraction of the radius that blends back into the terrain, defaults to 0.3
+}
+
+async fn flatten_terrain(ctx: &ToolContext, tool_call: &ToolCall) -> ToolResult {
+    let selected_project = ctx.selected_project;
+    let mut tool_result = ToolResult::ok();
+
+    log!("Flattening terrain...");
+    let args: Result<FlattenTerrainArgs, _> = serde_json::from_str(&tool_call.function.arguments);
+    if let Ok(args) = args {
+        let mut notes = Vec::new();
+        let (Some(radius), Some(height)) = (
+            SCATTER_RADIUS.check("radius", args.radius, &mut notes),
+            TERRAIN_HEIGHT.check("height", args.height, &mut notes),
+        ) else {
+            return ToolResult::error(notes_message(&notes).unwrap_or_default());
+        };
+        let edge = args.edge.unwrap_or(0.3).clamp(0.0, 1.0);
+        let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
+
+        let info = ctx.pipeline_store.get()
+            .and_then(|pipeline_arc_val| pipeline_arc_val.as_ref().and_then(|pipeline_arc| {
+                let pipeline = pipeline_arc.borrow();
+                pipeline.export_editor.as_ref()
+                    .and_then(|editor| editor.saved_state.as_ref())
+                    .and_then(|saved_state| terrain_edit::landscape_heightmap_info(saved_state, args.component_id.as_deref()))
+            }));
+        let Some(info) = info else {
+            return ToolResult::error("No landscape with a heightmap to flatten");
+        };
+
+        // Fetched before borrowing the pipeline, the heightmap may not be in memory yet
+        if let Err(e) = terrain_edit::load(&project_path, &info).await {
+            return ToolResult::error(e);
+        }
+
+        let brush = terrain_edit::Brush {
+            mode: terrain_edit::SculptMode::Flatten,
+            radius: radius / terrain_edit::HEIGHTMAP_WORLD_UNITS_PER_PIXEL,
+            strength: 1.0,
+            falloff: terrain_edit::falloff_from_label(args.falloff.as_deref().unwrap_or("Smooth")),
+            edge,
+            target: ((height - info.position[1]) / terrain_edit::HEIGHTMAP_HEIGHT_SCALE).clamp(0.0, 1.0),
+        };
+        let edited = terrain_edit::with_field_mut(&info.asset_id, |field| {
+            let center = field.world_to_pixel(info.position, args.center[0], args.center[1]);
+            terrain_edit::apply_brush(field, center, &brush);
+            field.clone()
+        });
+
+        if let Some(field) = edited {
+            let rebuilt = with_editor_mut(ctx.pipeline_store, async |editor| {
+                terrain_edit::rebuild_landscape(editor, &field, &info);
+                ToolResult::ok()
+            }).await;
+            if !rebuilt.success {
+                return rebuilt;
+            }
+
+            if let Err(e) = terrain_edit::upload(&project_path, &info, &field).await {
+                return ToolResult::error(e);
+            }
+
+            tool_result = ToolResult::ok().with_data(serde_json::json!({
+                "center": args.center,
+                "radius": radius,
+                "height": height,
+            }));
+            if let Some(message) = notes_message(&notes) {
+                tool_result = tool_result.with_message(message);
+            }
+        }
+    }
+
+    tool_result
+}
+
+/// Pushes the level-wide render settings stored in saved data into the renderer after a project is placed,
+/// along with the player spawn gizmo.
+fn apply_level_render_settings(editor: &mut Editor) {
+    let level = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0));
+    let post_processing = level.and_then(|level| level.post_processing.clone());
+    let ambient_light = level.and_then(|level| level.ambient_light.clone());
+    let player_spawn = level.and_then(|level| level.player_spawn.clone());
+    let clouds = level.and_then(|level| level.clouds.clone());
+    let ambient_occlusion = level.and_then(|level| level.ambient_occlusion.clone());
+    let color_grade = level.and_then(|level| level.color_grade.clone());
+    let fog = level.and_then(|level| level.fog.clone());
+    let player_config = level.and_then(|level| level.player_config.clone());
+
+    if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+        if let Some(config) = post_processing.as_ref() {
+            renderer_state.update_post_processing(&gpu_resources.queue, config);
+        }
+        if let Some(config) = ambient_light.as_ref() {
+            renderer_state.update_ambient_light(&gpu_resources.queue, config);
+        }
+        if let Some(config) = color_grade.as_ref() {
+            renderer_state.update_color_grade(&gpu_resources.queue, config);
+        }
+        if let Some(config) = fog.as_ref() {
+            renderer_state.update_fog(&gpu_resources.queue, config);
+        }
+        // Off unless the level turned them on, they're the priciest part of the sky
+        if let Some(config) = clouds.as_ref().filter(|config| config.enabled) {
+            renderer_state.update_clouds(&gpu_resources.device, &gpu_resources.queue, config);
+        }
+        if let Some(config) = ambient_occlusion.as_ref().filter(|config| config.enabled) {
+            renderer_state.update_ambient_occlusion(&gpu_resources.device, &gpu_resources.queue, config);
+        }
+        if let (Some(spawn), Some(camera)) = (player_spawn.as_ref(), editor.camera.as_ref()) {
+            handle_set_player_spawn(renderer_state, &gpu_resources.device, &gpu_resources.queue, spawn, camera);
+        }
+        if let Some(config) = player_config.as_ref() {
+            renderer_state.update_player_config(config);
+        }
+        // Projects saved before the density cap could still ask for enough blades to freeze the tab
+        for grass in renderer_state.grasses.iter_mut() {
+            grass.config.blade_density = safe_grass_density(grass.config.blade_density);
+        }
+    }
+
+    weather::apply(editor);
+}
+
+/// How much new grass thins out towards its render distance.
+const DEFAULT_GRASS_DENSITY_FALLOFF: f32 = 0.75;
+/// How much new grass takes on the terrain's color. Grass saved before the setting existed stays uniform.
+const DEFAULT_GRASS_TERRAIN_TINT: f32 = 0.5;
+
+/// Blade density capped to what the preview can draw, for values that didn't come through a tool or the editor.
+fn safe_grass_density(density: f32) -> f32 {
+    GRASS_BLADE_DENSITY.clamp(density).unwrap_or(GRASS_BLADE_DENSITY.min)
+}
+
+/// Turns the level's ambient occlusion on or off from the preview toolbar, keeping its radius and intensity.
+/// Returns the saved state to persist.
+fn set_ambient_occlusion_enabled(editor: &mut Editor, enabled: bool) -> Option<SavedState> {
+    let saved_state = editor.saved_state.as_mut()?;
+    let level = saved_state.levels.as_mut().and_then(|l| l.get_mut(0))?;
+    let config = level.ambient_occlusion.get_or_insert_with(AmbientOcclusionConfig::default);
+    config.enabled = enabled;
+    let config = config.clone();
+    let saved_state = saved_state.clone();
+
+    if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+        renderer_state.update_ambient_occlusion(&gpu_resources.device, &gpu_resources.queue, &config);
+    }
+
+    Some(saved_state)
+}
+
+/// Moves the preview camera back along its current view direction until the component fills a comfortable part of the view.
+pub fn focus_camera_on(editor: &mut Editor, component: &ComponentData) {
+    let Some(camera) = editor.camera.as_mut() else { return };
+    let position = component.generic_properties.position;
+    let size = component.generic_properties.scale.iter().fold(1.0f32, |max, s| max.max(s.abs()));
+    let distance = (size * FOCUS_DISTANCE_PER_SCALE).clamp(MIN_FOCUS_DISTANCE, MAX_CAMERA_DISTANCE);
+
+    let direction = camera.direction.normalize();
+    camera.position = nalgebra::Point3::new(position[0], position[1], position[2]) - direction * distance;
+}
+
+/// Where `frame_scene` put the camera, for the `frameScene` tool result.
+pub struct FramedScene {
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub camera_position: [f32; 3],
+}
+
+/// Bounds of every component in the level, each taken as a box of its largest scale axis around its position.
+fn scene_bounds(saved_state: &SavedState) -> Option<([f32; 3], [f32; 3])> {
+    let components = saved_state.levels.as_ref()
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_ref())?;
+
+    components.iter().fold(None, |bounds, component| {
+        let position = component.generic_properties.position;
+        let extent = component.generic_properties.scale.iter().fold(0.5f32, |max, s| max.max(s.abs()));
+        let (low, high) = bounds.unwrap_or((position, position));
+        Some((
+            [0, 1, 2].map(|i| low[i].min(position[i] - extent)),
+            [0, 1, 2].map(|i| high[i].max(position[i] + extent)),
+        ))
+    })
+}
+
+/// Glides the camera back along its view direction until every component is in view.
+/// Shared by the `frameScene` tool, the Frame All button and project loading. `None` for an empty level.
+pub fn frame_scene(editor: &mut Editor) -> Option<FramedScene> {
+    let (low, high) = editor.saved_state.as_ref().and_then(scene_bounds)?;
+    let camera = editor.camera.as_ref()?;
+
+    let center = [0, 1, 2].map(|i| (low[i] + high[i]) / 2.0);
+    let radius = ([0, 1, 2].map(|i| (high[i] - low[i]) / 2.0).iter().map(|v| v * v).sum::<f32>()).sqrt();
+    let distance = (radius * FRAME_DISTANCE_PER_RADIUS).clamp(MIN_FOCUS_DISTANCE, MAX_CAMERA_DISTANCE);
+
+    let target = nalgebra::Point3::new(center[0], center[1], center[2]) - camera.direction.normalize() * distance;
+    camera_move::start(editor, target);
+
+    Some(FramedScene { center, radius, camera_position: [target.x, target.y, target.z] })
+}
+
+/// Slots a level's inventory starts with, the first time an item is added to it.
+const DEFAULT_INVENTORY_SLOTS: u32 = 20;
+
+/// Component kinds `duplicate_component` can copy, as reported when asked for any other kind.
+const DUPLICABLE_KINDS: [&str; 10] = [
+    "Model", "PointLight", "DirectionalLight", "NPC", "Collectable", "WaterPlane", "TriggerVolume", "InstancedModel", "AudioSource", "Forest",
+];
+
+/// How far a duplicate lands from its source by default, so the two don't overlap exactly.
+pub const DUPLICATE_OFFSET: [f32; 3] = [2.0, 0.0, 0.0];
+/// Point lights don't store their range, copies get the spawn default.
+const DEFAULT_POINT_LIGHT_RADIUS: f32 = 10.0;
+
+/// Copies a component `offset` away from the original, adding it to the renderer the way its spawn tool does
+/// and to the level. Shared by the `duplicateObject` tool and the editor's Duplicate button.
+pub async fn duplicate_component(editor: &mut Editor, project_id: &str, component_id: &str, offset: [f32; 3]) -> Result<ComponentData, String> {
+    let source = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_ref())
+        .and_then(|components| components.iter().find(|c| c.id == component_id).cloned())
+        .ok_or_else(|| format!("Component {} not found", component_id))?;
+
+    let generic = &source.generic_properties;
+    let mut copy = ComponentData {
+        id: Uuid::new_v4().to_string(),
+        generic_properties: GenericProperties {
+            name: format!("{} (Copy)", generic.name),
+            position: [generic.position[0] + offset[0], generic.position[1] + offset[1], generic.position[2] + offset[2]],
+            ..generic.clone()
+        },
+        locked: false,
+        ..source.clone()
+    };
+    let position = copy.generic_properties.position;
+    let rotation = copy.generic_properties.rotation;
+    let scale = copy.generic_properties.scale;
+
+    // Instances and trees are placed in world space, they move with the copy
+    let shift = |transform_position: &mut [f32; 3]| {
+        for axis in 0..3 {
+            transform_position[axis] += offset[axis];
+        }
+    };
+    if let Some(instanced) = copy.instanced_model_properties.as_mut() {
+        instanced.transforms.iter_mut().for_each(|transform| shift(&mut transform.position));
+    }
+    if let Some(forest) = copy.forest_properties.as_mut() {
+        shift(&mut forest.center);
+        forest.transforms.iter_mut().for_each(|transform| shift(&mut transform.position));
+    }
+    // A collectable's model part goes by the component's own id
+    if let Some(collectable) = copy.collectable_properties.as_mut() {
+        if collectable.model_id.as_deref() == Some(source.id.as_str()) {
+            collectable.model_id = Some(copy.id.clone());
+        }
+    }
+
+    let model_file_name = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.models.iter().find(|m| m.id == copy.asset_id))
+        .map(|model| model.fileName.clone())
+        .ok_or_else(|| format!("Model asset {} not found", copy.asset_id));
+    let model_iso = Isometry3::from_parts(
+        Translation3::new(position[0], position[1], position[2]),
+        UnitQuaternion::from_euler_angles(rotation[0].to_radians(), rotation[1].to_radians(), rotation[2].to_radians()),
+    );
+    let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
+
+    let (Some(renderer_state), Some(gpu_resources), Some(camera)) =
+        (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref(), editor.camera.as_ref())
+    else {
+        return Err("The preview isn't ready yet".to_string());
+    };
+
+    match copy.kind {
+        Some(ComponentKind::Model) => {
+            handle_add_model(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                project_id.to_string(),
+                copy.asset_id.clone(),
+                copy.id.clone(),
+                model_file_name.clone()?,
+                model_iso,
+                model_scale,
+                camera,
+                None // Script state
+            ).await;
+        }
+        Some(ComponentKind::PointLight) => {
+            let properties = copy.light_properties.as_ref().ok_or("This light has no properties to copy")?;
+            renderer_state.point_lights.push(new_point_light(
+                copy.id.clone(),
+                position,
+                [properties.color[0], properties.color[1], properties.color[2]],
+                properties.intensity,
+                DEFAULT_POINT_LIGHT_RADIUS,
+            ));
+        }
+        Some(ComponentKind::DirectionalLight) => {
+            let properties = copy.directional_light_properties.as_ref().ok_or("This light has no properties to copy")?;
+            renderer_state.directional_lights.push(DirectionalLight {
+                id: copy.id.clone(),
+                direction: light_direction_from_rotation(rotation),
+                color: properties.color,
+                intensity: properties.intensity,
+                cast_shadows: properties.cast_shadows,
+            });
+        }
+        Some(ComponentKind::NPC) => {
+            let properties = copy.npc_properties.as_ref().ok_or("This NPC has no properties to copy")?;
+            handle_add_npc(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                project_id.to_string(),
+                copy.asset_id.clone(),
+                copy.id.clone(),
+                model_file_name.clone()?,
+                model_iso,
+                model_scale,
+                camera,
+                None, // Script state
+                properties.behavior.clone()
+            ).await;
+        }
+        Some(ComponentKind::Collectable) => {
+            let properties = copy.collectable_properties.as_ref().ok_or("This collectable has no properties to copy")?;
+            let collectable_type = properties.collectable_type.clone().unwrap_or(CollectableType::Item);
+            let related_stat = editor.saved_state.as_ref()
+                .and_then(|saved_state| saved_state.stats.as_ref())
+                .and_then(|stats| select_collectable_stat(stats, &collectable_type, properties.stat_id.as_deref()))
+                .ok_or("The collectable's stat no longer exists")?;
+            handle_add_collectable(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                project_id.to_string(),
+                copy.asset_id.clone(),
+                copy.id.clone(),
+                model_file_name.clone()?,
+                model_iso,
+                model_scale,
+                camera,
+                properties,
+                &related_stat,
+                false, // Don't hide
+                None // Script state
+            ).await;
+        }
+        Some(ComponentKind::WaterPlane) => {
+            let camera_binding = editor.camera_binding.as_ref().ok_or("The preview isn't ready yet")?;
+            let surface_format = editor.surface_format.expect("Surface format is set when the preview is configured");
+            handle_add_water_plane(
+                renderer_state,
+                &gpu_resources.device,
+                &camera_binding.bind_group_layout,
+                surface_format,
+                copy.id.clone(),
+                copy.water_properties.clone(),
+                None,
+            );
+            if let Some(water_plane) = renderer_state.water_planes.iter_mut().find(|w| w.id == copy.id) {
+                water_plane.transform.update_position(position);
+                water_plane.transform.update_scale(scale);
+            }
+        }
+        Some(ComponentKind::TriggerVolume) => {
+            let properties = copy.trigger_properties.as_ref().ok_or("This trigger has no properties to copy")?;
+            handle_add_trigger_volume(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                copy.id.clone(),
+                position,
+                properties,
+                camera
+            );
+        }
+        Some(ComponentKind::InstancedModel) => {
+            let properties = copy.instanced_model_properties.as_ref().ok_or("This instanced model has no instances to copy")?;
+            handle_add_instanced_model(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                project_id.to_string(),
+                copy.asset_id.clone(),
+                copy.id.clone(),
+                model_file_name.clone()?,
+                &properties.transforms,
+                camera
+            ).await;
+        }
+        Some(ComponentKind::AudioSource) => {
+            let properties = copy.audio_source_properties.as_ref().ok_or("This audio source has no properties to copy")?;
+            handle_add_audio_source(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                copy.id.clone(),
+                position,
+                properties,
+                camera
+            );
+        }
+        Some(ComponentKind::Forest) => {
+            let forest = copy.forest_properties.as_ref().ok_or("This forest has no trees to copy")?;
+            let tree = copy.procedural_tree_properties.clone().unwrap_or_default();
+            handle_add_forest(renderer_state, &gpu_resources.device, &gpu_resources.queue, copy.id.clone(), &tree, &forest.transforms);
+        }
+        ref kind => return Err(format!(
+            "{:?} components can't be duplicated. Supported kinds: {}",
+            kind.clone().unwrap_or(ComponentKind::Model),
+            DUPLICABLE_KINDS.join(", "),
+        )),
+    }
+
+    if let Some(level) = editor.saved_state.as_mut().and_then(|s| s.levels.as_mut()).and_then(|l| l.get_mut(0)) {
+        level.components.get_or_insert_with(Vec::new).push(copy.clone());
+    }
+
+    Ok(copy)
+}
+
+/// Adds a model asset to the renderer and the level as a new component.
+/// Shared by the `spawnModel` tool and the preview's right-click Spawn menu.
+pub async fn spawn_model_component(
+    editor: &mut Editor,
+    project_id: &str,
+    asset_id: &str,
+    position: [f32; 3],
+    rotation: [f32; 3],
+    scale: [f32; 3],
+) -> Result<ComponentData, String> {
+    let asset_file_name = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.models.iter().find(|m| m.id == asset_id))
+        .map(|model| model.fileName.clone())
+        .ok_or_else(|| format!("Asset not found: {}", asset_id))?;
+
+    let (Some(renderer_state), Some(gpu_resources), Some(camera)) =
+        (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref(), editor.camera.as_ref())
+    else {
+        return Err("The preview isn't ready yet".to_string());
+    };
+
+    let component = ComponentData {
+        id: Uuid::new_v4().to_string(),
+        kind: Some(ComponentKind::Model),
+        asset_id: asset_id.to_string(),
+        generic_properties: GenericProperties {
+            name: "New Model".to_string(),
+            position,
+            rotation,
+            scale,
+        },
+        ..Default::default()
+    };
+
+    let model_position = Translation3::new(position[0], position[1], position[2]);
+    let model_rotation = UnitQuaternion::from_euler_angles(
+        rotation[0].to_radians(), rotation[1].to_radians(), rotation[2].to_radians()
+    );
+    handle_add_model(
+        renderer_state,
+        &gpu_resources.device,
+        &gpu_resources.queue,
+        project_id.to_string(),
+        component.asset_id.clone(),
+        component.id.clone(),
+        asset_file_name,
+        Isometry3::from_parts(model_position, model_rotation),
+        Vector3::new(scale[0], scale[1], scale[2]),
+        camera,
+        None // Script state
+    ).await;
+
+    if let Some(level) = editor.saved_state.as_mut().and_then(|s| s.levels.as_mut()).and_then(|l| l.get_mut(0)) {
+        level.components.get_or_insert_with(Vec::new).push(component.clone());
+    }
+
+    Ok(component)
+}
+
+/// Removes a component from the level and drops its renderer objects, which releases their GPU buffers.
+/// Shared by the `deleteObject` tool and the editor's Delete button. Deleting a group leaves its children in place.
+pub fn delete_component(editor: &mut Editor, component_id: &str) -> Result<ComponentData, String> {
+    let components = editor.saved_state.as_mut()
+        .and_then(|saved_state| saved_state.levels.as_mut())
+        .and_then(|levels| levels.get_mut(0))
+        .and_then(|level| level.components.as_mut())
+        .ok_or_else(|| format!("Component {} not found", component_id))?;
+    let index = components.iter().position(|c| c.id == component_id)
+        .ok_or_else(|| format!("Component {} not found", component_id))?;
+    let removed = components.remove(index);
+
+    // Groups it belonged to forget it
+    for component in components.iter_mut() {
+        if let Some(group) = component.group_properties.as_mut() {
+            group.child_ids.retain(|id| id != component_id);
+        }
+    }
+
+    if let Some(renderer_state) = editor.renderer_state.as_mut() {
+        match removed.kind {
+            Some(ComponentKind::PointLight) => {
+                remove_point_light(&mut renderer_state.point_lights, component_id);
+            }
+            Some(ComponentKind::Landscape) => {
+                renderer_state.landscapes.retain(|l| l.id != component_id);
+                renderer_state.terrain_managers.retain(|t| t.landscape_id != component_id);
+            }
+            _ => {
+                // Models, collectables and NPCs all render as a model under the component id
+                renderer_state.models.retain(|m| m.id != component_id);
+                renderer_state.instanced_models.retain(|m| m.id != component_id);
+                renderer_state.water_planes.retain(|w| w.id != component_id);
+                renderer_state.directional_lights.retain(|l| l.id != component_id);
+                renderer_state.particle_emitters.retain(|e| e.id != component_id);
+                renderer_state.audio_sources.retain(|a| a.id != component_id);
+                renderer_state.forests.retain(|f| f.id != component_id);
+                renderer_state.trigger_volumes.retain(|t| t.id != component_id);
+                renderer_state.grasses.retain(|g| g.id != component_id);
+                renderer_state.procedural_trees.retain(|t| t.id != component_id);
+                renderer_state.cubes.retain(|c| c.id != component_id);
+                renderer_state.spheres.retain(|s| s.id != component_id);
+            }
+        }
+    }
+    sync_locked_components(editor);
+
+    Ok(removed)
+}
+
+/// Moves whatever the renderer draws for a component to `position`, whichever kind of component it is.
+pub fn apply_component_position(editor: &mut Editor, component_id: &str, position: [f32; 3]) {
+    let Some(renderer_state) = editor.renderer_state.as_mut() else { return };
+
+    // Models, collectables and NPCs all render as a model under the component id
+    if let Some(model) = renderer_state.models.iter_mut().find(|m| m.id == component_id) {
+        for mesh in model.meshes.iter_mut() {
+            mesh.transform.update_position(position);
+        }
+    }
+    if let Some(water_plane) = renderer_state.water_planes.iter_mut().find(|w| w.id == component_id) {
+        water_plane.transform.update_position(position);
+    }
+    if let Some(trigger) = renderer_state.trigger_volumes.iter_mut().find(|t| t.id == component_id) {
+        trigger.transform.update_position(position);
+    }
+    if let Some(audio_source) = renderer_state.audio_sources.iter_mut().find(|a| a.id == component_id) {
+        audio_source.transform.update_position(position);
+    }
+    if let Some(emitter) = renderer_state.particle_emitters.iter_mut().find(|e| e.id == component_id) {
+        emitter.transform.update_position(position);
+    }
+    if let Some(cube) = renderer_state.cubes.iter_mut().find(|c| c.id == component_id) {
+        cube.transform.update_position(position);
+    }
+    if let Some(sphere) = renderer_state.spheres.iter_mut().find(|s| s.id == component_id) {
+        sphere.transform.update_position(position);
+    }
+    // Point light gizmos follow on the next frame
+    if let Some(light) = renderer_state.point_lights.iter_mut().find(|l| l.id == component_id) {
+        light.position = position;
+    }
+}
+
+/// Pushes a component's kind-specific properties into the renderer after an edit made outside a tool call.
+pub fn apply_component_properties(editor: &mut Editor, component: &ComponentData) {
+    let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) else {
+        return;
+    };
+
+    match component.kind {
+        Some(ComponentKind::PointLight) => {
+            if let Some(properties) = component.light_properties.as_ref() {
+                if let Some(light) = renderer_state.point_lights.iter_mut().find(|l| l.id == component.id) {
+                    light.color = [properties.color[0], properties.color[1], properties.color[2]];
+                    light.intensity = properties.intensity;
+                }
+            }
+        }
+        Some(ComponentKind::WaterPlane) => {
+            if let Some(config) = component.water_properties.as_ref() {
+                if let Some(water_plane) = renderer_state.water_planes.iter_mut().find(|w| w.id == component.id) {
+                    water_plane.update_config(&gpu_resources.queue, config.clone());
+                }
+            }
+        }
+        Some(ComponentKind::DirectionalLight) => {
+            if let Some(properties) = component.directional_light_properties.as_ref() {
+                if let Some(light) = renderer_state.directional_lights.iter_mut().find(|l| l.id == component.id) {
+                    light.color = properties.color;
+                    light.intensity = properties.intensity;
+                    light.cast_shadows = properties.cast_shadows;
+                }
+            }
+        }
+        Some(ComponentKind::ParticleEffect) => {
+            if let Some(properties) = component.particle_properties.as_ref() {
+                if let Some(emitter) = renderer_state.particle_emitters.iter_mut().find(|e| e.id == component.id) {
+                    emitter.update_config(&gpu_resources.queue, properties);
+                }
+            }
+        }
+        // Grass and trees aren't mapped to renderer objects by id yet, same as configureGrass/configureTrees
+        Some(ComponentKind::ProceduralGrass) => {
+            if let Some(props) = component.procedural_grass_properties.as_ref() {
+                for grass in renderer_state.grasses.iter_mut() {
+                    grass.config.wind_strength = props.wind_strength;
+                    grass.config.wind_speed = props.wind_speed;
+                    grass.config.blade_height = props.blade_height;
+                    grass.config.blade_width = props.blade_width;
+                    grass.config.blade_density = safe_grass_density(props.blade_density as f32);
+                    grass.config.render_distance = props.render_distance;
+                    grass.config.density_falloff = props.density_falloff;
+                    grass.config.terrain_tint_strength = props.terrain_tint_strength;
+                }
+            }
+        }
+        Some(ComponentKind::ProceduralTree) => {
+            if let Some(props) = component.procedural_tree_properties.as_ref() {
+                for trees in renderer_state.procedural_trees.iter_mut() {
+                    trees.regenerate(&gpu_resources.device, props.clone());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Loads a model component's PBR material into the renderer. `apply_component_properties` can't, the
+/// textures load asynchronously, so property panel edits to the material come through here.
+pub async fn apply_model_material(editor: &mut Editor, project_id: String, component_id: &str) -> Result<(), String> {
+    let pbr_data = editor.saved_state.as_ref()
+        .and_then(|saved_state| {
+            let pbr_texture_id = saved_state.levels.as_ref()
+                .and_then(|l| l.get(0))
+                .and_then(|level| level.components.as_ref())
+                .and_then(|components| components.iter().find(|c| c.id == component_id))
+                .and_then(|component| component.model_properties.as_ref())
+                .and_then(|properties| properties.pbr_texture_id.as_ref())?;
+            saved_state.pbr_textures.as_ref()?.iter().find(|p| &p.id == pbr_texture_id).cloned()
+        })
+        .ok_or_else(|| format!("No PBR material found for model {}", component_id))?;
+
+    if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+        handle_set_model_material(
+            renderer_state,
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            project_id,
+            component_id.to_string(),
+            &pbr_data
+        ).await;
+    }
+    Ok(())
+}
+
+/// Tells the editor which components are locked, so canvas clicks and gizmo drags pass over them.
+pub fn sync_locked_components(editor: &mut Editor) {
+    editor.locked_component_ids = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_ref())
+        .map(|components| components.iter().filter(|c| c.locked).map(|c| c.id.clone()).collect())
+        .unwrap_or_default();
+}
+
+/// Tells the renderer which components are hidden, so the preview skips drawing them.
+pub fn sync_hidden_components(editor: &mut Editor) {
+    editor.hidden_component_ids = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_ref())
+        .map(|components| components.iter().filter(|c| c.hidden).map(|c| c.id.clone()).collect())
+        .unwrap_or_default();
+}
+
+/// Swaps the loaded scene for `saved_state` without recreating the device or surface.
+async fn reset_scene(editor: &mut Editor, project_id: &str, saved_state: Option<SavedState>) {
+    // Dropping the renderer objects releases their GPU buffers
+    if let Some(renderer_state) = editor.renderer_state.as_mut() {
+        renderer_state.models.clear();
+        renderer_state.instanced_models.clear();
+        renderer_state.landscapes.clear();
+        renderer_state.terrain_managers.clear();
+        renderer_state.water_planes.clear();
+        renderer_state.grasses.clear();
+        renderer_state.procedural_trees.clear();
+        renderer_state.forests.clear();
+        renderer_state.point_lights.clear();
+        renderer_state.light_gizmos.clear();
+        renderer_state.directional_lights.clear();
+        renderer_state.audio_sources.clear();
+        renderer_state.particle_emitters.clear();
+        renderer_state.trigger_volumes.clear();
+        renderer_state.player_spawn_gizmo = None;
+        renderer_state.cubes.clear();
+        renderer_state.spheres.clear();
+    }
+
+    editor.saved_state = saved_state.clone();
+    // Heights are reloaded from the project's heightmaps on the next edit
+    terrain_edit::clear();
+    day_night::restart();
+
+    if let Some(saved_state) = saved_state {
+        place_project(editor, project_id, saved_state).await;
+        apply_level_render_settings(editor);
+        sync_locked_components(editor);
+        sync_hidden_components(editor);
+    }
+}
+
+/// Moves keyboard focus to a project list item (or the filter input), ignoring anything that can't take focus.
+fn focus_project_item(element: Option<web_sys::Element>) {
+    if let Some(element) = element.and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok()) {
+        let _ = element.focus();
+    }
+}
+
+/// The preview's right-click menu and what was under the cursor when it opened.
+#[derive(Clone, Debug)]
+struct CanvasContextMenu {
+    // Page coordinates the menu is placed at
+    left: f32,
+    top: f32,
+    component: Option<ComponentData>,
+    // Where Spawn places the new model, the terrain under the cursor or the origin
+    spawn_point: [f32; 3],
+    // Model assets offered by Spawn, as (id, file name)
+    models: Vec<(String, String)>,
+    choosing_model: bool,
+}
+
+/// Mouse movement gathered between animation frames so the editor handlers run at most once per frame.
+#[derive(Clone, Copy, Debug, Default)]
+struct PendingMouseMove {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    // Only the movement that happened while shift was held, so shift-panning stays 1:1
+    shift_dx: f32,
+    shift_dy: f32,
+    shifted: bool,
+    left_mouse_pressed: bool,
+    // Orbit mode drags, left to turn around the pivot and middle to pan
+    orbit_dx: f32,
+    orbit_dy: f32,
+    pan_dx: f32,
+    pan_dy: f32,
+    // Timestamp of the oldest event waiting, in `performance.now()` time, for the input latency readout
+    input_at: Option<f64>,
+    dirty: bool,
+}
+
+/// A sculpt drag on the landscape, from mousedown to mouseup.
+#[derive(Clone, Debug)]
+struct SculptStroke {
+    info: terrain_edit::LandscapeHeightmapInfo,
+    // Height under the cursor where the stroke started, flatten levels to it
+    target: f32,
+    changed: bool,
+}
+
+/// Meters between grid lines in the preview, and the range the spacing input allows.
+const DEFAULT_GRID_SPACING: f32 = 1.0;
+const MIN_GRID_SPACING: f32 = 0.1;
+const MAX_GRID_SPACING: f32 = 1000.0;
+
+/// WASD fly speed multiplier, remembered per project, and the slider's range.
+const DEFAULT_CAMERA_SPEED: f32 = 1.0;
+const MIN_CAMERA_SPEED: f32 = 0.1;
+const MAX_CAMERA_SPEED: f32 = 20.0;
+/// Extra speed while Shift is held.
+const CAMERA_SPRINT_FACTOR: f32 = 4.0;
+
+/// Where a ray through canvas position (x, y) meets the y = 0 ground plane, for scenes with no terrain there.
+/// `None` when the ray points level with or away from the ground.
+fn ground_plane_point(editor: &Editor, x: f32, y: f32) -> Option<[f32; 3]> {
+    let camera = editor.camera.as_ref()?;
+    let size = &camera.viewport.window_size;
+    if size.width == 0 || size.height == 0 {
+        return None;
+    }
+    let forward = camera.direction.normalize();
+    let right = forward.cross(&nalgebra::Vector3::y()).normalize();
+    let up = right.cross(&forward);
+
+    // Canvas position in -1..1, scaled to the view frustum at unit distance
+    let half_height = (camera.fovy / 2.0).tan();
+    let half_width = half_height * size.width as f32 / size.height as f32;
+    let ndc_x = x / size.width as f32 * 2.0 - 1.0;
+    let ndc_y = 1.0 - y / size.height as f32 * 2.0;
+    let ray = (forward + right * ndc_x * half_width + up * ndc_y * half_height).normalize();
+
+    if ray.y.abs() <= f32::EPSILON {
+        return None;
+    }
+    let distance = -camera.position.y / ray.y;
+    if distance <= 0.0 || !distance.is_finite() {
+        return None;
+    }
+    let point = camera.position + ray * distance;
+    Some([point.x, point.y, point.z])
+}
+
+/// The terrain under canvas position (x, y), or the y = 0 ground plane where there's no terrain.
+fn placement_point(editor: &Editor, x: f32, y: f32) -> Option<[f32; 3]> {
+    editor.pick_landscape_point(EntropyPosition { x, y }).or_else(|| ground_plane_point(editor, x, y))
+}
+
+/// Orbit pivot at a component's position.
+fn component_orbit_pivot(editor: &Editor, component_id: &str) -> Option<nalgebra::Point3<f32>> {
+    editor.saved_state.as_ref()?
+        .levels.as_ref()?
+        .get(0)?
+        .components.as_ref()?
+        .iter()
+        .find(|c| c.id == component_id)
+        .map(|c| nalgebra::Point3::from(c.generic_properties.position))
+}
+
+/// Orbit pivot at the selected component, the first choice whenever there is one.
+fn selected_orbit_pivot(editor: &Editor) -> Option<nalgebra::Point3<f32>> {
+    component_orbit_pivot(editor, editor.selected_component_id.as_deref()?)
+}
+
+/// Orbit pivot for a double-click in the preview: the component under the cursor, or the ground there.
+fn pick_orbit_pivot(editor: &Editor, x: f32, y: f32) -> Option<nalgebra::Point3<f32>> {
+    if let Some(pivot) = editor.pick_component(EntropyPosition { x, y }).and_then(|component_id| component_orbit_pivot(editor, &component_id)) {
+        return Some(pivot);
+    }
+    let point = placement_point(editor, x, y)?;
+    Some(nalgebra::Point3::from(point))
+}
+
+fn camera_speed_key(project_id: &str) -> String {
+    format!("entropy-chat:camera-speed:{}", project_id)
+}
+
+fn camera_view_key(project_id: &str) -> String {
+    format!("entropy-chat:camera-view:{}", project_id)
+}
+
+/// Where the preview camera was left in a project, remembered per browser since projects don't save one.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct SavedCameraView {
+    position: [f32; 3],
+    direction: [f32; 3],
+}
+
+fn save_camera_view(editor: &Editor, project_id: &str) {
+    let Some(camera) = editor.camera.as_ref() else { return };
+    let view = SavedCameraView {
+        position: [camera.position.x, camera.position.y, camera.position.z],
+        direction: [camera.direction.x, camera.direction.y, camera.direction.z],
+    };
+    if let Ok(value) = serde_json::to_string(&view) {
+        storage::set_item(&camera_view_key(project_id), &value);
+    }
+}
+
+/// Puts the camera back where it was left in the project. Returns false when there's no saved view.
+fn restore_camera_view(editor: &mut Editor, project_id: &str) -> bool {
+    let view = storage::get_item(&camera_view_key(project_id))
+        .and_then(|value| serde_json::from_str::<SavedCameraView>(&value).ok())
+        .filter(|view| view.position.iter().chain(view.direction.iter()).all(|v| v.is_finite()));
+    let (Some(view), Some(camera)) = (view, editor.camera.as_mut()) else {
+        return false;
+    };
+    camera.position = nalgebra::Point3::from(view.position);
+    camera.direction = nalgebra::Vector3::from(view.direction);
+    true
+}
+
+/// How the preview hands frames to the display, remembered across projects. Fifo waits for vsync and
+/// is always available, Mailbox and Immediate trade tearing or power for lower latency where the GPU has them.
+const PRESENT_MODE_KEY: &str = "entropy-chat:present-mode";
+const PRESENT_MODES: &[(&str, wgpu::PresentMode)] = &[
+    ("Fifo", wgpu::PresentMode::Fifo),
+    ("Mailbox", wgpu::PresentMode::Mailbox),
+    ("Immediate", wgpu::PresentMode::Immediate),
+];
+/// Frames the GPU may queue ahead of the display, lower reacts faster and higher smooths out weak GPUs.
+const FRAME_LATENCY_KEY: &str = "entropy-chat:frame-latency";
+const DEFAULT_FRAME_LATENCY: u32 = 2;
+const MAX_FRAME_LATENCY: u32 = 3;
+
+/// The requested present mode if the surface supports it, Fifo otherwise.
+fn supported_present_mode(requested: wgpu::PresentMode, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+    if supported.contains(&requested) { requested } else { wgpu::PresentMode::Fifo }
+}
+
+/// Brush size and strength bounds for canvas sculpting, in meters.
+const MIN_SCULPT_RADIUS: f32 = 2.0;
+const MAX_SCULPT_RADIUS: f32 = 200.0;
+const DEFAULT_SCULPT_RADIUS: f32 = 20.0;
+/// Height raise/lower moves per frame at full strength.
+const MAX_SCULPT_STEP: f32 = 2.0;
+/// Outer fraction of the brush that fades out.
+const SCULPT_BRUSH_EDGE: f32 = 0.5;
+
+/// World units the camera dollies per pixel of wheel `deltaY`.
+const WHEEL_ZOOM_SPEED: f32 = 0.05;
+/// Step multiplier while shift is held, for fine positioning.
+const WHEEL_ZOOM_FINE_FACTOR: f32 = 0.1;
+/// Largest single dolly step, so fast trackpads don't jump across the scene.
+const MAX_WHEEL_ZOOM_STEP: f32 = 25.0;
+/// How far from the world origin the wheel is allowed to take the camera.
+const MAX_CAMERA_DISTANCE: f32 = 5000.0;
+/// Closest the wheel brings the camera to whatever is in the middle of the view.
+const MIN_CAMERA_DISTANCE: f32 = 0.5;
+/// How far back focusing on a component puts the camera, per unit of its largest scale axis.
+const FOCUS_DISTANCE_PER_SCALE: f32 = 8.0;
+const MIN_FOCUS_DISTANCE: f32 = 5.0;
+/// How far back framing the whole scene puts the camera, per unit of the scene's bounding radius.
+const FRAME_DISTANCE_PER_RADIUS: f32 = 2.0;
+
+/// Delay before a typed draft is mirrored into localStorage.
+const DRAFT_SAVE_DEBOUNCE_MS: f64 = 500.0;
+
+fn draft_storage_key(session_id: &str) -> String {
+    format!("entropy-chat:draft:{}", session_id)
+}
+
+/// Models the chat backend can route to, as `(value sent to the server, label)`.
+/// The empty value leaves the choice to the server.
+const CHAT_MODELS: &[(&str, &str)] = &[
+    ("", "Server default"),
+    ("openai/gpt-4o", "GPT-4o"),
+    ("openai/gpt-4o-mini", "GPT-4o mini"),
+    ("google/gemini-2.5-pro", "Gemini 2.5 Pro"),
+    ("mistral/mistral-large-latest", "Mistral Large"),
+];
+
+fn model_storage_key(session_id: &str) -> String {
+    format!("entropy-chat:model:{}", session_id)
+}
+
+fn temperature_storage_key(session_id: &str) -> String {
+    format!("entropy-chat:temperature:{}", session_id)
+}
+
+/// Temperature range offered by the creativity slider.
+const MIN_TEMPERATURE: f32 = 0.0;
+const MAX_TEMPERATURE: f32 = 2.0;
+/// Used until the user moves the slider in a session.
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+fn chat_model_label(value: &str) -> String {
+    CHAT_MODELS.iter()
+        .find(|(model, _)| *model == value)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// How close (in px) to the bottom of the chat counts as "following" new messages.
+const CHAT_NEAR_BOTTOM_PX: i32 = 48;
+
+/// Consecutive `Lost`/`Outdated` frames we try to reconfigure through before giving up on the surface.
+const MAX_SURFACE_RECOVERY_ATTEMPTS: u32 = 5;
+
+/// Share of the width the chat pane gets when nothing has been saved, or after a double-click on the divider.
+const DEFAULT_CHAT_PANE_RATIO: f64 = 0.5;
+/// Keeps either pane from being dragged away completely.
+const MIN_CHAT_PANE_RATIO: f64 = 0.2;
+const MAX_CHAT_PANE_RATIO: f64 = 0.8;
+const CHAT_PANE_RATIO_KEY: &str = "entropy-chat:pane-ratio";
+
+/// Whether system and tool messages are folded into "N actions" lines.
+const COLLAPSE_ACTIONS_KEY: &str = "entropy-chat:collapse-actions";
+
+/// Explicit theme choice, the system preference applies until one is made.
+const THEME_KEY: &str = "entropy-chat:theme";
+
+/// Remembered answer to "continue the last conversation or start a new one", per project.
+fn session_choice_key(project_id: &str) -> String {
+    format!("entropy-chat:session-choice:{}", project_id)
+}
+const SESSION_CHOICE_CONTINUE: &str = "continue";
+const SESSION_CHOICE_NEW: &str = "new";
+
+/// Status lines, tool results and tool-call-only replies, as opposed to the user/assistant dialogue.
+fn is_action_message(message: &ChatMessage) -> bool {
+    match message.role.as_str() {
+        "system" | "tool" => true,
+        "assistant" => message.content.as_deref().is_none_or(|content| content.trim().is_empty())
+            && message.tool_calls.as_ref().is_some_and(|calls| !calls.is_empty()),
+        _ => false,
+    }
+}
+
+/// A run of the transcript: one dialogue message, or consecutive action messages that can collapse into one line.
+enum TranscriptEntry {
+    Message(usize, ChatMessage),
+    Actions(Vec<(usize, ChatMessage)>),
+}
+
+/// Groups consecutive action messages, keeping each message's index in the full list.
+fn group_action_messages(messages: Vec<ChatMessage>) -> Vec<TranscriptEntry> {
+    let mut entries = Vec::new();
+    for (index, message) in messages.into_iter().enumerate() {
+        if !is_action_message(&message) {
+            entries.push(TranscriptEntry::Message(index, message));
+            continue;
+        }
+        match entries.last_mut() {
+            Some(TranscriptEntry::Actions(actions)) => actions.push((index, message)),
+            _ => entries.push(TranscriptEntry::Actions(vec![(index, message)])),
+        }
+    }
+    entries
+}
+
+/// Whether a message's text or tool calls contain `query`, which is already lowercased.
+fn message_matches(message: &ChatMessage, query: &str) -> bool {
+    let content_matches = message.content.as_ref().is_some_and(|content| content.to_lowercase().contains(query));
+    let tool_call_matches = message.tool_calls.iter().flatten().any(|tool_call| {
+        tool_call.function.name.to_lowercase().contains(query) || tool_call.function.arguments.to_lowercase().contains(query)
+    });
+    content_matches || tool_call_matches
+}
+
+/// Longest automatic session title, cut at a word boundary.
+const SESSION_TITLE_MAX_CHARS: usize = 48;
+
+/// A short session title from its opening message: the first line, cut to a few words.
+fn session_title_from(message: &str) -> String {
+    let line = message.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+    let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= SESSION_TITLE_MAX_CHARS {
+        return line;
+    }
+
+    let cut: String = line.chars().take(SESSION_TITLE_MAX_CHARS).collect();
+    let cut = match cut.rfind(' ') {
+        Some(index) if index > 0 => &cut[..index],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+}
+
+/// How a session is listed: its title, or its position for sessions that don't have one yet.
+fn session_label(session: &ChatSession, index: usize) -> String {
+    session.title.clone().unwrap_or_else(|| format!("Session {}", index + 1))
+}
+
+/// Preview clear color per theme, behind the sky.
+const LIGHT_CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.96, g: 0.96, b: 0.96, a: 1.0 };
+const DARK_CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.09, g: 0.09, b: 0.09, a: 1.0 };
+
+/// How often the preview stats readout is refreshed, so it doesn't rerender every frame.
+const RENDER_STATS_INTERVAL_MS: f64 = 500.0;
+
+/// Frame timing and culling counts shown under the preview.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct RenderStats {
+    fps: f64,
+    frame_ms: f64,
+    // Mouse event to presented frame, averaged. None when the mouse didn't move since the last refresh
+    input_ms: Option<f64>,
+    rendered: u32,
+    culled: u32,
+}
+
+/// Frames accumulated since the stats readout was last refreshed.
+#[derive(Clone, Copy, Debug, Default)]
+struct FrameTimer {
+    frames: u32,
+    elapsed_ms: f64,
+    input_samples: u32,
+    input_ms: f64,
+}
+
+#[component]
+pub fn ProjectCanvas(
+    selected_project: ReadSignal<Option<Project>>,
+    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
+    is_initialized: ReadSignal<bool>,
+    set_is_initialized: WriteSignal<bool>,
+    show_preview: ReadSignal<bool>,
+    dark_theme: Signal<bool>,
+    // Bumped after tool calls change the scene, so level settings shown in the toolbar stay current
+    scene_version: ReadSignal<u32>,
+    // Bumped after edits from the right-click menu, so the component list picks them up
+    set_scene_version: WriteSignal<u32>,
+    // Shared with the Components tab, clicking a component here opens it there
+    selected_component_id: ReadSignal<Option<String>>,
+    set_selected_component_id: WriteSignal<Option<String>>,
+) -> impl IntoView {
+    let canvas_ref = NodeRef::<Canvas>::new();
+
+    // The preview draws a bounding box around the selected component
+    create_effect(move |_| {
+        let component_id = selected_component_id.get();
+        if let Some(pipeline_arc) = pipeline_store.get().flatten() {
+            let mut pipeline_guard = pipeline_arc.borrow_mut();
+            if let Some(editor) = pipeline_guard.export_editor.as_mut() {
+                editor.selected_component_id = component_id;
+            }
+        }
+    });
+    let surface_config_store = StoredValue::new(None::<wgpu::SurfaceConfiguration>);
+    let surface_failures = StoredValue::new(0u32);
+    let (preview_lost, set_preview_lost) = signal(false);
+    let (restart_token, set_restart_token) = signal(0u32);
+    let pending_mouse_move = StoredValue::new(PendingMouseMove::default());
+    // Set by the "Preview lost" overlay so the next run recreates the device and surface
+    let force_reinitialize = StoredValue::new(false);
+    // Debug toggle, turning LOD off renders every model at full detail
+    let (lod_enabled, set_lod_enabled) = signal(true);
+    // Debug toggle for the main pass, shadow and reflection passes always keep their off-screen casters
+    let (frustum_culling, set_frustum_culling) = signal(true);
+    // Debug views for checking geometry, editor-only and never saved with the project
+    let (wireframe, set_wireframe) = signal(false);
+    let (debug_overlays, set_debug_overlays) = signal(false);
+    // Grid increment the gizmo snaps to, off by default so organic placement stays freeform
+    let (snap_enabled, set_snap_enabled) = signal(false);
+    let (snap_increment, set_snap_increment) = signal(1.0f32);
+    // Ground grid and axis gizmo at the world origin, drawn in the editor preview only
+    let (show_grid, set_show_grid) = signal(true);
+    let (grid_spacing, set_grid_spacing) = signal(DEFAULT_GRID_SPACING);
+    let (camera_speed, set_camera_speed) = signal(DEFAULT_CAMERA_SPEED);
+    let (present_mode, set_present_mode) = signal(
+        storage::get_item(PRESENT_MODE_KEY)
+            .and_then(|label| PRESENT_MODES.iter().find(|(name, _)| *name == label).map(|(_, mode)| *mode))
+            .unwrap_or(wgpu::PresentMode::Fifo)
+    );
+    let (frame_latency, set_frame_latency) = signal(
+        storage::get_item(FRAME_LATENCY_KEY)
+            .and_then(|value| value.parse::<u32>().ok())
+            .map(|latency| latency.clamp(1, MAX_FRAME_LATENCY))
+            .unwrap_or(DEFAULT_FRAME_LATENCY)
+    );
+    // Filled in once the surface exists, modes missing here are greyed out in the toolbar
+    let (supported_present_modes, set_supported_present_modes) = signal(vec![wgpu::PresentMode::Fifo]);
+    // Orbit mode turns the camera around a pivot instead of flying, the pivot is the selected component
+    let (orbit_mode, set_orbit_mode) = signal(false);
+    let orbit_pivot = StoredValue::new(None::<nalgebra::Point3<f32>>);
+
+    // Turning orbit on, or selecting another component while it's on, orbits around the selection
+    create_effect(move |_| {
+        let component_id = selected_component_id.get();
+        if !orbit_mode.get() {
+            return;
+        }
+        let Some(component_id) = component_id else { return };
+        if let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() {
+            let pipeline = pipeline_arc.borrow();
+            if let Some(pivot) = pipeline.export_editor.as_ref().and_then(|editor| component_orbit_pivot(editor, &component_id)) {
+                orbit_pivot.set_value(Some(pivot));
+            }
+        }
+    });
+    // Mirrors the level's ambient occlusion setting, off until a level turns it on
+    let (ao_enabled, set_ao_enabled) = signal(false);
+    let (render_stats, set_render_stats) = signal(RenderStats::default());
+    // World position of the terrain under the cursor, for giving the assistant exact coordinates
+    let (cursor_point, set_cursor_point) = signal(None::<[f32; 3]>);
+    let (context_menu, set_context_menu) = signal(None::<CanvasContextMenu>);
+    let frame_timer = StoredValue::new(FrameTimer::default());
+    // Canvas size in pixels once its box has changed, applied at the start of the next frame
+    let pending_resize = StoredValue::new(None::<(u32, u32)>);
+    // Terrain sculpting, off unless a mode is picked
+    let (sculpt_mode, set_sculpt_mode) = signal(None::<terrain_edit::SculptMode>);
+    let (sculpt_radius, set_sculpt_radius) = signal(DEFAULT_SCULPT_RADIUS);
+    let (sculpt_strength, set_sculpt_strength) = signal(0.5f32);
+    let (sculpt_falloff, set_sculpt_falloff) = signal("Smooth");
+    let (sculpt_status, set_sculpt_status) = signal(None::<String>);
+    let sculpt_stroke = StoredValue::new(None::<SculptStroke>);
+    // Cursor position of the latest sculpt drag, applied on the next frame
+    let pending_sculpt = StoredValue::new(None::<(f32, f32)>);
+
+    // The canvas fills the pane width, so the drawing buffer follows the pane as it's resized
+    use_resize_observer(canvas_ref, move |entries, _| {
+        if let Some(entry) = entries.first() {
+            let rect = entry.content_rect();
+            let width = rect.width().round() as u32;
+            let height = rect.height().round() as u32;
+            if width > 0 && height > 0 {
+                pending_resize.set_value(Some((width, height)));
+            }
+        }
+    });
+    
+    // Project whose scene is loaded, its camera view is saved when another one replaces it
+    let loaded_project_id = StoredValue::new(None::<String>);
+
+    create_effect(move |_| {
+        // Bumped by the "Preview lost" overlay to rerun initialization
+        restart_token.get();
+
+        let canvas = canvas_ref.get();
+        if canvas.is_none() {
+            return;
+        }
+        let canvas = canvas.expect("canvas should be loaded");
+
+        if let Some(project) = selected_project.get() {
+            let project_data = project.clone();
+            if let Some(pipeline_arc) = pipeline_store.get() {
+                if let Some(pipeline_arc) = pipeline_arc.as_ref() {
+                    let pipeline_arc_clone = pipeline_arc.clone();
+
+                    // Stop the render loop and editors from borrowing the pipeline while it loads
+                    set_is_initialized.set(false);
+
+                    spawn_local(async move {
+                        let mut pipeline_guard = pipeline_arc_clone.borrow_mut();
+
+                        let reuse_gpu = pipeline_guard.gpu_resources.is_some() && !force_reinitialize.get_value();
+                        force_reinitialize.set_value(false);
+
+                        if reuse_gpu {
+                            log!("resetting scene...");
+
+                            let editor = pipeline_guard.export_editor.as_mut().expect("Couldn't get editor");
+                            if let Some(previous_id) = loaded_project_id.get_value() {
+                                save_camera_view(editor, &previous_id);
+                            }
+                            loaded_project_id.set_value(Some(project_data.id.clone()));
+                            reset_scene(editor, &project_data.id, project_data.saved_data).await;
+                            // Opens where the camera was left, or with everything in view the first time
+                            if !restore_camera_view(editor, &project_data.id) {
+                                frame_scene(editor);
+                            }
+
+                            log!("Scene Reset Complete!");
+
+                            set_is_initialized.set(true);
+                            return;
+                        }
+
+                        log!("initializing...");
+                        
+                        #[cfg(target_arch = "wasm32")]
+                        pipeline_guard
+                            .initialize(
+                                Some(canvas),
+                                WindowSize {
+                                    width: 1024,
+                                    height: 768,
+                                },
+                                Vec::new(),
+                                SavedTimelineStateConfig {
+                                    timeline_sequences: Vec::new(),
+                                },
+                                1024,
+                                768,
+                                Uuid::new_v4().to_string(),
+                                false,
+                            )
+                            .await;
+
+                        log!("loading project...");
+
+                        let editor = pipeline_guard.export_editor.as_mut().expect("Couldn't get editor");
+                        // Manually load saved state
+                        if let Some(saved_data) = project_data.saved_data {
+                            editor.saved_state = Some(saved_data.clone());
+                            // We also need to trigger loading of assets/models based on this saved state
+                            // But load_project usually does that. 
+                            // Since we can't easily call load_project which might do FS ops,
+                            // we might need to rely on what's available or implement a lighter load_project.
+                            // For now, let's assume placing the project state is enough or call a helper if available.
+                            
+                            // Re-implement basic loading logic from place_project/load_project if needed
+                            // But place_project is available.
+                             place_project(editor, &project_data.id, saved_data.clone()).await;
+                             apply_level_render_settings(editor);
+                             sync_locked_components(editor);
+                             sync_hidden_components(editor);
+                             if !restore_camera_view(editor, &project_data.id) {
+                                 frame_scene(editor);
+                             }
                         }
-                    }
+                        loaded_project_id.set_value(Some(project_data.id.clone()));
+
+                        log!("configuring surface...");
+
+                        let editor = pipeline_guard.export_editor.as_ref().expect("Couldn't get editor");
+                        let camera = editor.camera.as_ref().expect("Couldn't get camera");
+                        let gpu_resources = pipeline_guard.gpu_resources.as_ref().expect("Couldn't get gpu resources");
+                        let surface = gpu_resources.surface.as_ref().expect("Couldn't get surface").clone();
+                        let size = camera.viewport.window_size.clone();
+
+                        let supported_modes = surface.get_capabilities(&gpu_resources.adapter).present_modes;
+                        set_supported_present_modes.set(supported_modes.clone());
+
+                        let swapchain_format = wgpu::TextureFormat::Rgba8Unorm;
+                        let surface_config = wgpu::SurfaceConfiguration {
+                            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                            format: swapchain_format,
+                            width: size.width,
+                            height: size.height,
+                            present_mode: supported_present_mode(present_mode.get_untracked(), &supported_modes),
+                            alpha_mode: wgpu::CompositeAlphaMode::PreMultiplied,
+                            view_formats: vec![],
+                            desired_maximum_frame_latency: frame_latency.get_untracked()
+                        };
+
+                        surface.configure(&gpu_resources.device, &surface_config);
+                        surface_config_store.set_value(Some(surface_config));
+                        surface_failures.set_value(0);
+
+                        // Pipelines created later (e.g. water planes) must target the same format
+                        if let Some(editor) = pipeline_guard.export_editor.as_mut() {
+                            editor.surface_format = Some(swapchain_format);
+                        }
+
+                        log!("Setup Complete!");
+
+                        set_is_initialized.set(true);
+                    });
                 }
             }
         }
-    } else if tool_call.function.name == "configureGrass" {
-        log!("Configuring grass...");
-        let args: Result<ConfigureGrassArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-             if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
+    });
+
+    // Starts a sculpt stroke under the cursor, loading the landscape's heights first if needed
+    let begin_sculpt = move |x: f32, y: f32| {
+        let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
+        let Some(pipeline) = pipeline_store.get_untracked() else { return };
+        let Some(pipeline_arc) = pipeline.as_ref() else { return };
+        let mut pipeline_guard = pipeline_arc.borrow_mut();
+        let Some(editor) = pipeline_guard.export_editor.as_mut() else { return };
+
+        // The landscape under the cursor, not just the level's first one
+        let Some(info) = terrain_edit::landscape_at(editor, x, y).and_then(|component_id| {
+            editor.saved_state.as_ref().and_then(|saved_state| terrain_edit::landscape_heightmap_info(saved_state, Some(&component_id)))
+        }) else {
+            set_sculpt_status.set(Some("No landscape under the cursor to sculpt".to_string()));
+            return;
+        };
+        let Some(field) = terrain_edit::get(&info.asset_id) else {
+            set_sculpt_status.set(Some("Loading heightmap...".to_string()));
+            spawn_local(async move {
+                match terrain_edit::load(&project_path, &info).await {
+                    Ok(_) => set_sculpt_status.set(None),
+                    Err(e) => set_sculpt_status.set(Some(e)),
+                }
+            });
+            return;
+        };
+
+        let target = editor.pick_landscape_point(EntropyPosition { x, y })
+            .map(|point| {
+                let center = field.world_to_pixel(info.position, point[0], point[2]);
+                field.sample(center[0], center[1])
+            })
+            .unwrap_or(0.0);
+        sculpt_stroke.set_value(Some(SculptStroke { info, target, changed: false }));
+        pending_sculpt.set_value(Some((x, y)));
+    };
+
+    // Strokes end wherever the mouse is let go, the new heights are saved once per stroke
+    // Leaving the page keeps the camera where it is for next time
+    let _camera_view_listener = window_event_listener(leptos::ev::pagehide, move |_| {
+        let Some(project_id) = loaded_project_id.get_value() else { return };
+        if let Some(pipeline_arc) = pipeline_store.get_untracked().and_then(|pipeline| pipeline.as_ref().cloned()) {
+            if let Ok(pipeline) = pipeline_arc.try_borrow() {
+                if let Some(editor) = pipeline.export_editor.as_ref() {
+                    save_camera_view(editor, &project_id);
+                }
+            }
+        }
+    });
+
+    let _sculpt_up_listener = window_event_listener(leptos::ev::mouseup, move |_| {
+        let Some(stroke) = sculpt_stroke.get_value() else { return };
+        sculpt_stroke.set_value(None);
+        pending_sculpt.set_value(None);
+        if !stroke.changed {
+            return;
+        }
+
+        let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
+        if let Some(field) = terrain_edit::get(&stroke.info.asset_id) {
+            spawn_local(async move {
+                if let Err(e) = terrain_edit::upload(&project_path, &stroke.info, &field).await {
+                    set_sculpt_status.set(Some(e));
+                }
+            });
+        }
+    });
+
+    let Pausable { pause, resume, is_active: _ } = use_raf_fn(move |args| {
+        if is_initialized.get() && !preview_lost.get_untracked() {
+            if let Some(pipeline) = pipeline_store.get_untracked() {
+                if let Some(pipeline_arc) = pipeline.as_ref() {
                     let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        
-                        // Update RendererState (Live)
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                             for grass in renderer_state.grasses.iter_mut() {
-                                 if let Some(val) = args.wind_strength { grass.config.wind_strength = val; }
-                                 if let Some(val) = args.wind_speed { grass.config.wind_speed = val; }
-                                 if let Some(val) = args.blade_height { grass.config.blade_height = val; }
-                                 if let Some(val) = args.blade_width { grass.config.blade_width = val; }
-                                 if let Some(val) = args.blade_density { grass.config.blade_density = val; }
-                                 if let Some(val) = args.render_distance { grass.config.render_distance = val; }
-                             }
-                        }
 
-                        // Update SavedState
-                        if let Some(saved_state) = editor.saved_state.as_mut() {
-                            if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                if let Some(components) = level.components.as_mut() {
-                                    // Find existing grass
-                                    let mut found = false;
-                                    for component in components.iter_mut() {
-                                        if component.kind == Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralGrass) {
-                                            if let Some(target_id) = &args.component_id {
-                                                if &component.id != target_id {
-                                                    continue;
-                                                }
-                                            }
-                                            
-                                            if component.procedural_grass_properties.is_none() {
-                                                component.procedural_grass_properties = Some(entropy_engine::helpers::saved_data::ProceduralGrassProperties::default());
-                                            }
-                                            if let Some(props) = component.procedural_grass_properties.as_mut() {
-                                                if let Some(val) = args.wind_strength { props.wind_strength = val; }
-                                                if let Some(val) = args.wind_speed { props.wind_speed = val; }
-                                                if let Some(val) = args.blade_height { props.blade_height = val; }
-                                                if let Some(val) = args.blade_width { props.blade_width = val; }
-                                                if let Some(val) = args.blade_density { props.blade_density = val as u32; }
-                                                if let Some(val) = args.render_distance { props.render_distance = val; }
-                                            }
-                                            found = true;
-                                        }
+                    // Apply the mouse movement coalesced since the last frame
+                    let pending = pending_mouse_move.get_value();
+                    let input_at = pending.input_at;
+                    if pending.dirty {
+                        pending_mouse_move.set_value(PendingMouseMove::default());
+
+                        if let Some(editor) = pipeline.export_editor.as_mut() {
+                            if orbit_mode.get_untracked() {
+                                if let Some(mut pivot) = orbit_pivot.get_value().or_else(|| selected_orbit_pivot(editor)).or_else(|| orbit_camera::default_pivot(editor)) {
+                                    if pending.orbit_dx != 0.0 || pending.orbit_dy != 0.0 {
+                                        orbit_camera::orbit(editor, pivot, pending.orbit_dx, pending.orbit_dy);
                                     }
-                                    
-                                    if !found && args.component_id.is_none() {
-                                        let new_grass_props = entropy_engine::helpers::saved_data::ProceduralGrassProperties {
-                                            wind_strength: args.wind_strength.unwrap_or(2.5),
-                                            wind_speed: args.wind_speed.unwrap_or(0.3),
-                                            blade_height: args.blade_height.unwrap_or(2.75),
-                                            blade_width: args.blade_width.unwrap_or(0.03),
-                                            blade_density: args.blade_density.unwrap_or(15.0) as u32,
-                                            render_distance: args.render_distance.unwrap_or(150.0),
-                                            grid_size: 10.0,
-                                            brownian_strength: 0.5,
-                                        };
-                                        
-                                        let new_component = ComponentData {
-                                            id: Uuid::new_v4().to_string(),
-                                            kind: Some(entropy_engine::helpers::saved_data::ComponentKind::ProceduralGrass),
-                                            asset_id: "".to_string(),
-                                            procedural_grass_properties: Some(new_grass_props),
-                                            ..Default::default()
-                                        };
-                                        components.push(new_component);
-                                        log!("Created new grass component in saved state.");
+                                    if pending.pan_dx != 0.0 || pending.pan_dy != 0.0 {
+                                        pivot = orbit_camera::pan(editor, pivot, pending.pan_dx, pending.pan_dy);
+                                    }
+                                    orbit_pivot.set_value(Some(pivot));
+                                }
+                            } else {
+                                handle_mouse_move(
+                                    pending.left_mouse_pressed,
+                                    EntropyPosition {
+                                        x: pending.x,
+                                        y: pending.y,
+                                    },
+                                    pending.dx,
+                                    pending.dy,
+                                    editor
+                                );
+
+                                if pending.shifted {
+                                    handle_mouse_move_on_shift(pending.shift_dx, pending.shift_dy, editor);
+                                }
+                            }
+
+                            let point = placement_point(editor, pending.x, pending.y);
+                            if point != cursor_point.get_untracked() {
+                                set_cursor_point.set(point);
+                            }
+                        }
+                    }
+
+                    // Apply the latest sculpt drag, rebuilding the landscape mesh at most once per frame
+                    if let (Some((x, y)), Some(mode)) = (pending_sculpt.get_value(), sculpt_mode.get_untracked()) {
+                        pending_sculpt.set_value(None);
+
+                        if let (Some(editor), Some(stroke)) = (pipeline.export_editor.as_mut(), sculpt_stroke.get_value()) {
+                            if let Some(point) = editor.pick_landscape_point(EntropyPosition { x, y }) {
+                                let brush = terrain_edit::Brush {
+                                    mode,
+                                    radius: sculpt_radius.get_untracked() / terrain_edit::HEIGHTMAP_WORLD_UNITS_PER_PIXEL,
+                                    strength: match mode {
+                                        terrain_edit::SculptMode::Raise | terrain_edit::SculptMode::Lower =>
+                                            sculpt_strength.get_untracked() * MAX_SCULPT_STEP / terrain_edit::HEIGHTMAP_HEIGHT_SCALE,
+                                        _ => sculpt_strength.get_untracked(),
+                                    },
+                                    falloff: terrain_edit::falloff_from_label(sculpt_falloff.get_untracked()),
+                                    edge: SCULPT_BRUSH_EDGE,
+                                    target: stroke.target,
+                                };
+                                // Rebuilt straight from the stored heights, the brush only touched the pixels under it
+                                let edited = terrain_edit::with_field_mut(&stroke.info.asset_id, |field| {
+                                    let center = field.world_to_pixel(stroke.info.position, point[0], point[2]);
+                                    let changed = terrain_edit::apply_brush(field, center, &brush);
+                                    if changed {
+                                        terrain_edit::rebuild_landscape(editor, field, &stroke.info);
                                     }
+                                    changed
+                                }).unwrap_or(false);
+
+                                if edited {
+                                    sculpt_stroke.update_value(|stroke| {
+                                        if let Some(stroke) = stroke.as_mut() {
+                                            stroke.changed = true;
+                                        }
+                                    });
+                                }
+                            }
+                        }
+                    }
+
+                    let gpu_resources = match pipeline.gpu_resources.as_ref() {
+                        Some(res) => res.clone(),
+                        None => return,
+                    };
+
+                    let surface = match gpu_resources.surface.as_ref() {
+                        Some(s) => s,
+                        None => return,
+                    };
+
+                    // Match the surface and camera to the canvas before drawing, so the preview doesn't stretch
+                    if let Some((width, height)) = pending_resize.get_value() {
+                        pending_resize.set_value(None);
+
+                        if let Some(canvas) = canvas_ref.get_untracked() {
+                            canvas.set_width(width);
+                            canvas.set_height(height);
+                        }
+                        if let Some(mut surface_config) = surface_config_store.get_value() {
+                            surface_config.width = width;
+                            surface_config.height = height;
+                            surface.configure(&gpu_resources.device, &surface_config);
+                            surface_config_store.set_value(Some(surface_config));
+                        }
+                        pipeline.resize(WindowSize { width, height });
+                    }
+
+                    let output = match surface.get_current_texture() {
+                        Ok(o) => {
+                            surface_failures.set_value(0);
+                            o
+                        },
+                        Err(wgpu::SurfaceError::Lost) | Err(wgpu::SurfaceError::Outdated) => {
+                            let attempts = surface_failures.get_value() + 1;
+                            surface_failures.set_value(attempts);
+
+                            if attempts > MAX_SURFACE_RECOVERY_ATTEMPTS {
+                                if !preview_lost.get_untracked() {
+                                    log!("Surface could not be recovered after {} attempts", MAX_SURFACE_RECOVERY_ATTEMPTS);
+                                    set_preview_lost.set(true);
                                 }
+                            } else if let Some(surface_config) = surface_config_store.get_value() {
+                                log!("Surface lost or outdated, reconfiguring (attempt {})", attempts);
+                                surface.configure(&gpu_resources.device, &surface_config);
+                            }
+                            return;
+                        },
+                        Err(e) => {
+                            log!("Failed to get current texture: {:?}", e);
+                            return;
+                        }
+                    };
+
+                    let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+                    let now = js_sys::Date::now();
+                    if let Some(editor) = pipeline.export_editor.as_mut() {
+                        camera_move::apply(editor, now);
+                        // The preview is the editor view, play mode runs in the exported game
+                        day_night::apply(editor, now, false);
+                        selection_bounds::apply(editor);
+                        light_gizmos::sync(editor);
+                    }
+                    pipeline.render_frame(Some(&view), now, false);
+                    output.present();
+
+                    let mut timer = frame_timer.get_value();
+                    timer.frames += 1;
+                    timer.elapsed_ms += args.delta;
+                    // Same clock as the event timestamps
+                    if let (Some(input_at), Some(performance)) = (input_at, web_sys::window().and_then(|w| w.performance())) {
+                        timer.input_samples += 1;
+                        timer.input_ms += performance.now() - input_at;
+                    }
+                    if timer.elapsed_ms >= RENDER_STATS_INTERVAL_MS {
+                        let culling = pipeline.export_editor.as_ref()
+                            .and_then(|editor| editor.renderer_state.as_ref())
+                            .map(|renderer_state| renderer_state.culling_stats)
+                            .unwrap_or_default();
+
+                        set_render_stats.set(RenderStats {
+                            fps: timer.frames as f64 * 1000.0 / timer.elapsed_ms,
+                            frame_ms: timer.elapsed_ms / timer.frames as f64,
+                            input_ms: (timer.input_samples > 0).then(|| timer.input_ms / timer.input_samples as f64),
+                            rendered: culling.rendered,
+                            culled: culling.culled,
+                        });
+                        timer = FrameTimer::default();
+                    }
+                    frame_timer.set_value(timer);
+                }   
+            }
+        }
+    });
+
+    create_effect(move |_| {
+        let lod = lod_enabled.get();
+        let culling = frustum_culling.get();
+        let wireframe = wireframe.get();
+        let debug_overlays = debug_overlays.get();
+        let grid = show_grid.get().then(|| grid_spacing.get());
+        let snap = snap_enabled.get().then(|| snap_increment.get());
+        let clear_color = if dark_theme.get() { DARK_CLEAR_COLOR } else { LIGHT_CLEAR_COLOR };
+        // Reapplied after (re)initialization since a fresh editor starts with the defaults
+        if !is_initialized.get() {
+            return;
+        }
+
+        if let Some(pipeline_store_val) = pipeline_store.get() {
+            if let Some(pipeline_arc) = pipeline_store_val.as_ref() {
+                let mut pipeline = pipeline_arc.borrow_mut();
+                if let Some(editor) = pipeline.export_editor.as_mut() {
+                    editor.snap_increment = snap;
+                    editor.clear_color = clear_color;
+                    if let Some(renderer_state) = editor.renderer_state.as_mut() {
+                        renderer_state.lod_enabled = lod;
+                        renderer_state.frustum_culling = culling;
+                        renderer_state.wireframe = wireframe;
+                        renderer_state.show_normals = debug_overlays;
+                        renderer_state.show_bounding_boxes = debug_overlays;
+                        renderer_state.show_axes = grid.is_some();
+                        renderer_state.grid_spacing = grid;
+                    }
+                }
+            }
+        }
+    });
+
+    create_effect(move |_| {
+        scene_version.get();
+        if !is_initialized.get() {
+            return;
+        }
+
+        if let Some(pipeline_store_val) = pipeline_store.get() {
+            if let Some(pipeline_arc) = pipeline_store_val.as_ref() {
+                let pipeline = pipeline_arc.borrow();
+                let enabled = pipeline.export_editor.as_ref()
+                    .and_then(|editor| editor.saved_state.as_ref())
+                    .and_then(|saved_state| saved_state.levels.as_ref())
+                    .and_then(|levels| levels.get(0))
+                    .and_then(|level| level.ambient_occlusion.as_ref())
+                    .is_some_and(|config| config.enabled);
+                set_ao_enabled.set(enabled);
+            }
+        }
+    });
+
+    // Each project keeps the fly speed that suits its scale
+    create_effect(move |_| {
+        let Some(project_id) = selected_project.get().map(|p| p.id) else { return };
+        let speed = storage::get_item(&camera_speed_key(&project_id))
+            .and_then(|value| value.parse::<f32>().ok())
+            .filter(|speed| speed.is_finite())
+            .map(|speed| speed.clamp(MIN_CAMERA_SPEED, MAX_CAMERA_SPEED))
+            .unwrap_or(DEFAULT_CAMERA_SPEED);
+        set_camera_speed.set(speed);
+    });
+
+    let change_camera_speed = move |speed: f32| {
+        let speed = speed.clamp(MIN_CAMERA_SPEED, MAX_CAMERA_SPEED);
+        set_camera_speed.set(speed);
+        if let Some(project_id) = selected_project.get_untracked().map(|p| p.id) {
+            storage::set_item(&camera_speed_key(&project_id), &speed.to_string());
+        }
+    };
+
+    // Reconfigures the live surface when the present mode or latency setting changes
+    create_effect(move |_| {
+        let requested = present_mode.get();
+        let latency = frame_latency.get();
+        if !is_initialized.get() {
+            return;
+        }
+        let Some(pipeline_arc) = pipeline_store.get().flatten() else { return };
+        let pipeline = pipeline_arc.borrow();
+        let (Some(gpu_resources), Some(mut surface_config)) = (pipeline.gpu_resources.as_ref(), surface_config_store.get_value()) else { return };
+        let Some(surface) = gpu_resources.surface.as_ref() else { return };
+
+        let mode = supported_present_mode(requested, &supported_present_modes.get_untracked());
+        if surface_config.present_mode == mode && surface_config.desired_maximum_frame_latency == latency {
+            return;
+        }
+        surface_config.present_mode = mode;
+        surface_config.desired_maximum_frame_latency = latency;
+        surface.configure(&gpu_resources.device, &surface_config);
+        surface_config_store.set_value(Some(surface_config));
+    });
+
+    let change_present_mode = move |label: String| {
+        let Some((_, mode)) = PRESENT_MODES.iter().find(|(name, _)| *name == label) else { return };
+        set_present_mode.set(*mode);
+        storage::set_item(PRESENT_MODE_KEY, &label);
+    };
+
+    let change_frame_latency = move |latency: u32| {
+        let latency = latency.clamp(1, MAX_FRAME_LATENCY);
+        set_frame_latency.set(latency);
+        storage::set_item(FRAME_LATENCY_KEY, &latency.to_string());
+    };
+
+    let toggle_ambient_occlusion = move |enabled: bool| {
+        set_ao_enabled.set(enabled);
+        let Some(project_id) = selected_project.get_untracked().map(|p| p.id) else { return };
+        let saved_state = pipeline_store.get_untracked().flatten().and_then(|pipeline_arc| {
+            let mut pipeline = pipeline_arc.borrow_mut();
+            pipeline.export_editor.as_mut().and_then(|editor| set_ambient_occlusion_enabled(editor, enabled))
+        });
+        if let Some(saved_state) = saved_state {
+            spawn_local(async move {
+                let _ = save_project(&project_id, &saved_state).await;
+            });
+        }
+    };
+
+    // Saves the level after an edit from the right-click menu and refreshes the component list
+    let save_menu_edit = move |editor: &Editor| {
+        let project_id = selected_project.get_untracked().map(|p| p.id).unwrap_or_default();
+        if let Some(saved_state) = editor.saved_state.clone() {
+            if !project_id.is_empty() {
+                spawn_local(async move {
+                    let _ = save_project(&project_id, &saved_state).await;
+                });
+            }
+        }
+        set_scene_version.update(|version| *version += 1);
+    };
+
+    let open_context_menu = move |ev: web_sys::MouseEvent| {
+        ev.prevent_default();
+        let Some(canvas) = canvas_ref.get_untracked() else { return };
+        let rect = canvas.get_bounding_client_rect();
+        let (x, y) = (ev.client_x() as f32 - rect.left() as f32, ev.client_y() as f32 - rect.top() as f32);
+
+        let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() else { return };
+        let pipeline = pipeline_arc.borrow();
+        let Some(editor) = pipeline.export_editor.as_ref() else { return };
+        let Some(saved_state) = editor.saved_state.as_ref() else { return };
+
+        let component = editor.pick_component(EntropyPosition { x, y }).and_then(|component_id| {
+            saved_state.levels.as_ref()
+                .and_then(|levels| levels.get(0))
+                .and_then(|level| level.components.as_ref())
+                .and_then(|components| components.iter().find(|c| c.id == component_id).cloned())
+        });
+        set_context_menu.set(Some(CanvasContextMenu {
+            left: ev.client_x() as f32,
+            top: ev.client_y() as f32,
+            component,
+            spawn_point: placement_point(editor, x, y).unwrap_or([0.0, 0.0, 0.0]),
+            models: saved_state.models.iter().map(|m| (m.id.clone(), m.fileName.clone())).collect(),
+            choosing_model: false,
+        }));
+    };
+
+    // Menu actions share their implementations with the component list and the matching tools
+    let focus_from_menu = move |component: ComponentData| {
+        set_context_menu.set(None);
+        let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() else { return };
+        let mut pipeline = pipeline_arc.borrow_mut();
+        if let Some(editor) = pipeline.export_editor.as_mut() {
+            focus_camera_on(editor, &component);
+        }
+    };
+
+    let duplicate_from_menu = move |component_id: String| {
+        set_context_menu.set(None);
+        let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() else { return };
+        let project_id = selected_project.get_untracked().map(|p| p.id).unwrap_or_default();
+        spawn_local(async move {
+            let mut pipeline = pipeline_arc.borrow_mut();
+            let Some(editor) = pipeline.export_editor.as_mut() else { return };
+            match duplicate_component(editor, &project_id, &component_id, DUPLICATE_OFFSET).await {
+                Ok(_) => save_menu_edit(editor),
+                Err(e) => log!("Couldn't duplicate {}: {}", component_id, e),
+            }
+        });
+    };
+
+    let delete_from_menu = move |component: ComponentData| {
+        set_context_menu.set(None);
+        confirm_action(
+            ConfirmKind::DeleteComponent,
+            format!("Delete {}?", component.generic_properties.name),
+            "It's removed from the scene and the project.",
+            "Delete",
+            move || {
+                let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() else { return };
+                let mut pipeline = pipeline_arc.borrow_mut();
+                let Some(editor) = pipeline.export_editor.as_mut() else { return };
+                match delete_component(editor, &component.id) {
+                    Ok(_) => save_menu_edit(editor),
+                    Err(e) => log!("Couldn't delete {}: {}", component.id, e),
+                }
+            },
+        );
+    };
+
+    // Flips the component's hidden or locked flag
+    let toggle_from_menu = move |component_id: String, toggle: fn(&mut ComponentData)| {
+        set_context_menu.set(None);
+        let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() else { return };
+        let mut pipeline = pipeline_arc.borrow_mut();
+        let Some(editor) = pipeline.export_editor.as_mut() else { return };
+        let component = editor.saved_state.as_mut()
+            .and_then(|saved_state| saved_state.levels.as_mut())
+            .and_then(|levels| levels.get_mut(0))
+            .and_then(|level| level.components.as_mut())
+            .and_then(|components| components.iter_mut().find(|c| c.id == component_id));
+        if let Some(component) = component {
+            toggle(component);
+            sync_locked_components(editor);
+            sync_hidden_components(editor);
+            save_menu_edit(editor);
+        }
+    };
+
+    let spawn_from_menu = move |asset_id: String, position: [f32; 3]| {
+        set_context_menu.set(None);
+        let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() else { return };
+        let project_id = selected_project.get_untracked().map(|p| p.id).unwrap_or_default();
+        spawn_local(async move {
+            let mut pipeline = pipeline_arc.borrow_mut();
+            let Some(editor) = pipeline.export_editor.as_mut() else { return };
+            match spawn_model_component(editor, &project_id, &asset_id, position, [0.0, 0.0, 0.0], [1.0, 1.0, 1.0]).await {
+                Ok(_) => save_menu_edit(editor),
+                Err(e) => log!("Couldn't spawn {}: {}", asset_id, e),
+            }
+        });
+    };
+
+    // Any click outside the menu, or Escape, closes it
+    let _context_menu_dismiss = window_event_listener(leptos::ev::mousedown, move |ev| {
+        if context_menu.with_untracked(|menu| menu.is_none()) {
+            return;
+        }
+        let inside = ev.target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+            .and_then(|element| element.closest(".canvas-context-menu").ok().flatten())
+            .is_some();
+        if !inside {
+            set_context_menu.set(None);
+        }
+    });
+    let _context_menu_escape = window_event_listener(leptos::ev::keydown, move |ev| {
+        if ev.key() == "Escape" && context_menu.with_untracked(|menu| menu.is_some()) {
+            set_context_menu.set(None);
+        }
+    });
+
+    // The canvas stays mounted while the chat is closed, so only render while it's visible
+    create_effect(move |_| {
+        if show_preview.get() {
+            resume();
+        } else {
+            pause();
+        }
+    });
+
+    view! {
+        <section class="project-canvas">
+            <Show
+                when=move || { !is_initialized.get() }
+                fallback=|| view! { <span>{""}</span> }
+            >
+                <span>{"Initializing..."}</span>
+            </Show>
+            <Show when=move || preview_lost.get()>
+                <div
+                    class="preview-lost-overlay"
+                    on:click=move |_| {
+                        log!("Restarting preview...");
+                        set_preview_lost.set(false);
+                        force_reinitialize.set_value(true);
+                        set_restart_token.update(|token| *token += 1);
+                    }
+                >
+                    {"Preview lost — click to restart"}
+                </div>
+            </Show>
+            <canvas 
+                id="project-canvas" 
+                node_ref=canvas_ref 
+                tabindex="0"
+                on:keydown=move |ev: web_sys::KeyboardEvent| {
+                    let key = ev.key();
+                    // F toggles the wireframe view instead of reaching the camera controls
+                    if key.eq_ignore_ascii_case("f") && !ev.ctrl_key() && !ev.meta_key() && !ev.alt_key() {
+                        set_wireframe.update(|on| *on = !*on);
+                        return;
+                    }
+                    // O switches between orbiting and flying
+                    if key.eq_ignore_ascii_case("o") && !ev.ctrl_key() && !ev.meta_key() && !ev.alt_key() {
+                        set_orbit_mode.update(|on| *on = !*on);
+                        return;
+                    }
+                    if let Some(pipeline_store_val) = pipeline_store.get() {
+                        if let Some(pipeline_arc) = pipeline_store_val.as_ref() {
+                            let mut pipeline = pipeline_arc.borrow_mut();
+                            if let Some(editor) = pipeline.export_editor.as_mut() {
+                                let camera = editor.camera.as_mut().expect("Couldn't get camera");
+                                // Read on every key repeat, so letting go of Shift slows back down mid-flight
+                                let sprint = if ev.shift_key() { CAMERA_SPRINT_FACTOR } else { 1.0 };
+                                camera.speed_multiplier = camera_speed.get_untracked() * sprint;
+
+                                // log!("handle_key_press {:?} {:?} {:?}", key, camera.position, camera.direction);
+
+                                handle_key_press(editor, key.as_str(), true);
                             }
-                            saved_state_clone = Some(saved_state.clone());
                         }
                     }
                 }
-            }
-        }
-    } else if tool_call.function.name == "spawnPrimitive" {
-        log!("Spawning primitive...");
-        let args: Result<SpawnPrimitiveArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        let device = &editor.gpu_resources.as_ref().unwrap().device;
-                        let queue = &editor.gpu_resources.as_ref().unwrap().queue;
-                        let model_layout = editor.model_bind_group_layout.as_ref().unwrap();
-                        let group_layout = editor.group_bind_group_layout.as_ref().unwrap();
-                        let camera = editor.camera.as_ref().unwrap();
-
-                        // We need access to texture render mode buffer which is in RendererState or Pipeline
-                        // But access via RendererState is hard because we are borrowing pipeline/editor.
-                        // However, Cube::new needs it.
-                        // In pipeline.rs, `texture_render_mode_buffer` is passed to `RendererState`.
-                        // But `editor.renderer_state` has it.
-                        // `renderer_state.texture_render_mode_buffer`
-                        
-                        let buffer = if let Some(rs) = &editor.renderer_state {
-                            rs.texture_render_mode_buffer.clone()
-                        } else {
-                            // Fallback or error
-                            log!("Renderer state not found");
-                            return "{\"success\": false}".to_string();
-                        };
-
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                            match args.r#type.as_str() {
-                                "Cube" => {
-                                    let mut cube = Cube::new(
-                                        device,
-                                        queue,
-                                        model_layout,
-                                        group_layout,
-                                        &buffer,
-                                        camera
-                                    );
-                                    cube.transform.update_position(args.position);
-                                    if let Some(scale) = args.scale {
-                                        cube.transform.update_scale(scale);
-                                    }
-                                    renderer_state.cubes.push(cube);
-                                },
-                                "Sphere" => {
-                                    let mut sphere = Sphere::new(
-                                        device,
-                                        queue,
-                                        model_layout,
-                                        group_layout,
-                                        &buffer,
-                                        camera,
-                                        1.0, // radius
-                                        32, // sectors
-                                        32, // stacks
-                                        [1.0, 1.0, 1.0], // color
-                                        false // debug_moving
-                                    );
-                                    sphere.transform.update_position(args.position);
-                                    if let Some(scale) = args.scale {
-                                        sphere.transform.update_scale(scale);
-                                    }
-                                    renderer_state.spheres.push(sphere);
-                                },
-                                _ => log!("Unknown primitive type"),
+                on:mousedown=move |ev: web_sys::MouseEvent| {
+                    if sculpt_mode.get_untracked().is_some() && ev.button() == 0 {
+                        ev.prevent_default();
+                        if let Some(canvas) = canvas_ref.get_untracked() {
+                            let rect = canvas.get_bounding_client_rect();
+                            begin_sculpt(ev.client_x() as f32 - rect.left() as f32, ev.client_y() as f32 - rect.top() as f32);
+                        }
+                        return;
+                    }
+                    // Clicking a component selects it, so its bounding box shows
+                    if !orbit_mode.get_untracked() {
+                        if ev.button() == 0 {
+                            let (Some(canvas), Some(pipeline_arc)) = (canvas_ref.get_untracked(), pipeline_store.get_untracked().flatten()) else { return };
+                            let rect = canvas.get_bounding_client_rect();
+                            let mut pipeline = pipeline_arc.borrow_mut();
+                            if let Some(editor) = pipeline.export_editor.as_mut() {
+                                let (x, y) = (ev.client_x() as f32 - rect.left() as f32, ev.client_y() as f32 - rect.top() as f32);
+                                if let Some(component_id) = editor.pick_component(EntropyPosition { x, y }) {
+                                    editor.selected_component_id = Some(component_id.clone());
+                                    set_selected_component_id.set(Some(component_id));
+                                }
                             }
-                            
-                            if let Some(saved_state) = editor.saved_state.as_mut() {
-                                saved_state_clone = Some(saved_state.clone());
+                        }
+                        return;
+                    }
+                    // Middle-drag pans, so keep the browser from starting autoscroll
+                    if ev.button() == 1 {
+                        ev.prevent_default();
+                    }
+                    // Pressing on a component selects it and makes it the pivot, pressing on empty space keeps the last one
+                    if ev.button() == 0 {
+                        let (Some(canvas), Some(pipeline_arc)) = (canvas_ref.get_untracked(), pipeline_store.get_untracked().flatten()) else { return };
+                        let rect = canvas.get_bounding_client_rect();
+                        let mut pipeline = pipeline_arc.borrow_mut();
+                        if let Some(editor) = pipeline.export_editor.as_mut() {
+                            let (x, y) = (ev.client_x() as f32 - rect.left() as f32, ev.client_y() as f32 - rect.top() as f32);
+                            if let Some(component_id) = editor.pick_component(EntropyPosition { x, y }) {
+                                editor.selected_component_id = Some(component_id.clone());
+                                set_selected_component_id.set(Some(component_id));
+                                if let Some(pivot) = selected_orbit_pivot(editor) {
+                                    orbit_pivot.set_value(Some(pivot));
+                                }
                             }
                         }
                     }
                 }
-            }
-        }
-    } else if tool_call.function.name == "spawnNPC" {
-        log!("Spawning NPC...");
-        let args: Result<SpawnNPCArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                         let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
-                         let mut asset_file_name = String::new();
+                on:dblclick=move |ev: web_sys::MouseEvent| {
+                    // Double-clicking the ground orbits around that point instead
+                    if !orbit_mode.get_untracked() {
+                        return;
+                    }
+                    let (Some(canvas), Some(pipeline_arc)) = (canvas_ref.get_untracked(), pipeline_store.get_untracked().flatten()) else { return };
+                    let rect = canvas.get_bounding_client_rect();
+                    let pipeline = pipeline_arc.borrow();
+                    if let Some(editor) = pipeline.export_editor.as_ref() {
+                        let (x, y) = (ev.client_x() as f32 - rect.left() as f32, ev.client_y() as f32 - rect.top() as f32);
+                        if let Some(pivot) = pick_orbit_pivot(editor, x, y) {
+                            orbit_pivot.set_value(Some(pivot));
+                        }
+                    }
+                }
+                on:mousemove=move |ev: web_sys::MouseEvent| {
+                    // Only record the movement here, it gets applied once per animation frame
+                    let canv = canvas_ref.get();
+                    let canv = canv.as_ref().expect("Couldn't get canvas ref");
+                    let rect = canv.get_bounding_client_rect();
+
+                    // A sculpt drag shapes the terrain instead of moving the camera or gizmo
+                    if sculpt_stroke.with_value(|stroke| stroke.is_some()) && ev.buttons() & 1 != 0 {
+                        pending_sculpt.set_value(Some((ev.client_x() as f32 - rect.left() as f32, ev.client_y() as f32 - rect.top() as f32)));
+                        return;
+                    }
 
-                        // Find asset in SavedState
-                        if let Some(saved_state) = editor.saved_state.as_ref() {
-                            if let Some(model) = saved_state.models.iter().find(|m| m.id == args.asset_id) {
-                                asset_file_name = model.fileName.clone();
+                    let dx = ev.movement_x() as f32;
+                    let dy = ev.movement_y() as f32;
+
+                    pending_mouse_move.update_value(|pending| {
+                        pending.x = ev.client_x() as f32 - rect.left() as f32;
+                        pending.y = ev.client_y() as f32 - rect.top() as f32;
+                        pending.dx += dx;
+                        pending.dy += dy;
+                        pending.left_mouse_pressed = ev.button() == 0;
+                        pending.input_at.get_or_insert(ev.time_stamp());
+
+                        if orbit_mode.get_untracked() {
+                            if ev.buttons() & 1 != 0 {
+                                pending.orbit_dx += dx;
+                                pending.orbit_dy += dy;
+                            }
+                            if ev.buttons() & 4 != 0 {
+                                pending.pan_dx += dx;
+                                pending.pan_dy += dy;
                             }
                         }
 
-                        if !asset_file_name.is_empty() {
-                            let component_id = Uuid::new_v4().to_string();
-                            let pos = args.position.unwrap_or([0.0, 0.0, 0.0]);
-                            let rot = args.rotation.unwrap_or([0.0, 0.0, 0.0]);
-                            let scale = args.scale.unwrap_or([1.0, 1.0, 1.0]);
-
-                            let model_position = Translation3::new(pos[0], pos[1], pos[2]);
-                            let model_rotation = UnitQuaternion::from_euler_angles(
-                                rot[0].to_radians(), rot[1].to_radians(), rot[2].to_radians()
-                            );
-                            let model_iso = Isometry3::from_parts(model_position, model_rotation);
-                            let model_scale = Vector3::new(scale[0], scale[1], scale[2]);
-
-                            let combat_type = match args.combat_type.as_deref() {
-                                Some("Ranged") => CombatType::Ranged,
-                                _ => CombatType::Melee,
-                            };
+                        if ev.shift_key() {
+                            pending.shift_dx += dx;
+                            pending.shift_dy += dy;
+                            pending.shifted = true;
+                        }
 
-                            let damage = args.damage.unwrap_or(10.0);
-                            let attack_stats = Some(AttackStats {
-                                damage: damage,
-                                range: if combat_type == CombatType::Melee { 2.0 } else { 15.0 },
-                                cooldown: 1.5,
-                                wind_up_time: 0.5,
-                                recovery_time: 0.5,
-                            });
+                        pending.dirty = true;
+                    });
+                }
+                on:mouseleave=move |_| set_cursor_point.set(None)
+                on:contextmenu=open_context_menu
+                on:wheel=move |ev: web_sys::WheelEvent| {
+                    // Keep the page from scrolling while zooming the preview
+                    ev.prevent_default();
 
-                            let behavior_config = BehaviorConfig {
-                                aggressiveness: args.aggressiveness.unwrap_or(0.5),
-                                combat_type: combat_type,
-                                wander_radius: args.wander_radius.unwrap_or(10.0),
-                                wander_speed: args.wander_speed.unwrap_or(2.0),
-                                detection_radius: args.detection_radius.unwrap_or(15.0),
-                                melee_stats: if combat_type == CombatType::Melee { attack_stats } else { None },
-                                ranged_stats: if combat_type == CombatType::Ranged { attack_stats } else { None },
-                            };
+                    if let Some(pipeline_store_val) = pipeline_store.get() {
+                        if let Some(pipeline_arc) = pipeline_store_val.as_ref() {
+                            let mut pipeline = pipeline_arc.borrow_mut();
+                            if let Some(editor) = pipeline.export_editor.as_mut() {
+                                // Orbit zoom moves towards the pivot and never passes it
+                                if orbit_mode.get_untracked() {
+                                    if let Some(pivot) = orbit_pivot.get_value().or_else(|| selected_orbit_pivot(editor)).or_else(|| orbit_camera::default_pivot(editor)) {
+                                        let factor = if ev.shift_key() { WHEEL_ZOOM_FINE_FACTOR } else { 1.0 };
+                                        orbit_camera::zoom(editor, pivot, ev.delta_y() as f32 * factor);
+                                        orbit_pivot.set_value(Some(pivot));
+                                    }
+                                    return;
+                                }
+                                // The terrain or component in the middle of the view, the wheel stops short of it
+                                let target = canvas_ref.get_untracked().and_then(|canvas| {
+                                    let (x, y) = (canvas.client_width() as f32 / 2.0, canvas.client_height() as f32 / 2.0);
+                                    editor.pick_component(EntropyPosition { x, y })
+                                        .and_then(|component_id| component_orbit_pivot(editor, &component_id))
+                                        .or_else(|| editor.pick_landscape_point(EntropyPosition { x, y }).map(nalgebra::Point3::from))
+                                });
+                                if let Some(camera) = editor.camera.as_mut() {
+                                    let factor = if ev.shift_key() { WHEEL_ZOOM_FINE_FACTOR } else { 1.0 };
+                                    // Scrolling up (negative deltaY) moves forward
+                                    let mut step = (-ev.delta_y() as f32 * WHEEL_ZOOM_SPEED * factor)
+                                        .clamp(-MAX_WHEEL_ZOOM_STEP, MAX_WHEEL_ZOOM_STEP);
+                                    if let Some(target) = target {
+                                        let room = (target - camera.position).norm() - MIN_CAMERA_DISTANCE;
+                                        step = step.min(room.max(0.0));
+                                    }
 
-                            let renderer_state = editor.renderer_state.as_mut().unwrap();
-                            let gpu_resources = editor.gpu_resources.as_ref().unwrap();
-                            let camera = editor.camera.as_ref().unwrap();
-
-                            handle_add_npc(
-                                renderer_state,
-                                &gpu_resources.device,
-                                &gpu_resources.queue,
-                                project_id,
-                                args.asset_id.clone(),
-                                component_id.clone(),
-                                asset_file_name,
-                                model_iso,
-                                model_scale,
-                                camera,
-                                None, // Script state
-                                behavior_config.clone()
-                            ).await;
-
-                            // Update SavedState
-                            if let Some(saved_state) = editor.saved_state.as_mut() {
-                                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                    let new_component = ComponentData {
-                                        id: component_id,
-                                        kind: Some(ComponentKind::NPC),
-                                        asset_id: args.asset_id.clone(),
-                                        generic_properties: GenericProperties {
-                                            name: "New NPC".to_string(),
-                                            position: pos,
-                                            rotation: rot,
-                                            scale: scale,
-                                        },
-                                        npc_properties: Some(NPCProperties {
-                                            model_id: args.asset_id,
-                                            behavior: behavior_config,
-                                        }),
-                                        ..Default::default()
-                                    };
-                                    
-                                    if let Some(components) = level.components.as_mut() {
-                                        components.push(new_component);
-                                    } else {
-                                        level.components = Some(vec![new_component]);
+                                    let direction = camera.direction.normalize();
+                                    camera.position += direction * step;
+
+                                    let distance = camera.position.coords.norm();
+                                    if distance > MAX_CAMERA_DISTANCE {
+                                        camera.position.coords *= MAX_CAMERA_DISTANCE / distance;
                                     }
                                 }
-                                saved_state_clone = Some(saved_state.clone());
                             }
-                        } else {
-                            log!("Asset not found for NPC: {}", args.asset_id);
                         }
                     }
                 }
-            }
-        }
-    } else if tool_call.function.name == "saveScript" {
-        log!("Saving script...");
-        let args: Result<SaveScriptArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            // Update the component script path if provided
-            if let Some(component_id) = &args.componentId {
-                if let Some(pipeline_arc_val) = pipeline_store.get() {
-                    if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                        let mut pipeline = pipeline_arc.borrow_mut();
-                        if let Some(editor) = pipeline.export_editor.as_mut() {
-                            // Update SavedState
-                            if let Some(saved_state) = editor.saved_state.as_mut() {
-                                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
-                                    if let Some(components) = level.components.as_mut() {
-                                        if let Some(component) = components.iter_mut().find(|c| c.id == *component_id) {
-                                            let script_path = format!("scripts/{}", args.filename);
-                                            component.rhai_script_path = Some(script_path);
-                                        }
-                                    }
+            />
+            {move || context_menu.get().map(|menu| {
+                let spawn_point = menu.spawn_point;
+                let items = match menu.component.clone() {
+                    Some(component) => {
+                        let (focus_target, delete_target) = (component.clone(), component.clone());
+                        let (duplicate_id, hide_id, lock_id) = (component.id.clone(), component.id.clone(), component.id.clone());
+                        view! {
+                            <div class="context-menu-title">{component.generic_properties.name.clone()}</div>
+                            <button on:click=move |_| focus_from_menu(focus_target.clone())>{"Focus"}</button>
+                            <button on:click=move |_| duplicate_from_menu(duplicate_id.clone())>{"Duplicate"}</button>
+                            <button
+                                disabled=component.locked
+                                title=if component.locked { "Unlock to delete" } else { "" }
+                                on:click=move |_| delete_from_menu(delete_target.clone())
+                            >
+                                {"Delete"}
+                            </button>
+                            <button on:click=move |_| toggle_from_menu(hide_id.clone(), |c| c.hidden = !c.hidden)>
+                                {if component.hidden { "Show" } else { "Hide" }}
+                            </button>
+                            <button on:click=move |_| toggle_from_menu(lock_id.clone(), |c| c.locked = !c.locked)>
+                                {if component.locked { "Unlock" } else { "Lock" }}
+                            </button>
+                        }.into_any()
+                    }
+                    None if menu.choosing_model && menu.models.is_empty() => view! {
+                        <div class="context-menu-title">{"No models in this project yet"}</div>
+                    }.into_any(),
+                    None if menu.choosing_model => view! {
+                        <div class="context-menu-title">{"Spawn model"}</div>
+                        <div class="context-menu-models">
+                            {menu.models.iter().cloned().map(|(asset_id, file_name)| view! {
+                                <button on:click=move |_| spawn_from_menu(asset_id.clone(), spawn_point)>{file_name}</button>
+                            }).collect_view()}
+                        </div>
+                    }.into_any(),
+                    None => view! {
+                        <button on:click=move |_| set_context_menu.update(|menu| {
+                            if let Some(menu) = menu.as_mut() {
+                                menu.choosing_model = true;
+                            }
+                        })>
+                            {"Spawn..."}
+                        </button>
+                    }.into_any(),
+                };
+                view! {
+                    <div
+                        class="canvas-context-menu"
+                        style=format!("left: {}px; top: {}px;", menu.left, menu.top)
+                        on:contextmenu=|ev: web_sys::MouseEvent| ev.prevent_default()
+                    >
+                        {items}
+                    </div>
+                }
+            })}
+            {move || cursor_point.get().map(|[x, y, z]| view! {
+                <div class="cursor-coordinates">{format!("X {:.1}  Y {:.1}  Z {:.1}", x, y, z)}</div>
+            })}
+            <div class="preview-toolbar">
+                <button
+                    title="Move the camera to see every component"
+                    on:click=move |_| {
+                        if let Some(pipeline_arc) = pipeline_store.get_untracked().flatten() {
+                            let mut pipeline = pipeline_arc.borrow_mut();
+                            if let Some(editor) = pipeline.export_editor.as_mut() {
+                                // Orbit around the middle of what was framed
+                                if let Some(framed) = frame_scene(editor) {
+                                    orbit_pivot.set_value(Some(nalgebra::Point3::from(framed.center)));
                                 }
-                                saved_state_clone = Some(saved_state.clone());
                             }
                         }
                     }
-                }
+                >
+                    {"Frame All"}
+                </button>
+                <label title="Drag to orbit around the selected component, double-click the ground to orbit a point, middle-drag to pan, scroll to zoom (O)">
+                    <input
+                        type="checkbox"
+                        prop:checked=orbit_mode
+                        on:change=move |ev| set_orbit_mode.set(event_target_checked(&ev))
+                    />
+                    {"Orbit"}
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=lod_enabled
+                        on:change=move |ev| set_lod_enabled.set(event_target_checked(&ev))
+                    />
+                    {"LOD"}
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=frustum_culling
+                        on:change=move |ev| set_frustum_culling.set(event_target_checked(&ev))
+                    />
+                    {"Frustum culling"}
+                </label>
+                <label title="Draw meshes, grass and trees as wireframe (F)">
+                    <input
+                        type="checkbox"
+                        prop:checked=wireframe
+                        on:change=move |ev| set_wireframe.set(event_target_checked(&ev))
+                    />
+                    {"Wireframe"}
+                </label>
+                <label title="Show vertex normals and bounding boxes">
+                    <input
+                        type="checkbox"
+                        prop:checked=debug_overlays
+                        on:change=move |ev| set_debug_overlays.set(event_target_checked(&ev))
+                    />
+                    {"Normals & bounds"}
+                </label>
+                <label title="Screen-space ambient occlusion, darkens creases and contact shadows at some cost per frame">
+                    <input
+                        type="checkbox"
+                        prop:checked=ao_enabled
+                        on:change=move |ev| toggle_ambient_occlusion(event_target_checked(&ev))
+                    />
+                    {"AO"}
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=snap_enabled
+                        on:change=move |ev| set_snap_enabled.set(event_target_checked(&ev))
+                    />
+                    {"Snap"}
+                </label>
+                <input
+                    class="snap-increment"
+                    type="number"
+                    step="0.25"
+                    min="0.05"
+                    prop:value=move || snap_increment.get().to_string()
+                    disabled=move || !snap_enabled.get()
+                    on:change=move |ev| {
+                        if let Ok(increment) = event_target_value(&ev).parse::<f32>() {
+                            if increment > 0.0 {
+                                set_snap_increment.set(increment);
+                            }
+                        }
+                    }
+                />
+                <label class="camera-speed" title="WASD fly speed, hold Shift to sprint">
+                    {"Speed"}
+                    <input
+                        type="range"
+                        min=MIN_CAMERA_SPEED
+                        max=MAX_CAMERA_SPEED
+                        step="0.1"
+                        prop:value=move || camera_speed.get().to_string()
+                        on:input=move |ev| {
+                            if let Ok(speed) = event_target_value(&ev).parse::<f32>() {
+                                change_camera_speed(speed);
+                            }
+                        }
+                    />
+                    <span class="camera-speed-value">{move || format!("{:.1}×", camera_speed.get())}</span>
+                </label>
+                <label class="present-mode" title="Fifo syncs to the display, Mailbox and Immediate lower latency where the GPU supports them">
+                    {"Present"}
+                    <select on:change=move |ev| change_present_mode(event_target_value(&ev))>
+                        {PRESENT_MODES.iter().map(|(label, mode)| {
+                            let mode = *mode;
+                            view! {
+                                <option
+                                    value=*label
+                                    selected=move || present_mode.get() == mode
+                                    disabled=move || !supported_present_modes.get().contains(&mode)
+                                >
+                                    {*label}
+                                </option>
+                            }
+                        }).collect_view()}
+                    </select>
+                </label>
+                <label class="frame-latency" title="Frames queued ahead of the display, fewer feels snappier and more smooths out slow frames">
+                    {"Latency"}
+                    <select on:change=move |ev| {
+                        if let Ok(latency) = event_target_value(&ev).parse::<u32>() {
+                            change_frame_latency(latency);
+                        }
+                    }>
+                        {(1..=MAX_FRAME_LATENCY).map(|latency| view! {
+                            <option value=latency.to_string() selected=move || frame_latency.get() == latency>
+                                {latency.to_string()}
+                            </option>
+                        }).collect_view()}
+                    </select>
+                </label>
+                <label title="Ground grid and XYZ axes at the world origin">
+                    <input
+                        type="checkbox"
+                        prop:checked=show_grid
+                        on:change=move |ev| set_show_grid.set(event_target_checked(&ev))
+                    />
+                    {"Grid"}
+                </label>
+                <input
+                    class="grid-spacing"
+                    type="number"
+                    step="0.5"
+                    min=MIN_GRID_SPACING
+                    max=MAX_GRID_SPACING
+                    prop:value=move || grid_spacing.get().to_string()
+                    disabled=move || !show_grid.get()
+                    on:change=move |ev| {
+                        if let Ok(spacing) = event_target_value(&ev).parse::<f32>() {
+                            if spacing.is_finite() {
+                                set_grid_spacing.set(spacing.clamp(MIN_GRID_SPACING, MAX_GRID_SPACING));
+                            }
+                        }
+                    }
+                />
+                <span class="field-unit">{METERS}</span>
+                <label class="sculpt-controls">
+                    {"Sculpt "}
+                    <select on:change=move |ev| {
+                        set_sculpt_mode.set(terrain_edit::SculptMode::from_label(&event_target_value(&ev)));
+                        set_sculpt_status.set(None);
+                    }>
+                        <option value="Off" prop:selected=move || sculpt_mode.get().is_none()>{"Off"}</option>
+                        {terrain_edit::SculptMode::ALL.into_iter().map(|mode| view! {
+                            <option value=mode.label() prop:selected=move || sculpt_mode.get() == Some(mode)>{mode.label()}</option>
+                        }).collect_view()}
+                    </select>
+                </label>
+                <Show when=move || sculpt_mode.get().is_some()>
+                    <label>
+                        {"Radius "}
+                        <input
+                            class="sculpt-radius"
+                            type="number"
+                            step="1"
+                            min=MIN_SCULPT_RADIUS
+                            max=MAX_SCULPT_RADIUS
+                            prop:value=move || sculpt_radius.get().to_string()
+                            on:change=move |ev| {
+                                if let Ok(radius) = event_target_value(&ev).parse::<f32>() {
+                                    if radius.is_finite() {
+                                        set_sculpt_radius.set(radius.clamp(MIN_SCULPT_RADIUS, MAX_SCULPT_RADIUS));
+                                    }
+                                }
+                            }
+                        />
+                        <span class="field-unit">{METERS}</span>
+                    </label>
+                    <label>
+                        {"Strength "}
+                        <input
+                            type="range"
+                            min="0.05"
+                            max="1"
+                            step="0.05"
+                            prop:value=move || sculpt_strength.get().to_string()
+                            on:input=move |ev| {
+                                if let Ok(strength) = event_target_value(&ev).parse::<f32>() {
+                                    set_sculpt_strength.set(strength.clamp(0.05, 1.0));
+                                }
+                            }
+                        />
+                    </label>
+                    <label>
+                        {"Falloff "}
+                        <select on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            if let Some(label) = terrain_edit::FALLOFF_LABELS.into_iter().find(|label| *label == value) {
+                                set_sculpt_falloff.set(label);
+                            }
+                        }>
+                            {terrain_edit::FALLOFF_LABELS.into_iter().map(|label| view! {
+                                <option value=label prop:selected=move || sculpt_falloff.get() == label>{label}</option>
+                            }).collect_view()}
+                        </select>
+                    </label>
+                </Show>
+                {move || sculpt_status.get().map(|status| view! { <span class="sculpt-status">{status}</span> })}
+                <span class="render-stats">
+                    {move || {
+                        let stats = render_stats.get();
+                        let input = stats.input_ms.map(|ms| format!(" · input {:.1} ms", ms)).unwrap_or_default();
+                        format!(
+                            "{:.0} fps ({:.1} ms){} · {} rendered / {} culled",
+                            stats.fps, stats.frame_ms, input, stats.rendered, stats.culled
+                        )
+                    }}
+                </span>
+            </div>
+        </section>
+    }
+}
+
+/// Placeholder rows shown while a list loads.
+#[component]
+fn SkeletonList(rows: usize) -> impl IntoView {
+    view! {
+        <div class="skeleton-list" aria-busy="true">
+            {(0..rows).map(|_| view! { <div class="skeleton-row"></div> }).collect_view()}
+        </div>
+    }
+}
+
+/// What went wrong while loading, with a way to try again.
+#[component]
+fn LoadError<R>(message: String, on_retry: R) -> impl IntoView
+where R: Fn() + 'static
+{
+    view! {
+        <div class="load-error" role="alert">
+            <p>{message}</p>
+            <button on:click=move |_| on_retry()>{"Retry"}</button>
+        </div>
+    }
+}
+
+#[component]
+pub fn App() -> impl IntoView {
+    let (show_chat, set_show_chat) = signal(false);
+    let (selected_project, set_selected_project) = signal::<Option<Project>>(None);
+    let (current_session, set_current_session) = signal::<Option<ChatSession>>(None);
+    // The open project's sessions, for switching between and renaming them
+    let (project_sessions, set_project_sessions) = signal::<Vec<ChatSession>>(Vec::new());
+    let (renaming_session, set_renaming_session) = signal(false);
+    let (refetch_projects, set_refetch_projects) = signal(false);
+    let (refetch_messages, set_refetch_messages) = signal(false);
+    let (is_initialized, set_is_initialized) = signal(false);
+    // Bumped whenever tool calls have changed the scene, for views derived from SavedState
+    let (scene_version, set_scene_version) = signal(0u32);
+    // Component selected in the preview or the Components tab, kept while switching tabs
+    let (selected_component_id, set_selected_component_id) = signal::<Option<String>>(None);
+    let (message_content, set_message_content) = signal(String::new());
+    let (local_messages, set_local_messages) = signal(Vec::<ChatMessage>::new());
+    let (active_editor_tab, set_active_editor_tab) = signal(0);
+    let input_ref: NodeRef<leptos::html::Input> = NodeRef::new();
+    let messages_ref: NodeRef<leptos::html::Div> = NodeRef::new();
+    let (stick_to_bottom, set_stick_to_bottom) = signal(true);
+    // Search over the loaded messages, and which of its matches is being shown
+    let (message_search, set_message_search) = signal(String::new());
+    let (search_position, set_search_position) = signal(0usize);
+    let (collapse_actions, set_collapse_actions) = signal(storage::get_item(COLLAPSE_ACTIONS_KEY).as_deref() == Some("true"));
+    // Collapsed runs the user opened, by the id of their first message
+    let (expanded_actions, set_expanded_actions) = signal(HashSet::<String>::new());
+    let (pending, set_pending) = signal(false);
+    // Progress of a slow tool call, e.g. heightmap erosion
+    let (tool_progress, set_tool_progress) = signal::<Option<(String, f32)>>(None);
+    // Last user message being edited, its reply is discarded when the edit is sent
+    let (editing_message, set_editing_message) = signal::<Option<ChatMessage>>(None);
+    // Model the server should answer with, empty for its default. Remembered per session
+    let (chat_model, set_chat_model) = signal(String::new());
+    // Token usage from message POST responses, by message id
+    let (response_usage, set_response_usage) = signal(HashMap::<String, TokenUsage>::new());
+    // What each reply's tool calls changed in the scene, by the reply's message id
+    let (change_summaries, set_change_summaries) = signal(HashMap::<String, Vec<String>>::new());
+    // Sampling temperature sent with each message, also remembered per session
+    let (temperature, set_temperature) = signal(DEFAULT_TEMPERATURE);
+    let (drop_kind, set_drop_kind) = signal(DroppedAssetKind::Model);
+    let (is_dragging_over, set_is_dragging_over) = signal(false);
+    // Fraction of the width the chat pane takes, the preview gets the rest
+    let (chat_pane_ratio, set_chat_pane_ratio) = signal(
+        storage::get_item(CHAT_PANE_RATIO_KEY)
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(DEFAULT_CHAT_PANE_RATIO)
+            .clamp(MIN_CHAT_PANE_RATIO, MAX_CHAT_PANE_RATIO)
+    );
+    let (is_resizing_panes, set_is_resizing_panes) = signal(false);
+    // Typed filter for the inbox project list
+    let (project_filter, set_project_filter) = signal(String::new());
+    let project_filter_ref: NodeRef<leptos::html::Input> = NodeRef::new();
+    let project_list_ref: NodeRef<leptos::html::Div> = NodeRef::new();
+
+    // None follows the system setting
+    let (theme_choice, set_theme_choice) = signal(storage::get_item(THEME_KEY));
+    let prefers_dark = use_preferred_dark();
+    let dark_theme = Signal::derive(move || match theme_choice.get().as_deref() {
+        Some("dark") => true,
+        Some("light") => false,
+        _ => prefers_dark.get(),
+    });
+    create_effect(move |_| {
+        let theme = if dark_theme.get() { "dark" } else { "light" };
+        if let Some(root) = web_sys::window().and_then(|w| w.document()).and_then(|d| d.document_element()) {
+            let _ = root.set_attribute("data-theme", theme);
+        }
+    });
+    let toggle_theme = move |_| {
+        let theme = if dark_theme.get_untracked() { "light" } else { "dark" };
+        storage::set_item(THEME_KEY, theme);
+        set_theme_choice.set(Some(theme.to_string()));
+    };
+    let chat_view_ref: NodeRef<leptos::html::Section> = NodeRef::new();
+
+    // DO NOT use "create_resource" as the leptos_reactive crate is deprecated, LocalResource is the recommended way for a client-side Tauri + Leptos app
+    let projects_resource: LocalResource<Result<Vec<ProjectInfo>, String>> = LocalResource::new(
+        // move || refetch_projects.get(),
+        move || async move {
+            if refetch_projects.get() {
+                set_refetch_projects.update_untracked(|val| *val = false);
             }
-            
-             // We need the project path. It's in `selected_project`.
-            let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
-            
-            if !project_path.is_empty() {
-                let url = format!("{}/api/save-script", get_api_url());
-                let body = serde_json::json!({
-                    "projectPath": project_path,
-                    "filename": args.filename,
-                    "content": args.content
-                });
+            Request::get(&format!("{}/api/projects", get_api_url()))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())
+        },
+    );
+
+    let pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>> =
+        LocalResource::new(
+        move || async move {
+            Some(Rc::new(RefCell::new(ExportPipeline::new())))
+        },
+    );
+
+    let messages_resource: LocalResource<std::result::Result<Vec<ChatMessage>, String>> = LocalResource::new(
+    move || async move { 
+            if refetch_messages.get() {
+                set_refetch_messages.update_untracked(|val| *val = false);
+            }
+            let session_id = current_session.get().map(|s| s.id);
+            if let Some(session_id) = session_id {
+                let url = format!("{}/api/sessions/{}/messages", get_api_url(), session_id);
+                let remote: Vec<ChatMessage> = Request::get(&url)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .json()
+                    .await
+                    .map_err(|e| e.to_string())?;
                 
-                spawn_local(async move {
-                    let _ = Request::post(&url)
-                        .json(&body)
-                        .expect("Couldn't make post body")
-                        .send()
-                        .await;
+                Ok(remote)
+            }
+            else {
+                Ok(Vec::new())
+            }
+        },
+    );
+
+    // Local status messages are merged in by id here rather than inside the resource,
+    // so toggling `refetch_messages` can never duplicate or drop them
+    let chat_messages = move || {
+        messages_resource.get().map(|result| {
+            result.map(|remote| {
+                let mut messages = merge_messages(remote, &local_messages.get());
+                // Usage the server reported when replying but didn't store with the message
+                response_usage.with(|usage| {
+                    for message in messages.iter_mut().filter(|m| m.usage.is_none()) {
+                        message.usage = usage.get(&message.id).copied();
+                    }
                 });
+                messages
+            })
+        })
+    };
+
+    let session_usage = move || {
+        chat_messages()
+            .and_then(|result| result.ok())
+            .map(|messages| messages.iter().filter_map(|m| m.usage).map(|usage| usage.total()).sum::<u32>())
+            .unwrap_or(0)
+    };
+
+    let scroll_messages_to_bottom = move || {
+        // Wait a frame so the newly rendered messages are included in scroll_height
+        request_animation_frame(move || {
+            if let Some(container) = messages_ref.get_untracked() {
+                container.set_scroll_top(container.scroll_height());
             }
+        });
+    };
+
+    // Follow new messages, unless the user has scrolled up to read history
+    create_effect(move |_| {
+        chat_messages();
+        pending.get();
+        if stick_to_bottom.get_untracked() {
+            scroll_messages_to_bottom();
         }
-    } else if tool_call.function.name == "generateHeightmap" {
-        log!("Generating heightmap...");
-        let args: Result<GenerateHeightmapArgs, _> = serde_json::from_str(&tool_call.function.arguments);
-        if let Ok(args) = args {
-            if let Some(pipeline_arc_val) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    if let Some(editor) = pipeline.export_editor.as_mut() {
-                        // 1. Find existing landscape info
-                        let mut existing_info = None;
-                        
-                        if let Some(target_id) = &args.component_id {
-                             if let Some(saved_state) = editor.saved_state.as_ref() {
-                                if let Some(levels) = saved_state.levels.as_ref() {
-                                    if let Some(components) = levels.get(0).and_then(|l| l.components.as_ref()) {
-                                        if let Some(component) = components.iter().find(|c| c.id == *target_id) {
-                                             let position = component.generic_properties.position;
-                                             let asset_id = component.asset_id.clone();
-                                             
-                                             if let Some(landscapes) = saved_state.landscapes.as_ref() {
-                                                if let Some(landscape_data) = landscapes.iter().find(|l| l.id == asset_id) {
-                                                    if let Some(heightmap_file) = &landscape_data.heightmap {
-                                                        existing_info = Some((position, asset_id, heightmap_file.fileName.clone()));
-                                                    }
-                                                }
-                                             }
-                                        }
-                                    }
-                                }
-                             }
-                        } else {
-                             // Try to find first existing landscape if no ID specified
-                             if let Some(saved_state) = editor.saved_state.as_ref() {
-                                if let Some(levels) = saved_state.levels.as_ref() {
-                                    if let Some(components) = levels.get(0).and_then(|l| l.components.as_ref()) {
-                                        if let Some(component) = components.iter().find(|c| c.kind == Some(entropy_engine::helpers::saved_data::ComponentKind::Landscape)) {
-                                            let position = component.generic_properties.position;
-                                            let asset_id = component.asset_id.clone();
-                                            if let Some(landscapes) = saved_state.landscapes.as_ref() {
-                                                if let Some(landscape_data) = landscapes.iter().find(|l| l.id == asset_id) {
-                                                    if let Some(heightmap_file) = &landscape_data.heightmap {
-                                                        existing_info = Some((position, asset_id, heightmap_file.fileName.clone()));
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    });
+
+    let rename_session = move |session_id: String, title: String| {
+        let title = title.trim().to_string();
+        let unchanged = project_sessions.with_untracked(|sessions| {
+            sessions.iter().any(|s| s.id == session_id && s.title.as_deref() == Some(title.as_str()))
+        });
+        if title.is_empty() || unchanged {
+            return;
+        }
+
+        set_current_session.update(|session| {
+            if let Some(session) = session.as_mut().filter(|s| s.id == session_id) {
+                session.title = Some(title.clone());
+            }
+        });
+        set_project_sessions.update(|sessions| {
+            if let Some(session) = sessions.iter_mut().find(|s| s.id == session_id) {
+                session.title = Some(title.clone());
+            }
+        });
+        spawn_local(async move {
+            if let Err(e) = save_session_title(&session_id, &title).await {
+                log!("Couldn't save the session title: {}", e);
+            }
+        });
+    };
+
+    // Titles an untitled session from its opening message once the first reply is in
+    create_effect(move |_| {
+        let Some(session) = current_session.get().filter(|session| session.title.is_none()) else { return };
+        let Some(Ok(messages)) = chat_messages() else { return };
+        if !messages.iter().any(|m| m.role == "assistant") {
+            return;
+        }
+
+        let opening = messages.iter().find(|m| m.role == "user").and_then(|m| m.content.as_deref());
+        if let Some(title) = opening.map(session_title_from).filter(|title| !title.is_empty()) {
+            rename_session(session.id, title);
+        }
+    });
+
+    // Ids of the messages matching the search, oldest first. Worked out once per query or message
+    // change, every message row reads it
+    let search_matches = Memo::new(move |_| {
+        let query = message_search.get().trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        chat_messages()
+            .and_then(|result| result.ok())
+            .map(|messages| messages.iter().filter(|m| message_matches(m, &query)).map(|m| m.id.clone()).collect::<Vec<_>>())
+            .unwrap_or_default()
+    });
+    let current_search_match = move || {
+        search_matches.with(|matches| {
+            (!matches.is_empty()).then(|| matches[search_position.get() % matches.len()].clone())
+        })
+    };
+    let step_search = move |forward: bool| {
+        let count = search_matches.with_untracked(|matches| matches.len());
+        if count > 0 {
+            set_stick_to_bottom.set(false);
+            set_search_position.update(|position| {
+                *position = if forward { (*position + 1) % count } else { (*position + count - 1) % count };
+            });
+        }
+    };
+
+    // A new session or closing the chat starts the search over
+    create_effect(move |_| {
+        show_chat.get();
+        current_session.track();
+        set_message_search.set(String::new());
+        set_search_position.set(0);
+    });
+
+    let switch_session = move |session_id: String| {
+        set_renaming_session.set(false);
+        if let Some(session) = project_sessions.with_untracked(|sessions| sessions.iter().find(|s| s.id == session_id).cloned()) {
+            set_current_session.set(Some(session));
+        }
+    };
+
+    // Restore the unsent draft for whichever session opens
+    create_effect(move |_| {
+        if let Some(session) = current_session.get() {
+            let draft = storage::get_item(&draft_storage_key(&session.id)).unwrap_or_default();
+            set_message_content.set(draft);
+        }
+    });
+
+    create_effect(move |_| {
+        if let Some(session) = current_session.get() {
+            set_chat_model.set(storage::get_item(&model_storage_key(&session.id)).unwrap_or_default());
+            let saved_temperature = storage::get_item(&temperature_storage_key(&session.id))
+                .and_then(|value| value.parse::<f32>().ok());
+            set_temperature.set(saved_temperature.unwrap_or(DEFAULT_TEMPERATURE).clamp(MIN_TEMPERATURE, MAX_TEMPERATURE));
+        }
+    });
 
-                        let (position, asset_id, filename) = if let Some(info) = existing_info {
-                            info
-                        } else {
-                            log!("Creating new landscape info.");
-                            let new_asset_id = Uuid::new_v4().to_string();
-                            ([0.0, 0.0, 0.0], new_asset_id, format!("heightmap_{}.png", Uuid::new_v4()))
-                        };
+    let debounced_message_content = signal_debounced(message_content, DRAFT_SAVE_DEBOUNCE_MS);
+    create_effect(move |_| {
+        let content = debounced_message_content.get();
+        if let Some(session) = current_session.get_untracked() {
+            let key = draft_storage_key(&session.id);
+            if content.is_empty() {
+                storage::remove_item(&key);
+            } else {
+                storage::set_item(&key, &content);
+            }
+        }
+    });
 
-                        let width = 1024;
-                        let height = 1024;
-                        let mut generator = HeightmapGenerator::new(width, height)
-                                                                    .with_scale(1024.0)
-                                                                    .with_octaves(8)
-                                                                    .with_persistence(0.5)
-                                                                    .with_seed(42);
-                        
-                        if let Some(seed) = args.seed { generator = generator.with_seed(seed); }
-                        if let Some(scale) = args.scale { generator = generator.with_scale(scale); }
-                        if let Some(persistence) = args.persistence { generator = generator.with_persistence(persistence); }
-                        if let Some(lacunarity) = args.lacunarity { generator = generator.with_lacunarity(lacunarity); }
-
-                        if let Some(features) = args.features {
-                            for f in features {
-                                let f_type = match f.r#type.as_str() {
-                                    "Mountain" => FeatureType::Mountain,
-                                    "Valley" => FeatureType::Valley,
-                                    "Plateau" => FeatureType::Plateau,
-                                    "Ridge" => FeatureType::Ridge,
-                                    _ => FeatureType::Mountain,
-                                };
-                                let falloff = match f.falloff.as_str() {
-                                    "Linear" => FalloffType::Linear,
-                                    "Smooth" => FalloffType::Smooth,
-                                    "Gaussian" => FalloffType::Gaussian,
-                                    _ => FalloffType::Smooth,
-                                };
-                                let mut feature = TerrainFeature::new(
-                                    (f.center[0], f.center[1]),
-                                    f.radius,
-                                    f.intensity,
-                                    falloff,
-                                    f_type
-                                );
-                                if let Some(ft) = f.flat_top { feature = feature.with_flat_top(ft); }
-                                if let Some(t) = f.transition { feature = feature.with_transition(t); }
-                                generator.add_feature(feature);
-                            }
-                        }
+    // Divider drags are tracked on the window so they keep going when the pointer leaves the divider
+    let _divider_move_listener = window_event_listener(leptos::ev::mousemove, move |ev| {
+        if !is_resizing_panes.get_untracked() {
+            return;
+        }
+        if let Some(chat_view) = chat_view_ref.get_untracked() {
+            let rect = chat_view.get_bounding_client_rect();
+            if rect.width() > 0.0 {
+                let ratio = (ev.client_x() as f64 - rect.left()) / rect.width();
+                set_chat_pane_ratio.set(ratio.clamp(MIN_CHAT_PANE_RATIO, MAX_CHAT_PANE_RATIO));
+            }
+        }
+    });
+    let _divider_up_listener = window_event_listener(leptos::ev::mouseup, move |_| {
+        if is_resizing_panes.get_untracked() {
+            set_is_resizing_panes.set(false);
+            storage::set_item(CHAT_PANE_RATIO_KEY, &chat_pane_ratio.get_untracked().to_string());
+        }
+    });
 
-                        let img = generator.generate();
-                        
-                        // Convert to PNG bytes
-                        let mut png_bytes: Vec<u8> = Vec::new();
-                        let _ = image::ImageBuffer::from_raw(width, height, img.clone().into_raw())
-                            .map(|buf: image::ImageBuffer<image::Luma<u16>, Vec<u16>>| {
-                                let dyn_img = image::DynamicImage::ImageLuma16(buf);
-                                let _ = dyn_img.write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png);
-                            });
+    // Unsynced local edits found in the offline cache when a project opens
+    let (restore_offer, set_restore_offer) = signal::<Option<CachedProject>>(None);
 
-                        // Upload to API
-                        // We need the project path. It's in `selected_project`.
-                        let project_path = selected_project.get_untracked().map(|p| p.path).unwrap_or_default();
-                        
-                        if !png_bytes.is_empty() && !project_path.is_empty() {
-                            let file_part = FormData::new().expect("FormData error");
-                            // file_part.append("projectPath", &project_path);
-                            
-                            // Manually constructing body or using a way to send FormData compatible with the server
-                            // Gloo-net Request supports body.
-                            
-                            // Let's use web_sys for FormData as it's cleaner in browser context
-                            let form_data = web_sys::FormData::new().unwrap();
-                            form_data.append_with_str("projectPath", &project_path).unwrap();
-                            form_data.append_with_str("landscapeAssetId", &asset_id).unwrap();
-                            form_data.append_with_str("filename", &filename).unwrap();
-                            
-                            let uint8_array = js_sys::Uint8Array::from(&png_bytes[..]);
-                            let blob_parts = js_sys::Array::new();
-                            blob_parts.push(&uint8_array);
-                            let blob = web_sys::Blob::new_with_u8_array_sequence(&blob_parts).unwrap();
-                            form_data.append_with_blob("file", &blob).unwrap();
-
-                            let url = format!("{}/api/save-heightmap", get_api_url());
-                            spawn_local(async move {
-                                let _ = Request::post(&url)
-                                    .body(form_data)
-                                    .expect("Couldn't make post body")
-                                    .send()
-                                    .await;
-                            });
-                        }
+    // Saves that failed while offline go out as soon as the connection is back
+    let _online_listener = window_event_listener(leptos::ev::online, move |_| {
+        spawn_local(flush_unsynced_saves());
+    });
 
-                        // Update In-Memory
-                        let height_data: Vec<f32> = img.pixels().map(|p| p.0[0] as f32 / 65535.0).collect();
+    // Shows the chat for `project`, continuing in `session`
+    let start_chat = move |project: Project, session: ChatSession| {
+        spawn_local(async move {
+            log!("Setting up chat {:?} {:?}", project.id, session.id);
 
-                        let landscape_data = generate_landscape_data(
-                            width as usize,
-                            height as usize,
-                            height_data,
-                            1024.0 * 4.0, // size match existing default or reasonable size
-                            1024.0 * 4.0,
-                            150.0 * 4.0, // height scale
-                        );
+            // Edits cached in this browser that the server never got, e.g. the tab closed while offline
+            let server_version = project.saved_data.as_ref().and_then(|saved_state| saved_state.version).unwrap_or(0);
+            if let Some(cached) = offline_cache::get(&project.id).await {
+                if !cached.synced && cached.version > server_version {
+                    set_restore_offer.set(Some(cached));
+                }
+            }
 
-                        if let Some(renderer_state) = editor.renderer_state.as_mut() {
-                            // Clear existing landscapes
-                            renderer_state.landscapes.clear();
-                            renderer_state.terrain_managers.clear();
-                            
-                            // Add new landscape with CORRECT position
-                            let device = &editor.gpu_resources.as_ref().unwrap().device;
-                            let queue = &editor.gpu_resources.as_ref().unwrap().queue;
-                            let camera = editor.camera.as_ref().unwrap();
-                            
-                            renderer_state.add_landscape(
-                                device,
-                                queue,
-                                &"generated_landscape".to_string(),
-                                &landscape_data,
-                                position, // Use the position from saved_state
-                                camera
-                            );
-                            
-                            log!("Heightmap generated and loaded!");
-                            
-                            if let Some(saved_state) = editor.saved_state.as_mut() {
-                                saved_state_clone = Some(saved_state.clone());
-                            }
-                        }
-                    }
+            let mut sessions = project.sessions.clone();
+            if !sessions.iter().any(|s| s.id == session.id) {
+                sessions.push(session.clone());
+            }
+            set_project_sessions.set(sessions);
+
+            set_selected_project.update(|val| *val = Some(project));
+            set_current_session.update(|val| *val = Some(session));
+            set_show_chat.update(|val| *val = true);
+        });
+    };
+
+    let start_new_session = move |project: Project| {
+        spawn_local(async move {
+            let session_res = Request::post(&format!("{}/api/sessions", get_api_url()))
+                .json(&serde_json::json!({ "projectId": project.id }))
+                .expect("Couldn't get json")
+                .send()
+                .await;
+
+            if let Ok(session_resp) = session_res {
+                if let Ok(session) = session_resp.json::<ChatSession>().await {
+                    start_chat(project, session);
+                } else {
+                    log!("Failed to parse session response");
                 }
+            } else {
+                log!("Failed to create session");
             }
-        }
-    }
+        });
+    };
 
-    if let Some(saved_state) = saved_state_clone {
+    // A project with earlier conversations waiting on "continue or start new"
+    let (session_prompt, set_session_prompt) = signal::<Option<Project>>(None);
+    let (remember_session_choice, set_remember_session_choice) = signal(false);
+
+    let open_project_chat = move |project_info: ProjectInfo| {
         spawn_local(async move {
-            let _ = save_project(&project_id, &saved_state).await;
+            // 1. Fetch full project details (including savedData)
+            match fetch_project(&project_info.id).await {
+                Ok(project) => {
+                    // 2. Continue the latest session or create one, asking unless a choice was remembered
+                    let Some(last_session) = project.sessions.last().cloned() else {
+                        start_new_session(project);
+                        return;
+                    };
+                    match storage::get_item(&session_choice_key(&project.id)).as_deref() {
+                        Some(SESSION_CHOICE_CONTINUE) => start_chat(project, last_session),
+                        Some(SESSION_CHOICE_NEW) => start_new_session(project),
+                        _ => set_session_prompt.set(Some(project)),
+                    }
+                }
+                Err(e) => log!("Failed to fetch project: {}", e),
+            }
         });
-    }
+    };
 
-    "{\"success\": true}".to_string()
-}
+    let choose_session = move |continue_last: bool| {
+        let Some(project) = session_prompt.get_untracked() else { return };
+        set_session_prompt.set(None);
+        if remember_session_choice.get_untracked() {
+            let choice = if continue_last { SESSION_CHOICE_CONTINUE } else { SESSION_CHOICE_NEW };
+            storage::set_item(&session_choice_key(&project.id), choice);
+        }
+        set_remember_session_choice.set(false);
 
-#[component]
-pub fn ProjectCanvas(
-    selected_project: ReadSignal<Option<Project>>,
-    pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
-    is_initialized: ReadSignal<bool>,
-    set_is_initialized: WriteSignal<bool>,
-) -> impl IntoView {
-    let canvas_ref = NodeRef::<Canvas>::new();
-    
-    create_effect(move |_| {
-        let canvas = canvas_ref.get();
-        if canvas.is_none() {
-            return;
+        match project.sessions.last().cloned().filter(|_| continue_last) {
+            Some(last_session) => start_chat(project, last_session),
+            None => start_new_session(project),
         }
-        let canvas = canvas.expect("canvas should be loaded");
+    };
 
-        if let Some(project) = selected_project.get() {
-            let project_data = project.clone();
-            if let Some(pipeline_arc) = pipeline_store.get() {
-                if let Some(pipeline_arc) = pipeline_arc.as_ref() {
-                    let pipeline_arc_clone = pipeline_arc.clone();
-                    spawn_local(async move {
-                        let mut pipeline_guard = pipeline_arc_clone.borrow_mut();
+    // "Start New Project" dialog
+    let (new_project_open, set_new_project_open) = signal(false);
+    let (new_project_name, set_new_project_name) = signal(String::new());
+    let (new_project_error, set_new_project_error) = signal::<Option<String>>(None);
+    let (creating_project, set_creating_project) = signal(false);
 
-                        log!("initializing...");
-                        
-                        #[cfg(target_arch = "wasm32")]
-                        pipeline_guard
-                            .initialize(
-                                Some(canvas),
-                                WindowSize {
-                                    width: 1024,
-                                    height: 768,
-                                },
-                                Vec::new(),
-                                SavedTimelineStateConfig {
-                                    timeline_sequences: Vec::new(),
-                                },
-                                1024,
-                                768,
-                                Uuid::new_v4().to_string(),
-                                false,
-                            )
-                            .await;
+    let close_new_project = move || {
+        set_new_project_open.set(false);
+        set_new_project_name.set(String::new());
+        set_new_project_error.set(None);
+    };
 
-                        log!("loading project...");
+    let submit_new_project = move || {
+        if creating_project.get_untracked() {
+            return;
+        }
+        let name = new_project_name.get_untracked().trim().to_string();
+        if name.is_empty() {
+            set_new_project_error.set(Some("Give the project a name".to_string()));
+            return;
+        }
 
-                        let editor = pipeline_guard.export_editor.as_mut().expect("Couldn't get editor");
-                        // Manually load saved state
-                        if let Some(saved_data) = project_data.saved_data {
-                            editor.saved_state = Some(saved_data.clone());
-                            // We also need to trigger loading of assets/models based on this saved state
-                            // But load_project usually does that. 
-                            // Since we can't easily call load_project which might do FS ops,
-                            // we might need to rely on what's available or implement a lighter load_project.
-                            // For now, let's assume placing the project state is enough or call a helper if available.
-                            
-                            // Re-implement basic loading logic from place_project/load_project if needed
-                            // But place_project is available.
-                             place_project(editor, &project_data.id, saved_data.clone()).await;
-                        }
+        // Catch the obvious collisions before asking the server
+        let taken = projects_resource.get_untracked()
+            .and_then(|result| result.ok())
+            .map(|projects| projects.iter().any(|p| p.name.eq_ignore_ascii_case(&name)))
+            .unwrap_or(false);
+        if taken {
+            set_new_project_error.set(Some(format!("A project named \"{}\" already exists", name)));
+            return;
+        }
 
-                        log!("configuring surface...");
+        set_creating_project.set(true);
+        set_new_project_error.set(None);
+        spawn_local(async move {
+            match create_project(&name).await {
+                Ok(project) => {
+                    close_new_project();
+                    set_refetch_projects.set(true);
+                    open_project_chat(project);
+                }
+                Err(e) => set_new_project_error.set(Some(e)),
+            }
+            set_creating_project.set(false);
+        });
+    };
 
-                        let editor = pipeline_guard.export_editor.as_ref().expect("Couldn't get editor");
-                        let camera = editor.camera.as_ref().expect("Couldn't get camera");
-                        let gpu_resources = pipeline_guard.gpu_resources.as_ref().expect("Couldn't get gpu resources");
-                        let surface = gpu_resources.surface.as_ref().expect("Couldn't get surface").clone();
-                        let size = camera.viewport.window_size.clone();
+    let restore_local_changes = move |_| {
+        if let Some(cached) = restore_offer.get_untracked() {
+            set_restore_offer.set(None);
+            if let Some(mut project) = selected_project.get_untracked().filter(|p| p.id == cached.project_id) {
+                project.saved_data = Some(cached.saved_state.clone());
+                // Reloads the scene through the preview's project effect
+                set_selected_project.set(Some(project));
+                spawn_local(async move {
+                    let _ = save_project(&cached.project_id, &cached.saved_state).await;
+                });
+            }
+        }
+    };
 
-                        let swapchain_format = wgpu::TextureFormat::Rgba8Unorm;
-                        let surface_config = wgpu::SurfaceConfiguration {
-                            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                            format: swapchain_format,
-                            width: size.width,
-                            height: size.height,
-                            present_mode: wgpu::PresentMode::Fifo,
-                            alpha_mode: wgpu::CompositeAlphaMode::PreMultiplied,
-                            view_formats: vec![],
-                            desired_maximum_frame_latency: 2
-                        };
+    let discard_local_changes = move |_| {
+        let Some(cached) = restore_offer.get_untracked() else { return };
+        confirm_action(
+            ConfirmKind::DiscardChanges,
+            "Discard your unsaved changes?",
+            "They were never saved to the server, so they can't be recovered afterwards.",
+            "Discard",
+            move || {
+                set_restore_offer.set(None);
+                let project_id = cached.project_id.clone();
+                spawn_local(async move {
+                    offline_cache::remove(&project_id).await;
+                });
+            },
+        );
+    };
 
-                        surface.configure(&gpu_resources.device, &surface_config);
+    // Another tab saved this project since we loaded it, so our save was refused
+    let (save_conflict, set_save_conflict) = signal::<Option<String>>(None);
+    project_sync::set_conflict_handler(set_save_conflict);
+
+    // Drop our edits and load the server copy
+    let reload_from_server = move |_| {
+        let Some(project_id) = save_conflict.get_untracked() else { return };
+        confirm_action(
+            ConfirmKind::DiscardChanges,
+            "Reload their version?",
+            "Your changes since the last successful save are discarded.",
+            "Reload",
+            move || {
+                set_save_conflict.set(None);
+                let project_id = project_id.clone();
+                spawn_local(async move {
+                    match fetch_project(&project_id).await {
+                        // Reloads the scene through the preview's project effect
+                        Ok(project) => set_selected_project.set(Some(project)),
+                        Err(e) => log!("Failed to reload project: {}", e),
+                    }
+                });
+            },
+        );
+    };
 
-                        log!("Setup Complete!");
+    // Rebase our edits onto the server version and save them over it
+    let reapply_local_changes = move |_| {
+        if let Some(project_id) = save_conflict.get_untracked() {
+            set_save_conflict.set(None);
+            spawn_local(async move {
+                // The edits are measured against the copy we had, so take it before the fetch replaces it
+                let local = pipeline_store.get_untracked().and_then(|pipeline_arc_val| {
+                    pipeline_arc_val.as_ref().and_then(|pipeline_arc| {
+                        pipeline_arc.borrow().export_editor.as_ref().and_then(|editor| editor.saved_state.clone())
+                    })
+                });
+                let Some(local) = local else { return };
 
-                        set_is_initialized.set(true);
-                    });
+                let project = match fetch_project(&project_id).await {
+                    Ok(project) => project,
+                    Err(e) => {
+                        log!("Failed to fetch project: {}", e);
+                        return;
+                    }
+                };
+                let remote = project.saved_data.clone().unwrap_or_default();
+                let rebased = project_sync::rebase(&project_id, &local, &remote);
+
+                // Saved on top of the version just fetched, not the one our refused save expected
+                project_sync::record_version(&project_id, remote.version.unwrap_or(0));
+                if let Err(e) = save_project(&project_id, &rebased).await {
+                    log!("Failed to save rebased project: {}", e);
+                    return;
                 }
-            }
-        }
-    });
 
-    let Pausable { pause, resume, is_active } = use_raf_fn(move |_| {
-        if is_initialized.get() {
-            if let Some(pipeline) = pipeline_store.get_untracked() {
-                if let Some(pipeline_arc) = pipeline.as_ref() {
-                    let mut pipeline = pipeline_arc.borrow_mut();
-                    let gpu_resources = match pipeline.gpu_resources.as_ref() {
-                        Some(res) => res.clone(),
-                        None => return,
-                    };
-
-                    let surface = match gpu_resources.surface.as_ref() {
-                        Some(s) => s,
-                        None => return,
-                    };
+                // Reloads the scene with both sides' changes through the preview's project effect
+                set_selected_project.set(Some(Project { saved_data: Some(rebased), ..project }));
+            });
+        }
+    };
 
-                    let output = match surface.get_current_texture() {
-                        Ok(o) => o,
-                        Err(e) => {
-                            log!("Failed to get current texture: {:?}", e);
-                            return;
-                        }
-                    };
+    // Scene as it was before the latest message was sent, keyed by session, so a regenerated
+    // reply can start over without the discarded reply's tool calls
+    let pre_send_state = StoredValue::new(None::<(String, SavedState)>);
+
+    // Notes about files dropped onto the chat since the last message, sent along with the next one
+    // so the assistant knows the new assets are there
+    let pending_drop_notes = StoredValue::new(Vec::<String>::new());
+
+    // Posts a user message and runs the tool calls in the reply. Callers set `pending` first
+    let post_message = move |session_id: String, content: String, current_saved_state: Option<SavedState>| async move {
+        let project_id = selected_project.get_untracked().as_ref().expect("Couldn't get selected project").id.clone();
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SendMessageArgs {
+            role: String,
+            content: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            tool_call_id: Option<String>,
+            #[serde(rename = "saved_state")]
+            saved_state: Option<SavedState>,
+            // Servers that don't route by model ignore this
+            #[serde(skip_serializing_if = "Option::is_none")]
+            model: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            temperature: Option<f32>,
+        }
 
-                    let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-                    let now = js_sys::Date::now();
-                    pipeline.render_frame(Some(&view), now, false);
-                    output.present();
-                }   
-            }
+        if let Some(state) = current_saved_state.clone() {
+            pre_send_state.set_value(Some((session_id.clone(), state)));
         }
-    });
 
-    view! {
-        <section>
-            <Show
-                when=move || { !is_initialized.get() }
-                fallback=|| view! { <span>{""}</span> }
-            >
-                <span>{"Initializing..."}</span>
-            </Show>
-            <canvas 
-                id="project-canvas" 
-                node_ref=canvas_ref 
-                tabindex="0"
-                on:keydown=move |ev: web_sys::KeyboardEvent| {
-                    let key = ev.key();
-                    if let Some(pipeline_store_val) = pipeline_store.get() {
-                        if let Some(pipeline_arc) = pipeline_store_val.as_ref() {
-                            let mut pipeline = pipeline_arc.borrow_mut();
-                            if let Some(editor) = pipeline.export_editor.as_mut() {
-                                let camera = editor.camera.as_ref().expect("Couldn't get camera");
+        let drop_notes = pending_drop_notes.get_value();
+        pending_drop_notes.set_value(Vec::new());
+        let content = if drop_notes.is_empty() {
+            content
+        } else {
+            format!("{}\n\n{}", drop_notes.join("\n"), content)
+        };
+
+        let body = SendMessageArgs {
+            role: "user".to_string(),
+            content,
+            tool_call_id: None,
+            saved_state: current_saved_state,
+            model: Some(chat_model.get_untracked()).filter(|model| !model.is_empty()),
+            temperature: Some(temperature.get_untracked()),
+        };
+
+        let url = format!("{}/api/sessions/{}/messages", get_api_url(), session_id);
+        let response = Request::post(&url)
+            .json(&body)
+            .expect("Couldn't get json")
+            .send()
+            .await;
+
+        if let Ok(resp) = response {
+            if let Ok(message) = resp.json::<ChatMessage>().await {
+                log!("Response okay");
+
+                if let Some(usage) = message.usage {
+                    set_response_usage.update(|by_id| {
+                        by_id.insert(message.id.clone(), usage);
+                    });
+                }
 
-                                // log!("handle_key_press {:?} {:?} {:?}", key, camera.position, camera.direction);
+                if let Some(tool_calls) = message.tool_calls {
+                    log!("Tool calls...");
+
+                    let tool_calls_data = tool_calls.clone();
+
+                    set_local_messages.update(|messages| {
+                        for tool_call in tool_calls_data {
+                            messages.push(ChatMessage {
+                                // Stable per tool call so re-merging after a refetch is idempotent
+                                id: format!("tool-status-{}", tool_call.id),
+                                role: "system".to_string(),
+                                content: Some(format!("Implementing changes... {:?} {:?}", tool_call.function.name, tool_call.function.arguments)),
+                                tool_call_id: None,
+                                tool_calls: None,
+                                usage: None,
+                                created_at: None,
+                            });
+                        }
+                    });
 
-                                handle_key_press(editor, key.as_str(), true);
-                            }
+                    for tool_call in tool_calls {
+                        let result = execute_tool_call(&tool_call, pipeline_store, project_id.clone(), selected_project, set_tool_progress).await;
+                        log!("Tool result for {}: {:?}", tool_call.function.name, result);
+                        // Failures go back to the model so it can retry with fixed arguments
+                        if !result.success {
+                            report_tool_result(&session_id, &tool_call.id, &result).await;
                         }
                     }
-                }
-                on:mousemove=move |ev: web_sys::MouseEvent| {
-                    
-                        if let Some(pipeline_store_val) = pipeline_store.get() {
-                            if let Some(pipeline_arc) = pipeline_store_val.as_ref() {
-                                let mut pipeline = pipeline_arc.borrow_mut();
-                                if let Some(editor) = pipeline.export_editor.as_mut() {
-                                    let canv = canvas_ref.get();
-                                    let canv = canv.as_ref().expect("Couldn't get canvas ref");
-                                    let rect = canv.get_bounding_client_rect();
-
-                                    let dx = ev.movement_x() as f32;
-                                    let dy = ev.movement_y() as f32;
-
-                                    // log!("handle_mouse_move_on_shift {:?} {:?}", dx, dy);
-
-                                    let left_mouse_pressed = ev.button() == 0;
-                                    
-                                    handle_mouse_move(
-                                        left_mouse_pressed,
-                                        EntropyPosition {
-                                            x: ev.client_x() as f32 - rect.left() as f32,
-                                            y: ev.client_y() as f32 - rect.top() as f32,
-                                        }, 
-                                        dx, 
-                                        dy, 
-                                        editor
-                                    );
-                                    
-                                    if ev.shift_key() {
-                                        handle_mouse_move_on_shift(dx, dy, editor);
-                                    }
-                                }
-                            }
-                        }
-                   
-                }
-            />
-        </section>
-    }
-}
 
-#[component]
-pub fn App() -> impl IntoView {
-    let (show_chat, set_show_chat) = signal(false);
-    let (selected_project, set_selected_project) = signal::<Option<Project>>(None);
-    let (current_session, set_current_session) = signal::<Option<ChatSession>>(None);
-    let (refetch_projects, set_refetch_projects) = signal(false);
-    let (refetch_messages, set_refetch_messages) = signal(false);
-    let (is_initialized, set_is_initialized) = signal(false);
-    let (message_content, set_message_content) = signal(String::new());
-    let (local_messages, set_local_messages) = signal(Vec::<ChatMessage>::new());
-    let (active_editor_tab, set_active_editor_tab) = signal(0);
-    let input_ref: NodeRef<leptos::html::Input> = NodeRef::new();
+                    // Compared against the same snapshot a regenerate rolls back to
+                    let before = pre_send_state.get_value()
+                        .filter(|(snapshot_session_id, _)| snapshot_session_id == &session_id)
+                        .map(|(_, state)| state);
+                    let after = pipeline_store.get_untracked().flatten().and_then(|pipeline_arc| {
+                        let pipeline = pipeline_arc.borrow();
+                        pipeline.export_editor.as_ref().and_then(|editor| editor.saved_state.clone())
+                    });
+                    if let (Some(before), Some(after)) = (before, after) {
+                        let changes = scene_diff::summarize(&before, &after);
+                        set_change_summaries.update(|summaries| {
+                            summaries.insert(message.id.clone(), changes);
+                        });
+                    }
 
-    // DO NOT use "create_resource" as the leptos_reactive crate is deprecated, LocalResource is the recommended way for a client-side Tauri + Leptos app
-    let projects_resource: LocalResource<Result<Vec<ProjectInfo>, String>> = LocalResource::new(
-        // move || refetch_projects.get(),
-        move || async move {
-            if refetch_projects.get() {
-                set_refetch_projects.update_untracked(|val| *val = false);
+                    set_scene_version.update(|version| *version += 1);
+                }
             }
-            Request::get(&format!("{}/api/projects", get_api_url()))
-                .send()
-                .await
-                .map_err(|e| e.to_string())?
-                .json()
-                .await
-                .map_err(|e| e.to_string())
-        },
-    );
+        }
 
-    let pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>> =
-        LocalResource::new(
-        move || async move {
-            Some(Rc::new(RefCell::new(ExportPipeline::new())))
-        },
-    );
+        // Stays pending through the tool-call loop above
+        set_pending.set(false);
+        set_refetch_messages.update(|val| *val = true);
+    };
 
-    let messages_resource: LocalResource<std::result::Result<Vec<ChatMessage>, String>> = LocalResource::new(
-    move || async move { 
-            if refetch_messages.get() {
-                set_refetch_messages.update_untracked(|val| *val = false);
-            }
-            let session_id = current_session.get().map(|s| s.id);
-            if let Some(session_id) = session_id {
-                let url = format!("{}/api/sessions/{}/messages", get_api_url(), session_id);
-                let mut remote: Vec<ChatMessage> = Request::get(&url)
-                    .send()
-                    .await
-                    .map_err(|e| e.to_string())?
-                    .json()
-                    .await
-                    .map_err(|e| e.to_string())?;
-                
-                // Combine with local messages here
-                remote.extend(local_messages.get_untracked().iter().cloned());
-                Ok(remote)
-            }
-            else {
-                Ok(local_messages.get_untracked())
-            }
-        },
-    );
+    // Puts the scene back the way it was before the last message was sent, so a discarded reply's
+    // tool calls don't linger. Returns the state the next message should be sent with
+    let roll_back_last_reply = move |session_id: String| async move {
+        let project_id = selected_project.get_untracked().map(|p| p.id).unwrap_or_default();
+        let snapshot = pre_send_state.get_value()
+            .filter(|(snapshot_session_id, _)| snapshot_session_id == &session_id)
+            .map(|(_, state)| state);
+
+        let mut current_saved_state = None;
+        if let Some(pipeline_arc_val) = pipeline_store.get_untracked() {
+            if let Some(pipeline_arc) = pipeline_arc_val.as_ref() {
+                match snapshot {
+                    Some(state) => {
+                        // Keep the render loop and editors off the pipeline while the scene reloads
+                        set_is_initialized.set(false);
+                        {
+                            let mut pipeline = pipeline_arc.borrow_mut();
+                            if let Some(editor) = pipeline.export_editor.as_mut() {
+                                reset_scene(editor, &project_id, Some(state.clone())).await;
+                            }
+                        }
+                        set_is_initialized.set(true);
+                        set_scene_version.update(|version| *version += 1);
 
-    let open_project_chat = move |project_info: ProjectInfo| {
-        spawn_local(async move {
-            // 1. Fetch full project details (including savedData)
-            let project_res = Request::get(&format!("{}/api/projects/{}", get_api_url(), project_info.id))
-                .send()
-                .await;
-            
-            if let Ok(resp) = project_res {
-                if let Ok(project) = resp.json::<Project>().await {
-                    
-                    // 2. Create or get session
-                    // For now, always create a new session or pick the last one if we implemented logic for it.
-                    // The simplest is to create a new session for this "chat instance".
-                    // Or if we want persistent chat, we should list sessions and pick one.
-                    // Let's create a new one for simplicity as per requirements "Start New Project" or "Chat with...".
-                    // The old code returned `session` from `open_project_chat`.
-                    
-                    let session_res = Request::post(&format!("{}/api/sessions", get_api_url()))
-                        .json(&serde_json::json!({ "projectId": project.id }))
-                        .expect("Couldn't get json")
-                        .send()
-                        .await;
-
-                    if let Ok(session_resp) = session_res {
-                        if let Ok(session) = session_resp.json::<ChatSession>().await {
-                            log!("Setting up chat {:?} {:?}", project.id, session.id);
-
-                            set_selected_project.update(|val| *val = Some(project));
-                            set_current_session.update(|val| *val = Some(session));
-                            set_show_chat.update(|val| *val = true);
-                        } else {
-                            log!("Failed to parse session response");
+                        let _ = save_project(&project_id, &state).await;
+                        current_saved_state = Some(state);
+                    }
+                    None => {
+                        // Sent before this page load, so there's nothing to roll back to
+                        set_local_messages.update(|messages| {
+                            messages.push(ChatMessage {
+                                id: Uuid::new_v4().to_string(),
+                                role: "system".to_string(),
+                                content: Some("Changes made by the previous reply couldn't be undone and are still in the scene.".to_string()),
+                                tool_call_id: None,
+                                tool_calls: None,
+                                usage: None,
+                                created_at: None,
+                            });
+                        });
+                        let pipeline = pipeline_arc.borrow();
+                        if let Some(editor) = pipeline.export_editor.as_ref() {
+                            current_saved_state = editor.saved_state.clone();
                         }
-                    } else {
-                        log!("Failed to create session");
                     }
-                } else {
-                    log!("Failed to parse project response");
                 }
-            } else {
-                log!("Failed to fetch project");
             }
-        });
+        }
+
+        current_saved_state
     };
 
-    let send_message = move |pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>| {
-        if let Some(session) = current_session.get() {
-            let content = message_content.get(); // Get value before spawn
+    // Replaces `message` and everything after it with `content`, sent as a fresh user message
+    let replace_from_message = move |session_id: String, message: ChatMessage, content: String| async move {
+        let current_saved_state = roll_back_last_reply(session_id.clone()).await;
+
+        // The server re-adds the user message when it's posted again
+        if let Err(e) = delete_messages_from(&session_id, &message.id).await {
+            log!("Failed to remove the previous reply: {}", e);
+            set_pending.set(false);
+            set_refetch_messages.update(|val| *val = true);
+            return;
+        }
+
+        post_message(session_id, content, current_saved_state).await;
+    };
+
+    // Drops the last reply and asks again with the same user message
+    let regenerate_response = move || {
+        confirm_action(
+            ConfirmKind::ReplaceReply,
+            "Regenerate the reply?",
+            "The current reply will be deleted, and the scene changes it made are rolled back where possible.",
+            "Regenerate",
+            move || {
+                if pending.get_untracked() {
+                    return;
+                }
+                let Some(session) = current_session.get_untracked() else { return };
+                let Some(Ok(messages)) = messages_resource.get_untracked() else { return };
+                let Some(last_user_message) = messages.iter().rev().find(|m| m.role == "user").cloned() else { return };
+                let content = last_user_message.content.clone().unwrap_or_default();
+
+                set_local_messages.set(Vec::new());
+                set_pending.set(true);
+                spawn_local(replace_from_message(session.id, last_user_message, content));
+            },
+        );
+    };
+
+    let submit_message = move |pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>| {
+        if pending.get_untracked() {
+            return;
+        }
+        if let Some(session) = current_session.get_untracked() {
+            let content = message_content.get_untracked(); // Get value before spawn
             set_local_messages.set(Vec::new());
+            set_pending.set(true);
             
             // Get current saved state from pipeline
             let mut current_saved_state = None;
@@ -1658,75 +7766,97 @@ pub fn App() -> impl IntoView {
                 }
             }
 
-            spawn_local(async move {
-                let session_id = session.id.clone();
-                let project_id = selected_project.get().as_ref().expect("Couldn't get selected project").id.clone();
-                
-                #[derive(Serialize)]
-                #[serde(rename_all = "camelCase")]
-                struct SendMessageArgs {
-                    role: String,
-                    content: String,
-                    #[serde(skip_serializing_if = "Option::is_none")]
-                    tool_call_id: Option<String>,
-                    #[serde(rename = "saved_state")]
-                    saved_state: Option<SavedState>,
-                }
-
-                let body = SendMessageArgs {
-                    role: "user".to_string(),
-                    content,
-                    tool_call_id: None,
-                    saved_state: current_saved_state,
-                };
+            set_message_content.update(|val| *val = String::new());
+            storage::remove_item(&draft_storage_key(&session.id));
+            if let Some(input) = input_ref.get_untracked() {
+                input.set_value("");
+            }
 
-                set_message_content.update(|val| *val = String::new());
-                if let Some(input) = input_ref.get_untracked() {
-                    input.set_value("");
+            match editing_message.get_untracked() {
+                Some(message) => {
+                    set_editing_message.set(None);
+                    spawn_local(replace_from_message(session.id.clone(), message, content));
                 }
+                None => spawn_local(post_message(session.id.clone(), content, current_saved_state)),
+            }
+        }
+    };
 
-                let url = format!("{}/api/sessions/{}/messages", get_api_url(), session_id);
-                let response = Request::post(&url)
-                    .json(&body)
-                    .expect("Couldn't get json")
-                    .send()
-                    .await;
+    let send_message = move |pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>| {
+        if pending.get_untracked() {
+            return;
+        }
+        if editing_message.get_untracked().is_some() {
+            confirm_action(
+                ConfirmKind::ReplaceReply,
+                "Replace the reply?",
+                "The reply to your original message will be deleted, and the scene changes it made are rolled back where possible.",
+                "Resend",
+                move || submit_message(pipeline_store),
+            );
+        } else {
+            submit_message(pipeline_store);
+        }
+    };
 
-                if let Ok(resp) = response {
-                    if let Ok(message) = resp.json::<ChatMessage>().await {
-                        log!("Response okay");
+    // Loads the last user message back into the input, the next send replaces it
+    let start_editing = move |message: ChatMessage| {
+        set_message_content.set(message.content.clone().unwrap_or_default());
+        set_editing_message.set(Some(message));
+        if let Some(input) = input_ref.get_untracked() {
+            let _ = input.focus();
+        }
+    };
 
-                        if let Some(tool_calls) = message.tool_calls {
-                            log!("Tool calls...");
+    // Asks first while a reply is still running or the latest scene edits haven't reached the server
+    let close_chat = move |_| {
+        let project_id = selected_project.get_untracked().map(|p| p.id).unwrap_or_default();
+        spawn_local(async move {
+            let unsynced = offline_cache::get(&project_id).await.map(|cached| !cached.synced).unwrap_or(false);
+            let replying = pending.get_untracked();
+            if !unsynced && !replying {
+                set_show_chat.set(false);
+                return;
+            }
 
-                            let tool_calls_data = tool_calls.clone();
+            let mut description = Vec::new();
+            if replying {
+                description.push("A reply is still coming in, and its scene changes may only be partly applied.");
+            }
+            if unsynced {
+                description.push("Your latest scene changes haven't reached the server yet. They stay in this browser until they can be saved.");
+            }
+            confirm_action(
+                ConfirmKind::CloseChat,
+                "Close the chat?",
+                description.join(" "),
+                "Close",
+                move || set_show_chat.set(false),
+            );
+        });
+    };
 
-                            set_local_messages.update(|messages| {
-                                for tool_call in tool_calls_data {
-                                    messages.push(ChatMessage {
-                                        id: Uuid::new_v4().to_string(),
-                                        role: "system".to_string(),
-                                        content: Some(format!("Implementing changes... {:?} {:?}", tool_call.function.name, tool_call.function.arguments)),
-                                        tool_call_id: None,
-                                        tool_calls: None,
-                                    });
-                                }
-                            });
+    let export_chat = move |_| {
+        let Some(Ok(messages)) = messages_resource.get_untracked() else { return };
+        let project_name = selected_project.get_untracked().map(|p| p.name).unwrap_or_default();
+        let markdown = chat_to_markdown(&project_name, &messages);
 
-                            for tool_call in tool_calls {
-                                let _ = execute_tool_call(&tool_call, pipeline_store, project_id.clone(), selected_project).await;
-                            }
-                        }
-                    }
-                }
-                
-                set_refetch_messages.update(|val| *val = true);
-            });
+        let file_name = format!("{}-chat.md", if project_name.is_empty() { "entropy" } else { project_name.as_str() });
+        if let Err(e) = download_text(&file_name, "text/markdown", &markdown) {
+            log!("Failed to export chat: {}", e);
         }
     };
 
+    let cancel_editing = move |_| {
+        set_editing_message.set(None);
+        set_message_content.set(String::new());
+    };
+
     view! {
         <main class="container">
+            <button class="theme-toggle" on:click=toggle_theme>
+                {move || if dark_theme.get() { "Light mode" } else { "Dark mode" }}
+            </button>
             <Show
                 when=move || { !show_chat.get() }
                 fallback=|| view! { <span>{""}</span> }
@@ -1735,17 +7865,57 @@ pub fn App() -> impl IntoView {
                 <h2>{"Welcome, Alex"}</h2>
                 <h1>{"Projects"}</h1>
 
-                <button class="primary-btn">{"Start New Project"}</button>
+                <button class="primary-btn" on:click=move |_| set_new_project_open.set(true)>{"Start New Project"}</button>
 
                 <span class="instructions">{"Chat with apps / projects or other content and add people or bots to the conversation. Optionally mark as public."}</span>
 
                 <section class="more">
                     <div class="">
                         <h3>{"Your Files"}</h3>
-                        <Suspense fallback=move || {
-                            view! { <div>"Loading projects..."</div> }
-                        }>
-                            <div class="files-inner">
+                        <input
+                            class="project-filter"
+                            type="search"
+                            placeholder="Filter projects..."
+                            node_ref=project_filter_ref
+                            prop:value=project_filter
+                            on:input=move |ev| set_project_filter.set(event_target_value(&ev))
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                if ev.key() == "ArrowDown" {
+                                    ev.prevent_default();
+                                    focus_project_item(project_list_ref.get_untracked().and_then(|list| list.first_element_child()));
+                                }
+                            }
+                        />
+                        <Suspense fallback=move || view! { <SkeletonList rows=4 /> }>
+                            <div
+                                class="files-inner"
+                                role="listbox"
+                                aria-label="Projects"
+                                node_ref=project_list_ref
+                                on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                    let Some(item) = ev.target().and_then(|t| t.dyn_into::<web_sys::Element>().ok()) else { return };
+                                    match ev.key().as_str() {
+                                        "ArrowDown" => {
+                                            ev.prevent_default();
+                                            focus_project_item(item.next_element_sibling());
+                                        }
+                                        "ArrowUp" => {
+                                            ev.prevent_default();
+                                            match item.previous_element_sibling() {
+                                                Some(previous) => focus_project_item(Some(previous)),
+                                                None => focus_project_item(project_filter_ref.get_untracked().map(|input| input.unchecked_into())),
+                                            }
+                                        }
+                                        key if key.chars().count() == 1 && !ev.ctrl_key() && !ev.meta_key() && !ev.alt_key() => {
+                                            // Typing anywhere in the list goes to the filter, the key lands there
+                                            if let Some(input) = project_filter_ref.get_untracked() {
+                                                let _ = input.focus();
+                                            }
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            >
                                 {move || {
                                     projects_resource
                                         .get()
@@ -1757,14 +7927,39 @@ pub fn App() -> impl IntoView {
                                                     return view! { <p>{"No projects found."}</p> }.into_view().into_any();
                                                 }
 
+                                                let filter = project_filter.get().trim().to_lowercase();
+                                                let mut items: Vec<&ProjectInfo> = items.iter()
+                                                    .filter(|project| filter.is_empty() || project.name.to_lowercase().contains(&filter))
+                                                    .collect();
+                                                // Most recently updated first, undated projects last in server order
+                                                items.sort_by(|a, b| {
+                                                    b.last_activity_ms().unwrap_or(f64::MIN)
+                                                        .total_cmp(&a.last_activity_ms().unwrap_or(f64::MIN))
+                                                });
+                                                let now = Date::now();
+                                                if items.is_empty() {
+                                                    return view! { <p>{"No projects match."}</p> }.into_view().into_any();
+                                                }
+
                                                 items
                                                     .into_iter()
                                                     .map(|project| {
                                                         let p = project.clone();
+                                                        let p_key = project.clone();
                                                         view! {
-                                                            <div class="inbox-item" on:click=move |_| {
-                                                                open_project_chat(p.clone());
-                                                            }>
+                                                            <div
+                                                                class="inbox-item"
+                                                                role="option"
+                                                                tabindex="0"
+                                                                on:click=move |_| {
+                                                                    open_project_chat(p.clone());
+                                                                }
+                                                                on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                                                    if ev.key() == "Enter" {
+                                                                        open_project_chat(p_key.clone());
+                                                                    }
+                                                                }
+                                                            >
                                                                 <div class="item-icon">
                                                                     <Icon icon=GAME_CONTROLLER color="#AE2983" weight=IconWeight::Fill size="32px" />
                                                                 </div>
@@ -1775,11 +7970,16 @@ pub fn App() -> impl IntoView {
                                                                     </div>
 
                                                                     <div class="item-type">
-                                                                        {"Project"} // Hardcoded for now
+                                                                        {project.kind.clone().filter(|kind| !kind.is_empty()).unwrap_or_else(|| "Project".to_string())}
                                                                     </div>
 
-                                                                    <div class="item-date">
-                                                                        {"N/A"} // No date in ProjectInfo yet
+                                                                    <div
+                                                                        class="item-date"
+                                                                        title=project.updated_at.clone().or(project.created_at.clone()).unwrap_or_default()
+                                                                    >
+                                                                        {project.last_activity_ms()
+                                                                            .map(|then| format!("Updated {}", relative_time(then, now)))
+                                                                            .unwrap_or_else(|| "No date".to_string())}
                                                                     </div>
                                                                 </div>
                                                             </div>
@@ -1787,7 +7987,13 @@ pub fn App() -> impl IntoView {
                                                     })
                                                     .collect_view().into_any()
                                             } else {
-                                                view! { <p>{"Error."}</p> }.into_view().into_any()
+                                                let error = project_items.err().map(|e| e.to_string()).unwrap_or_default();
+                                                view! {
+                                                    <LoadError
+                                                        message=format!("Couldn't load your projects: {}", error)
+                                                        on_retry=move || set_refetch_projects.set(true)
+                                                    />
+                                                }.into_view().into_any()
                                             }
                                         })
                                 }}
@@ -1798,36 +8004,391 @@ pub fn App() -> impl IntoView {
             </section>
             </Show>
 
-            <Show
-                when=move || { show_chat.get() }
-                fallback=|| view! { <span>{""}</span> }
+            <ConfirmDialog />
+
+            <Show when=move || new_project_open.get()>
+                <div class="modal-backdrop">
+                    <div class="modal">
+                        <h3>{"Start a new project"}</h3>
+                        <input
+                            type="text"
+                            placeholder="Project name"
+                            autofocus=true
+                            prop:value=new_project_name
+                            on:input=move |ev| set_new_project_name.set(event_target_value(&ev))
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                match ev.key().as_str() {
+                                    "Enter" => submit_new_project(),
+                                    "Escape" => close_new_project(),
+                                    _ => {}
+                                }
+                            }
+                        />
+                        <Show when=move || new_project_error.get().is_some()>
+                            <p class="error-text">{move || new_project_error.get().unwrap_or_default()}</p>
+                        </Show>
+                        <div class="modal-actions">
+                            <button on:click=move |_| close_new_project()>{"Cancel"}</button>
+                            <button
+                                class="primary-btn"
+                                disabled=move || creating_project.get()
+                                on:click=move |_| submit_new_project()
+                            >
+                                {move || if creating_project.get() { "Creating..." } else { "Create" }}
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+
+            <Show when=move || session_prompt.get().is_some()>
+                <div class="modal-backdrop">
+                    <div class="modal">
+                        <h3>{"Continue where you left off?"}</h3>
+                        <p>
+                            {move || {
+                                let count = session_prompt.with(|p| p.as_ref().map(|p| p.sessions.len()).unwrap_or(0));
+                                format!("This project has {} earlier conversation{}.", count, if count == 1 { "" } else { "s" })
+                            }}
+                        </p>
+                        <label class="dont-ask-again">
+                            <input
+                                type="checkbox"
+                                prop:checked=remember_session_choice
+                                on:change=move |ev| set_remember_session_choice.set(event_target_checked(&ev))
+                            />
+                            {"Remember for this project"}
+                        </label>
+                        <div class="modal-actions">
+                            <button on:click=move |_| choose_session(false)>{"Start a new session"}</button>
+                            <button class="primary-btn" autofocus=true on:click=move |_| choose_session(true)>{"Continue last"}</button>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+
+            <Show when=move || restore_offer.get().is_some()>
+                <div class="modal-backdrop">
+                    <div class="modal">
+                        <h3>{"Restore unsaved changes?"}</h3>
+                        <p>{"This browser has changes to this project that never reached the server, newer than the copy that was just loaded."}</p>
+                        <div class="modal-actions">
+                            <button on:click=discard_local_changes>{"Discard them"}</button>
+                            <button class="primary-btn" on:click=restore_local_changes>{"Restore my changes"}</button>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+
+            <Show when=move || save_conflict.get().is_some()>
+                <div class="modal-backdrop">
+                    <div class="modal">
+                        <h3>{"This project was changed elsewhere"}</h3>
+                        <p>{"It was saved from another tab or window since you opened it, so your latest change wasn't saved."}</p>
+                        <div class="modal-actions">
+                            <button on:click=reload_from_server>{"Reload their version"}</button>
+                            <button class="primary-btn" on:click=reapply_local_changes>{"Keep my changes"}</button>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+
+            // Kept mounted (just hidden) so the preview canvas and its GPU resources survive project switches
+            <section
+                class="chat-view"
+                class:hidden=move || !show_chat.get()
+                class:resizing=move || is_resizing_panes.get()
+                node_ref=chat_view_ref
             >
-            <section class="chat-view">
-                <div class="chat-pane">
-                    <h3>{"Chat with "} {move || selected_project.get().map(|p| p.name).unwrap_or_default()}</h3>
-                    <button on:click=move |_| set_show_chat.set(false)>{"Close Chat"}</button>
-                    <div class="chat-messages">
-                        <Suspense fallback=move || {
-                            view! { <div>"Loading messages..."</div> }
-                        }>
+                <div
+                    class="chat-pane"
+                    style:flex-basis=move || format!("{:.2}%", chat_pane_ratio.get() * 100.0)
+                    class:drag-over=move || is_dragging_over.get()
+                    on:dragover=move |ev: web_sys::DragEvent| {
+                        ev.prevent_default();
+                        set_is_dragging_over.set(true);
+                    }
+                    on:dragleave=move |_| set_is_dragging_over.set(false)
+                    on:drop=move |ev: web_sys::DragEvent| {
+                        ev.prevent_default();
+                        set_is_dragging_over.set(false);
+
+                        let kind = drop_kind.get_untracked();
+                        if let Some(files) = ev.data_transfer().and_then(|data| data.files()) {
+                            for index in 0..files.length() {
+                                if let Some(file) = files.get(index) {
+                                    spawn_local(async move {
+                                        let note = add_dropped_asset(kind, file, pipeline_store, selected_project).await;
+                                        log!("{}", note);
+                                        pending_drop_notes.update_value(|notes| notes.push(note.clone()));
+                                        set_local_messages.update(|messages| {
+                                            messages.push(ChatMessage {
+                                                id: Uuid::new_v4().to_string(),
+                                                role: "system".to_string(),
+                                                content: Some(note),
+                                                tool_call_id: None,
+                                                tool_calls: None,
+                                                usage: None,
+                                                created_at: None,
+                                            });
+                                        });
+                                    });
+                                }
+                            }
+                        }
+                    }
+                >
+                    <h3>
+                        {"Chat with "} {move || selected_project.get().map(|p| p.name).unwrap_or_default()}
+                        <small class="active-model">{move || chat_model_label(&chat_model.get())}</small>
+                        <Show when=move || { session_usage() > 0 }>
+                            <small class="session-usage">{move || format!("{} tokens this session", session_usage())}</small>
+                        </Show>
+                    </h3>
+                    <div class="session-picker">
+                        <Show
+                            when=move || renaming_session.get()
+                            fallback=move || view! {
+                                <select on:change=move |ev| switch_session(event_target_value(&ev))>
+                                    {move || project_sessions.get().into_iter().enumerate().map(|(index, session)| {
+                                        let selected = current_session.with(|current| current.as_ref().is_some_and(|c| c.id == session.id));
+                                        view! {
+                                            <option value=session.id.clone() prop:selected=selected>{session_label(&session, index)}</option>
+                                        }
+                                    }).collect_view()}
+                                </select>
+                                <button on:click=move |_| set_renaming_session.set(true)>{"Rename"}</button>
+                            }
+                        >
+                            <input
+                                type="text"
+                                placeholder="Session title"
+                                autofocus=true
+                                prop:value=move || current_session.get().and_then(|s| s.title).unwrap_or_default()
+                                on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                    match ev.key().as_str() {
+                                        "Enter" => {
+                                            if let Some(session) = current_session.get_untracked() {
+                                                rename_session(session.id, event_target_value(&ev));
+                                            }
+                                            set_renaming_session.set(false);
+                                        }
+                                        "Escape" => set_renaming_session.set(false),
+                                        _ => {}
+                                    }
+                                }
+                                on:blur=move |ev| {
+                                    if let Some(session) = current_session.get_untracked() {
+                                        rename_session(session.id, event_target_value(&ev));
+                                    }
+                                    set_renaming_session.set(false);
+                                }
+                            />
+                        </Show>
+                    </div>
+                    <button on:click=close_chat>{"Close Chat"}</button>
+                    <button on:click=export_chat>{"Export Chat"}</button>
+                    <div class="chat-settings">
+                        <label>
+                            {"Model: "}
+                            <select
+                                on:change=move |ev| {
+                                    let model = event_target_value(&ev);
+                                    if let Some(session) = current_session.get_untracked() {
+                                        storage::set_item(&model_storage_key(&session.id), &model);
+                                    }
+                                    set_chat_model.set(model);
+                                }
+                            >
+                                {CHAT_MODELS.iter().map(|(value, label)| view! {
+                                    <option value=*value prop:selected=move || chat_model.get() == *value>{*label}</option>
+                                }).collect_view()}
+                            </select>
+                        </label>
+                        <label>
+                            {"Creativity: "}
+                            <input
+                                type="range"
+                                min=MIN_TEMPERATURE
+                                max=MAX_TEMPERATURE
+                                step="0.1"
+                                prop:value=move || temperature.get().to_string()
+                                on:input=move |ev| {
+                                    if let Ok(value) = event_target_value(&ev).parse::<f32>() {
+                                        if let Some(session) = current_session.get_untracked() {
+                                            storage::set_item(&temperature_storage_key(&session.id), &value.to_string());
+                                        }
+                                        set_temperature.set(value);
+                                    }
+                                }
+                            />
+                            <span class="temperature-value">{move || format!("{:.1}", temperature.get())}</span>
+                        </label>
+                    </div>
+                    <div class="message-search">
+                        <label class="collapse-actions-toggle">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || collapse_actions.get()
+                                on:change=move |ev| {
+                                    let collapse = event_target_checked(&ev);
+                                    storage::set_item(COLLAPSE_ACTIONS_KEY, if collapse { "true" } else { "false" });
+                                    set_collapse_actions.set(collapse);
+                                }
+                            />
+                            {"Collapse actions"}
+                        </label>
+                        <input
+                            type="search"
+                            placeholder="Search messages"
+                            prop:value=move || message_search.get()
+                            on:input=move |ev| {
+                                set_message_search.set(event_target_value(&ev));
+                                set_search_position.set(0);
+                                set_stick_to_bottom.set(false);
+                            }
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                match ev.key().as_str() {
+                                    "Enter" => step_search(!ev.shift_key()),
+                                    "Escape" => set_message_search.set(String::new()),
+                                    _ => {}
+                                }
+                            }
+                        />
+                        <Show when=move || !message_search.get().trim().is_empty()>
+                            <small class="search-count">
+                                {move || {
+                                    let count = search_matches.with(|matches| matches.len());
+                                    if count == 0 {
+                                        "No matches".to_string()
+                                    } else {
+                                        format!("{} of {}", search_position.get() % count + 1, count)
+                                    }
+                                }}
+                            </small>
+                            <button title="Previous match" on:click=move |_| step_search(false)>{"↑"}</button>
+                            <button title="Next match" on:click=move |_| step_search(true)>{"↓"}</button>
+                        </Show>
+                    </div>
+                    <div
+                        class="chat-messages"
+                        node_ref=messages_ref
+                        on:scroll=move |_| {
+                            if let Some(container) = messages_ref.get_untracked() {
+                                let distance_from_bottom = container.scroll_height() - container.scroll_top() - container.client_height();
+                                set_stick_to_bottom.set(distance_from_bottom <= CHAT_NEAR_BOTTOM_PX);
+                            }
+                        }
+                    >
+                        <Suspense fallback=move || view! { <SkeletonList rows=3 /> }>
                             {move || {
-                                messages_resource.get().and_then(|result| {
-                                    result.as_ref().ok().map(|messages| {
-                                        messages
+                                chat_messages().map(|result| match result {
+                                    Err(error) => view! {
+                                        <LoadError
+                                            message=format!("Couldn't load messages: {}", error)
+                                            on_retry=move || set_refetch_messages.set(true)
+                                        />
+                                    }.into_view().into_any(),
+                                    Ok(messages) => {
+                                        let last_assistant = messages.iter().rposition(|m| m.role == "assistant");
+                                        let last_user = messages.iter().rposition(|m| m.role == "user");
+                                        let render_message = move |index: usize, message: ChatMessage| {
+                                            let on_regenerate = (Some(index) == last_assistant)
+                                                .then(|| Callback::new(move |_| regenerate_response()));
+                                            let edited = message.clone();
+                                            let on_edit = (Some(index) == last_user)
+                                                .then(|| Callback::new(move |_| start_editing(edited.clone())));
+                                            let (match_id, current_id) = (message.id.clone(), message.id.clone());
+                                            let changes = change_summaries.with(|summaries| summaries.get(&message.id).cloned());
+                                            view! {
+                                                <ChatMessageItem
+                                                    message=message.clone()
+                                                    on_regenerate=on_regenerate
+                                                    on_edit=on_edit
+                                                    actions_disabled=Signal::derive(move || pending.get())
+                                                    search_match=Signal::derive(move || search_matches.with(|matches| matches.contains(&match_id)))
+                                                    search_current=Signal::derive(move || current_search_match().as_ref() == Some(&current_id))
+                                                />
+                                                {changes.map(|changes| view! {
+                                                    <details class="change-summary">
+                                                        <summary>
+                                                            {if changes.is_empty() {
+                                                                "No scene changes".to_string()
+                                                            } else {
+                                                                format!("Changes ({})", changes.len())
+                                                            }}
+                                                        </summary>
+                                                        <ul>
+                                                            {changes.into_iter().map(|change| view! { <li>{change}</li> }).collect_view()}
+                                                        </ul>
+                                                    </details>
+                                                })}
+                                            }
+                                        };
+
+                                        if !collapse_actions.get() {
+                                            return messages
+                                                .into_iter()
+                                                .enumerate()
+                                                .map(|(index, message)| render_message(index, message))
+                                                .collect_view()
+                                                .into_any();
+                                        }
+
+                                        group_action_messages(messages)
                                             .into_iter()
-                                            .map(|message| {
-                                                view! {
-                                                    <div class="chat-message">
-                                                        <strong>{message.role.clone()}":"</strong>
-                                                        <span>{message.content.clone().unwrap_or_default()}</span>
-                                                    </div>
+                                            .map(|entry| match entry {
+                                                TranscriptEntry::Message(index, message) => render_message(index, message).into_any(),
+                                                TranscriptEntry::Actions(actions) => {
+                                                    let group_id = actions[0].1.id.clone();
+                                                    let ids: Vec<String> = actions.iter().map(|(_, m)| m.id.clone()).collect();
+                                                    let count = actions.len();
+                                                    // Open while the user expanded it or the search is showing one of its messages
+                                                    let expanded = {
+                                                        let group_id = group_id.clone();
+                                                        move || expanded_actions.with(|expanded| expanded.contains(&group_id))
+                                                            || current_search_match().is_some_and(|id| ids.contains(&id))
+                                                    };
+                                                    let toggle_id = group_id.clone();
+                                                    view! {
+                                                        <div class="action-group">
+                                                            <button
+                                                                class="action-group-toggle"
+                                                                on:click=move |_| set_expanded_actions.update(|expanded| {
+                                                                    if !expanded.remove(&toggle_id) {
+                                                                        expanded.insert(toggle_id.clone());
+                                                                    }
+                                                                })
+                                                            >
+                                                                {format!("{} action{}", count, if count == 1 { "" } else { "s" })}
+                                                            </button>
+                                                            <Show when=expanded>
+                                                                {actions.iter().cloned().map(|(index, message)| render_message(index, message)).collect_view()}
+                                                            </Show>
+                                                        </div>
+                                                    }.into_any()
                                                 }
                                             })
                                             .collect_view()
-                                    })
+                                            .into_any()
+                                    }
                                 })
                             }}
                         </Suspense>
+                        <Show when=move || pending.get() && tool_progress.get().is_none()>
+                            <div class="typing-indicator">
+                                {"Assistant is thinking"}
+                                <span class="dot">{"."}</span>
+                                <span class="dot">{"."}</span>
+                                <span class="dot">{"."}</span>
+                            </div>
+                        </Show>
+                        {move || tool_progress.get().map(|(label, progress)| view! {
+                            <div class="tool-progress">
+                                <span>{format!("{}... {:.0}%", label, progress * 100.0)}</span>
+                                <progress max="1" value=progress></progress>
+                            </div>
+                        })}
                         // Recommendations
                         // <button class="primary-btn">"Let's turn the ocean blood red and more intense"</button>
                         // <button class="primary-btn">"Please move the sword near the shoreline"</button>
@@ -1837,18 +8398,59 @@ pub fn App() -> impl IntoView {
                         <span>"You can also drop models and images here in the chat, but remember to let Chat know if you are sending textures, heightmaps, or something else so it gets organized properly"</span>
                         <span>"Feel free to chat about point lights, models, collectables, game behaviors, NPCs, particle effects, dialogue, quests, water, trees, grass, new terrains, or anything else that you would like to see in your game world"</span>
                     </div>
+                    <Show when=move || !stick_to_bottom.get()>
+                        <button
+                            class="jump-to-latest"
+                            on:click=move |_| {
+                                set_stick_to_bottom.set(true);
+                                scroll_messages_to_bottom();
+                            }
+                        >{"Jump to latest"}</button>
+                    </Show>
+                    <div class="chat-drop-kind">
+                        <label>
+                            {"Files dropped here are: "}
+                            <select on:change=move |ev| set_drop_kind.set(DroppedAssetKind::from_value(&event_target_value(&ev)))>
+                                <option value="model">{"Models"}</option>
+                                <option value="texture">{"Textures"}</option>
+                                <option value="heightmap">{"Heightmaps"}</option>
+                            </select>
+                        </label>
+                    </div>
+                    <Show when=move || editing_message.get().is_some()>
+                        <div class="editing-banner">
+                            {"Editing your last message, sending replaces its reply."}
+                            <button on:click=cancel_editing>{"Cancel"}</button>
+                        </div>
+                    </Show>
                     <div class="chat-input">
                         <input
                             type="text"
                             placeholder="Type a message..."
                             node_ref=input_ref
+                            prop:value=message_content
                             on:input=move |ev| {
                                 set_message_content.set(event_target_value(&ev));
                             }
                         />
-                        <button on:click=move |_| send_message(pipeline_store)>{"Send"}</button>
+                        <button
+                            disabled=move || pending.get()
+                            on:click=move |_| send_message(pipeline_store)
+                        >{move || if editing_message.get().is_some() { "Resend" } else { "Send" }}</button>
                     </div>
                 </div>
+                <div
+                    class="pane-divider"
+                    title="Drag to resize, double-click to reset"
+                    on:mousedown=move |ev| {
+                        ev.prevent_default();
+                        set_is_resizing_panes.set(true);
+                    }
+                    on:dblclick=move |_| {
+                        set_chat_pane_ratio.set(DEFAULT_CHAT_PANE_RATIO);
+                        storage::remove_item(CHAT_PANE_RATIO_KEY);
+                    }
+                ></div>
                 <div class="content-preview-pane">
                     <h3>{"Content Preview: "} {move || selected_project.get().map(|p| p.name).unwrap_or_default()}</h3>
                     <ProjectCanvas 
@@ -1856,6 +8458,12 @@ pub fn App() -> impl IntoView {
                         pipeline_store={pipeline_store}
                         is_initialized={is_initialized}
                         set_is_initialized={set_is_initialized} 
+                        show_preview={show_chat}
+                        dark_theme={dark_theme}
+                        scene_version=scene_version
+                        set_scene_version=set_scene_version
+                        selected_component_id=selected_component_id
+                        set_selected_component_id=set_selected_component_id
                     />
                     
                     <div class="editor-tabs">
@@ -1873,6 +8481,10 @@ pub fn App() -> impl IntoView {
                         <ComponentPropertiesEditor
                             pipeline_store={pipeline_store}
                             is_initialized={is_initialized}
+                            project_id=Signal::derive(move || selected_project.get().map(|p| p.id))
+                            scene_version=scene_version
+                            selected_component_id=selected_component_id
+                            set_selected_component_id=set_selected_component_id
                         />
                     </Show>
                     
@@ -1882,12 +8494,226 @@ pub fn App() -> impl IntoView {
                             is_initialized={is_initialized}
                             project_path=Signal::derive(move || selected_project.get().map(|p| p.path))
                             project_id=Signal::derive(move || selected_project.get().map(|p| p.id))
+                            scene_version=scene_version
                         />
                     </Show>
                 </div>
             </section>
-            </Show>
         </main>
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            r#type: "function".to_string(),
+            function: ToolCallFunction { name: name.to_string(), arguments: arguments.to_string() },
+        }
+    }
+
+    /// One valid and one malformed argument string for every registered tool.
+    const TOOL_ARGS: &[(&str, &str, &str)] = &[
+        ("transformObject", r#"{"componentId": "a", "translation": [1, 2, 3]}"#, r#"{"translation": [1, 2, 3]}"#),
+        ("configureWater", r#"{"shallow_color": [0.1, 0.5, 0.6]}"#, r#"{"shallow_color": "teal"}"#),
+        ("spawnWaterPlane", r#"{"position": [0, 0, 0], "width": 10, "length": 20}"#, r#"{"position": [0, 0, 0]}"#),
+        ("removeWaterPlane", r#"{"componentId": "a"}"#, r#"{"componentId": 4}"#),
+        ("configureSky", r#"{"preset": "sunset"}"#, r#"{"horizon_color": [1, 2]}"#),
+        ("configureDayNightCycle", r#"{"enabled": true, "cycleLength": 120}"#, r#"{"enabled": "yes"}"#),
+        ("configureWeather", r#"{"intensity": 0.5}"#, r#"{"intensity": "heavy"}"#),
+        ("configureClouds", r#"{"coverage": 0.4}"#, r#"{"coverage": [0.4]}"#),
+        ("configureAO", r#"{"enabled": true, "radius": 0.5}"#, r#"{"radius": "wide"}"#),
+        ("configureFog", r#"{"color": [0.8, 0.8, 0.9]}"#, r#"{"color": "grey"}"#),
+        ("configurePostProcessing", r#"{"bloom_intensity": 0.3}"#, r#"{"bloom_intensity": true}"#),
+        ("configureColorGrade", r#"{"contrast": 1.1}"#, r#"{"contrast": "high"}"#),
+        ("setAmbientLight", r#"{"intensity": 0.2}"#, r#"{"color": 5}"#),
+        ("configureLandscapeTextures", r#"{"rock_slope_start": 0.3}"#, r#"{"rock_slope_start": "steep"}"#),
+        ("configureParticle", r#"{"componentId": "a", "emission_rate": 20}"#, r#"{"emission_rate": 20}"#),
+        ("configureTrees", r#"{"trunk_height": 4}"#, r#"{"seed": -1}"#),
+        ("spawnForest", r#"{"center": [0, 0, 0], "radius": 30}"#, r#"{"count": "many"}"#),
+        ("spawnModel", r#"{"assetId": "a", "position": [0, 0, 0]}"#, r#"{"position": [0, 0, 0]}"#),
+        ("instanceModel", r#"{"assetId": "a"}"#, r#"{"remove": ["first"]}"#),
+        ("configureLOD", r#"{"distances": [20, 60, 150]}"#, r#"{"distances": 20}"#),
+        ("mirrorObject", r#"{"componentId": "a", "axis": "x"}"#, r#"{"componentId": "a"}"#),
+        ("duplicateObject", r#"{"componentId": "a"}"#, r#"{"offset": [1, 0, 0]}"#),
+        ("deleteObject", r#"{"componentId": "a"}"#, r#"{"force": true}"#),
+        ("setMaterial", r#"{"componentId": "a", "pbrTextureId": "b"}"#, r#"{"componentId": "a"}"#),
+        ("spawnPointLight", r#"{"position": [0, 2, 0]}"#, r#"{"color": [1, 1, 1]}"#),
+        ("spawnDirectionalLight", r#"{"intensity": 1.5}"#, r#"{"direction": "down"}"#),
+        ("spawnCollectable", r#"{"assetId": "a"}"#, r#"{"statId": "b"}"#),
+        ("setWeaponStats", r#"{"componentId": "a", "damage": 10}"#, r#"{"damage": 10}"#),
+        ("configureGrass", r#"{"blade_height": 1.5}"#, r#"{"blade_height": "tall"}"#),
+        ("spawnPrimitive", r#"{"position": [0, 0, 0]}"#, r#"{"scale": [1, 1, 1]}"#),
+        ("spawnNPC", r#"{"assetId": "a"}"#, r#"{"position": [0, 0, 0]}"#),
+        ("spawnTrigger", r#"{"position": [0, 0, 0]}"#, r#"{"name": "door"}"#),
+        ("spawnAudioSource", r#"{"audioId": "a", "position": [0, 0, 0]}"#, r#"{"audioId": "a"}"#),
+        ("setPlayerSpawn", r#"{"position": [0, 0, 0]}"#, r#"{"facing": 90}"#),
+        ("configurePlayerStats", r#"{"health": 100}"#, r#"{"health": "full"}"#),
+        ("addInventoryItem", r#"{"statId": "a", "quantity": 2}"#, r#"{"quantity": -2}"#),
+        ("conformToTerrain", r#"{"componentIds": ["a"]}"#, r#"{"componentIds": "a"}"#),
+        ("renameComponent", r#"{"componentId": "a", "name": "Tower"}"#, r#"{"componentId": "a"}"#),
+        ("groupComponents", r#"{"componentIds": ["a", "b"]}"#, r#"{"name": "Group"}"#),
+        ("ungroupComponents", r#"{"componentId": "a"}"#, r#"{}"#),
+        ("lockComponent", r#"{"componentId": "a", "locked": true}"#, r#"{"locked": true}"#),
+        ("frameScene", r#"{}"#, r#""everything""#),
+        ("measureDistance", r#"{"fromComponentId": "a", "toComponentId": "b"}"#, r#"{"fromComponentId": "a"}"#),
+        ("saveScript", r#"{"filename": "door.rhai", "content": "let x = 1;"}"#, r#"{"filename": "door.rhai"}"#),
+        ("generateHeightmap", r#"{"seed": 7}"#, r#"{"seed": "random"}"#),
+        ("flattenTerrain", r#"{"center": [0, 0], "radius": 10, "height": 2}"#, r#"{"center": [0, 0]}"#),
+    ];
+
+    #[test]
+    fn every_tool_has_argument_cases() {
+        for handler in TOOL_HANDLERS {
+            assert!(TOOL_ARGS.iter().any(|(name, _, _)| *name == handler.name), "no argument cases for {}", handler.name);
+        }
+    }
+
+    #[test]
+    fn tools_accept_valid_arguments() {
+        for (name, valid, _) in TOOL_ARGS {
+            let call = tool_call(name, valid);
+            if let Err(result) = resolve_tool_call(&call) {
+                panic!("{} rejected {}: {:?}", name, valid, result.message);
+            }
+        }
+    }
+
+    #[test]
+    fn tools_reject_malformed_arguments() {
+        for (name, _, malformed) in TOOL_ARGS {
+            match resolve_tool_call(&tool_call(name, malformed)) {
+                Ok(_) => panic!("{} accepted {}", name, malformed),
+                Err(result) => {
+                    assert!(!result.success);
+                    assert!(result.message.unwrap_or_default().starts_with("Invalid arguments"));
+                }
+            }
+        }
+    }
+
+    fn message(id: &str, role: &str) -> ChatMessage {
+        ChatMessage {
+            id: id.to_string(),
+            role: role.to_string(),
+            content: Some(format!("{} message", role)),
+            tool_call_id: None,
+            tool_calls: None,
+            usage: None,
+            created_at: None,
+        }
+    }
+
+    fn ids(messages: &[ChatMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn merge_keeps_optimistic_local_messages() {
+        let remote = vec![message("u1", "user"), message("a1", "assistant")];
+        let local = vec![message("local-note", "system")];
+
+        let merged = merge_messages(remote, &local);
+        assert_eq!(ids(&merged), ["u1", "a1", "local-note"]);
+    }
+
+    #[test]
+    fn merge_after_tool_call_neither_duplicates_nor_drops() {
+        let mut assistant = message("a1", "assistant");
+        assistant.tool_calls = Some(vec![tool_call("spawnPointLight", r#"{"position": [0, 2, 0]}"#)]);
+        let mut result = message("t1", "tool");
+        result.tool_call_id = Some("call_1".to_string());
+
+        // The server already stored the tool-call reply, the status line only exists locally
+        let local = vec![assistant.clone(), message("tool-status-call_1", "system")];
+        let remote = vec![message("u1", "user"), assistant, result];
+
+        let merged = merge_messages(remote.clone(), &local);
+        assert_eq!(ids(&merged), ["u1", "a1", "t1", "tool-status-call_1"]);
+
+        // Refetching and merging again gives the same list
+        assert_eq!(ids(&merge_messages(remote, &local)), ids(&merged));
+    }
+
+    #[test]
+    fn merge_follows_a_reordered_refetch() {
+        let local = vec![message("u1", "user"), message("a1", "assistant"), message("local-note", "system")];
+        let remote = vec![message("a1", "assistant"), message("u1", "user"), message("u2", "user")];
+
+        let merged = merge_messages(remote, &local);
+        assert_eq!(ids(&merged), ["a1", "u1", "u2", "local-note"]);
+    }
+
+    #[test]
+    fn non_square_heightmaps_keep_their_shape() {
+        let (width, height) = (2048u32, 512u32);
+        // A ramp along x, so swapped axes would show up as different samples too
+        let image = image::ImageBuffer::from_fn(width, height, |x, _| image::Luma([(x * 32) as u16]));
+        let mut png_bytes: Vec<u8> = Vec::new();
+        image::DynamicImage::ImageLuma16(image)
+            .write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png)
+            .unwrap();
+
+        let decoded = decode_heightmap_image(&png_bytes).unwrap();
+        assert_eq!(decoded.dimensions(), (2048, 512));
+        assert_eq!(decoded.pixels().count(), 2048 * 512);
+        assert_eq!(decoded.get_pixel(2047, 0).0[0], 2047 * 32);
+
+        let field = HeightField::from_image(&decoded);
+        assert_eq!((field.width, field.height), (2048, 512));
+        assert_eq!(field.heights.len(), 2048 * 512);
+
+        // Exporting and re-importing the field doesn't square or rotate it either
+        let reimported = decode_heightmap_image(&field.to_png().unwrap()).unwrap();
+        assert_eq!(reimported.dimensions(), (2048, 512));
+    }
+
+    #[test]
+    fn unknown_tools_are_errors() {
+        match resolve_tool_call(&tool_call("summonDragon", "{}")) {
+            Ok(_) => panic!("summonDragon resolved to a handler"),
+            Err(result) => {
+                assert!(!result.success);
+                assert_eq!(result.message.as_deref(), Some("Unknown tool summonDragon"));
+            }
+        }
+    }
+
+    #[test]
+    fn deleting_a_point_light_leaves_the_others_alone() {
+        let mut point_lights = vec![
+            new_point_light("first".to_string(), [0.0, 1.0, 0.0], [1.0, 0.0, 0.0], 1.0, 10.0),
+            new_point_light("second".to_string(), [5.0, 1.0, 0.0], [0.0, 0.0, 1.0], 2.0, 20.0),
+        ];
+
+        let removed = remove_point_light(&mut point_lights, "first").expect("first light is removed");
+        assert_eq!(removed.id, "first");
+        assert_eq!(point_lights.len(), 1);
+        assert_eq!(point_lights[0].id, "second");
+        assert_eq!(point_lights[0].position, [5.0, 1.0, 0.0]);
+        assert_eq!(point_lights[0].color, [0.0, 0.0, 1.0]);
+        assert_eq!(point_lights[0].max_distance, 20.0);
+        assert!(remove_point_light(&mut point_lights, "first").is_none());
+    }
+
+    #[derive(Default)]
+    struct Effect {
+        enabled: bool,
+    }
+
+    #[test]
+    fn level_effects_switch_on_when_first_configured_and_then_only_when_asked() {
+        let mut slot = None::<Effect>;
+        assert!(configure_level_effect(&mut slot, None, |e| &mut e.enabled).enabled);
+
+        slot.as_mut().unwrap().enabled = false;
+        assert!(!configure_level_effect(&mut slot, None, |e| &mut e.enabled).enabled);
+        assert!(configure_level_effect(&mut slot, Some(true), |e| &mut e.enabled).enabled);
+
+        let mut slot = None::<Effect>;
+        assert!(!configure_level_effect(&mut slot, Some(false), |e| &mut e.enabled).enabled);
+    }
+}
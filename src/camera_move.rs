@@ -0,0 +1,45 @@
+//! Eased camera moves for framing the scene, so the preview glides to its new view instead of jumping.
+
+use entropy_engine::core::editor::Editor;
+use nalgebra::Point3;
+use std::cell::RefCell;
+
+/// How long a move takes.
+const DURATION_MS: f64 = 600.0;
+
+struct CameraMove {
+    from: Point3<f32>,
+    to: Point3<f32>,
+    start_ms: f64,
+}
+
+thread_local! {
+    // At most one move at a time, a new one starts from wherever the camera is
+    static ACTIVE: RefCell<Option<CameraMove>> = const { RefCell::new(None) };
+}
+
+/// Starts moving the camera to `to`. Does nothing without a camera.
+pub fn start(editor: &Editor, to: Point3<f32>) {
+    let Some(camera) = editor.camera.as_ref() else { return };
+    ACTIVE.with(|active| {
+        *active.borrow_mut() = Some(CameraMove { from: camera.position, to, start_ms: js_sys::Date::now() });
+    });
+}
+
+/// Advances the current move, once per frame.
+pub fn apply(editor: &mut Editor, now_ms: f64) {
+    ACTIVE.with(|active| {
+        let mut active = active.borrow_mut();
+        let Some(camera_move) = active.as_ref() else { return };
+        let Some(camera) = editor.camera.as_mut() else { return };
+
+        let t = (((now_ms - camera_move.start_ms) / DURATION_MS).clamp(0.0, 1.0)) as f32;
+        // Ease in and out
+        let eased = t * t * (3.0 - 2.0 * t);
+        camera.position = camera_move.from + (camera_move.to - camera_move.from) * eased;
+
+        if t >= 1.0 {
+            *active = None;
+        }
+    });
+}
@@ -1,6 +1,7 @@
 use entropy_engine::core::pipeline::ExportPipeline;
 use entropy_engine::helpers::saved_data::{File, LandscapeData, PBRTextureData, StatData, SavedState};
 use leptos::{html, prelude::*};
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 use uuid::Uuid;
@@ -9,6 +10,11 @@ use wasm_bindgen::JsCast;
 use gloo_net::http::Request;
 use leptos::logging::log;
 use leptos::task::spawn_local;
+use serde::Deserialize;
+
+use crate::app::save_project;
+use crate::terrain_edit;
+use crate::components::confirm_dialog::{confirm_action, ConfirmKind};
 
 #[derive(Clone, PartialEq)]
 enum AssetCategory {
@@ -17,6 +23,255 @@ enum AssetCategory {
     PBRTextures,
     Landscapes,
     Stats,
+    AudioClips,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum AssetSort {
+    RecentlyAdded,
+    Name,
+}
+
+/// Common view of the asset types so one search/sort works for every panel.
+trait AssetEntry {
+    fn entry_id(&self) -> &str;
+    fn display_name(&self) -> String;
+}
+
+impl AssetEntry for File {
+    fn entry_id(&self) -> &str {
+        &self.id
+    }
+
+    fn display_name(&self) -> String {
+        self.fileName.clone()
+    }
+}
+
+impl AssetEntry for PBRTextureData {
+    fn entry_id(&self) -> &str {
+        &self.id
+    }
+
+    fn display_name(&self) -> String {
+        self.diff.as_ref().map(|f| f.fileName.clone()).unwrap_or_default()
+    }
+}
+
+impl AssetEntry for LandscapeData {
+    fn entry_id(&self) -> &str {
+        &self.id
+    }
+
+    fn display_name(&self) -> String {
+        self.heightmap.as_ref().map(|f| f.fileName.clone()).unwrap_or_default()
+    }
+}
+
+impl AssetEntry for StatData {
+    fn entry_id(&self) -> &str {
+        &self.id
+    }
+
+    fn display_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// Keeps the assets whose name or id contains `query` (case-insensitive), in the chosen order.
+/// Assets are stored in the order they were added, so "recently added" is that order reversed.
+fn filter_assets<T: AssetEntry>(items: Vec<T>, query: &str, sort: AssetSort) -> Vec<T> {
+    let query = query.trim().to_lowercase();
+    let mut items: Vec<T> = items
+        .into_iter()
+        .filter(|item| {
+            query.is_empty()
+                || item.display_name().to_lowercase().contains(&query)
+                || item.entry_id().to_lowercase().contains(&query)
+        })
+        .collect();
+
+    match sort {
+        AssetSort::RecentlyAdded => items.reverse(),
+        AssetSort::Name => items.sort_by_key(|item| item.display_name().to_lowercase()),
+    }
+
+    items
+}
+
+/// Maps each asset id to the names of the components that reference it, across all levels.
+fn asset_usages(saved_state: &SavedState) -> HashMap<String, Vec<String>> {
+    let mut usages: HashMap<String, Vec<String>> = HashMap::new();
+
+    for level in saved_state.levels.iter().flatten() {
+        for component in level.components.iter().flatten() {
+            let mut referenced = vec![Some(component.asset_id.clone())];
+            if let Some(model) = &component.model_properties {
+                referenced.push(model.pbr_texture_id.clone());
+            }
+            if let Some(collectable) = &component.collectable_properties {
+                referenced.push(collectable.model_id.clone());
+                referenced.push(collectable.stat_id.clone());
+            }
+            if let Some(landscape) = &component.landscape_properties {
+                referenced.push(landscape.primary_texture_id.clone());
+                referenced.push(landscape.rockmap_texture_id.clone());
+                referenced.push(landscape.soil_texture_id.clone());
+                referenced.push(landscape.primary_pbr_texture_id.clone());
+                referenced.push(landscape.rockmap_pbr_texture_id.clone());
+                referenced.push(landscape.soil_pbr_texture_id.clone());
+            }
+            if let Some(npc) = &component.npc_properties {
+                referenced.push(Some(npc.model_id.clone()));
+            }
+            if let Some(player) = &component.player_properties {
+                referenced.push(player.model_id.clone());
+                referenced.push(player.default_weapon_id.clone());
+            }
+            if let Some(audio_source) = &component.audio_source_properties {
+                referenced.push(Some(audio_source.audio_id.clone()));
+            }
+
+            let mut referenced: Vec<String> = referenced.into_iter().flatten().filter(|id| !id.is_empty()).collect();
+            referenced.sort();
+            referenced.dedup();
+
+            for id in referenced {
+                usages.entry(id).or_default().push(component.generic_properties.name.clone());
+            }
+        }
+    }
+
+    usages
+}
+
+/// The asset lists backed by a single uploaded file, the ones the details modal covers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FileAssetKind {
+    Model,
+    Texture,
+    Audio,
+}
+
+impl FileAssetKind {
+    /// Asset type as the server's asset endpoints name it.
+    fn api_type(self) -> &'static str {
+        match self {
+            FileAssetKind::Model => "model",
+            FileAssetKind::Texture => "texture",
+            FileAssetKind::Audio => "audio",
+        }
+    }
+
+    fn files(self, state: &mut SavedState) -> Option<&mut Vec<File>> {
+        match self {
+            FileAssetKind::Model => Some(&mut state.models),
+            FileAssetKind::Texture => state.textures.as_mut(),
+            FileAssetKind::Audio => state.audio.as_mut(),
+        }
+    }
+}
+
+/// What the server knows about an uploaded file beyond what the project stores. Every field is optional,
+/// dimensions only come with textures and triangle counts with models.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetMetadata {
+    file_size: Option<u64>,
+    uploaded_at: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    triangle_count: Option<u32>,
+    /// Where the file is served from, asked for after a rename when the server didn't send it.
+    url: Option<String>,
+}
+
+/// Fetched when an asset's details open, the list itself never needs it.
+async fn fetch_asset_metadata(project_path: &str, kind: FileAssetKind, file_name: &str) -> Result<AssetMetadata, String> {
+    let url = format!(
+        "{}/api/asset-metadata?projectPath={}&type={}&filename={}",
+        get_api_url(),
+        js_sys::encode_uri_component(project_path),
+        kind.api_type(),
+        js_sys::encode_uri_component(file_name),
+    );
+    let response = Request::get(&url).send().await.map_err(|e| e.to_string())?;
+    if !response.ok() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// Renames the file on the server, assets are loaded by file name so the project can't just relabel it.
+/// Returns the file's new url when the server sends one.
+async fn rename_asset_file(project_path: &str, kind: FileAssetKind, file_name: &str, new_file_name: &str) -> Result<Option<String>, String> {
+    #[derive(Deserialize)]
+    struct RenameAssetResponse {
+        url: Option<String>,
+    }
+
+    let url = format!("{}/api/rename-asset", get_api_url());
+    let response = Request::post(&url)
+        .json(&serde_json::json!({
+            "projectPath": project_path,
+            "type": kind.api_type(),
+            "filename": file_name,
+            "newFilename": new_file_name,
+        }))
+        .map_err(|e| e.to_string())?
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.ok() {
+        // Older servers answer with an empty body
+        Ok(response.json::<RenameAssetResponse>().await.ok().and_then(|r| r.url))
+    } else {
+        Err(format!("Rename failed with status {}", response.status()))
+    }
+}
+
+/// The url the renamed file is served from, as the server reports it. Falls back to the file's metadata
+/// for servers whose rename response has no body.
+async fn renamed_file_url(project_path: &str, kind: FileAssetKind, new_file_name: &str, from_rename: Option<String>) -> Result<String, String> {
+    if let Some(url) = from_rename {
+        return Ok(url);
+    }
+    fetch_asset_metadata(project_path, kind, new_file_name).await?
+        .url
+        .ok_or_else(|| "the server didn't report its new url".to_string())
+}
+
+fn format_file_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// An upload timestamp in the browser's locale, or as sent if it doesn't parse.
+fn format_upload_date(timestamp: &str) -> String {
+    let date = js_sys::Date::new(&timestamp.into());
+    if date.get_time().is_nan() {
+        timestamp.to_string()
+    } else {
+        String::from(date.to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED))
+    }
+}
+
+/// Keeps the extension when a new name is typed without one, so a model stays loadable as a model.
+fn with_extension_of(new_name: &str, original: &str) -> String {
+    match original.rsplit_once('.') {
+        Some((_, extension)) if !new_name.contains('.') => format!("{}.{}", new_name, extension),
+        _ => new_name.to_string(),
+    }
 }
 
 fn get_api_url() -> String {
@@ -31,26 +286,14 @@ fn get_api_url() -> String {
     }
 }
 
-async fn save_project_state(project_id: &str, saved_state: &SavedState) -> Result<(), String> {
-    let url = format!("{}/api/projects/{}", get_api_url(), project_id);
-    let body = serde_json::json!({ "savedData": saved_state });
-    
-    Request::patch(&url)
-        .json(&body)
-        .map_err(|e| e.to_string())?
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-        
-    Ok(())
-}
-
 #[component]
 pub fn AssetsBrowser(
     pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
     is_initialized: ReadSignal<bool>,
     project_path: Signal<Option<String>>,
     project_id: Signal<Option<String>>,
+    // Bumped after tool calls change the scene, so usage counts stay current
+    scene_version: ReadSignal<u32>,
 ) -> impl IntoView {
     let (active_category, set_active_category) = signal(AssetCategory::Models);
     
@@ -60,9 +303,25 @@ pub fn AssetsBrowser(
     let (pbr_list, set_pbr_list) = signal::<Vec<PBRTextureData>>(Vec::new());
     let (landscapes_list, set_landscapes_list) = signal::<Vec<LandscapeData>>(Vec::new());
     let (stats_list, set_stats_list) = signal::<Vec<StatData>>(Vec::new());
+    let (audio_list, set_audio_list) = signal::<Vec<File>>(Vec::new());
+    let (usages, set_usages) = signal::<HashMap<String, Vec<String>>>(HashMap::new());
+    // Asset shown in the details modal
+    let (details, set_details) = signal(None::<(FileAssetKind, File)>);
+
+    // Search and sort apply to whichever category is active
+    let (search, set_search) = signal(String::new());
+    let (sort, set_sort) = signal(AssetSort::RecentlyAdded);
+
+    let filtered_models = Signal::derive(move || filter_assets(models_list.get(), &search.get(), sort.get()));
+    let filtered_textures = Signal::derive(move || filter_assets(textures_list.get(), &search.get(), sort.get()));
+    let filtered_pbr = Signal::derive(move || filter_assets(pbr_list.get(), &search.get(), sort.get()));
+    let filtered_landscapes = Signal::derive(move || filter_assets(landscapes_list.get(), &search.get(), sort.get()));
+    let filtered_stats = Signal::derive(move || filter_assets(stats_list.get(), &search.get(), sort.get()));
+    let filtered_audio = Signal::derive(move || filter_assets(audio_list.get(), &search.get(), sort.get()));
 
     // Sync from Pipeline/SavedState
     create_effect(move |_| {
+        scene_version.get();
         if is_initialized.get() {
             if let Some(pipeline) = pipeline_store.get() {
                 if let Some(pipeline_arc) = pipeline.as_ref() {
@@ -74,6 +333,8 @@ pub fn AssetsBrowser(
                             set_pbr_list.set(saved_state.pbr_textures.clone().unwrap_or_default());
                             set_landscapes_list.set(saved_state.landscapes.clone().unwrap_or_default());
                             set_stats_list.set(saved_state.stats.clone().unwrap_or_default());
+                            set_audio_list.set(saved_state.audio.clone().unwrap_or_default());
+                            set_usages.set(asset_usages(saved_state));
                         }
                     }
                 }
@@ -95,13 +356,15 @@ pub fn AssetsBrowser(
                         set_pbr_list.set(saved_state.pbr_textures.clone().unwrap_or_default());
                         set_landscapes_list.set(saved_state.landscapes.clone().unwrap_or_default());
                         set_stats_list.set(saved_state.stats.clone().unwrap_or_default());
+                        set_audio_list.set(saved_state.audio.clone().unwrap_or_default());
+                        set_usages.set(asset_usages(saved_state));
                         
                         // Save to backend
                         let pid = project_id.get_untracked().unwrap_or_default();
                         let state_clone = saved_state.clone();
                         if !pid.is_empty() {
                             spawn_local(async move {
-                                let _ = save_project_state(&pid, &state_clone).await;
+                                let _ = save_project(&pid, &state_clone).await;
                             });
                         }
                     }
@@ -143,58 +406,305 @@ pub fn AssetsBrowser(
                 >
                     {"Stats"}
                 </button>
+                <button 
+                    class:active=move || active_category.get() == AssetCategory::AudioClips
+                    on:click=move |_| set_active_category.set(AssetCategory::AudioClips)
+                >
+                    {"Audio"}
+                </button>
+            </div>
+
+            <div class="assets-search">
+                <input
+                    type="search"
+                    placeholder="Search by name or id..."
+                    prop:value=search
+                    on:input=move |ev| set_search.set(event_target_value(&ev))
+                />
+                <select on:change=move |ev| set_sort.set(match event_target_value(&ev).as_str() {
+                    "name" => AssetSort::Name,
+                    _ => AssetSort::RecentlyAdded,
+                })>
+                    <option value="recent">{"Recently added"}</option>
+                    <option value="name">{"Name"}</option>
+                </select>
             </div>
 
             <div class="assets-content">
                 {move || match active_category.get() {
                     AssetCategory::Models => view! {
                         <ModelsPanel 
-                            list=models_list 
+                            on_open=Callback::new(move |file| set_details.set(Some((FileAssetKind::Model, file))))
+                            list=filtered_models 
                             project_path=project_path 
-                            on_add=update_saved_state.clone() 
+                            usages=usages
+                            on_update=update_saved_state.clone() 
                         />
                     }.into_view().into_any(),
                     AssetCategory::Textures => view! {
                         <TexturesPanel 
-                            list=textures_list 
+                            on_open=Callback::new(move |file| set_details.set(Some((FileAssetKind::Texture, file))))
+                            list=filtered_textures 
                             project_path=project_path 
-                            on_add=update_saved_state.clone() 
+                            usages=usages
+                            on_update=update_saved_state.clone() 
                         />
                     }.into_view().into_any(),
                     AssetCategory::PBRTextures => view! {
                         <PBRTexturesPanel 
-                            list=pbr_list 
+                            list=filtered_pbr 
                             project_path=project_path 
-                            on_add=update_saved_state.clone() 
+                            usages=usages
+                            on_update=update_saved_state.clone() 
                         />
                     }.into_view().into_any(),
                     AssetCategory::Landscapes => view! {
                         <LandscapesPanel 
-                            list=landscapes_list 
+                            list=filtered_landscapes 
                             project_path=project_path 
-                            on_add=update_saved_state.clone() 
+                            usages=usages
+                            on_update=update_saved_state.clone() 
                         />
                     }.into_view().into_any(),
                     AssetCategory::Stats => view! {
                         <StatsPanel 
-                            list=stats_list 
-                            on_add=update_saved_state.clone() 
+                            list=filtered_stats 
+                            usages=usages
+                            on_update=update_saved_state.clone() 
+                        />
+                    }.into_view().into_any(),
+                    AssetCategory::AudioClips => view! {
+                        <AudioPanel 
+                            on_open=Callback::new(move |file| set_details.set(Some((FileAssetKind::Audio, file))))
+                            list=filtered_audio 
+                            project_path=project_path 
+                            usages=usages
+                            on_update=update_saved_state.clone() 
                         />
                     }.into_view().into_any(),
                 }}
             </div>
+
+            <AssetDetails
+                asset=details
+                set_asset=set_details
+                project_path=project_path
+                usages=usages
+                on_update=update_saved_state.clone()
+            />
         </div>
     }
 }
 
+/// Usage count (with the referencing component names on hover) and a delete button that
+/// asks for confirmation first, naming the components still using the asset.
+#[component]
+fn AssetUsage<D>(
+    asset_id: String,
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_delete: D
+) -> impl IntoView 
+where D: Fn() + 'static
+{
+    let asset_id = StoredValue::new(asset_id);
+    let used_by = move || usages.with(|u| u.get(&asset_id.get_value()).cloned().unwrap_or_default());
+
+    let on_delete = Rc::new(on_delete);
+    let on_click = move |_| {
+        let used_by = used_by();
+        let description = if used_by.is_empty() {
+            "It will be removed from the project. No components use it.".to_string()
+        } else {
+            format!(
+                "It's used by {} component(s): {}. Deleting it will break them.",
+                used_by.len(),
+                used_by.join(", ")
+            )
+        };
+        let on_delete = on_delete.clone();
+        confirm_action(ConfirmKind::DeleteAsset, "Delete this asset?", description, "Delete", move || on_delete());
+    };
+
+    view! {
+        <span
+            class="asset-usage"
+            class:unused=move || used_by().is_empty()
+            title=move || used_by().join(", ")
+        >
+            {move || format!("Used by {}", used_by().len())}
+        </span>
+        <button class="delete-btn" on:click=on_click>{"Delete"}</button>
+    }
+}
+
+/// Everything known about one uploaded file: what the project stores, what the server reports and
+/// which components use it, with rename and delete.
+#[component]
+fn AssetDetails<F>(
+    asset: ReadSignal<Option<(FileAssetKind, File)>>,
+    set_asset: WriteSignal<Option<(FileAssetKind, File)>>,
+    project_path: Signal<Option<String>>,
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
+) -> impl IntoView
+where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
+{
+    // None while loading
+    let (metadata, set_metadata) = signal(None::<Result<AssetMetadata, String>>);
+    let (new_name, set_new_name) = signal(String::new());
+    let (action_error, set_action_error) = signal(None::<String>);
+
+    create_effect(move |_| {
+        let Some((kind, file)) = asset.get() else { return };
+        set_metadata.set(None);
+        set_new_name.set(file.fileName.clone());
+        set_action_error.set(None);
+
+        let project_path_val = project_path.get_untracked().unwrap_or_default();
+        spawn_local(async move {
+            let result = fetch_asset_metadata(&project_path_val, kind, &file.fileName).await;
+            // Another asset may have been opened while this one loaded
+            if asset.with_untracked(|open| open.as_ref().is_some_and(|(_, open)| open.id == file.id)) {
+                set_metadata.set(Some(result));
+            }
+        });
+    });
+
+    let close = move || set_asset.set(None);
+
+    let on_rename = {
+        let on_update = on_update.clone();
+        move |_| {
+            let Some((kind, file)) = asset.get_untracked() else { return };
+            let name = new_name.get_untracked().trim().to_string();
+            if name.is_empty() || name == file.fileName {
+                return;
+            }
+            let name = with_extension_of(&name, &file.fileName);
+            let project_path_val = project_path.get_untracked().unwrap_or_default();
+            let on_update = on_update.clone();
+            spawn_local(async move {
+                match rename_asset_file(&project_path_val, kind, &file.fileName, &name).await {
+                    Ok(url) => {
+                        // The file has moved either way, so the name is updated even if the url can't be found
+                        let url = renamed_file_url(&project_path_val, kind, &name, url).await;
+                        let id = file.id.clone();
+                        let (new_file_name, new_url) = (name.clone(), url.clone().ok());
+                        on_update(Box::new(move |state: &mut SavedState| {
+                            if let Some(entry) = kind.files(state).and_then(|files| files.iter_mut().find(|f| f.id == id)) {
+                                entry.fileName = new_file_name;
+                                if let Some(new_url) = new_url {
+                                    entry.cloudfrontUrl = new_url;
+                                }
+                            }
+                        }));
+                        let cloudfront_url = url.clone().unwrap_or_else(|_| file.cloudfrontUrl.clone());
+                        set_asset.set(Some((kind, File { fileName: name, cloudfrontUrl: cloudfront_url, ..file })));
+                        // After reopening the asset, which clears the modal's error
+                        if let Err(e) = url {
+                            set_action_error.set(Some(format!("Renamed, but {}. Reload the project to pick it up", e)));
+                        }
+                    }
+                    Err(e) => set_action_error.set(Some(e)),
+                }
+            });
+        }
+    };
+
+    let on_delete = move |_| {
+        let Some((kind, file)) = asset.get_untracked() else { return };
+        let used_by = usages.with_untracked(|u| u.get(&file.id).cloned().unwrap_or_default());
+        let description = if used_by.is_empty() {
+            "It will be removed from the project. No components use it.".to_string()
+        } else {
+            format!(
+                "It's used by {} component(s): {}. Deleting it will break them.",
+                used_by.len(),
+                used_by.join(", ")
+            )
+        };
+        let on_update = on_update.clone();
+        confirm_action(ConfirmKind::DeleteAsset, "Delete this asset?", description, "Delete", move || {
+            let id = file.id.clone();
+            on_update(Box::new(move |state: &mut SavedState| {
+                if let Some(files) = kind.files(state) {
+                    files.retain(|f| f.id != id);
+                }
+            }));
+            set_asset.set(None);
+        });
+    };
+
+    // One metadata row, "Loading..." until the server answers
+    let metadata_text = move |field: fn(&AssetMetadata) -> Option<String>| {
+        move || match metadata.get() {
+            None => "Loading...".to_string(),
+            Some(Err(_)) => "Unavailable".to_string(),
+            Some(Ok(metadata)) => field(&metadata).unwrap_or_else(|| "Unknown".to_string()),
+        }
+    };
+
+    view! {
+        <Show when=move || asset.with(|a| a.is_some())>
+            <div class="modal-backdrop" on:click=move |_| close()>
+                <div class="modal asset-details" on:click=|ev| ev.stop_propagation()>
+                    <h3>{move || asset.with(|a| a.as_ref().map(|(_, f)| f.fileName.clone()).unwrap_or_default())}</h3>
+                    <dl class="asset-details-list">
+                        <dt>{"Id"}</dt>
+                        <dd>{move || asset.with(|a| a.as_ref().map(|(_, f)| f.id.clone()).unwrap_or_default())}</dd>
+                        <Show when=move || asset.with(|a| matches!(a, Some((FileAssetKind::Texture, _))))>
+                            <dt>{"Dimensions"}</dt>
+                            <dd>{metadata_text(|m| Some(format!("{} × {}", m.width?, m.height?)))}</dd>
+                        </Show>
+                        <Show when=move || asset.with(|a| matches!(a, Some((FileAssetKind::Model, _))))>
+                            <dt>{"Triangles"}</dt>
+                            <dd>{metadata_text(|m| m.triangle_count.map(|count| count.to_string()))}</dd>
+                        </Show>
+                        <dt>{"File size"}</dt>
+                        <dd>{metadata_text(|m| m.file_size.map(format_file_size))}</dd>
+                        <dt>{"Uploaded"}</dt>
+                        <dd>{metadata_text(|m| m.uploaded_at.as_deref().map(format_upload_date))}</dd>
+                        <dt>{"Used by"}</dt>
+                        <dd>
+                            {move || {
+                                let used_by = asset.with(|a| a.as_ref().and_then(|(_, f)| usages.with(|u| u.get(&f.id).cloned())))
+                                    .unwrap_or_default();
+                                if used_by.is_empty() { "No components".to_string() } else { used_by.join(", ") }
+                            }}
+                        </dd>
+                    </dl>
+                    <div class="asset-rename">
+                        <input
+                            type="text"
+                            prop:value=new_name
+                            on:input=move |ev| set_new_name.set(event_target_value(&ev))
+                        />
+                        <button on:click=on_rename.clone()>{"Rename"}</button>
+                    </div>
+                    {move || action_error.get().map(|e| view! { <p class="error-text">{e}</p> })}
+                    <div class="modal-actions">
+                        <button class="delete-btn" on:click=on_delete.clone()>{"Delete"}</button>
+                        <button on:click=move |_| close()>{"Close"}</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
+
 #[component]
 fn ModelsPanel<F>(
-    list: ReadSignal<Vec<File>>,
+    list: Signal<Vec<File>>,
+    // Opens the asset's details when its name is clicked
+    on_open: Callback<File>,
     project_path: Signal<Option<String>>,
-    on_add: F
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
 ) -> impl IntoView 
 where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 {
+    // Deleting goes through the same SavedState update as adding
+    let on_remove = on_update.clone();
     let file_input_ref = NodeRef::<html::Input>::new();
 
     let on_upload = move |_| {
@@ -216,7 +726,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     form_data.append_with_str("filename", &file_name).unwrap();
                     form_data.append_with_blob("file", &file).unwrap();
 
-                    let on_add = on_add.clone();
+                    let on_update = on_update.clone();
                     
                     spawn_local(async move {
                          let url = format!("{}/api/upload-model", get_api_url());
@@ -235,7 +745,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                                  normalFilePath: "".to_string(),
                              };
                              
-                             on_add(Box::new(move |state: &mut SavedState| {
+                             on_update(Box::new(move |state: &mut SavedState| {
                                  state.models.push(new_file);
                              }));
                          } else {
@@ -254,9 +764,24 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     each=move || list.get()
                     key=|item| item.id.clone()
                     children=move |item| {
+                        let id = item.id.clone();
+                        let on_remove = on_remove.clone();
+                        let file = item.clone();
                         view! {
                             <div class="asset-item">
-                                <span class="asset-name">{item.fileName}</span>
+                                <button class="asset-name" title="Show details" on:click=move |_| on_open.run(file.clone())>
+                                    {item.fileName}
+                                </button>
+                                <AssetUsage
+                                    asset_id=item.id
+                                    usages=usages
+                                    on_delete=move || {
+                                        let id = id.clone();
+                                        on_remove(Box::new(move |state: &mut SavedState| {
+                                            state.models.retain(|m| m.id != id);
+                                        }));
+                                    }
+                                />
                             </div>
                         }
                     }
@@ -277,12 +802,17 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 
 #[component]
 fn TexturesPanel<F>(
-    list: ReadSignal<Vec<File>>,
+    list: Signal<Vec<File>>,
+    // Opens the asset's details when its name is clicked
+    on_open: Callback<File>,
     project_path: Signal<Option<String>>,
-    on_add: F
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
 ) -> impl IntoView 
 where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 {
+    // Deleting goes through the same SavedState update as adding
+    let on_remove = on_update.clone();
     let file_input_ref = NodeRef::<html::Input>::new();
 
     let on_upload = move |_| {
@@ -301,7 +831,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     form_data.append_with_str("filename", &file_name).unwrap();
                     form_data.append_with_blob("file", &file).unwrap();
 
-                    let on_add = on_add.clone();
+                    let on_update = on_update.clone();
                     
                     spawn_local(async move {
                          let url = format!("{}/api/upload-texture", get_api_url());
@@ -319,7 +849,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                                  normalFilePath: "".to_string(),
                              };
                              
-                             on_add(Box::new(move |state: &mut SavedState| {
+                             on_update(Box::new(move |state: &mut SavedState| {
                                  if let Some(textures) = state.textures.as_mut() {
                                      textures.push(new_file);
                                  } else {
@@ -340,9 +870,26 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     each=move || list.get()
                     key=|item| item.id.clone()
                     children=move |item| {
+                        let id = item.id.clone();
+                        let on_remove = on_remove.clone();
+                        let file = item.clone();
                         view! {
                             <div class="asset-item">
-                                <span class="asset-name">{item.fileName}</span>
+                                <button class="asset-name" title="Show details" on:click=move |_| on_open.run(file.clone())>
+                                    {item.fileName}
+                                </button>
+                                <AssetUsage
+                                    asset_id=item.id
+                                    usages=usages
+                                    on_delete=move || {
+                                        let id = id.clone();
+                                        on_remove(Box::new(move |state: &mut SavedState| {
+                                            if let Some(textures) = state.textures.as_mut() {
+                                                textures.retain(|t| t.id != id);
+                                            }
+                                        }));
+                                    }
+                                />
                             </div>
                         }
                     }
@@ -361,14 +908,154 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
     }
 }
 
+/// Audio formats every major browser can decode.
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "ogg", "wav"];
+
+#[component]
+fn AudioPanel<F>(
+    list: Signal<Vec<File>>,
+    // Opens the asset's details when its name is clicked
+    on_open: Callback<File>,
+    project_path: Signal<Option<String>>,
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
+) -> impl IntoView 
+where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
+{
+    // Deleting goes through the same SavedState update as adding
+    let on_remove = on_update.clone();
+    let file_input_ref = NodeRef::<html::Input>::new();
+    let (upload_error, set_upload_error) = signal::<Option<String>>(None);
+
+    let on_upload = move |_| {
+        let input = file_input_ref.get();
+        if let Some(input) = input {
+            if let Some(files) = input.files() {
+                if let Some(file) = files.get(0) {
+                    let project_path_val = project_path.get().unwrap_or_default();
+                    if project_path_val.is_empty() { return; }
+
+                    let file_name = file.name();
+                    let extension = file_name.rsplit('.').next().unwrap_or_default().to_lowercase();
+                    if !AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+                        set_upload_error.set(Some(format!("{} isn't supported, use .mp3, .ogg or .wav", file_name)));
+                        return;
+                    }
+                    set_upload_error.set(None);
+
+                    let file_name_clone = file_name.clone();
+                    
+                    let form_data = FormData::new().unwrap();
+                    form_data.append_with_str("projectPath", &project_path_val).unwrap();
+                    form_data.append_with_str("filename", &file_name).unwrap();
+                    form_data.append_with_blob("file", &file).unwrap();
+
+                    let on_update = on_update.clone();
+                    
+                    spawn_local(async move {
+                         #[derive(Deserialize)]
+                         struct UploadAudioResponse {
+                             url: Option<String>,
+                         }
+
+                         let url = format!("{}/api/upload-audio", get_api_url());
+                         let res = Request::post(&url)
+                            .body(form_data)
+                            .unwrap()
+                            .send()
+                            .await;
+
+                         match res {
+                             Ok(res) if res.ok() => {
+                                 // The url is only used for previews, older servers don't send it
+                                 let uploaded_url = res.json::<UploadAudioResponse>().await.ok().and_then(|r| r.url);
+                                 let new_file = File {
+                                     id: Uuid::new_v4().to_string(),
+                                     fileName: file_name_clone,
+                                     cloudfrontUrl: uploaded_url.unwrap_or_default(),
+                                     normalFilePath: "".to_string(),
+                                 };
+                                 
+                                 on_update(Box::new(move |state: &mut SavedState| {
+                                     if let Some(audio) = state.audio.as_mut() {
+                                         audio.push(new_file);
+                                     } else {
+                                         state.audio = Some(vec![new_file]);
+                                     }
+                                 }));
+                             }
+                             Ok(res) => set_upload_error.set(Some(format!("Upload failed with status {}", res.status()))),
+                             Err(e) => set_upload_error.set(Some(format!("Upload failed: {}", e))),
+                         }
+                    });
+                }
+            }
+        }
+    };
+
+    view! {
+        <div class="asset-panel">
+            <div class="asset-list">
+                <For
+                    each=move || list.get()
+                    key=|item| item.id.clone()
+                    children=move |item| {
+                        let id = item.id.clone();
+                        let on_remove = on_remove.clone();
+                        let preview_url = item.cloudfrontUrl.clone();
+                        let file = item.clone();
+                        view! {
+                            <div class="asset-item">
+                                <button class="asset-name" title="Show details" on:click=move |_| on_open.run(file.clone())>
+                                    {item.fileName}
+                                </button>
+                                {(!preview_url.is_empty()).then(|| view! {
+                                    <audio class="audio-preview" controls=true preload="none" src=preview_url></audio>
+                                })}
+                                <AssetUsage
+                                    asset_id=item.id
+                                    usages=usages
+                                    on_delete=move || {
+                                        let id = id.clone();
+                                        on_remove(Box::new(move |state: &mut SavedState| {
+                                            if let Some(audio) = state.audio.as_mut() {
+                                                audio.retain(|a| a.id != id);
+                                            }
+                                        }));
+                                    }
+                                />
+                            </div>
+                        }
+                    }
+                />
+            </div>
+            
+             <div class="add-asset-form">
+                <h4>{"Add Audio Clip"}</h4>
+                <div class="form-group">
+                    <label>{"Select File:"}</label>
+                    <input type="file" node_ref=file_input_ref accept=".mp3,.ogg,.wav" />
+                </div>
+                <Show when=move || upload_error.get().is_some()>
+                    <p class="error-text">{move || upload_error.get().unwrap_or_default()}</p>
+                </Show>
+                <button class="add-btn" on:click=on_upload>{"Add"}</button>
+            </div>
+        </div>
+    }
+}
+
 #[component]
 fn PBRTexturesPanel<F>(
-    list: ReadSignal<Vec<PBRTextureData>>,
+    list: Signal<Vec<PBRTextureData>>,
     project_path: Signal<Option<String>>,
-    on_add: F
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
 ) -> impl IntoView 
 where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 {
+    // Deleting goes through the same SavedState update as adding
+    let on_remove = on_update.clone();
     let diff_ref = NodeRef::<html::Input>::new();
     let norm_ref = NodeRef::<html::Input>::new();
     let rough_ref = NodeRef::<html::Input>::new();
@@ -379,7 +1066,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
         let project_path_val = project_path.get().unwrap_or_default();
         if project_path_val.is_empty() { return; }
 
-        let on_add = on_add.clone();
+        let on_update = on_update.clone();
         let id = Uuid::new_v4().to_string();
         
         // Helper to upload one file
@@ -429,7 +1116,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     ..Default::default()
                 };
                 
-                 on_add(Box::new(move |state: &mut SavedState| {
+                 on_update(Box::new(move |state: &mut SavedState| {
                      if let Some(pbrs) = state.pbr_textures.as_mut() {
                          pbrs.push(pbr_data);
                      } else {
@@ -447,10 +1134,24 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     each=move || list.get()
                     key=|item| item.id.clone()
                     children=move |item| {
+                        let id = item.id.clone();
+                        let on_remove = on_remove.clone();
                         view! {
                             <div class="asset-item">
                                 <span class="asset-name">{"PBR Set"}</span>
-                                <span class="asset-id">{item.id}</span>
+                                <span class="asset-id">{item.id.clone()}</span>
+                                <AssetUsage
+                                    asset_id=item.id
+                                    usages=usages
+                                    on_delete=move || {
+                                        let id = id.clone();
+                                        on_remove(Box::new(move |state: &mut SavedState| {
+                                            if let Some(pbrs) = state.pbr_textures.as_mut() {
+                                                pbrs.retain(|p| p.id != id);
+                                            }
+                                        }));
+                                    }
+                                />
                             </div>
                         }
                     }
@@ -472,21 +1173,42 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 
 #[component]
 fn LandscapesPanel<F>(
-    list: ReadSignal<Vec<LandscapeData>>,
+    list: Signal<Vec<LandscapeData>>,
     project_path: Signal<Option<String>>,
-    on_add: F
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
 ) -> impl IntoView 
 where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 {
+    // Deleting goes through the same SavedState update as adding
+    let on_remove = on_update.clone();
     let height_ref = NodeRef::<html::Input>::new();
     let rock_ref = NodeRef::<html::Input>::new();
     let soil_ref = NodeRef::<html::Input>::new();
+    let (export_error, set_export_error) = signal(None::<String>);
+
+    // The full-resolution heights the preview mesh is built from, in memory once generated or sculpted
+    let export_heightmap = move |info: terrain_edit::LandscapeHeightmapInfo| {
+        let project_path_val = project_path.get_untracked().unwrap_or_default();
+        set_export_error.set(None);
+        spawn_local(async move {
+            let result = match terrain_edit::load(&project_path_val, &info).await {
+                Ok(field) => field.to_png().and_then(|png_bytes| {
+                    crate::download::download_bytes(&format!("{}_heightmap.png", info.asset_id), "image/png", &png_bytes)
+                }),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                set_export_error.set(Some(e));
+            }
+        });
+    };
 
     let on_upload = move |_| {
         let project_path_val = project_path.get().unwrap_or_default();
         if project_path_val.is_empty() { return; }
 
-        let on_add = on_add.clone();
+        let on_update = on_update.clone();
         let landscape_id = Uuid::new_v4().to_string();
         let landscape_id_clone = landscape_id.clone();
         
@@ -536,7 +1258,7 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     soil: soil,
                 };
                 
-                 on_add(Box::new(move |state: &mut SavedState| {
+                 on_update(Box::new(move |state: &mut SavedState| {
                      if let Some(lands) = state.landscapes.as_mut() {
                          lands.push(l_data);
                      } else {
@@ -554,17 +1276,41 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     each=move || list.get()
                     key=|item| item.id.clone()
                     children=move |item| {
+                        let id = item.id.clone();
+                        let on_remove = on_remove.clone();
+                        // Position doesn't matter for loading, only which file to read
+                        let heightmap_info = item.heightmap.as_ref().map(|f| terrain_edit::LandscapeHeightmapInfo {
+                            position: [0.0, 0.0, 0.0],
+                            asset_id: item.id.clone(),
+                            file_name: f.fileName.clone(),
+                        });
                         view! {
                             <div class="asset-item">
-                                <span class="asset-name">{item.id}</span>
+                                <span class="asset-name">{item.id.clone()}</span>
                                 <span class="asset-detail">
                                     {item.heightmap.map(|f| f.fileName).unwrap_or_else(|| "No Heightmap".to_string())}
                                 </span>
+                                {heightmap_info.map(|info| view! {
+                                    <button on:click=move |_| export_heightmap(info.clone())>{"Export Heightmap"}</button>
+                                })}
+                                <AssetUsage
+                                    asset_id=item.id
+                                    usages=usages
+                                    on_delete=move || {
+                                        let id = id.clone();
+                                        on_remove(Box::new(move |state: &mut SavedState| {
+                                            if let Some(lands) = state.landscapes.as_mut() {
+                                                lands.retain(|l| l.id != id);
+                                            }
+                                        }));
+                                    }
+                                />
                             </div>
                         }
                     }
                 />
             </div>
+            {move || export_error.get().map(|e| view! { <p class="error-text">{e}</p> })}
             
             <div class="add-asset-form">
                 <h4>{"Add Landscape"}</h4>
@@ -579,11 +1325,14 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 
 #[component]
 fn StatsPanel<F>(
-    list: ReadSignal<Vec<StatData>>,
-    on_add: F
+    list: Signal<Vec<StatData>>,
+    usages: ReadSignal<HashMap<String, Vec<String>>>,
+    on_update: F
 ) -> impl IntoView 
 where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
 {
+    // Deleting goes through the same SavedState update as adding
+    let on_remove = on_update.clone();
     let (name, set_name) = signal(String::new());
     // Simplified stat creation
     let on_click = move |_| {
@@ -599,8 +1348,8 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
             weight: None,
         };
         
-        let on_add = on_add.clone();
-        on_add(Box::new(move |state: &mut SavedState| {
+        let on_update = on_update.clone();
+        on_update(Box::new(move |state: &mut SavedState| {
              if let Some(stats) = state.stats.as_mut() {
                  stats.push(new_stat);
              } else {
@@ -617,10 +1366,24 @@ where F: Fn(Box<dyn FnOnce(&mut SavedState)>) + Clone + 'static
                     each=move || list.get()
                     key=|item| item.id.clone()
                     children=move |item| {
+                        let id = item.id.clone();
+                        let on_remove = on_remove.clone();
                         view! {
                             <div class="asset-item">
                                 <span class="asset-name">{item.name}</span>
-                                <span class="asset-id">{item.id}</span>
+                                <span class="asset-id">{item.id.clone()}</span>
+                                <AssetUsage
+                                    asset_id=item.id
+                                    usages=usages
+                                    on_delete=move || {
+                                        let id = id.clone();
+                                        on_remove(Box::new(move |state: &mut SavedState| {
+                                            if let Some(stats) = state.stats.as_mut() {
+                                                stats.retain(|s| s.id != id);
+                                            }
+                                        }));
+                                    }
+                                />
                             </div>
                         }
                     }
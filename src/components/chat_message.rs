@@ -0,0 +1,175 @@
+use leptos::logging::log;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use std::time::Duration;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::app::ChatMessage;
+
+/// How long the "Copied" confirmation stays visible.
+const COPIED_FEEDBACK_MS: u64 = 1500;
+
+#[derive(Clone, Debug, PartialEq)]
+enum MessageSegment {
+    Text(String),
+    Code { language: String, code: String },
+}
+
+/// Splits raw message content on ``` fences so code blocks can be rendered (and copied) on their own.
+fn split_code_blocks(content: &str) -> Vec<MessageSegment> {
+    let mut segments = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("```") {
+        if start > 0 {
+            segments.push(MessageSegment::Text(rest[..start].to_string()));
+        }
+
+        let block_start = &rest[start + 3..];
+        match block_start.find("```") {
+            Some(end) => {
+                let block = &block_start[..end];
+                let (language, code) = match block.split_once('\n') {
+                    Some((language, code)) => (language.trim().to_string(), code.to_string()),
+                    None => (String::new(), block.to_string()),
+                };
+                segments.push(MessageSegment::Code { language, code });
+                rest = &block_start[end + 3..];
+            }
+            None => {
+                // Unterminated fence, keep the remainder as plain text
+                segments.push(MessageSegment::Text(rest[start..].to_string()));
+                rest = "";
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        segments.push(MessageSegment::Text(rest.to_string()));
+    }
+
+    segments
+}
+
+fn copy_to_clipboard(text: String, set_copied: WriteSignal<bool>) {
+    let clipboard = web_sys::window().unwrap().navigator().clipboard();
+    spawn_local(async move {
+        if JsFuture::from(clipboard.write_text(&text)).await.is_ok() {
+            set_copied.set(true);
+            set_timeout(move || set_copied.set(false), Duration::from_millis(COPIED_FEEDBACK_MS));
+        } else {
+            log!("Couldn't write to clipboard");
+        }
+    });
+}
+
+#[component]
+pub fn ChatMessageItem(
+    message: ChatMessage,
+    // Only passed for the latest assistant reply
+    #[prop(default = None)]
+    on_regenerate: Option<Callback<()>>,
+    // Only passed for the latest user message
+    #[prop(default = None)]
+    on_edit: Option<Callback<()>>,
+    // True while a reply is pending, so the message actions can't start another request
+    #[prop(optional, into)]
+    actions_disabled: Signal<bool>,
+    // Set while the message matches the chat search, and while it's the match being jumped to
+    #[prop(optional, into)]
+    search_match: Signal<bool>,
+    #[prop(optional, into)]
+    search_current: Signal<bool>,
+) -> impl IntoView {
+    let content = message.content.clone().unwrap_or_default();
+    let (copied, set_copied) = signal(false);
+    let raw_content = content.clone();
+
+    let item_ref = NodeRef::<leptos::html::Div>::new();
+    create_effect(move |_| {
+        if search_current.get() {
+            if let Some(item) = item_ref.get() {
+                item.scroll_into_view();
+            }
+        }
+    });
+
+    view! {
+        <div
+            class="chat-message"
+            class:search-match=move || search_match.get()
+            class:search-current=move || search_current.get()
+            node_ref=item_ref
+        >
+            <div class="chat-message-header">
+                <strong>{message.role.clone()}":"</strong>
+                <button
+                    class="copy-btn"
+                    on:click=move |_| copy_to_clipboard(raw_content.clone(), set_copied)
+                >
+                    {move || if copied.get() { "Copied" } else { "Copy" }}
+                </button>
+                {on_edit.map(|on_edit| view! {
+                    <button
+                        class="copy-btn"
+                        title="Edit and resend"
+                        disabled=move || actions_disabled.get()
+                        on:click=move |_| on_edit.run(())
+                    >
+                        {"Edit"}
+                    </button>
+                })}
+                {on_regenerate.map(|on_regenerate| view! {
+                    <button
+                        class="copy-btn"
+                        title="Discard this reply and ask again"
+                        disabled=move || actions_disabled.get()
+                        on:click=move |_| on_regenerate.run(())
+                    >
+                        {"Regenerate"}
+                    </button>
+                })}
+            </div>
+            {split_code_blocks(&content)
+                .into_iter()
+                .map(|segment| match segment {
+                    MessageSegment::Text(text) => view! {
+                        <span class="message-text">{text}</span>
+                    }.into_view().into_any(),
+                    MessageSegment::Code { language, code } => view! {
+                        <CodeBlock language=language code=code />
+                    }.into_view().into_any(),
+                })
+                .collect_view()}
+            {message.usage.map(|usage| view! {
+                <small class="message-usage">
+                    {format!(
+                        "{} tokens ({} prompt, {} completion)",
+                        usage.total(), usage.prompt_tokens, usage.completion_tokens
+                    )}
+                </small>
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn CodeBlock(language: String, code: String) -> impl IntoView {
+    let (copied, set_copied) = signal(false);
+    let raw_code = code.clone();
+
+    view! {
+        <div class="code-block">
+            <div class="code-block-header">
+                <small>{language}</small>
+                <button
+                    class="copy-btn"
+                    on:click=move |_| copy_to_clipboard(raw_code.clone(), set_copied)
+                >
+                    {move || if copied.get() { "Copied" } else { "Copy code" }}
+                </button>
+            </div>
+            <pre><code>{code}</code></pre>
+        </div>
+    }
+}
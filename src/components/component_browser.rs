@@ -9,7 +9,7 @@ use leptos::task::spawn_local;
 use leptos::{prelude::*};
 use leptos_use::use_raf_fn;
 use leptos_use::utils::Pausable;
-use phosphor_leptos::{CHAT, CHATS, GAME_CONTROLLER, Icon, IconWeight, VIDEO};
+use phosphor_leptos::{CHAT, CHATS, GAME_CONTROLLER, Icon, IconWeight, VIDEO, CUBE, LIGHTBULB, SUN, PERSON, USER, PLANT, TREE, DROP, MOUNTAINS, FOLDER, COIN, SPEAKER_HIGH, SPARKLE, BOUNDING_BOX};
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 use std::cell::RefCell;
@@ -30,20 +30,144 @@ use entropy_engine::helpers::saved_data::{
     LightProperties, 
     CollectableProperties,
     PlayerProperties, 
-    ScatterSettings
+    ScatterSettings,
+    TriggerProperties,
+    TriggerShape,
+    InstancedModelProperties,
+    DirectionalLightProperties,
+    GroupProperties,
+    AudioSourceProperties,
+    ProceduralGrassProperties
 };
 use std::time::{Duration, SystemTime};
 
+use crate::app::{apply_component_properties, apply_model_material, delete_component, duplicate_component, focus_camera_on, save_project, sync_hidden_components, sync_locked_components, DUPLICATE_OFFSET};
+use crate::components::confirm_dialog::{confirm_action, ConfirmKind};
+use crate::property_fields::*;
+use crate::storage;
+
+/// A change made in one of the property panels, applied to the component by the editor.
+#[derive(Clone)]
+struct FieldEdit(Rc<dyn Fn(&mut ComponentData)>);
+
+impl FieldEdit {
+    fn new(edit: impl Fn(&mut ComponentData) + 'static) -> Self {
+        FieldEdit(Rc::new(edit))
+    }
+}
+
+/// Getter and setter for one numeric field of a config, so panels can list their fields as data.
+type FieldAccess<T> = (&'static str, FieldSpec, fn(&T) -> f32, fn(&mut T, f32));
+
+/// Kind-specific properties copied from one component, ready to paste onto another of the same kind.
+#[derive(Clone, Debug)]
+struct CopiedProperties {
+    kind: ComponentKind,
+    source_name: String,
+    properties: serde_json::Value,
+}
+
+/// Puts a component's kind-specific config back to its `Default`, for kinds that have a reset.
+/// Returns None for kinds without one.
+fn reset_kind_properties(component: &mut ComponentData) -> Option<&'static str> {
+    match component.kind.as_ref()? {
+        ComponentKind::WaterPlane => {
+            component.water_properties = Some(WaterConfig::default());
+            Some("water")
+        }
+        ComponentKind::ProceduralGrass => {
+            component.procedural_grass_properties = Some(ProceduralGrassProperties::default());
+            Some("grass")
+        }
+        ComponentKind::ProceduralTree => {
+            component.procedural_tree_properties = Some(entropy_engine::helpers::saved_data::ProceduralTreeProperties::default());
+            Some("tree")
+        }
+        _ => None,
+    }
+}
+
+fn pbr_texture_id(component: &ComponentData) -> Option<String> {
+    component.model_properties.as_ref().and_then(|p| p.pbr_texture_id.clone())
+}
+
+/// Serializes the properties that belong to the component's kind, leaving name and transform out.
+fn kind_properties(component: &ComponentData) -> Option<serde_json::Value> {
+    let value = match component.kind.as_ref()? {
+        ComponentKind::Model => serde_json::to_value(&component.model_properties),
+        ComponentKind::NPC => serde_json::to_value(&component.npc_properties),
+        ComponentKind::Landscape => serde_json::to_value(&component.landscape_properties),
+        ComponentKind::PointLight => serde_json::to_value(&component.light_properties),
+        ComponentKind::DirectionalLight => serde_json::to_value(&component.directional_light_properties),
+        ComponentKind::WaterPlane => serde_json::to_value(&component.water_properties),
+        ComponentKind::Collectable => serde_json::to_value(&component.collectable_properties),
+        ComponentKind::PlayerCharacter => serde_json::to_value(&component.player_properties),
+        ComponentKind::TriggerVolume => serde_json::to_value(&component.trigger_properties),
+        ComponentKind::ParticleEffect => serde_json::to_value(&component.particle_properties),
+        ComponentKind::AudioSource => serde_json::to_value(&component.audio_source_properties),
+        // Instance transforms and group children are layout, not settings
+        _ => return None,
+    };
+    value.ok().filter(|value| !value.is_null())
+}
+
+fn set_kind_properties(component: &mut ComponentData, properties: serde_json::Value) -> Result<(), String> {
+    fn parse<T: serde::de::DeserializeOwned>(properties: serde_json::Value) -> Result<Option<T>, String> {
+        serde_json::from_value(properties).map(Some).map_err(|e| e.to_string())
+    }
+
+    match component.kind.as_ref() {
+        Some(ComponentKind::Model) => component.model_properties = parse(properties)?,
+        Some(ComponentKind::NPC) => component.npc_properties = parse(properties)?,
+        Some(ComponentKind::Landscape) => component.landscape_properties = parse(properties)?,
+        Some(ComponentKind::PointLight) => component.light_properties = parse(properties)?,
+        Some(ComponentKind::DirectionalLight) => component.directional_light_properties = parse(properties)?,
+        Some(ComponentKind::WaterPlane) => component.water_properties = parse(properties)?,
+        Some(ComponentKind::Collectable) => component.collectable_properties = parse(properties)?,
+        Some(ComponentKind::PlayerCharacter) => component.player_properties = parse(properties)?,
+        Some(ComponentKind::TriggerVolume) => component.trigger_properties = parse(properties)?,
+        Some(ComponentKind::ParticleEffect) => component.particle_properties = parse(properties)?,
+        Some(ComponentKind::AudioSource) => component.audio_source_properties = parse(properties)?,
+        _ => return Err("This component has no properties to paste".to_string()),
+    }
+    Ok(())
+}
+
+/// Whether a component's name, kind or id contains `query`, which is already lowercase.
+fn component_matches(component: &ComponentData, query: &str) -> bool {
+    query.is_empty()
+        || component.generic_properties.name.to_lowercase().contains(query)
+        || format!("{:?}", component.kind.clone().unwrap_or(ComponentKind::Model)).to_lowercase().contains(query)
+        || component.id.to_lowercase().contains(query)
+}
+
 #[component]
 pub fn ComponentPropertiesEditor(
     pipeline_store: LocalResource<Option<Rc<RefCell<ExportPipeline>>>>,
     is_initialized: ReadSignal<bool>,
+    project_id: Signal<Option<String>>,
+    // Bumped after tool calls change the scene, so the list picks up new and renamed components
+    scene_version: ReadSignal<u32>,
+    // Shared with the preview, where clicking a component selects it too
+    selected_component_id: ReadSignal<Option<String>>,
+    set_selected_component_id: WriteSignal<Option<String>>,
 ) -> impl IntoView {
-    let (selected_component_id, set_selected_component_id) = signal::<Option<String>>(None);
     let (components_list, set_components_list) = signal::<Vec<ComponentData>>(Vec::new());
+    let (renaming_id, set_renaming_id) = signal::<Option<String>>(None);
+    let (copied_properties, set_copied_properties) = signal::<Option<CopiedProperties>>(None);
+    let (action_message, set_action_message) = signal::<Option<String>>(None);
+    let (search, set_search) = signal(String::new());
+    // Row to scroll into view once, after jumping to it from the search box
+    let (scroll_target, set_scroll_target) = signal::<Option<String>>(None);
+
+    let filtered_components = move || {
+        let query = search.get().trim().to_lowercase();
+        components_list.get().into_iter().filter(|c| component_matches(c, &query)).collect::<Vec<_>>()
+    };
     
     // Extract components in an effect to avoid borrow issues
     create_effect(move |_| {
+        scene_version.get();
         if is_initialized.get() {
             if let Some(pipeline) = pipeline_store.get() {
                 if let Some(pipeline_arc) = pipeline.as_ref() {
@@ -61,28 +185,369 @@ pub fn ComponentPropertiesEditor(
             }
         }
     });
-    
+
+    // Applies `edit` to one component, pushes it to the renderer, refreshes the list and saves
+    let edit_component = move |component_id: String, edit: &dyn Fn(&mut ComponentData)| {
+        let mut material_changed = false;
+        if let Some(pipeline) = pipeline_store.get_untracked() {
+            if let Some(pipeline_arc) = pipeline.as_ref() {
+                let mut pipeline_guard = pipeline_arc.borrow_mut();
+                if let Some(editor) = pipeline_guard.export_editor.as_mut() {
+                    let mut edited = None;
+                    if let Some(saved_state) = editor.saved_state.as_mut() {
+                        let pbr_texture_ids: Vec<String> = saved_state.pbr_textures.iter().flatten().map(|p| p.id.clone()).collect();
+                        if let Some(components) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)).and_then(|l| l.components.as_mut()) {
+                            if let Some(component) = components.iter_mut().find(|c| c.id == component_id) {
+                                let previous_material = pbr_texture_id(component);
+                                edit(component);
+                                match pbr_texture_id(component) {
+                                    material if material == previous_material => {}
+                                    Some(material) if !pbr_texture_ids.contains(&material) => {
+                                        log!("PBR material {} not found, keeping the previous one", material);
+                                        if let Some(properties) = component.model_properties.as_mut() {
+                                            properties.pbr_texture_id = previous_material;
+                                        }
+                                    }
+                                    _ => material_changed = true,
+                                }
+                                edited = Some(component.clone());
+                            }
+                            set_components_list.set(components.clone());
+                        }
+
+                        // Save to backend
+                        let pid = project_id.get_untracked().unwrap_or_default();
+                        let state_clone = saved_state.clone();
+                        if !pid.is_empty() {
+                            spawn_local(async move {
+                                let _ = save_project(&pid, &state_clone).await;
+                            });
+                        }
+                    }
+
+                    if let Some(component) = edited.as_ref() {
+                        apply_component_properties(editor, component);
+                    }
+                    sync_locked_components(editor);
+                    sync_hidden_components(editor);
+                }
+            }
+
+            // The new material's textures load in the background, after the edit has been saved
+            if material_changed {
+                let pid = project_id.get_untracked().unwrap_or_default();
+                spawn_local(async move {
+                    let Some(pipeline_arc) = pipeline.as_ref() else { return };
+                    let mut pipeline_guard = pipeline_arc.borrow_mut();
+                    if let Some(editor) = pipeline_guard.export_editor.as_mut() {
+                        if let Err(e) = apply_model_material(editor, pid, &component_id).await {
+                            log!("{}", e);
+                        }
+                    }
+                });
+            }
+        }
+    };
+
+    let rename_component = move |component_id: String, name: String| {
+        set_renaming_id.set(None);
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        edit_component(component_id, &|component| component.generic_properties.name = name.clone());
+    };
+
+    let toggle_lock = move |component_id: String| {
+        edit_component(component_id, &|component| component.locked = !component.locked);
+    };
+
+    let toggle_hidden = move |component_id: String| {
+        edit_component(component_id, &|component| component.hidden = !component.hidden);
+    };
+
+    let find_component = move |component_id: &str| {
+        components_list.with_untracked(|list| list.iter().find(|c| c.id == component_id).cloned())
+    };
+
+    let focus_component = move |component_id: String| {
+        let Some(component) = find_component(&component_id) else { return };
+        if let Some(pipeline) = pipeline_store.get_untracked() {
+            if let Some(pipeline_arc) = pipeline.as_ref() {
+                let mut pipeline_guard = pipeline_arc.borrow_mut();
+                if let Some(editor) = pipeline_guard.export_editor.as_mut() {
+                    focus_camera_on(editor, &component);
+                }
+            }
+        }
+    };
+
+    // Adds a copy next to the component through the same path as the duplicateObject tool, then opens it
+    let duplicate = move |component_id: String| {
+        let Some(pipeline) = pipeline_store.get_untracked() else { return };
+        let pid = project_id.get_untracked().unwrap_or_default();
+        spawn_local(async move {
+            let Some(pipeline_arc) = pipeline.as_ref() else { return };
+            let mut pipeline_guard = pipeline_arc.borrow_mut();
+            let Some(editor) = pipeline_guard.export_editor.as_mut() else { return };
+
+            match duplicate_component(editor, &pid, &component_id, DUPLICATE_OFFSET).await {
+                Ok(copy) => {
+                    if let Some(saved_state) = editor.saved_state.as_ref() {
+                        if let Some(components) = saved_state.levels.as_ref().and_then(|l| l.get(0)).and_then(|l| l.components.as_ref()) {
+                            set_components_list.set(components.clone());
+                        }
+
+                        let state_clone = saved_state.clone();
+                        if !pid.is_empty() {
+                            spawn_local(async move {
+                                let _ = save_project(&pid, &state_clone).await;
+                            });
+                        }
+                    }
+                    set_action_message.set(None);
+                    set_selected_component_id.set(Some(copy.id.clone()));
+                    set_scroll_target.set(Some(copy.id));
+                }
+                Err(e) => set_action_message.set(Some(e)),
+            }
+        });
+    };
+
+    // Removes the component through the same path as the deleteObject tool, after asking
+    let delete = move |component_id: String| {
+        let Some(component) = find_component(&component_id) else { return };
+        confirm_action(
+            ConfirmKind::DeleteComponent,
+            format!("Delete {}?", component.generic_properties.name),
+            "It's removed from the scene and the project.",
+            "Delete",
+            move || {
+                let Some(pipeline) = pipeline_store.get_untracked() else { return };
+                let Some(pipeline_arc) = pipeline.as_ref() else { return };
+                let mut pipeline_guard = pipeline_arc.borrow_mut();
+                let Some(editor) = pipeline_guard.export_editor.as_mut() else { return };
+
+                match delete_component(editor, &component_id) {
+                    Ok(_) => {
+                        if let Some(saved_state) = editor.saved_state.as_ref() {
+                            if let Some(components) = saved_state.levels.as_ref().and_then(|l| l.get(0)).and_then(|l| l.components.as_ref()) {
+                                set_components_list.set(components.clone());
+                            }
+
+                            let pid = project_id.get_untracked().unwrap_or_default();
+                            let state_clone = saved_state.clone();
+                            if !pid.is_empty() {
+                                spawn_local(async move {
+                                    let _ = save_project(&pid, &state_clone).await;
+                                });
+                            }
+                        }
+                        set_selected_component_id.set(None);
+                        set_action_message.set(None);
+                    }
+                    Err(e) => set_action_message.set(Some(e)),
+                }
+            },
+        );
+    };
+
+    // Opens a component from the search box and frames it in the preview
+    let jump_to_component = move |component_id: String| {
+        set_selected_component_id.set(Some(component_id.clone()));
+        set_scroll_target.set(Some(component_id.clone()));
+        focus_component(component_id);
+    };
+
+    let copy_properties = move |component_id: String| {
+        let Some(component) = find_component(&component_id) else { return };
+        match (component.kind.clone(), kind_properties(&component)) {
+            (Some(kind), Some(properties)) => {
+                set_action_message.set(Some(format!("Copied {:?} properties from {}", kind, component.generic_properties.name)));
+                set_copied_properties.set(Some(CopiedProperties {
+                    kind,
+                    source_name: component.generic_properties.name.clone(),
+                    properties,
+                }));
+            }
+            _ => set_action_message.set(Some("This component has no properties to copy".to_string())),
+        }
+    };
+
+    let paste_properties = move |component_id: String| {
+        let Some(copied) = copied_properties.get_untracked() else { return };
+        let Some(component) = find_component(&component_id) else { return };
+        if component.kind.as_ref() != Some(&copied.kind) {
+            set_action_message.set(Some(format!(
+                "Can't paste {:?} properties onto a {:?}",
+                copied.kind,
+                component.kind.unwrap_or(ComponentKind::Model)
+            )));
+            return;
+        }
+
+        // Check the copy still parses before touching saved data
+        let mut preview = component.clone();
+        if let Err(e) = set_kind_properties(&mut preview, copied.properties.clone()) {
+            set_action_message.set(Some(format!("Couldn't paste properties: {}", e)));
+            return;
+        }
+
+        edit_component(component_id, &|component| {
+            let _ = set_kind_properties(component, copied.properties.clone());
+        });
+        set_action_message.set(Some(format!("Pasted properties from {}", copied.source_name)));
+    };
+
+    let reset_properties = move |component_id: String| {
+        let Some(component) = find_component(&component_id) else { return };
+        let Some(group) = reset_kind_properties(&mut component.clone()) else { return };
+        confirm_action(
+            ConfirmKind::ResetProperties,
+            format!("Reset {} settings?", group),
+            format!("{} goes back to the default {} settings. Your changes to it are lost.", component.generic_properties.name, group),
+            "Reset",
+            move || {
+                edit_component(component_id.clone(), &|component| {
+                    reset_kind_properties(component);
+                });
+                set_action_message.set(None);
+            },
+        );
+    };
+
+    // Sky is level-wide, so it resets from here rather than a component
+    let reset_sky = move || {
+        confirm_action(
+            ConfirmKind::ResetProperties,
+            "Reset sky settings?",
+            "The sky goes back to the default colors and sun. Your changes to it are lost.",
+            "Reset",
+            move || {
+                if let Some(pipeline) = pipeline_store.get_untracked() {
+                    if let Some(pipeline_arc) = pipeline.as_ref() {
+                        let mut pipeline_guard = pipeline_arc.borrow_mut();
+                        if let Some(editor) = pipeline_guard.export_editor.as_mut() {
+                            let mut sky = entropy_engine::helpers::saved_data::ProceduralSkyConfig::default();
+                            if let Some(saved_state) = editor.saved_state.as_mut() {
+                                if let Some(level) = saved_state.levels.as_mut().and_then(|l| l.get_mut(0)) {
+                                    level.procedural_sky = Some(sky.clone());
+                                    // Shown under the current weather, the same as any other sky change
+                                    if let Some(weather) = level.weather.as_ref() {
+                                        sky = crate::weather::darken_sky(&sky, weather);
+                                    }
+                                }
+
+                                let pid = project_id.get_untracked().unwrap_or_default();
+                                let state_clone = saved_state.clone();
+                                if !pid.is_empty() {
+                                    spawn_local(async move {
+                                        let _ = save_project(&pid, &state_clone).await;
+                                    });
+                                }
+                            }
+
+                            if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+                                renderer_state.update_procedural_sky(&gpu_resources.queue, &sky);
+                            }
+                        }
+                    }
+                }
+            },
+        );
+    };
+
     view! {
         <div class="component-editor">
             <h3>{"Components"}</h3>
+            <div class="property-actions">
+                <button on:click=move |_| reset_sky()>{"Reset Sky"}</button>
+            </div>
+            
+            <input
+                class="component-search"
+                type="search"
+                placeholder="Search by name, kind or id"
+                prop:value=search
+                on:input=move |ev| set_search.set(event_target_value(&ev))
+                on:keydown=move |ev: web_sys::KeyboardEvent| {
+                    match ev.key().as_str() {
+                        "Enter" => {
+                            if let Some(first) = filtered_components().first() {
+                                jump_to_component(first.id.clone());
+                            }
+                        }
+                        "Escape" => set_search.set(String::new()),
+                        _ => {}
+                    }
+                }
+            />
             
             <div class="component-list">
                 <Show
                     when=move || !components_list.get().is_empty()
                     fallback=|| view! {<div class="no-components">{"No components loaded"}</div>}
                 >
+                    <Show when=move || filtered_components().is_empty()>
+                        <div class="no-components">{"No components match"}</div>
+                    </Show>
                     <For
-                        each=move || components_list.get()
+                        each=filtered_components
                         key=|component| component.id.clone()
                         children=move |component: ComponentData| {
                             let comp_id = component.id.clone();
-                            let comp_name = component.generic_properties.name.clone();
                             let comp_kind = component.kind.clone();
                             let comp_id_clone = comp_id.clone();
                             let is_selected = move || selected_component_id.get() == Some(comp_id.clone());
+
+                            // Read from the list so renames show up, `For` keeps the row for the same id
+                            let name_id = component.id.clone();
+                            let comp_name = move || components_list.with(|list| {
+                                list.iter()
+                                    .find(|c| c.id == name_id)
+                                    .map(|c| c.generic_properties.name.clone())
+                                    .unwrap_or_default()
+                            });
+                            let rename_id = component.id.clone();
+                            let is_renaming = move || renaming_id.get() == Some(rename_id.clone());
+                            let start_rename_id = component.id.clone();
+                            let commit_id = component.id.clone();
+                            let blur_id = component.id.clone();
+                            let comp_name_for_input = comp_name.clone();
+                            let lock_id = component.id.clone();
+                            let is_locked = move || components_list.with(|list| {
+                                list.iter().any(|c| c.id == lock_id && c.locked)
+                            });
+                            let toggle_id = component.id.clone();
+                            let hidden_id = component.id.clone();
+                            let is_hidden = move || components_list.with(|list| {
+                                list.iter().any(|c| c.id == hidden_id && c.hidden)
+                            });
+                            let toggle_hidden_id = component.id.clone();
+                            let copy_id = component.id.clone();
+                            let paste_id = component.id.clone();
+                            let panel_id = component.id.clone();
+                            let reset_id = component.id.clone();
+                            let has_reset = reset_kind_properties(&mut component.clone()).is_some();
+                            let focus_id = component.id.clone();
+                            let duplicate_id = component.id.clone();
+                            let delete_id = component.id.clone();
+                            let delete_locked = is_locked.clone();
+
+                            let item_ref = NodeRef::<leptos::html::Div>::new();
+                            let scroll_id = component.id.clone();
+                            create_effect(move |_| {
+                                if scroll_target.get().as_ref() == Some(&scroll_id) {
+                                    if let Some(item) = item_ref.get() {
+                                        item.scroll_into_view();
+                                    }
+                                    set_scroll_target.set(None);
+                                }
+                            });
                             
                             view! {
-                                <div class="component-item">
+                                <div class="component-item" node_ref=item_ref>
                                     <div 
                                         class="component-header"
                                         class:selected=is_selected.clone()
@@ -90,14 +555,128 @@ pub fn ComponentPropertiesEditor(
                                             set_selected_component_id.set(Some(comp_id_clone.clone()));
                                         }
                                     >
-                                        <strong>{comp_name}</strong>
+                                        <ComponentKindIcon kind=comp_kind.clone() />
+                                        <Show
+                                            when=is_renaming.clone()
+                                            fallback=move || view! {
+                                                <strong
+                                                    title="Double-click to rename"
+                                                    on:dblclick={
+                                                        let start_rename_id = start_rename_id.clone();
+                                                        move |_| set_renaming_id.set(Some(start_rename_id.clone()))
+                                                    }
+                                                >{comp_name.clone()}</strong>
+                                            }
+                                        >
+                                            <input
+                                                class="rename-input"
+                                                type="text"
+                                                value=comp_name_for_input.clone()
+                                                autofocus=true
+                                                on:click=|ev| ev.stop_propagation()
+                                                on:keydown={
+                                                    let commit_id = commit_id.clone();
+                                                    move |ev: web_sys::KeyboardEvent| {
+                                                        match ev.key().as_str() {
+                                                            "Enter" => rename_component(commit_id.clone(), event_target_value(&ev)),
+                                                            "Escape" => set_renaming_id.set(None),
+                                                            _ => {}
+                                                        }
+                                                    }
+                                                }
+                                                on:blur={
+                                                    let blur_id = blur_id.clone();
+                                                    move |ev| {
+                                                        // Enter/Escape already closed the input
+                                                        if renaming_id.get_untracked().as_ref() == Some(&blur_id) {
+                                                            rename_component(blur_id.clone(), event_target_value(&ev));
+                                                        }
+                                                    }
+                                                }
+                                            />
+                                        </Show>
                                         <small>{" ("}{format!("{:?}", comp_kind.unwrap_or(ComponentKind::Model))}{")"}</small>
+                                        <button
+                                            class="visibility-toggle"
+                                            class:is-hidden=is_hidden.clone()
+                                            title={
+                                                let is_hidden = is_hidden.clone();
+                                                move || if is_hidden() { "Show in the preview" } else { "Hide from the preview" }
+                                            }
+                                            on:click=move |ev| {
+                                                ev.stop_propagation();
+                                                toggle_hidden(toggle_hidden_id.clone());
+                                            }
+                                        >
+                                            {move || if is_hidden() { "Hidden" } else { "Hide" }}
+                                        </button>
+                                        <button
+                                            class="lock-toggle"
+                                            class:locked=is_locked.clone()
+                                            title={
+                                                let is_locked = is_locked.clone();
+                                                move || if is_locked() { "Unlock" } else { "Lock to prevent edits" }
+                                            }
+                                            on:click=move |ev| {
+                                                ev.stop_propagation();
+                                                toggle_lock(toggle_id.clone());
+                                            }
+                                        >
+                                            {move || if is_locked() { "Locked" } else { "Lock" }}
+                                        </button>
                                     </div>
                                     
                                     <Show when=is_selected>
-                                        <ComponentPropertyPanel 
-                                            component=component.clone()
-                                        />
+                                        <div class="property-actions">
+                                            <button on:click={
+                                                let focus_id = focus_id.clone();
+                                                move |_| focus_component(focus_id.clone())
+                                            }>{"Focus"}</button>
+                                            <button on:click={
+                                                let duplicate_id = duplicate_id.clone();
+                                                move |_| duplicate(duplicate_id.clone())
+                                            }>{"Duplicate"}</button>
+                                            <button
+                                                class="delete-btn"
+                                                disabled=delete_locked.clone()
+                                                title={
+                                                    let delete_locked = delete_locked.clone();
+                                                    move || if delete_locked() { "Unlock to delete" } else { "" }
+                                                }
+                                                on:click={
+                                                    let delete_id = delete_id.clone();
+                                                    move |_| delete(delete_id.clone())
+                                                }
+                                            >{"Delete"}</button>
+                                            <button on:click={
+                                                let copy_id = copy_id.clone();
+                                                move |_| copy_properties(copy_id.clone())
+                                            }>{"Copy Properties"}</button>
+                                            <button
+                                                disabled=move || copied_properties.with(|c| c.is_none())
+                                                on:click={
+                                                    let paste_id = paste_id.clone();
+                                                    move |_| paste_properties(paste_id.clone())
+                                                }
+                                            >{"Paste Properties"}</button>
+                                            <Show when=move || has_reset>
+                                                <button on:click={
+                                                    let reset_id = reset_id.clone();
+                                                    move |_| reset_properties(reset_id.clone())
+                                                }>{"Reset to Defaults"}</button>
+                                            </Show>
+                                        </div>
+                                        {move || action_message.get().map(|message| view! {
+                                            <p class="info-text">{message}</p>
+                                        })}
+                                        // Rebuilt from the list so pasted and edited values show up
+                                        {
+                                            let panel_id = panel_id.clone();
+                                            let edit_id = panel_id.clone();
+                                            let on_edit = Callback::new(move |edit: FieldEdit| edit_component(edit_id.clone(), &*edit.0));
+                                            move || components_list.with(|list| list.iter().find(|c| c.id == panel_id).cloned())
+                                                .map(|component| view! { <ComponentPropertyPanel component=component on_edit=on_edit /> })
+                                        }
                                     </Show>
                                 </div>
                             }
@@ -109,9 +688,38 @@ pub fn ComponentPropertiesEditor(
     }
 }
 
+/// Small icon for a component's kind, in the inbox's icon style so lists scan at a glance.
+#[component]
+fn ComponentKindIcon(kind: Option<ComponentKind>) -> impl IntoView {
+    let icon = match kind {
+        Some(ComponentKind::PointLight) => LIGHTBULB,
+        Some(ComponentKind::DirectionalLight) => SUN,
+        Some(ComponentKind::NPC) => USER,
+        Some(ComponentKind::PlayerCharacter) => PERSON,
+        Some(ComponentKind::ProceduralGrass) => PLANT,
+        Some(ComponentKind::ProceduralTree) | Some(ComponentKind::Forest) => TREE,
+        Some(ComponentKind::WaterPlane) => DROP,
+        Some(ComponentKind::Landscape) => MOUNTAINS,
+        Some(ComponentKind::Group) => FOLDER,
+        Some(ComponentKind::Collectable) => COIN,
+        Some(ComponentKind::AudioSource) => SPEAKER_HIGH,
+        Some(ComponentKind::ParticleEffect) => SPARKLE,
+        Some(ComponentKind::TriggerVolume) => BOUNDING_BOX,
+        // Models, instanced models and anything without a kind
+        _ => CUBE,
+    };
+
+    view! {
+        <span class="component-kind-icon">
+            <Icon icon=icon color="#AE2983" weight=IconWeight::Fill size="16px" />
+        </span>
+    }
+}
+
 #[component]
 fn ComponentPropertyPanel(
     component: ComponentData,
+    on_edit: Callback<FieldEdit>,
 ) -> impl IntoView {
     view! {
         <div class="property-panel">
@@ -127,6 +735,7 @@ fn ComponentPropertyPanel(
                     <ModelPropertiesPanel 
                         properties=component.model_properties.clone().unwrap_or_default()
                         component_id=component.id.clone()
+                        on_edit=on_edit
                     />
                 }.into_view().into_any(),
                 
@@ -148,6 +757,15 @@ fn ComponentPropertyPanel(
                     <LightPropertiesPanel 
                         properties=component.light_properties.clone().unwrap_or_default()
                         component_id=component.id.clone()
+                        on_edit=on_edit
+                    />
+                }.into_view().into_any(),
+                
+                Some(ComponentKind::DirectionalLight) => view! {
+                    <DirectionalLightPropertiesPanel 
+                        properties=component.directional_light_properties.clone().unwrap_or_default()
+                        component_id=component.id.clone()
+                        on_edit=on_edit
                     />
                 }.into_view().into_any(),
                 
@@ -155,6 +773,15 @@ fn ComponentPropertyPanel(
                     <WaterPropertiesPanel 
                         properties=component.water_properties.clone()
                         component_id=component.id.clone()
+                        on_edit=on_edit
+                    />
+                }.into_view().into_any(),
+                
+                Some(ComponentKind::ProceduralGrass) => view! {
+                    <GrassPropertiesPanel 
+                        properties=component.procedural_grass_properties.clone().unwrap_or_default()
+                        component_id=component.id.clone()
+                        on_edit=on_edit
                     />
                 }.into_view().into_any(),
                 
@@ -172,6 +799,34 @@ fn ComponentPropertyPanel(
                     />
                 }.into_view().into_any(),
                 
+                Some(ComponentKind::TriggerVolume) => view! {
+                    <TriggerPropertiesPanel 
+                        properties=component.trigger_properties.clone().unwrap_or_default()
+                        component_id=component.id.clone()
+                    />
+                }.into_view().into_any(),
+                
+                Some(ComponentKind::InstancedModel) => view! {
+                    <InstancedModelPropertiesPanel 
+                        properties=component.instanced_model_properties.clone().unwrap_or_default()
+                        component_id=component.id.clone()
+                    />
+                }.into_view().into_any(),
+                
+                Some(ComponentKind::AudioSource) => view! {
+                    <AudioSourcePropertiesPanel 
+                        properties=component.audio_source_properties.clone().unwrap_or_default()
+                        component_id=component.id.clone()
+                    />
+                }.into_view().into_any(),
+                
+                Some(ComponentKind::Group) => view! {
+                    <GroupPropertiesPanel 
+                        properties=component.group_properties.clone().unwrap_or_default()
+                        component_id=component.id.clone()
+                    />
+                }.into_view().into_any(),
+                
                 _ => view! { <div></div> }.into_view().into_any(),
             }}
             
@@ -186,16 +841,115 @@ fn ComponentPropertyPanel(
     }
 }
 
+/// A collapsible group of fields. Whether it's open is remembered per component kind, which also
+/// keeps it open when the panel is rebuilt after an edit.
+#[component]
+fn PropertySection(
+    kind: &'static str,
+    title: &'static str,
+    #[prop(default = false)]
+    default_open: bool,
+    children: Children,
+) -> impl IntoView {
+    let storage_key = format!("entropy-chat:property-section:{}:{}", kind, title.to_lowercase().replace(' ', "-"));
+    let is_open = storage::get_item(&storage_key).map(|state| state == "open").unwrap_or(default_open);
+    let details_ref = NodeRef::<leptos::html::Details>::new();
+
+    view! {
+        <details
+            class="property-section"
+            node_ref=details_ref
+            open=is_open
+            on:toggle=move |_| {
+                if let Some(details) = details_ref.get_untracked() {
+                    storage::set_item(&storage_key, if details.open() { "open" } else { "closed" });
+                }
+            }
+        >
+            <summary>{title}</summary>
+            {children()}
+        </details>
+    }
+}
+
+/// Number input ranged by a `FieldSpec`. Out-of-range values are clamped and anything that
+/// isn't a number is refused, with a note saying which.
+#[component]
+fn NumberField(
+    spec: FieldSpec,
+    /// Overrides the spec's label, e.g. to say which wave a field belongs to
+    #[prop(optional, into)]
+    label: Option<String>,
+    value: f32,
+    on_commit: Callback<f32>,
+) -> impl IntoView {
+    let (message, set_message) = signal::<Option<String>>(None);
+    let input_ref = NodeRef::<leptos::html::Input>::new();
+
+    let on_change = move |ev: web_sys::Event| {
+        let parsed = event_target_value(&ev).trim().parse::<f32>().unwrap_or(f32::NAN);
+        match spec.clamp(parsed) {
+            Ok(clamped) => {
+                if clamped != parsed {
+                    set_message.set(Some(format!("Clamped to {} ({} to {})", clamped, spec.min, spec.max)));
+                    if let Some(input) = input_ref.get_untracked() {
+                        input.set_value(&clamped.to_string());
+                    }
+                } else {
+                    set_message.set(None);
+                }
+                on_commit.run(clamped);
+            }
+            Err(e) => set_message.set(Some(e)),
+        }
+    };
+
+    view! {
+        <label>
+            {label.unwrap_or_else(|| spec.label.to_string())}{": "}
+            <input
+                type="number"
+                node_ref=input_ref
+                min=spec.min
+                max=spec.max
+                step=spec.step
+                value=value
+                on:change=on_change
+            />
+            {unit_label(spec)}
+            {move || message.get().map(|message| view! { <small class="field-error">{message}</small> })}
+        </label>
+    }
+}
+
+/// The spec's unit next to an input, so it's clear whether a value is meters, degrees or a 0–1 fraction.
+fn unit_label(spec: FieldSpec) -> impl IntoView {
+    spec.unit.map(|unit| view! { <span class="field-unit">{unit}</span> })
+}
+
+/// One `NumberField` per entry in `fields`, each writing through `edit` into the component's config.
+fn number_fields<T: 'static>(
+    fields: Vec<FieldAccess<T>>,
+    config: &T,
+    on_edit: Callback<FieldEdit>,
+    edit: fn(&mut ComponentData) -> &mut T,
+) -> impl IntoView {
+    fields.into_iter().map(|(label, spec, get, set)| {
+        let on_commit = Callback::new(move |value: f32| {
+            on_edit.run(FieldEdit::new(move |component| set(edit(component), value)));
+        });
+        view! { <NumberField spec=spec label=label value=get(config) on_commit=on_commit /> }
+    }).collect_view()
+}
+
 #[component]
 fn GenericPropertiesPanel(
     generic: GenericProperties,
     component_id: String,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(true);
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Generic Properties"}</summary>
+        <PropertySection kind="generic" title="Generic Properties" default_open=true>
             <div class="property-group">
                 <label>
                     {"Name: "}
@@ -204,44 +958,53 @@ fn GenericPropertiesPanel(
                 
                 <label>
                     {"Position X: "}
-                    <input type="number" step="0.1" value=generic.position[0] />
+                    <input type="number" step=POSITION.step value=generic.position[0] />
+                    {unit_label(POSITION)}
                 </label>
                 <label>
                     {"Position Y: "}
-                    <input type="number" step="0.1" value=generic.position[1] />
+                    <input type="number" step=POSITION.step value=generic.position[1] />
+                    {unit_label(POSITION)}
                 </label>
                 <label>
                     {"Position Z: "}
-                    <input type="number" step="0.1" value=generic.position[2] />
+                    <input type="number" step=POSITION.step value=generic.position[2] />
+                    {unit_label(POSITION)}
                 </label>
                 
                 <label>
                     {"Rotation X: "}
-                    <input type="number" step="1" value=generic.rotation[0] />
+                    <input type="number" step=ROTATION.step value=generic.rotation[0] />
+                    {unit_label(ROTATION)}
                 </label>
                 <label>
                     {"Rotation Y: "}
-                    <input type="number" step="1" value=generic.rotation[1] />
+                    <input type="number" step=ROTATION.step value=generic.rotation[1] />
+                    {unit_label(ROTATION)}
                 </label>
                 <label>
                     {"Rotation Z: "}
-                    <input type="number" step="1" value=generic.rotation[2] />
+                    <input type="number" step=ROTATION.step value=generic.rotation[2] />
+                    {unit_label(ROTATION)}
                 </label>
                 
                 <label>
                     {"Scale X: "}
-                    <input type="number" step="0.1" value=generic.scale[0] />
+                    <input type="number" step=SCALE.step value=generic.scale[0] />
+                    {unit_label(SCALE)}
                 </label>
                 <label>
                     {"Scale Y: "}
-                    <input type="number" step="0.1" value=generic.scale[1] />
+                    <input type="number" step=SCALE.step value=generic.scale[1] />
+                    {unit_label(SCALE)}
                 </label>
                 <label>
                     {"Scale Z: "}
-                    <input type="number" step="0.1" value=generic.scale[2] />
+                    <input type="number" step=SCALE.step value=generic.scale[2] />
+                    {unit_label(SCALE)}
                 </label>
             </div>
-        </details>
+        </PropertySection>
     }
 }
 
@@ -249,16 +1012,36 @@ fn GenericPropertiesPanel(
 fn ModelPropertiesPanel(
     properties: ModelProperties,
     component_id: String,
+    on_edit: Callback<FieldEdit>,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
-    
+    let on_material_change = move |ev: web_sys::Event| {
+        let material = event_target_value(&ev).trim().to_string();
+        // The material can be swapped but not removed, the model would have nothing to render with
+        if !material.is_empty() {
+            on_edit.run(FieldEdit::new(move |component| {
+                component.model_properties.get_or_insert_with(ModelProperties::default).pbr_texture_id = Some(material.clone());
+            }));
+        }
+    };
+
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Model Properties"}</summary>
+        <PropertySection kind="model" title="Model Properties">
             <div class="property-group">
                 <p class="info-text">{"Model components use asset_id for the model reference"}</p>
+                <label>
+                    {"PBR Material ID: "}
+                    <input type="text" value=properties.pbr_texture_id.unwrap_or_default() placeholder="(set with setMaterial)" on:change=on_material_change />
+                </label>
+                <label>
+                    {"LOD Distances: "}
+                    <input
+                        type="text"
+                        value=properties.lod_distances.map(|d| d.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")).unwrap_or_default()
+                        placeholder="(from model size)"
+                    />
+                </label>
             </div>
-        </details>
+        </PropertySection>
     }
 }
 
@@ -267,18 +1050,16 @@ fn NPCPropertiesPanel(
     properties: NPCProperties,
     component_id: String,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"NPC Properties"}</summary>
+        <PropertySection kind="npc" title="NPC Properties">
             <div class="property-group">
                 <label>
                     {"Model ID: "}
                     <input type="text" value=properties.model_id />
                 </label>
             </div>
-        </details>
+        </PropertySection>
     }
 }
 
@@ -287,11 +1068,10 @@ fn LandscapePropertiesPanel(
     properties: LandscapeProperties,
     component_id: String,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
+    let blend = properties.texture_blend.clone().unwrap_or_default();
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Landscape Properties"}</summary>
+        <PropertySection kind="landscape" title="Landscape Properties">
             <div class="property-group">
                 <h4>{"Regular Textures"}</h4>
                 <label>
@@ -320,8 +1100,33 @@ fn LandscapePropertiesPanel(
                     {"Soil PBR Texture ID: "}
                     <input type="text" value=properties.soil_pbr_texture_id.unwrap_or_default() />
                 </label>
+                
+                <h4>{"Texture Blending"}</h4>
+                <label>
+                    {"Rock Slope Start: "}
+                    <input type="number" step=SLOPE_ANGLE.step min=SLOPE_ANGLE.min max=SLOPE_ANGLE.max value=blend.rock_slope_start />
+                    {unit_label(SLOPE_ANGLE)}
+                </label>
+                <label>
+                    {"Rock Slope End: "}
+                    <input type="number" step=SLOPE_ANGLE.step min=SLOPE_ANGLE.min max=SLOPE_ANGLE.max value=blend.rock_slope_end />
+                    {unit_label(SLOPE_ANGLE)}
+                </label>
+                <label>
+                    {"Soil Max Height: "}
+                    <input type="number" step=TERRAIN_HEIGHT.step value=blend.soil_height_max />
+                    {unit_label(TERRAIN_HEIGHT)}
+                </label>
+                <label>
+                    {"Tiling Scale: "}
+                    <input type="number" step=LANDSCAPE_TILING_SCALE.step min=LANDSCAPE_TILING_SCALE.min max=LANDSCAPE_TILING_SCALE.max value=blend.tiling_scale />
+                </label>
+                <label>
+                    {"Blend Sharpness: "}
+                    <input type="number" step=LANDSCAPE_BLEND_SHARPNESS.step min=LANDSCAPE_BLEND_SHARPNESS.min max=LANDSCAPE_BLEND_SHARPNESS.max value=blend.blend_sharpness />
+                </label>
             </div>
-        </details>
+        </PropertySection>
     }
 }
 
@@ -329,34 +1134,20 @@ fn LandscapePropertiesPanel(
 fn LightPropertiesPanel(
     properties: LightProperties,
     component_id: String,
+    on_edit: Callback<FieldEdit>,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
+    let fields: Vec<FieldAccess<LightProperties>> = vec![
+        ("Intensity", LIGHT_INTENSITY, |p| p.intensity, |p, v| p.intensity = v),
+        ("Color R", COLOR_CHANNEL, |p| p.color[0], |p, v| p.color[0] = v),
+        ("Color G", COLOR_CHANNEL, |p| p.color[1], |p, v| p.color[1] = v),
+        ("Color B", COLOR_CHANNEL, |p| p.color[2], |p, v| p.color[2] = v),
+        ("Color A", COLOR_CHANNEL, |p| p.color[3], |p, v| p.color[3] = v),
+    ];
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Light Properties"}</summary>
+        <PropertySection kind="point-light" title="Light Properties">
             <div class="property-group">
-                <label>
-                    {"Intensity: "}
-                    <input type="number" step="0.1" min="0" value=properties.intensity />
-                </label>
-                
-                <label>
-                    {"Color R: "}
-                    <input type="number" step="0.01" min="0" max="1" value=properties.color[0] />
-                </label>
-                <label>
-                    {"Color G: "}
-                    <input type="number" step="0.01" min="0" max="1" value=properties.color[1] />
-                </label>
-                <label>
-                    {"Color B: "}
-                    <input type="number" step="0.01" min="0" max="1" value=properties.color[2] />
-                </label>
-                <label>
-                    {"Color A: "}
-                    <input type="number" step="0.01" min="0" max="1" value=properties.color[3] />
-                </label>
+                {number_fields(fields, &properties, on_edit, |c| c.light_properties.get_or_insert_with(LightProperties::default))}
                 
                 <div class="color-preview" style=format!(
                     "background-color: rgba({}, {}, {}, {}); width: 50px; height: 50px; border: 1px solid #ccc;",
@@ -366,7 +1157,36 @@ fn LightPropertiesPanel(
                     properties.color[3]
                 )></div>
             </div>
-        </details>
+        </PropertySection>
+    }
+}
+
+#[component]
+fn DirectionalLightPropertiesPanel(
+    properties: DirectionalLightProperties,
+    component_id: String,
+    on_edit: Callback<FieldEdit>,
+) -> impl IntoView {
+    let fields: Vec<FieldAccess<DirectionalLightProperties>> = vec![
+        ("Intensity", DIRECTIONAL_LIGHT_INTENSITY, |p| p.intensity, |p, v| p.intensity = v),
+        ("Color R", COLOR_CHANNEL, |p| p.color[0], |p, v| p.color[0] = v),
+        ("Color G", COLOR_CHANNEL, |p| p.color[1], |p, v| p.color[1] = v),
+        ("Color B", COLOR_CHANNEL, |p| p.color[2], |p, v| p.color[2] = v),
+    ];
+    
+    view! {
+        <PropertySection kind="directional-light" title="Directional Light Properties">
+            <div class="property-group">
+                {number_fields(fields, &properties, on_edit, |c| c.directional_light_properties.get_or_insert_with(DirectionalLightProperties::default))}
+                
+                <label>
+                    {"Cast Shadows: "}
+                    <input type="checkbox" checked=properties.cast_shadows />
+                </label>
+                
+                <p class="info-text">{"Rotation sets the light direction"}</p>
+            </div>
+        </PropertySection>
     }
 }
 
@@ -374,20 +1194,113 @@ fn LightPropertiesPanel(
 fn WaterPropertiesPanel(
     properties: Option<WaterConfig>,
     component_id: String,
+    on_edit: Callback<FieldEdit>,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
+    let colors: Vec<FieldAccess<WaterConfig>> = vec![
+        ("Shallow R", COLOR_CHANNEL, |c| c.shallow_color[0], |c, v| c.shallow_color[0] = v),
+        ("Shallow G", COLOR_CHANNEL, |c| c.shallow_color[1], |c, v| c.shallow_color[1] = v),
+        ("Shallow B", COLOR_CHANNEL, |c| c.shallow_color[2], |c, v| c.shallow_color[2] = v),
+        ("Medium R", COLOR_CHANNEL, |c| c.medium_color[0], |c, v| c.medium_color[0] = v),
+        ("Medium G", COLOR_CHANNEL, |c| c.medium_color[1], |c, v| c.medium_color[1] = v),
+        ("Medium B", COLOR_CHANNEL, |c| c.medium_color[2], |c, v| c.medium_color[2] = v),
+        ("Deep R", COLOR_CHANNEL, |c| c.deep_color[0], |c, v| c.deep_color[0] = v),
+        ("Deep G", COLOR_CHANNEL, |c| c.deep_color[1], |c, v| c.deep_color[1] = v),
+        ("Deep B", COLOR_CHANNEL, |c| c.deep_color[2], |c, v| c.deep_color[2] = v),
+    ];
+    let waves: Vec<FieldAccess<WaterConfig>> = vec![
+        ("Wave 1 Amplitude", WATER_WAVE_AMPLITUDE, |c| c.wave1_amplitude, |c, v| c.wave1_amplitude = v),
+        ("Wave 1 Frequency", WATER_WAVE_FREQUENCY, |c| c.wave1_frequency, |c, v| c.wave1_frequency = v),
+        ("Wave 1 Speed", WATER_WAVE_SPEED, |c| c.wave1_speed, |c, v| c.wave1_speed = v),
+        ("Wave 1 Steepness", WATER_WAVE_STEEPNESS, |c| c.wave1_steepness, |c, v| c.wave1_steepness = v),
+        ("Wave 2 Amplitude", WATER_WAVE_AMPLITUDE, |c| c.wave2_amplitude, |c, v| c.wave2_amplitude = v),
+        ("Wave 2 Frequency", WATER_WAVE_FREQUENCY, |c| c.wave2_frequency, |c, v| c.wave2_frequency = v),
+        ("Wave 2 Speed", WATER_WAVE_SPEED, |c| c.wave2_speed, |c, v| c.wave2_speed = v),
+        ("Wave 2 Steepness", WATER_WAVE_STEEPNESS, |c| c.wave2_steepness, |c, v| c.wave2_steepness = v),
+        ("Wave 3 Amplitude", WATER_WAVE_AMPLITUDE, |c| c.wave3_amplitude, |c, v| c.wave3_amplitude = v),
+        ("Wave 3 Frequency", WATER_WAVE_FREQUENCY, |c| c.wave3_frequency, |c, v| c.wave3_frequency = v),
+        ("Wave 3 Speed", WATER_WAVE_SPEED, |c| c.wave3_speed, |c, v| c.wave3_speed = v),
+        ("Wave 3 Steepness", WATER_WAVE_STEEPNESS, |c| c.wave3_steepness, |c, v| c.wave3_steepness = v),
+        ("Ripple Amplitude", WATER_RIPPLE_AMPLITUDE, |c| c.ripple_amplitude_multiplier, |c, v| c.ripple_amplitude_multiplier = v),
+        ("Ripple Frequency", WATER_RIPPLE_FREQ, |c| c.ripple_freq, |c, v| c.ripple_freq = v),
+        ("Ripple Speed", WATER_RIPPLE_SPEED, |c| c.ripple_speed, |c, v| c.ripple_speed = v),
+    ];
+    let foam: Vec<FieldAccess<WaterConfig>> = vec![
+        ("Shoreline Foam Range", WATER_SHORELINE_FOAM_RANGE, |c| c.shoreline_foam_range, |c, v| c.shoreline_foam_range = v),
+        // Foam can't start above where it's full, so each end pushes the other along
+        ("Crest Foam Min", WATER_CREST_FOAM_MIN, |c| c.crest_foam_min, |c, v| {
+            c.crest_foam_min = v;
+            order_range("Crest Foam", &mut c.crest_foam_min, &mut c.crest_foam_max, false, &mut Vec::new());
+        }),
+        ("Crest Foam Max", WATER_CREST_FOAM_MAX, |c| c.crest_foam_max, |c, v| {
+            c.crest_foam_max = v;
+            order_range("Crest Foam", &mut c.crest_foam_min, &mut c.crest_foam_max, true, &mut Vec::new());
+        }),
+    ];
+    let advanced: Vec<FieldAccess<WaterConfig>> = vec![
+        ("Reflection", WATER_REFLECTION_STRENGTH, |c| c.reflection_strength, |c, v| c.reflection_strength = v),
+        ("Refraction", WATER_REFRACTION_STRENGTH, |c| c.refraction_strength, |c, v| c.refraction_strength = v),
+        ("Transparency", WATER_TRANSPARENCY, |c| c.transparency, |c, v| c.transparency = v),
+        ("Depth Fade", WATER_DEPTH_FADE, |c| c.depth_fade, |c, v| c.depth_fade = v),
+        ("Sparkle Intensity", WATER_SPARKLE_INTENSITY, |c| c.sparkle_intensity, |c, v| c.sparkle_intensity = v),
+        ("Sparkle Threshold", WATER_SPARKLE_THRESHOLD, |c| c.sparkle_threshold, |c, v| c.sparkle_threshold = v),
+        ("Subsurface", WATER_SUBSURFACE_MULTIPLIER, |c| c.subsurface_multiplier, |c, v| c.subsurface_multiplier = v),
+        ("Fresnel Power", WATER_FRESNEL_POWER, |c| c.fresnel_power, |c, v| c.fresnel_power = v),
+        ("Fresnel Multiplier", WATER_FRESNEL_MULTIPLIER, |c| c.fresnel_multiplier, |c, v| c.fresnel_multiplier = v),
+    ];
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Water Properties"}</summary>
-            <div class="property-group">
-                {properties.map(|_config| view! {
-                    <p class="info-text">{"Water configuration available (structure depends on WaterConfig)"}</p>
-                }).unwrap_or_else(|| view! {
+        <PropertySection kind="water" title="Water Properties">
+            {match properties {
+                Some(config) => {
+                    fn water(c: &mut ComponentData) -> &mut WaterConfig {
+                        c.water_properties.get_or_insert_with(WaterConfig::default)
+                    }
+                    view! {
+                        <PropertySection kind="water" title="Colors" default_open=true>
+                            <div class="property-group">{number_fields(colors, &config, on_edit, water)}</div>
+                        </PropertySection>
+                        <PropertySection kind="water" title="Waves" default_open=true>
+                            <div class="property-group">{number_fields(waves, &config, on_edit, water)}</div>
+                        </PropertySection>
+                        <PropertySection kind="water" title="Foam" default_open=true>
+                            <div class="property-group">{number_fields(foam, &config, on_edit, water)}</div>
+                        </PropertySection>
+                        <PropertySection kind="water" title="Advanced">
+                            <div class="property-group">{number_fields(advanced, &config, on_edit, water)}</div>
+                        </PropertySection>
+                    }.into_any()
+                }
+                None => view! {
                     <p class="info-text">{"No water configuration"}</p>
-                })}
+                }.into_any(),
+            }}
+        </PropertySection>
+    }
+}
+
+#[component]
+fn GrassPropertiesPanel(
+    properties: ProceduralGrassProperties,
+    component_id: String,
+    on_edit: Callback<FieldEdit>,
+) -> impl IntoView {
+    let fields: Vec<FieldAccess<ProceduralGrassProperties>> = vec![
+        ("Wind Strength", GRASS_WIND_STRENGTH, |p| p.wind_strength, |p, v| p.wind_strength = v),
+        ("Wind Speed", GRASS_WIND_SPEED, |p| p.wind_speed, |p, v| p.wind_speed = v),
+        ("Blade Height", GRASS_BLADE_HEIGHT, |p| p.blade_height, |p, v| p.blade_height = v),
+        ("Blade Width", GRASS_BLADE_WIDTH, |p| p.blade_width, |p, v| p.blade_width = v),
+        ("Blade Density", GRASS_BLADE_DENSITY, |p| p.blade_density as f32, |p, v| p.blade_density = v.round() as u32),
+        ("Render Distance", GRASS_RENDER_DISTANCE, |p| p.render_distance, |p, v| p.render_distance = v),
+        ("Density Falloff", GRASS_DENSITY_FALLOFF, |p| p.density_falloff, |p, v| p.density_falloff = v),
+        ("Terrain Tint", GRASS_TERRAIN_TINT, |p| p.terrain_tint_strength, |p, v| p.terrain_tint_strength = v),
+    ];
+    
+    view! {
+        <PropertySection kind="grass" title="Grass Properties">
+            <div class="property-group">
+                {number_fields(fields, &properties, on_edit, |c| c.procedural_grass_properties.get_or_insert_with(ProceduralGrassProperties::default))}
             </div>
-        </details>
+        </PropertySection>
     }
 }
 
@@ -396,11 +1309,9 @@ fn CollectablePropertiesPanel(
     properties: CollectableProperties,
     component_id: String,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Collectable Properties"}</summary>
+        <PropertySection kind="collectable" title="Collectable Properties">
             <div class="property-group">
                 <label>
                     {"Model ID: "}
@@ -423,7 +1334,7 @@ fn CollectablePropertiesPanel(
                     <input type="text" value=properties.stat_id.unwrap_or_default() placeholder="(optional reusable stat)" />
                 </label>
             </div>
-        </details>
+        </PropertySection>
     }
 }
 
@@ -432,11 +1343,9 @@ fn PlayerPropertiesPanel(
     properties: PlayerProperties,
     component_id: String,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Player Character Properties"}</summary>
+        <PropertySection kind="player" title="Player Character Properties">
             <div class="property-group">
                 <label>
                     {"Model ID: "}
@@ -450,7 +1359,134 @@ fn PlayerPropertiesPanel(
                 
                 <p class="info-text">{"Default weapon will be mounted on LowerArm.r"}</p>
             </div>
-        </details>
+        </PropertySection>
+    }
+}
+
+#[component]
+fn TriggerPropertiesPanel(
+    properties: TriggerProperties,
+    component_id: String,
+) -> impl IntoView {
+    
+    view! {
+        <PropertySection kind="trigger" title="Trigger Properties">
+            <div class="property-group">
+                <label>
+                    {"Shape: "}
+                    <select>
+                        <option selected=matches!(properties.shape, TriggerShape::Box)>{"Box"}</option>
+                        <option selected=matches!(properties.shape, TriggerShape::Sphere)>{"Sphere"}</option>
+                    </select>
+                </label>
+                
+                <label>
+                    {"Size X: "}
+                    <input type="number" step=TRIGGER_SIZE.step min=TRIGGER_SIZE.min value=properties.size[0] />
+                    {unit_label(TRIGGER_SIZE)}
+                </label>
+                <label>
+                    {"Size Y: "}
+                    <input type="number" step=TRIGGER_SIZE.step min=TRIGGER_SIZE.min value=properties.size[1] />
+                    {unit_label(TRIGGER_SIZE)}
+                </label>
+                <label>
+                    {"Size Z: "}
+                    <input type="number" step=TRIGGER_SIZE.step min=TRIGGER_SIZE.min value=properties.size[2] />
+                    {unit_label(TRIGGER_SIZE)}
+                </label>
+                
+                <label>
+                    {"On Enter Script: "}
+                    <input type="text" value=properties.on_enter_script.unwrap_or_default() placeholder="(scripts/...)" />
+                </label>
+                <label>
+                    {"On Enter Quest ID: "}
+                    <input type="text" value=properties.on_enter_quest_id.unwrap_or_default() />
+                </label>
+            </div>
+        </PropertySection>
+    }
+}
+
+#[component]
+fn AudioSourcePropertiesPanel(
+    properties: AudioSourceProperties,
+    component_id: String,
+) -> impl IntoView {
+    
+    view! {
+        <PropertySection kind="audio-source" title="Audio Source Properties">
+            <div class="property-group">
+                <label>
+                    {"Audio Asset: "}
+                    <input type="text" value=properties.audio_id.clone() readonly=true />
+                </label>
+                
+                <label>
+                    {"Volume: "}
+                    <input type="number" step=AUDIO_VOLUME.step min=AUDIO_VOLUME.min max=AUDIO_VOLUME.max value=properties.volume />
+                    {unit_label(AUDIO_VOLUME)}
+                </label>
+                
+                <label>
+                    {"Spatial Radius: "}
+                    <input type="number" step=AUDIO_SPATIAL_RADIUS.step min=AUDIO_SPATIAL_RADIUS.min value=properties.spatial_radius />
+                    {unit_label(AUDIO_SPATIAL_RADIUS)}
+                </label>
+                
+                <label>
+                    {"Loop: "}
+                    <input type="checkbox" checked=properties.looping />
+                </label>
+                <label>
+                    {"Autoplay: "}
+                    <input type="checkbox" checked=properties.autoplay />
+                </label>
+                
+                <p class="info-text">{"A radius of 0 plays at the same volume everywhere"}</p>
+            </div>
+        </PropertySection>
+    }
+}
+
+#[component]
+fn InstancedModelPropertiesPanel(
+    properties: InstancedModelProperties,
+    component_id: String,
+) -> impl IntoView {
+    
+    view! {
+        <PropertySection kind="instanced-model" title="Instanced Model Properties">
+            <div class="property-group">
+                <label>
+                    {"Instances: "}
+                    <input type="number" value=properties.transforms.len() disabled=true />
+                </label>
+                
+                <p class="info-text">{"All instances are drawn in a single call"}</p>
+            </div>
+        </PropertySection>
+    }
+}
+
+#[component]
+fn GroupPropertiesPanel(
+    properties: GroupProperties,
+    component_id: String,
+) -> impl IntoView {
+    
+    view! {
+        <PropertySection kind="group" title="Group Properties">
+            <div class="property-group">
+                <label>
+                    {"Children: "}
+                    <input type="number" value=properties.child_ids.len() disabled=true />
+                </label>
+                
+                <p class="info-text">{"Moving the group moves its children with it"}</p>
+            </div>
+        </PropertySection>
     }
 }
 
@@ -459,11 +1495,9 @@ fn ScatterPropertiesPanel(
     settings: ScatterSettings,
     component_id: String,
 ) -> impl IntoView {
-    let (is_open, set_is_open) = signal(false);
     
     view! {
-        <details open=is_open.get() on:toggle=move |_| set_is_open.update(|v| *v = !*v)>
-            <summary>{"Scatter Settings"}</summary>
+        <PropertySection kind="scatter" title="Scatter Settings">
             <div class="property-group">
                 <label>
                     {"Density: "}
@@ -472,7 +1506,8 @@ fn ScatterPropertiesPanel(
                 
                 <label>
                     {"Radius: "}
-                    <input type="number" step="0.5" min="0" value=settings.radius />
+                    <input type="number" step=SCATTER_RADIUS.step min=SCATTER_RADIUS.min value=settings.radius />
+                    {unit_label(SCATTER_RADIUS)}
                 </label>
                 
                 <label>
@@ -480,6 +1515,6 @@ fn ScatterPropertiesPanel(
                     <input type="number" step="1" min="0" value=settings.seed />
                 </label>
             </div>
-        </details>
+        </PropertySection>
     }
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+//! One confirmation dialog for every action that throws work away, so each asks the same way
+//! and can be told not to ask again.
+
+use leptos::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::storage;
+
+/// Kinds of destructive action, each with its own "don't ask again" setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfirmKind {
+    CloseChat,
+    DeleteAsset,
+    DeleteComponent,
+    ResetProperties,
+    /// Regenerating or editing a message drops its reply and rolls the scene back.
+    ReplaceReply,
+    /// Throwing away project edits that only exist in this browser.
+    DiscardChanges,
+}
+
+impl ConfirmKind {
+    fn storage_key(&self) -> &'static str {
+        match self {
+            ConfirmKind::CloseChat => "entropy-chat:skip-confirm:close-chat",
+            ConfirmKind::DeleteAsset => "entropy-chat:skip-confirm:delete-asset",
+            ConfirmKind::DeleteComponent => "entropy-chat:skip-confirm:delete-component",
+            ConfirmKind::ResetProperties => "entropy-chat:skip-confirm:reset-properties",
+            ConfirmKind::ReplaceReply => "entropy-chat:skip-confirm:replace-reply",
+            ConfirmKind::DiscardChanges => "entropy-chat:skip-confirm:discard-changes",
+        }
+    }
+
+    fn is_skipped(&self) -> bool {
+        storage::get_item(self.storage_key()).as_deref() == Some("true")
+    }
+}
+
+#[derive(Clone)]
+pub struct ConfirmRequest {
+    pub kind: ConfirmKind,
+    pub title: String,
+    /// What will be lost, in plain words.
+    pub description: String,
+    pub confirm_label: String,
+    pub on_confirm: Rc<dyn Fn()>,
+}
+
+thread_local! {
+    // Set by the mounted dialog
+    static REQUEST_HANDLER: Cell<Option<WriteSignal<Option<ConfirmRequest>, LocalStorage>>> = const { Cell::new(None) };
+}
+
+/// Runs `on_confirm` once the user agrees, or straight away if they chose not to be asked for this kind.
+pub fn confirm_action(
+    kind: ConfirmKind,
+    title: impl Into<String>,
+    description: impl Into<String>,
+    confirm_label: impl Into<String>,
+    on_confirm: impl Fn() + 'static,
+) {
+    if kind.is_skipped() {
+        on_confirm();
+        return;
+    }
+
+    let request = ConfirmRequest {
+        kind,
+        title: title.into(),
+        description: description.into(),
+        confirm_label: confirm_label.into(),
+        on_confirm: Rc::new(on_confirm),
+    };
+
+    match REQUEST_HANDLER.with(|cell| cell.get()) {
+        Some(handler) => handler.set(Some(request)),
+        None => {
+            // No dialog mounted, fall back to the browser's
+            let confirmed = web_sys::window()
+                .and_then(|window| window.confirm_with_message(&format!("{}\n\n{}", request.title, request.description)).ok())
+                .unwrap_or(false);
+            if confirmed {
+                (request.on_confirm)();
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ConfirmDialog() -> impl IntoView {
+    let (request, set_request) = signal_local::<Option<ConfirmRequest>>(None);
+    let (dont_ask_again, set_dont_ask_again) = signal(false);
+    REQUEST_HANDLER.with(|cell| cell.set(Some(set_request)));
+
+    let close = move || {
+        set_request.set(None);
+        set_dont_ask_again.set(false);
+    };
+
+    let on_confirm = move |_| {
+        if let Some(request) = request.get_untracked() {
+            if dont_ask_again.get_untracked() {
+                storage::set_item(request.kind.storage_key(), "true");
+            }
+            close();
+            (request.on_confirm)();
+        }
+    };
+
+    view! {
+        <Show when=move || request.with(|r| r.is_some())>
+            <div class="modal-backdrop">
+                <div class="modal">
+                    <h3>{move || request.with(|r| r.as_ref().map(|r| r.title.clone()).unwrap_or_default())}</h3>
+                    <p>{move || request.with(|r| r.as_ref().map(|r| r.description.clone()).unwrap_or_default())}</p>
+                    <label class="dont-ask-again">
+                        <input
+                            type="checkbox"
+                            prop:checked=dont_ask_again
+                            on:change=move |ev| set_dont_ask_again.set(event_target_checked(&ev))
+                        />
+                        {"Don't ask again"}
+                    </label>
+                    <div class="modal-actions">
+                        <button on:click=move |_| close()>{"Cancel"}</button>
+                        <button class="primary-btn" on:click=on_confirm>
+                            {move || request.with(|r| r.as_ref().map(|r| r.confirm_label.clone()).unwrap_or_default())}
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
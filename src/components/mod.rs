@@ -1,2 +1,4 @@
 pub mod component_browser;
-pub mod assets_browser;
\ No newline at end of file
+pub mod assets_browser;
+pub mod chat_message;
+pub mod confirm_dialog;
@@ -0,0 +1,107 @@
+//! Animated time of day: moves the sun across the level's procedural sky and shifts its colors
+//! through sunrise, day, sunset and night. The level's own sky settings are the midday look.
+
+use entropy_engine::core::editor::Editor;
+use entropy_engine::helpers::saved_data::{DayNightCycleConfig, ProceduralSkyConfig};
+use std::cell::Cell;
+
+/// A new cycle's length in seconds and the hour it starts from.
+pub const DEFAULT_CYCLE_LENGTH: f32 = 600.0;
+pub const DEFAULT_START_TIME: f32 = 8.0;
+
+const NIGHT_HORIZON: [f32; 3] = [0.03, 0.04, 0.09];
+const NIGHT_ZENITH: [f32; 3] = [0.0, 0.0, 0.02];
+const SUNSET_HORIZON: [f32; 3] = [0.95, 0.45, 0.2];
+const SUNSET_SUN: [f32; 3] = [1.0, 0.5, 0.25];
+/// Sun elevation (sine) where night has fully set in, slightly below the horizon so dusk lingers.
+const NIGHT_ELEVATION: f32 = -0.15;
+/// Sun elevation where the sky reaches its full daytime colors.
+const DAY_ELEVATION: f32 = 0.25;
+
+fn lerp3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Direction towards the sun at `hour` (0-24). It rises in +X at 6, peaks at 12 and sets in -X at 18,
+/// tilted slightly south so it never passes straight overhead.
+pub fn sun_direction(hour: f32) -> [f32; 3] {
+    let angle = (hour - 6.0) / 12.0 * std::f32::consts::PI;
+    let direction = [angle.cos(), angle.sin(), 0.3];
+    let length = (direction[0] * direction[0] + direction[1] * direction[1] + direction[2] * direction[2]).sqrt();
+    direction.map(|v| v / length)
+}
+
+/// The sky at `hour`, built from the level's daytime sky.
+pub fn sky_at(day: &ProceduralSkyConfig, hour: f32) -> ProceduralSkyConfig {
+    let sun_direction = sun_direction(hour);
+    let elevation = sun_direction[1];
+    let daylight = smoothstep(NIGHT_ELEVATION, DAY_ELEVATION, elevation);
+    // Strongest with the sun on the horizon, gone once it's well up or down
+    let sunset = (1.0 - (elevation.abs() / DAY_ELEVATION)).clamp(0.0, 1.0);
+
+    let horizon_color = lerp3(lerp3(NIGHT_HORIZON, day.horizon_color, daylight), SUNSET_HORIZON, sunset * 0.7);
+    ProceduralSkyConfig {
+        horizon_color,
+        zenith_color: lerp3(NIGHT_ZENITH, day.zenith_color, daylight),
+        sun_direction,
+        sun_color: lerp3(day.sun_color, SUNSET_SUN, sunset),
+        sun_intensity: day.sun_intensity * daylight,
+        ..day.clone()
+    }
+}
+
+thread_local! {
+    // When the running cycle was at its start time, set by the first frame after it's enabled or restarted
+    static CYCLE_STARTED_AT: Cell<Option<f64>> = const { Cell::new(None) };
+}
+
+/// Starts the cycle over from its start time on the next frame, e.g. when it's switched on or play starts.
+pub fn restart() {
+    CYCLE_STARTED_AT.with(|started_at| started_at.set(None));
+}
+
+/// Hour of day (0-24) the cycle shows `elapsed_ms` after it started, or the hour it's paused at.
+pub fn hour_at(config: &DayNightCycleConfig, elapsed_ms: f64) -> f32 {
+    if let Some(hour) = config.paused_at {
+        return hour.rem_euclid(24.0);
+    }
+    let length_ms = (config.cycle_length.max(1.0) as f64) * 1000.0;
+    let progress = (elapsed_ms.max(0.0) % length_ms / length_ms) as f32;
+    (config.start_time + progress * 24.0).rem_euclid(24.0)
+}
+
+/// Pushes the current time of day into the preview sky. Does nothing unless the level has an enabled cycle,
+/// or while `playing` unless the cycle is set to run in play mode.
+pub fn apply(editor: &mut Editor, now_ms: f64, playing: bool) {
+    let Some(level) = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0))
+    else {
+        return;
+    };
+    let Some(config) = level.day_night_cycle.as_ref()
+        .filter(|config| config.enabled && (!playing || config.run_in_play_mode))
+    else {
+        restart();
+        return;
+    };
+
+    let started_at = CYCLE_STARTED_AT.with(|started_at| {
+        let at = started_at.get().unwrap_or(now_ms);
+        started_at.set(Some(at));
+        at
+    });
+    let day = level.procedural_sky.clone().unwrap_or_default();
+    let mut sky = sky_at(&day, hour_at(config, now_ms - started_at));
+    if let Some(weather) = level.weather.as_ref() {
+        sky = crate::weather::darken_sky(&sky, weather);
+    }
+    if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+        renderer_state.update_procedural_sky(&gpu_resources.queue, &sky);
+    }
+}
@@ -0,0 +1,32 @@
+//! Saves generated content to the user's machine through a temporary object URL.
+
+use wasm_bindgen::JsCast;
+
+pub fn download_bytes(file_name: &str, mime_type: &str, bytes: &[u8]) -> Result<(), String> {
+    let parts = js_sys::Array::new();
+    parts.push(&js_sys::Uint8Array::from(bytes));
+
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(|_| "Couldn't create blob".to_string())?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "Couldn't create object URL".to_string())?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+    let anchor: web_sys::HtmlAnchorElement = document.create_element("a")
+        .map_err(|_| "Couldn't create link".to_string())?
+        .unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
+
+pub fn download_text(file_name: &str, mime_type: &str, text: &str) -> Result<(), String> {
+    download_bytes(file_name, mime_type, text.as_bytes())
+}
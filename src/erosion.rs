@@ -0,0 +1,153 @@
+//! Droplet-based hydraulic erosion for generated heightmaps.
+//!
+//! Each droplet runs downhill over the height field, picking up sediment where it speeds up
+//! and dropping it where it slows or fills a pit, which carves valleys and smooths slopes.
+//!
+//! It runs on the main thread in batches rather than in a web worker: the app has no worker build set up,
+//! and yielding between batches keeps the page responsive while the chat shows progress.
+
+use std::time::Duration;
+
+/// How much a droplet keeps its previous direction instead of following the slope.
+const INERTIA: f32 = 0.05;
+const SEDIMENT_CAPACITY_FACTOR: f32 = 4.0;
+const MIN_SEDIMENT_CAPACITY: f32 = 0.01;
+const ERODE_SPEED: f32 = 0.3;
+const DEPOSIT_SPEED: f32 = 0.3;
+const EVAPORATE_SPEED: f32 = 0.01;
+const GRAVITY: f32 = 4.0;
+const MAX_DROPLET_LIFETIME: usize = 30;
+/// Droplets simulated between yields to the browser, so the page stays responsive.
+const DROPLETS_PER_BATCH: u32 = 2000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ErosionSettings {
+    /// Number of droplets to simulate.
+    pub iterations: u32,
+    /// Scales how much material each droplet can carry away.
+    pub strength: f32,
+    pub seed: u32,
+}
+
+/// Small deterministic generator so the same seed always erodes the same way.
+/// Also scatters forests, for the same reason.
+pub(crate) struct XorShift(pub(crate) u32);
+
+impl XorShift {
+    /// Uniform in [0, 1).
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+/// Bilinear height and gradient at a point inside the grid.
+fn height_and_gradient(heights: &[f32], width: usize, x: f32, y: f32) -> (f32, f32, f32) {
+    let (cell_x, cell_y) = (x as usize, y as usize);
+    let (u, v) = (x - cell_x as f32, y - cell_y as f32);
+
+    let index = cell_y * width + cell_x;
+    let nw = heights[index];
+    let ne = heights[index + 1];
+    let sw = heights[index + width];
+    let se = heights[index + width + 1];
+
+    let gradient_x = (ne - nw) * (1.0 - v) + (se - sw) * v;
+    let gradient_y = (sw - nw) * (1.0 - u) + (se - ne) * u;
+    let height = nw * (1.0 - u) * (1.0 - v) + ne * u * (1.0 - v) + sw * (1.0 - u) * v + se * u * v;
+
+    (height, gradient_x, gradient_y)
+}
+
+/// Spreads `amount` over the four corners of the cell containing (x, y).
+fn add_bilinear(heights: &mut [f32], width: usize, x: f32, y: f32, amount: f32) {
+    let (cell_x, cell_y) = (x as usize, y as usize);
+    let (u, v) = (x - cell_x as f32, y - cell_y as f32);
+
+    let index = cell_y * width + cell_x;
+    heights[index] += amount * (1.0 - u) * (1.0 - v);
+    heights[index + 1] += amount * u * (1.0 - v);
+    heights[index + width] += amount * (1.0 - u) * v;
+    heights[index + width + 1] += amount * u * v;
+}
+
+fn simulate_droplet(heights: &mut [f32], width: usize, height: usize, strength: f32, rng: &mut XorShift) {
+    let max_x = (width - 1) as f32;
+    let max_y = (height - 1) as f32;
+
+    let mut x = rng.next_f32() * max_x;
+    let mut y = rng.next_f32() * max_y;
+    let (mut dir_x, mut dir_y) = (0.0f32, 0.0f32);
+    let mut speed = 1.0f32;
+    let mut water = 1.0f32;
+    let mut sediment = 0.0f32;
+
+    for _ in 0..MAX_DROPLET_LIFETIME {
+        let (current_height, gradient_x, gradient_y) = height_and_gradient(heights, width, x, y);
+
+        dir_x = dir_x * INERTIA - gradient_x * (1.0 - INERTIA);
+        dir_y = dir_y * INERTIA - gradient_y * (1.0 - INERTIA);
+        let length = (dir_x * dir_x + dir_y * dir_y).sqrt();
+        if length <= f32::EPSILON {
+            break;
+        }
+        dir_x /= length;
+        dir_y /= length;
+
+        let (old_x, old_y) = (x, y);
+        x += dir_x;
+        y += dir_y;
+        if x < 0.0 || y < 0.0 || x >= max_x || y >= max_y {
+            break;
+        }
+
+        let delta_height = height_and_gradient(heights, width, x, y).0 - current_height;
+        let capacity = (-delta_height * speed * water * SEDIMENT_CAPACITY_FACTOR).max(MIN_SEDIMENT_CAPACITY);
+
+        if sediment > capacity || delta_height > 0.0 {
+            // Uphill: fill the pit behind us, otherwise drop what we can't carry
+            let deposit = if delta_height > 0.0 {
+                delta_height.min(sediment)
+            } else {
+                (sediment - capacity) * DEPOSIT_SPEED
+            };
+            sediment -= deposit;
+            add_bilinear(heights, width, old_x, old_y, deposit);
+        } else {
+            let erode = ((capacity - sediment) * ERODE_SPEED * strength).min(-delta_height);
+            sediment += erode;
+            add_bilinear(heights, width, old_x, old_y, -erode);
+        }
+
+        speed = (speed * speed - delta_height * GRAVITY).max(0.0).sqrt();
+        water *= 1.0 - EVAPORATE_SPEED;
+    }
+}
+
+/// Erodes `heights` (row-major, `width * height`, normalized 0..1) in place.
+/// Yields to the browser between batches and reports progress from 0 to 1.
+pub async fn erode(heights: &mut [f32], width: usize, height: usize, settings: ErosionSettings, on_progress: impl Fn(f32)) {
+    if width < 2 || height < 2 || heights.len() != width * height {
+        return;
+    }
+
+    let mut rng = XorShift(settings.seed.max(1));
+    let mut done = 0;
+
+    while done < settings.iterations {
+        let batch = DROPLETS_PER_BATCH.min(settings.iterations - done);
+        for _ in 0..batch {
+            simulate_droplet(heights, width, height, settings.strength, &mut rng);
+        }
+        done += batch;
+
+        on_progress(done as f32 / settings.iterations as f32);
+        let _ = wasm_timer::Delay::new(Duration::from_millis(0)).await;
+    }
+
+    for value in heights.iter_mut() {
+        *value = value.clamp(0.0, 1.0);
+    }
+}
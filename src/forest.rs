@@ -0,0 +1,36 @@
+//! Scatters procedural trees over a circle of ground for the `spawnForest` tool. The layout only depends
+//! on the forest's saved parameters, so a forest can be regenerated (or nudged) without storing every tree by hand.
+
+use entropy_engine::helpers::saved_data::{ForestProperties, InstanceTransform};
+
+use crate::erosion::XorShift;
+
+/// A new forest's layout, before the tool's arguments are applied.
+pub const DEFAULT_RADIUS: f32 = 30.0;
+pub const DEFAULT_TREE_COUNT: u32 = 50;
+pub const DEFAULT_SIZE_VARIANCE: f32 = 0.3;
+
+/// One transform per tree, spread evenly over the forest's circle with random yaw and size.
+/// `ground` gives the terrain height at an X/Z point, trees keep the center's height where it returns `None`.
+pub fn scatter(forest: &ForestProperties, ground: impl Fn(f32, f32) -> Option<f32>) -> Vec<InstanceTransform> {
+    // Mixed so seeds 0 and 1 don't produce the same forest, xorshift can't start from 0
+    let mut rng = XorShift((forest.seed ^ 0x9E37_79B9).max(1));
+    let variance = forest.size_variance.clamp(0.0, 1.0);
+
+    (0..forest.count).map(|_| {
+        // sqrt keeps the density even instead of bunching trees at the center
+        let distance = forest.radius * rng.next_f32().sqrt();
+        let angle = rng.next_f32() * std::f32::consts::TAU;
+        let x = forest.center[0] + distance * angle.cos();
+        let z = forest.center[2] + distance * angle.sin();
+        let y = if forest.snap_to_terrain { ground(x, z).unwrap_or(forest.center[1]) } else { forest.center[1] };
+
+        let yaw = rng.next_f32() * 360.0;
+        let size = 1.0 + (rng.next_f32() * 2.0 - 1.0) * variance;
+        InstanceTransform {
+            position: [x, y, z],
+            rotation: [0.0, yaw, 0.0],
+            scale: [size, size, size],
+        }
+    }).collect()
+}
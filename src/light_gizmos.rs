@@ -0,0 +1,121 @@
+//! Bulb gizmos for point lights, with a wireframe sphere showing how far each light reaches. Lights have no
+//! mesh of their own, so without these they can only be placed by coordinates. Gizmos are editor-only: the
+//! renderer skips them in play mode and they are never part of the saved project.
+
+use entropy_engine::core::editor::Editor;
+use entropy_engine::handlers::handle_add_light_gizmo;
+use entropy_engine::helpers::saved_data::{ComponentData, ComponentKind};
+use std::cell::RefCell;
+
+/// Range shown for a light with no matching GPU light, same as a newly spawned one.
+const FALLBACK_RANGE: f32 = 10.0;
+
+/// A point light as the gizmo shows it.
+struct LightMarker {
+    component_id: String,
+    position: [f32; 3],
+    color: [f32; 3],
+    range: f32,
+}
+
+thread_local! {
+    // Markers the gizmos were last synced to, so unchanged frames skip the rebuild
+    static LAST_MARKERS: RefCell<Vec<LightMarker>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The level's visible point lights, each with the range of its renderer light.
+fn visible_lights(editor: &Editor) -> impl Iterator<Item = (&ComponentData, f32)> {
+    let components = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_ref())
+        .map(|components| components.as_slice())
+        .unwrap_or_default();
+    let point_lights = editor.renderer_state.as_ref().map(|renderer_state| renderer_state.point_lights.as_slice()).unwrap_or_default();
+
+    components.iter()
+        .filter(|c| c.kind == Some(ComponentKind::PointLight) && !c.hidden)
+        .map(move |component| {
+            let range = point_lights.iter()
+                .find(|light| light.id == component.id)
+                .map(|light| light.max_distance)
+                .unwrap_or(FALLBACK_RANGE);
+            (component, range)
+        })
+}
+
+fn marker_color(component: &ComponentData) -> [f32; 3] {
+    component.light_properties.as_ref()
+        .map(|light| [light.color[0], light.color[1], light.color[2]])
+        .unwrap_or([1.0, 1.0, 1.0])
+}
+
+/// Whether any light was added, removed, moved, recolored or resized since the last sync.
+fn lights_changed(editor: &Editor, last: &[LightMarker]) -> bool {
+    // Gizmos dropped with the scene, e.g. on a project switch, need adding back even if the lights match
+    let gizmo_count = editor.renderer_state.as_ref().map(|renderer_state| renderer_state.light_gizmos.len()).unwrap_or(0);
+    if gizmo_count != last.len() {
+        return true;
+    }
+
+    let mut count = 0;
+    for (component, range) in visible_lights(editor) {
+        let Some(marker) = last.get(count) else { return true };
+        if marker.component_id != component.id
+            || marker.position != component.generic_properties.position
+            || marker.color != marker_color(component)
+            || marker.range != range
+        {
+            return true;
+        }
+        count += 1;
+    }
+    count != last.len()
+}
+
+fn markers(editor: &Editor) -> Vec<LightMarker> {
+    visible_lights(editor)
+        .map(|(component, range)| LightMarker {
+            component_id: component.id.clone(),
+            position: component.generic_properties.position,
+            color: marker_color(component),
+            range,
+        })
+        .collect()
+}
+
+/// Adds, moves, recolors and removes gizmos to match the point lights in the level. Called once per frame,
+/// it only rebuilds the markers and touches the GPU when a light changed.
+pub fn sync(editor: &mut Editor) {
+    if !LAST_MARKERS.with_borrow(|last| lights_changed(editor, last)) {
+        return;
+    }
+    let markers = markers(editor);
+    let (Some(renderer_state), Some(gpu_resources), Some(camera)) =
+        (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref(), editor.camera.as_ref())
+    else {
+        return;
+    };
+
+    renderer_state.light_gizmos.retain(|gizmo| markers.iter().any(|m| m.component_id == gizmo.id));
+    for marker in markers.iter() {
+        match renderer_state.light_gizmos.iter_mut().find(|gizmo| gizmo.id == marker.component_id) {
+            Some(gizmo) => {
+                if gizmo.position != marker.position || gizmo.color != marker.color || gizmo.range != marker.range {
+                    gizmo.update(&gpu_resources.queue, marker.position, marker.color, marker.range);
+                }
+            }
+            None => handle_add_light_gizmo(
+                renderer_state,
+                &gpu_resources.device,
+                &gpu_resources.queue,
+                marker.component_id.clone(),
+                marker.position,
+                marker.color,
+                marker.range,
+                camera,
+            ),
+        }
+    }
+    LAST_MARKERS.set(markers);
+}
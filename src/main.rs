@@ -6,6 +6,22 @@ use leptos::wasm_bindgen::JsCast;
 use crate::app::App;
 pub mod app;
 pub mod components;
+pub mod storage;
+pub mod erosion;
+pub mod project_sync;
+pub mod offline_cache;
+pub mod download;
+pub mod property_fields;
+pub mod terrain_edit;
+pub mod day_night;
+pub mod weather;
+pub mod scene_diff;
+pub mod forest;
+pub mod camera_move;
+pub mod orbit_camera;
+pub mod selection_bounds;
+pub mod light_gizmos;
+pub mod tool_result;
 
 fn main() {
     console_error_panic_hook::set_once();
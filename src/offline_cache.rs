@@ -0,0 +1,115 @@
+//! IndexedDB copy of each project's latest `SavedState`, written before every save, so edits
+//! survive a failed PATCH or a closed tab and can be pushed once the connection is back.
+
+use entropy_engine::helpers::saved_data::SavedState;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+const DB_NAME: &str = "entropy-chat";
+const DB_VERSION: u32 = 1;
+const STORE_NAME: &str = "projects";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedProject {
+    pub project_id: String,
+    pub saved_state: SavedState,
+    /// Version this state was saved (or was going to be saved) as.
+    pub version: u64,
+    /// False until the server has accepted this state.
+    pub synced: bool,
+}
+
+/// Waits for an IndexedDB request to finish and returns its result.
+async fn request_done(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise).await?;
+    request.result()
+}
+
+async fn open_db() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("No window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB is unavailable"))?;
+    let request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+    // First open (or a version bump) creates the store
+    let on_upgrade = Closure::once_into_js(move |event: web_sys::Event| {
+        let request: IdbOpenDbRequest = event.target().unwrap().unchecked_into();
+        if let Ok(result) = request.result() {
+            let db: IdbDatabase = result.unchecked_into();
+            if !db.object_store_names().contains(STORE_NAME) {
+                let _ = db.create_object_store(STORE_NAME);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    Ok(request_done(&request).await?.unchecked_into())
+}
+
+async fn store(mode: IdbTransactionMode) -> Result<IdbObjectStore, JsValue> {
+    open_db().await?
+        .transaction_with_str_and_mode(STORE_NAME, mode)?
+        .object_store(STORE_NAME)
+}
+
+fn parse_entry(value: JsValue) -> Option<CachedProject> {
+    serde_json::from_str(&value.as_string()?).ok()
+}
+
+pub async fn put(entry: &CachedProject) -> Result<(), JsValue> {
+    let json = serde_json::to_string(entry).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let request = store(IdbTransactionMode::Readwrite).await?
+        .put_with_key(&JsValue::from_str(&json), &JsValue::from_str(&entry.project_id))?;
+    request_done(&request).await?;
+    Ok(())
+}
+
+pub async fn get(project_id: &str) -> Option<CachedProject> {
+    let request = store(IdbTransactionMode::Readonly).await.ok()?
+        .get(&JsValue::from_str(project_id)).ok()?;
+    parse_entry(request_done(&request).await.ok()?)
+}
+
+/// Every cached project whose latest state hasn't reached the server.
+pub async fn unsynced() -> Vec<CachedProject> {
+    let request = match store(IdbTransactionMode::Readonly).await.and_then(|store| store.get_all()) {
+        Ok(request) => request,
+        Err(_) => return Vec::new(),
+    };
+    let entries: js_sys::Array = match request_done(&request).await {
+        Ok(result) => result.unchecked_into(),
+        Err(_) => return Vec::new(),
+    };
+
+    entries.iter()
+        .filter_map(parse_entry)
+        .filter(|entry| !entry.synced)
+        .collect()
+}
+
+/// Marks the cached state as saved, unless a newer edit has replaced it in the meantime.
+pub async fn mark_synced(project_id: &str, version: u64) {
+    if let Some(mut entry) = get(project_id).await {
+        if entry.version == version && !entry.synced {
+            entry.synced = true;
+            let _ = put(&entry).await;
+        }
+    }
+}
+
+pub async fn remove(project_id: &str) {
+    if let Ok(store) = store(IdbTransactionMode::Readwrite).await {
+        if let Ok(request) = store.delete(&JsValue::from_str(project_id)) {
+            let _ = request_done(&request).await;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Orbit controls for the preview camera: turn around a pivot, dolly towards it and pan it sideways,
+//! the way DCC tools inspect a single object. Works on `editor.camera` directly, the pivot is kept by the caller.
+
+use entropy_engine::core::editor::Editor;
+use nalgebra::{Point3, Vector3};
+
+/// Radians turned per pixel dragged.
+const ORBIT_SPEED: f32 = 0.005;
+/// Stops short of straight up or down, where yaw stops meaning anything.
+const MAX_PITCH: f32 = 1.5;
+/// Share of the pivot distance panned per pixel, so panning feels the same near and far.
+const PAN_SPEED: f32 = 0.0015;
+/// Share of the pivot distance dollied per pixel of wheel `deltaY`.
+const ZOOM_SPEED: f32 = 0.001;
+const MIN_DISTANCE: f32 = 0.5;
+const MAX_DISTANCE: f32 = 5000.0;
+/// Pivot used when orbiting starts with nothing under the cursor, this far in front of the camera.
+const DEFAULT_PIVOT_DISTANCE: f32 = 20.0;
+
+/// A point straight ahead of the camera to orbit around until something is clicked.
+pub fn default_pivot(editor: &Editor) -> Option<Point3<f32>> {
+    let camera = editor.camera.as_ref()?;
+    Some(camera.position + camera.direction.normalize() * DEFAULT_PIVOT_DISTANCE)
+}
+
+/// Puts the camera `distance` from the pivot at `yaw`/`pitch`, looking at it.
+fn place(editor: &mut Editor, pivot: Point3<f32>, yaw: f32, pitch: f32, distance: f32) {
+    let Some(camera) = editor.camera.as_mut() else { return };
+    let offset = Vector3::new(pitch.cos() * yaw.sin(), pitch.sin(), pitch.cos() * yaw.cos()) * distance;
+    camera.position = pivot + offset;
+    camera.direction = -offset.normalize();
+}
+
+/// Yaw, pitch and distance of the camera around the pivot.
+fn spherical(editor: &Editor, pivot: Point3<f32>) -> Option<(f32, f32, f32)> {
+    let camera = editor.camera.as_ref()?;
+    let offset = camera.position - pivot;
+    let distance = offset.norm().max(MIN_DISTANCE);
+    Some((offset.x.atan2(offset.z), (offset.y / distance).clamp(-1.0, 1.0).asin(), distance))
+}
+
+/// Turns the camera around the pivot by a mouse drag.
+pub fn orbit(editor: &mut Editor, pivot: Point3<f32>, dx: f32, dy: f32) {
+    let Some((yaw, pitch, distance)) = spherical(editor, pivot) else { return };
+    let pitch = (pitch + dy * ORBIT_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    place(editor, pivot, yaw - dx * ORBIT_SPEED, pitch, distance);
+}
+
+/// Moves the camera towards (negative `delta`) or away from the pivot.
+pub fn zoom(editor: &mut Editor, pivot: Point3<f32>, delta: f32) {
+    let Some((yaw, pitch, distance)) = spherical(editor, pivot) else { return };
+    let distance = (distance * (1.0 + delta * ZOOM_SPEED)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    place(editor, pivot, yaw, pitch, distance);
+}
+
+/// Slides the camera and pivot together across the view by a mouse drag. Returns the moved pivot.
+pub fn pan(editor: &mut Editor, pivot: Point3<f32>, dx: f32, dy: f32) -> Point3<f32> {
+    let Some(camera) = editor.camera.as_mut() else { return pivot };
+    let distance = (camera.position - pivot).norm().max(MIN_DISTANCE);
+    let forward = camera.direction.normalize();
+    let right = forward.cross(&Vector3::y()).normalize();
+    let up = right.cross(&forward).normalize();
+
+    // Dragging moves the scene with the cursor, so the camera goes the other way
+    let shift = (-right * dx + up * dy) * distance * PAN_SPEED;
+    camera.position += shift;
+    pivot + shift
+}
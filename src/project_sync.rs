@@ -0,0 +1,225 @@
+//! Version bookkeeping for project saves. Every save says which version it was based on,
+//! so the server can refuse it when another tab saved the same project in between. Saves of one
+//! project go out one at a time, so this tab's own saves never race each other to the server.
+
+use entropy_engine::helpers::saved_data::SavedState;
+use leptos::prelude::*;
+use serde_json::{Map, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+thread_local! {
+    // Last version of each project the server acknowledged, or that it was loaded at
+    static KNOWN_VERSIONS: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+    // Newest state waiting for the save in flight to finish, by project. Saves are full snapshots,
+    // so a newer one replaces whatever was waiting
+    static QUEUED_SAVES: RefCell<HashMap<String, SavedState>> = RefCell::new(HashMap::new());
+    // Projects with a save request in flight
+    static SAVING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+    // Set by the app so a rejected save can open the conflict dialog
+    static CONFLICT_HANDLER: Cell<Option<WriteSignal<Option<String>>>> = const { Cell::new(None) };
+    // The project as the server last had it, what local edits are measured against when rebasing
+    static BASE_STATES: RefCell<HashMap<String, Value>> = RefCell::new(HashMap::new());
+}
+
+/// Records a version the server has: the one a project was loaded at, or the one a save was accepted as.
+pub fn record_version(project_id: &str, version: u64) {
+    KNOWN_VERSIONS.with(|versions| {
+        versions.borrow_mut().insert(project_id.to_string(), version);
+    });
+}
+
+/// Last version the server acknowledged, what the next save is based on.
+pub fn acknowledged_version(project_id: &str) -> u64 {
+    KNOWN_VERSIONS.with(|versions| versions.borrow().get(project_id).copied().unwrap_or(0))
+}
+
+/// Queues `saved_state` to be saved. Returns true when no save of the project is in flight, in which
+/// case the caller sends the queue with `next_save`; otherwise the save in flight picks it up.
+pub fn queue_save(project_id: &str, saved_state: SavedState) -> bool {
+    QUEUED_SAVES.with(|queued| queued.borrow_mut().insert(project_id.to_string(), saved_state));
+    SAVING.with(|saving| saving.borrow_mut().insert(project_id.to_string()))
+}
+
+/// Takes the newest queued state to send next, or ends the project's save run when nothing is waiting.
+pub fn next_save(project_id: &str) -> Option<SavedState> {
+    let next = QUEUED_SAVES.with(|queued| queued.borrow_mut().remove(project_id));
+    if next.is_none() {
+        SAVING.with(|saving| saving.borrow_mut().remove(project_id));
+    }
+    next
+}
+
+/// Ends the project's save run after a failed save, handing back the state that was still waiting.
+pub fn abandon_saves(project_id: &str) -> Option<SavedState> {
+    SAVING.with(|saving| saving.borrow_mut().remove(project_id));
+    QUEUED_SAVES.with(|queued| queued.borrow_mut().remove(project_id))
+}
+
+pub fn set_conflict_handler(handler: WriteSignal<Option<String>>) {
+    CONFLICT_HANDLER.with(|cell| cell.set(Some(handler)));
+}
+
+/// Tells the app that a save of `project_id` was rejected because the server copy moved on.
+pub fn report_conflict(project_id: &str) {
+    if let Some(handler) = CONFLICT_HANDLER.with(|cell| cell.get()) {
+        handler.set(Some(project_id.to_string()));
+    }
+}
+
+/// Remembers `saved_state` as the server's copy of the project, after loading it or saving over it.
+pub fn record_base(project_id: &str, saved_state: &SavedState) {
+    if let Ok(value) = serde_json::to_value(saved_state) {
+        BASE_STATES.with(|bases| bases.borrow_mut().insert(project_id.to_string(), value));
+    }
+}
+
+/// Replays the edits made in `local` since the last known server copy on top of `remote`, the copy that
+/// was saved elsewhere in the meantime. Where both sides changed the same value, the local one wins.
+pub fn rebase(project_id: &str, local: &SavedState, remote: &SavedState) -> SavedState {
+    let base = BASE_STATES.with(|bases| bases.borrow().get(project_id).cloned());
+    let (Ok(local_value), Ok(remote_value)) = (serde_json::to_value(local), serde_json::to_value(remote)) else {
+        return local.clone();
+    };
+    serde_json::from_value(merge_value(base.as_ref(), &local_value, &remote_value)).unwrap_or_else(|_| local.clone())
+}
+
+/// Three-way merge of one value. Objects merge key by key and lists of objects with ids (components,
+/// assets) merge entry by entry, so edits to different components or assets don't clobber each other.
+pub fn merge_value(base: Option<&Value>, local: &Value, remote: &Value) -> Value {
+    if base == Some(local) {
+        return remote.clone();
+    }
+    if base == Some(remote) || local == remote {
+        return local.clone();
+    }
+
+    match (local, remote) {
+        (Value::Object(local_map), Value::Object(remote_map)) => {
+            let base_map = base.and_then(Value::as_object);
+            Value::Object(merge_entries(base_map, local_map, remote_map))
+        }
+        (Value::Array(local_items), Value::Array(remote_items)) if has_ids(local_items) && has_ids(remote_items) => {
+            let base_items = base.and_then(Value::as_array).filter(|items| has_ids(items));
+            Value::Array(merge_by_id(base_items, local_items, remote_items))
+        }
+        _ => local.clone(),
+    }
+}
+
+fn merge_entries(base: Option<&Map<String, Value>>, local: &Map<String, Value>, remote: &Map<String, Value>) -> Map<String, Value> {
+    let mut merged = Map::new();
+    let keys = remote.keys().chain(local.keys()).chain(base.into_iter().flat_map(|base| base.keys()));
+    for key in keys {
+        if merged.contains_key(key) {
+            continue;
+        }
+        let base_value = base.and_then(|base| base.get(key));
+        let value = match (local.get(key), remote.get(key)) {
+            // Untouched here, whatever the server has now
+            (local_value, remote_value) if local_value == base_value => remote_value.cloned(),
+            // Removed here
+            (None, _) => None,
+            (Some(local_value), None) => Some(local_value.clone()),
+            (Some(local_value), Some(remote_value)) => Some(merge_value(base_value, local_value, remote_value)),
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+    merged
+}
+
+fn entry_id(value: &Value) -> Option<&str> {
+    value.get("id").and_then(Value::as_str)
+}
+
+fn has_ids(items: &[Value]) -> bool {
+    !items.is_empty() && items.iter().all(|item| entry_id(item).is_some())
+}
+
+/// Merges lists of objects by their `id`, keeping the server's order with entries added here at the end.
+fn merge_by_id(base: Option<&Vec<Value>>, local: &[Value], remote: &[Value]) -> Vec<Value> {
+    let find = |items: &[Value], id: &str| items.iter().find(|item| entry_id(item) == Some(id)).cloned();
+    let base_items = base.map(Vec::as_slice).unwrap_or_default();
+
+    let mut merged = Vec::new();
+    for remote_item in remote {
+        let id = entry_id(remote_item).unwrap_or_default();
+        let base_item = find(base_items, id);
+        match find(local, id) {
+            Some(local_item) => merged.push(merge_value(base_item.as_ref(), &local_item, remote_item)),
+            // Deleted here, unless it's new on the server
+            None if base_item.is_some() => {}
+            None => merged.push(remote_item.clone()),
+        }
+    }
+    for local_item in local {
+        let id = entry_id(local_item).unwrap_or_default();
+        let on_server = remote.iter().any(|item| entry_id(item) == Some(id));
+        let in_base = base_items.iter().any(|item| entry_id(item) == Some(id));
+        // Added here, or edited here after the server deleted it
+        if !on_server && (!in_base || find(base_items, id).as_ref() != Some(local_item)) {
+            merged.push(local_item.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebasing_keeps_edits_from_both_sides() {
+        let base = serde_json::json!({
+            "name": "Level",
+            "components": [{ "id": "a", "x": 1 }, { "id": "b", "x": 1 }, { "id": "c", "x": 1 }],
+        });
+        // Here: moved a, deleted c, added d
+        let local = serde_json::json!({
+            "name": "Level",
+            "components": [{ "id": "a", "x": 2 }, { "id": "b", "x": 1 }, { "id": "d", "x": 1 }],
+        });
+        // Elsewhere: renamed the level, moved b, added e
+        let remote = serde_json::json!({
+            "name": "Renamed",
+            "components": [{ "id": "a", "x": 1 }, { "id": "b", "x": 3 }, { "id": "c", "x": 1 }, { "id": "e", "x": 1 }],
+        });
+
+        let merged = merge_value(Some(&base), &local, &remote);
+        assert_eq!(merged, serde_json::json!({
+            "name": "Renamed",
+            "components": [{ "id": "a", "x": 2 }, { "id": "b", "x": 3 }, { "id": "e", "x": 1 }, { "id": "d", "x": 1 }],
+        }));
+    }
+
+    #[test]
+    fn rebasing_prefers_local_values_on_conflict() {
+        let base = serde_json::json!({ "components": [{ "id": "a", "x": 1 }] });
+        let local = serde_json::json!({ "components": [{ "id": "a", "x": 2 }] });
+        let remote = serde_json::json!({ "components": [{ "id": "a", "x": 3 }] });
+
+        let merged = merge_value(Some(&base), &local, &remote);
+        assert_eq!(merged, local);
+    }
+
+    #[test]
+    fn saves_wait_for_the_one_in_flight_and_only_the_newest_goes_next() {
+        let state = |version| SavedState { version: Some(version), ..Default::default() };
+
+        assert!(queue_save("queued", state(1)));
+        assert_eq!(next_save("queued").and_then(|s| s.version), Some(1));
+
+        // Both arrive while the first is in flight
+        assert!(!queue_save("queued", state(2)));
+        assert!(!queue_save("queued", state(3)));
+        assert_eq!(next_save("queued").and_then(|s| s.version), Some(3));
+        assert!(next_save("queued").is_none());
+
+        // The run is over, the next save sends itself
+        assert!(queue_save("queued", state(4)));
+        assert_eq!(abandon_saves("queued").and_then(|s| s.version), Some(4));
+        assert!(queue_save("queued", state(5)));
+    }
+}
@@ -0,0 +1,207 @@
+//! Ranges for numeric properties, shared by the property editor and tool-call handling,
+//! so a stray value can't NaN out a shader or blow up the water waves.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldSpec {
+    pub label: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    /// Shown next to the input, e.g. `METERS`
+    pub unit: Option<&'static str>,
+}
+
+pub const METERS: &str = "m";
+pub const DEGREES: &str = "°";
+pub const SECONDS: &str = "s";
+pub const FRACTION: &str = "0–1";
+pub const MULTIPLIER: &str = "×";
+pub const HOURS: &str = "h";
+
+impl FieldSpec {
+    pub const fn new(label: &'static str, min: f32, max: f32, step: f32) -> Self {
+        FieldSpec { label, min, max, step, unit: None }
+    }
+
+    pub const fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Clamps `value` into range. NaN and infinity are rejected, they would poison the GPU buffers.
+    pub fn clamp(&self, value: f32) -> Result<f32, String> {
+        if !value.is_finite() {
+            return Err(format!("{} must be a number", self.label));
+        }
+        Ok(value.clamp(self.min, self.max))
+    }
+
+    /// Checks a tool argument, noting in `notes` when it was clamped or rejected so the model hears about it.
+    /// Returns the value to apply, or None to leave the field as it was.
+    pub fn check(&self, field: &str, value: f32, notes: &mut Vec<String>) -> Option<f32> {
+        match self.clamp(value) {
+            Ok(clamped) => {
+                if clamped != value {
+                    notes.push(format!("{} {} was out of range, clamped to {}", field, value, clamped));
+                }
+                Some(clamped)
+            }
+            Err(_) => {
+                notes.push(format!("{} was not a finite number and was ignored", field));
+                None
+            }
+        }
+    }
+
+    /// `check` for each channel of a color or axis of a vector. One bad entry drops the whole value.
+    pub fn check_each<const N: usize>(&self, field: &str, values: [f32; N], notes: &mut Vec<String>) -> Option<[f32; N]> {
+        let mut checked = values;
+        for (i, value) in values.iter().enumerate() {
+            checked[i] = self.check(&format!("{}[{}]", field, i), *value, notes)?;
+        }
+        Some(checked)
+    }
+}
+
+/// Keeps a min/max pair ordered. When they cross, the one that wasn't just set moves to meet the other,
+/// `max_changed` says which that is.
+pub fn order_range(field: &str, min: &mut f32, max: &mut f32, max_changed: bool, notes: &mut Vec<String>) {
+    if *min <= *max {
+        return;
+    }
+    if max_changed {
+        *min = *max;
+        notes.push(format!("{} min was above its max, lowered to {}", field, max));
+    } else {
+        *max = *min;
+        notes.push(format!("{} max was below its min, raised to {}", field, min));
+    }
+}
+
+/// Summarises the adjustments made to tool arguments, for the tool result message.
+pub fn notes_message(notes: &[String]) -> Option<String> {
+    if notes.is_empty() {
+        None
+    } else {
+        Some(format!("Some values were adjusted: {}", notes.join("; ")))
+    }
+}
+
+pub const COLOR_CHANNEL: FieldSpec = FieldSpec::new("Color", 0.0, 1.0, 0.01).with_unit(FRACTION);
+
+// Water
+pub const WATER_RIPPLE_AMPLITUDE: FieldSpec = FieldSpec::new("Ripple Amplitude", 0.0, 5.0, 0.05).with_unit(MULTIPLIER);
+pub const WATER_RIPPLE_FREQ: FieldSpec = FieldSpec::new("Ripple Frequency", 0.0, 10.0, 0.1);
+pub const WATER_RIPPLE_SPEED: FieldSpec = FieldSpec::new("Ripple Speed", 0.0, 10.0, 0.1);
+pub const WATER_SHORELINE_FOAM_RANGE: FieldSpec = FieldSpec::new("Shoreline Foam Range", 0.0, 50.0, 0.5).with_unit(METERS);
+pub const WATER_CREST_FOAM_MIN: FieldSpec = FieldSpec::new("Crest Foam Min", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_CREST_FOAM_MAX: FieldSpec = FieldSpec::new("Crest Foam Max", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_SPARKLE_INTENSITY: FieldSpec = FieldSpec::new("Sparkle Intensity", 0.0, 10.0, 0.1);
+pub const WATER_SPARKLE_THRESHOLD: FieldSpec = FieldSpec::new("Sparkle Threshold", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_SUBSURFACE_MULTIPLIER: FieldSpec = FieldSpec::new("Subsurface", 0.0, 5.0, 0.05).with_unit(MULTIPLIER);
+pub const WATER_FRESNEL_POWER: FieldSpec = FieldSpec::new("Fresnel Power", 0.1, 10.0, 0.1);
+pub const WATER_FRESNEL_MULTIPLIER: FieldSpec = FieldSpec::new("Fresnel Multiplier", 0.0, 5.0, 0.05).with_unit(MULTIPLIER);
+pub const WATER_WAVE_AMPLITUDE: FieldSpec = FieldSpec::new("Amplitude", 0.0, 5.0, 0.05).with_unit(METERS);
+pub const WATER_WAVE_FREQUENCY: FieldSpec = FieldSpec::new("Frequency", 0.0, 5.0, 0.01);
+pub const WATER_WAVE_SPEED: FieldSpec = FieldSpec::new("Speed", 0.0, 10.0, 0.1);
+// Past 1 the crests fold over themselves
+pub const WATER_WAVE_STEEPNESS: FieldSpec = FieldSpec::new("Steepness", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_WAVE_DIRECTION: FieldSpec = FieldSpec::new("Direction", -1.0, 1.0, 0.05);
+pub const WATER_REFLECTION_STRENGTH: FieldSpec = FieldSpec::new("Reflection", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_REFRACTION_STRENGTH: FieldSpec = FieldSpec::new("Refraction", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_TRANSPARENCY: FieldSpec = FieldSpec::new("Transparency", 0.0, 1.0, 0.01).with_unit(FRACTION);
+pub const WATER_DEPTH_FADE: FieldSpec = FieldSpec::new("Depth Fade", 0.01, 1000.0, 1.0).with_unit(METERS);
+pub const WATER_PLANE_SIZE: FieldSpec = FieldSpec::new("Size", 1.0, 100_000.0, 1.0).with_unit(METERS);
+
+// Grass
+pub const GRASS_WIND_STRENGTH: FieldSpec = FieldSpec::new("Wind Strength", 0.0, 10.0, 0.1);
+pub const GRASS_WIND_SPEED: FieldSpec = FieldSpec::new("Wind Speed", 0.0, 5.0, 0.05);
+pub const GRASS_BLADE_HEIGHT: FieldSpec = FieldSpec::new("Blade Height", 0.05, 10.0, 0.05).with_unit(METERS);
+pub const GRASS_BLADE_WIDTH: FieldSpec = FieldSpec::new("Blade Width", 0.005, 0.5, 0.005).with_unit(METERS);
+/// Capped so a request for "super dense" grass can't spawn enough blades to hang the tab.
+pub const GRASS_BLADE_DENSITY: FieldSpec = FieldSpec::new("Blade Density", 1.0, 50.0, 1.0);
+/// Share of the blades dropped by the render distance, thinning out far grass.
+pub const GRASS_DENSITY_FALLOFF: FieldSpec = FieldSpec::new("Density Falloff", 0.0, 1.0, 0.05).with_unit(FRACTION);
+/// How far blade color moves towards the terrain under it, 0 keeps the grass one uniform color.
+pub const GRASS_TERRAIN_TINT: FieldSpec = FieldSpec::new("Terrain Tint", 0.0, 1.0, 0.05).with_unit(FRACTION);
+pub const GRASS_RENDER_DISTANCE: FieldSpec = FieldSpec::new("Render Distance", 10.0, 1000.0, 10.0).with_unit(METERS);
+
+// Forests
+pub const FOREST_RADIUS: FieldSpec = FieldSpec::new("Radius", 1.0, 5000.0, 1.0).with_unit(METERS);
+/// Every tree is drawn each frame, so this stays well short of "fill the whole map".
+pub const FOREST_TREE_COUNT: FieldSpec = FieldSpec::new("Tree Count", 1.0, 5000.0, 1.0);
+pub const FOREST_SIZE_VARIANCE: FieldSpec = FieldSpec::new("Size Variance", 0.0, 0.9, 0.05).with_unit(FRACTION);
+
+// Trees
+pub const TREE_TRUNK_HEIGHT: FieldSpec = FieldSpec::new("Trunk Height", 0.5, 50.0, 0.1).with_unit(METERS);
+pub const TREE_TRUNK_RADIUS: FieldSpec = FieldSpec::new("Trunk Radius", 0.02, 5.0, 0.01).with_unit(METERS);
+/// Each level multiplies the branch count, so deep trees get expensive fast.
+pub const TREE_BRANCH_LEVELS: FieldSpec = FieldSpec::new("Branch Levels", 0.0, 6.0, 1.0);
+pub const TREE_FOLIAGE_RADIUS: FieldSpec = FieldSpec::new("Foliage Radius", 0.1, 20.0, 0.1).with_unit(METERS);
+
+// Lights
+pub const LIGHT_INTENSITY: FieldSpec = FieldSpec::new("Intensity", 0.0, 100.0, 0.1);
+pub const LIGHT_RADIUS: FieldSpec = FieldSpec::new("Radius", 0.1, 1000.0, 0.5).with_unit(METERS);
+pub const DIRECTIONAL_LIGHT_INTENSITY: FieldSpec = FieldSpec::new("Intensity", 0.0, 20.0, 0.1);
+pub const AMBIENT_LIGHT_INTENSITY: FieldSpec = FieldSpec::new("Intensity", 0.0, 5.0, 0.05);
+
+// Transforms, rotations are stored in degrees like the tool calls take them
+pub const POSITION: FieldSpec = FieldSpec::new("Position", -100_000.0, 100_000.0, 0.1).with_unit(METERS);
+pub const ROTATION: FieldSpec = FieldSpec::new("Rotation", -360.0, 360.0, 1.0).with_unit(DEGREES);
+pub const SCALE: FieldSpec = FieldSpec::new("Scale", 0.001, 1000.0, 0.1).with_unit(MULTIPLIER);
+
+// Everything else the editor shows
+pub const SLOPE_ANGLE: FieldSpec = FieldSpec::new("Slope", 0.0, 90.0, 1.0).with_unit(DEGREES);
+pub const TERRAIN_HEIGHT: FieldSpec = FieldSpec::new("Height", -10_000.0, 10_000.0, 1.0).with_unit(METERS);
+pub const LANDSCAPE_TILING_SCALE: FieldSpec = FieldSpec::new("Tiling Scale", 0.01, 100.0, 0.1).with_unit(MULTIPLIER);
+pub const LANDSCAPE_BLEND_SHARPNESS: FieldSpec = FieldSpec::new("Blend Sharpness", 0.0, 10.0, 0.1);
+pub const TRIGGER_SIZE: FieldSpec = FieldSpec::new("Size", 0.0, 10_000.0, 0.1).with_unit(METERS);
+pub const AUDIO_VOLUME: FieldSpec = FieldSpec::new("Volume", 0.0, 1.0, 0.05).with_unit(FRACTION);
+pub const AUDIO_SPATIAL_RADIUS: FieldSpec = FieldSpec::new("Spatial Radius", 0.0, 10_000.0, 1.0).with_unit(METERS);
+pub const SCATTER_RADIUS: FieldSpec = FieldSpec::new("Radius", 0.0, 10_000.0, 0.5).with_unit(METERS);
+
+// Day/night cycle
+pub const DAY_LENGTH: FieldSpec = FieldSpec::new("Day Length", 10.0, 86_400.0, 10.0).with_unit(SECONDS);
+pub const TIME_OF_DAY: FieldSpec = FieldSpec::new("Time of Day", 0.0, 24.0, 0.25).with_unit(HOURS);
+
+// Weather
+pub const WEATHER_INTENSITY: FieldSpec = FieldSpec::new("Intensity", 0.0, 1.0, 0.05).with_unit(FRACTION);
+pub const WEATHER_WIND_COUPLING: FieldSpec = FieldSpec::new("Wind Coupling", 0.0, 1.0, 0.05).with_unit(FRACTION);
+
+// Clouds
+pub const CLOUD_COVERAGE: FieldSpec = FieldSpec::new("Coverage", 0.0, 1.0, 0.05).with_unit(FRACTION);
+pub const CLOUD_DENSITY: FieldSpec = FieldSpec::new("Density", 0.0, 1.0, 0.05).with_unit(FRACTION);
+pub const CLOUD_ALTITUDE: FieldSpec = FieldSpec::new("Altitude", 50.0, 5000.0, 10.0).with_unit(METERS);
+pub const CLOUD_SPEED: FieldSpec = FieldSpec::new("Speed", 0.0, 50.0, 0.5);
+
+// Ambient occlusion
+pub const AO_RADIUS: FieldSpec = FieldSpec::new("Radius", 0.05, 5.0, 0.05).with_unit(METERS);
+pub const AO_INTENSITY: FieldSpec = FieldSpec::new("Intensity", 0.0, 2.0, 0.05);
+
+// Post processing
+pub const BLOOM_INTENSITY: FieldSpec = FieldSpec::new("Bloom Intensity", 0.0, 5.0, 0.05);
+pub const BLOOM_THRESHOLD: FieldSpec = FieldSpec::new("Bloom Threshold", 0.0, 10.0, 0.05);
+pub const VIGNETTE: FieldSpec = FieldSpec::new("Vignette", 0.0, 1.0, 0.05).with_unit(FRACTION);
+
+// Color grading
+pub const EXPOSURE: FieldSpec = FieldSpec::new("Exposure", 0.0, 10.0, 0.05).with_unit(MULTIPLIER);
+pub const CONTRAST: FieldSpec = FieldSpec::new("Contrast", 0.0, 2.0, 0.05).with_unit(MULTIPLIER);
+pub const SATURATION: FieldSpec = FieldSpec::new("Saturation", 0.0, 2.0, 0.05).with_unit(MULTIPLIER);
+pub const COLOR_TEMPERATURE: FieldSpec = FieldSpec::new("Temperature", -1.0, 1.0, 0.05);
+pub const COLOR_TINT: FieldSpec = FieldSpec::new("Tint", -1.0, 1.0, 0.05);
+
+// Fog
+pub const FOG_DISTANCE: FieldSpec = FieldSpec::new("Distance", 0.0, 20_000.0, 10.0).with_unit(METERS);
+pub const FOG_CEILING: FieldSpec = FieldSpec::new("Ground Fog Ceiling", -10_000.0, 10_000.0, 1.0).with_unit(METERS);
+pub const FOG_DENSITY: FieldSpec = FieldSpec::new("Density", 0.0, 1.0, 0.01).with_unit(FRACTION);
+
+// Weapons
+pub const WEAPON_DAMAGE: FieldSpec = FieldSpec::new("Damage", 0.0, 10_000.0, 1.0);
+pub const WEAPON_RANGE: FieldSpec = FieldSpec::new("Range", 0.0, 1000.0, 0.5).with_unit(METERS);
+pub const WEAPON_COOLDOWN: FieldSpec = FieldSpec::new("Cooldown", 0.0, 60.0, 0.05).with_unit(SECONDS);
+pub const WEAPON_WEIGHT: FieldSpec = FieldSpec::new("Weight", 0.0, 1000.0, 0.1);
+
+// Player
+pub const PLAYER_HEALTH: FieldSpec = FieldSpec::new("Health", 1.0, 100_000.0, 1.0);
+pub const PLAYER_MOVE_SPEED: FieldSpec = FieldSpec::new("Move Speed", 0.0, 100.0, 0.1);
+pub const PLAYER_JUMP_HEIGHT: FieldSpec = FieldSpec::new("Jump Height", 0.0, 50.0, 0.1).with_unit(METERS);
@@ -0,0 +1,112 @@
+//! A short "what changed" summary between two copies of a project, shown in the chat after a reply's
+//! tool calls. Compares the serialized data, so new fields are picked up without listing them here.
+
+use entropy_engine::helpers::saved_data::{ComponentData, SavedState};
+use serde_json::Value;
+
+/// Top-level fields left out of the asset comparison: the save counter, and the levels covered separately.
+const IGNORED_FIELDS: &[&str] = &["version", "levels"];
+
+fn components(saved_state: &SavedState) -> &[ComponentData] {
+    saved_state.levels.as_ref()
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| level.components.as_deref())
+        .unwrap_or_default()
+}
+
+fn describe(component: &ComponentData) -> String {
+    match component.kind.as_ref() {
+        Some(kind) => format!("{:?} \"{}\"", kind, component.generic_properties.name),
+        None => format!("\"{}\"", component.generic_properties.name),
+    }
+}
+
+/// Names of the fields that differ between two serialized objects. Nested objects report their own
+/// fields instead, so a moved component lists "position" rather than its whole generic properties.
+fn changed_fields(before: &Value, after: &Value) -> Vec<String> {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut keys: Vec<&String> = before.keys().chain(after.keys().filter(|key| !before.contains_key(*key))).collect();
+    keys.sort();
+
+    let mut changed = Vec::new();
+    for key in keys {
+        let (old, new) = (before.get(key).unwrap_or(&Value::Null), after.get(key).unwrap_or(&Value::Null));
+        if old == new {
+            continue;
+        }
+        match (old.is_object(), new.is_object()) {
+            (true, true) => changed.extend(changed_fields(old, new)),
+            _ => changed.push(key.clone()),
+        }
+    }
+    changed
+}
+
+/// One line per added, removed or edited component, plus level settings and project assets that changed.
+/// Empty when nothing did.
+pub fn summarize(before: &SavedState, after: &SavedState) -> Vec<String> {
+    let mut lines = Vec::new();
+    let (old_components, new_components) = (components(before), components(after));
+
+    for component in new_components {
+        match old_components.iter().find(|c| c.id == component.id) {
+            None => lines.push(format!("Added {}", describe(component))),
+            Some(old) => {
+                let fields = changed_fields(
+                    &serde_json::to_value(old).unwrap_or_default(),
+                    &serde_json::to_value(component).unwrap_or_default(),
+                );
+                if !fields.is_empty() {
+                    lines.push(format!("Changed {}: {}", describe(component), fields.join(", ")));
+                }
+            }
+        }
+    }
+    for component in old_components.iter().filter(|old| !new_components.iter().any(|c| c.id == old.id)) {
+        lines.push(format!("Removed {}", describe(component)));
+    }
+
+    // Fog, sky, weather and the rest of the level's own settings
+    let level_value = |saved_state: &SavedState| {
+        let mut level = saved_state.levels.as_ref()
+            .and_then(|levels| levels.get(0))
+            .and_then(|level| serde_json::to_value(level).ok())
+            .unwrap_or_default();
+        if let Some(level) = level.as_object_mut() {
+            level.remove("components");
+        }
+        level
+    };
+    let level_fields = changed_fields(&level_value(before), &level_value(after));
+    if !level_fields.is_empty() {
+        lines.push(format!("Changed level settings: {}", level_fields.join(", ")));
+    }
+
+    // Models, textures and other project assets
+    let project_value = |saved_state: &SavedState| {
+        let mut value = serde_json::to_value(saved_state).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            for field in IGNORED_FIELDS {
+                object.remove(*field);
+            }
+        }
+        value
+    };
+    let before_value = project_value(before);
+    let after_value = project_value(after);
+    if let (Some(old), Some(new)) = (before_value.as_object(), after_value.as_object()) {
+        let mut asset_fields: Vec<&String> = new.keys().filter(|key| old.get(*key) != new.get(*key)).collect();
+        asset_fields.sort();
+        if !asset_fields.is_empty() {
+            lines.push(format!(
+                "Updated project {}",
+                asset_fields.iter().map(|field| field.as_str()).collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+
+    lines
+}
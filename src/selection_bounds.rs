@@ -0,0 +1,94 @@
+//! World-space box around the selected component, drawn in the editor preview so scaled or procedurally
+//! sized objects show how far they really reach. Recomputed every frame, so it follows gizmo drags and
+//! `transformObject` edits without being told about them.
+
+use entropy_engine::core::editor::Editor;
+use entropy_engine::helpers::saved_data::{ComponentData, ComponentKind};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+/// Primitives, water planes and the like are unit meshes sized by their scale.
+const UNIT_HALF_EXTENT: f32 = 0.5;
+
+fn component<'a>(editor: &'a Editor, component_id: &str) -> Option<&'a ComponentData> {
+    editor.saved_state.as_ref()?
+        .levels.as_ref()?
+        .get(0)?
+        .components.as_ref()?
+        .iter()
+        .find(|c| c.id == component_id)
+}
+
+/// Grows `bounds` to include `point`.
+fn include(bounds: &mut Option<([f32; 3], [f32; 3])>, point: [f32; 3]) {
+    let (min, max) = bounds.get_or_insert((point, point));
+    for axis in 0..3 {
+        min[axis] = min[axis].min(point[axis]);
+        max[axis] = max[axis].max(point[axis]);
+    }
+}
+
+/// Mesh-space box of the component before its transform: the loaded meshes where there are some,
+/// a unit box otherwise.
+fn local_bounds(editor: &Editor, component: &ComponentData) -> ([f32; 3], [f32; 3]) {
+    let renderer_state = editor.renderer_state.as_ref();
+    let meshes = match component.kind {
+        Some(ComponentKind::Model) => renderer_state
+            .and_then(|renderer_state| renderer_state.models.iter().find(|m| m.id == component.id))
+            .map(|model| model.local_bounds()),
+        Some(ComponentKind::Landscape) => renderer_state
+            .and_then(|renderer_state| renderer_state.landscapes.iter().find(|l| l.id == component.id))
+            .map(|landscape| landscape.local_bounds()),
+        _ => None,
+    };
+    meshes.unwrap_or(([-UNIT_HALF_EXTENT; 3], [UNIT_HALF_EXTENT; 3]))
+}
+
+/// Axis-aligned world box of a component, as (min, max).
+pub fn world_bounds(editor: &Editor, component: &ComponentData) -> Option<([f32; 3], [f32; 3])> {
+    let mut bounds = None;
+
+    // Forest trees are already placed in world space, each reaching its foliage above the trunk
+    if let Some(forest) = component.forest_properties.as_ref() {
+        let tree = component.procedural_tree_properties.clone().unwrap_or_default();
+        for transform in forest.transforms.iter() {
+            let [x, y, z] = transform.position;
+            let size = transform.scale[1];
+            let spread = tree.foliage_radius * size;
+            include(&mut bounds, [x - spread, y, z - spread]);
+            include(&mut bounds, [x + spread, y + (tree.trunk_height + tree.foliage_radius) * size, z + spread]);
+        }
+        return bounds;
+    }
+
+    let (min, max) = local_bounds(editor, component);
+    let properties = &component.generic_properties;
+    let rotation = UnitQuaternion::from_euler_angles(
+        properties.rotation[0].to_radians(), properties.rotation[1].to_radians(), properties.rotation[2].to_radians()
+    );
+    let position = Point3::from(properties.position);
+    let scale = Vector3::from(properties.scale);
+
+    // A rotated box's corners give the tightest axis-aligned box around it
+    for corner in 0..8 {
+        let local = Vector3::new(
+            if corner & 1 == 0 { min[0] } else { max[0] },
+            if corner & 2 == 0 { min[1] } else { max[1] },
+            if corner & 4 == 0 { min[2] } else { max[2] },
+        );
+        let world = position + rotation * local.component_mul(&scale);
+        include(&mut bounds, [world.x, world.y, world.z]);
+    }
+    bounds
+}
+
+/// Points the renderer's selection box at the selected component, or hides it. Called once per frame.
+pub fn apply(editor: &mut Editor) {
+    let bounds = editor.selected_component_id.as_deref()
+        .and_then(|component_id| component(editor, component_id))
+        .filter(|component| !component.hidden)
+        .and_then(|component| world_bounds(editor, component));
+
+    if let Some(renderer_state) = editor.renderer_state.as_mut() {
+        renderer_state.selection_bounds = bounds;
+    }
+}
@@ -0,0 +1,363 @@
+//! Hand editing of landscape heightmaps: the full-resolution heights kept in memory per landscape,
+//! the brushes that reshape them, and saving them back as 16-bit PNGs.
+//! Heights are 0-1 here, the same as the PNG values scaled down.
+
+use entropy_engine::core::editor::Editor;
+use entropy_engine::handlers::EntropyPosition;
+use entropy_engine::helpers::landscapes::generate_landscape_data;
+use entropy_engine::helpers::saved_data::{ComponentKind, SavedState};
+use entropy_engine::procedural_heightmaps::heightmap_generation::FalloffType;
+use gloo_net::http::Request;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::app::get_api_url;
+use crate::selection_bounds;
+
+/// World units covered by one heightmap pixel, the same along both axes so rectangular maps keep their aspect.
+pub const HEIGHTMAP_WORLD_UNITS_PER_PIXEL: f32 = 4.0;
+/// World height of a full-white heightmap pixel.
+pub const HEIGHTMAP_HEIGHT_SCALE: f32 = 150.0 * 4.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeightField {
+    pub width: usize,
+    pub height: usize,
+    pub heights: Vec<f32>,
+}
+
+impl HeightField {
+    pub fn from_image(img: &image::ImageBuffer<image::Luma<u16>, Vec<u16>>) -> Self {
+        HeightField {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            heights: img.pixels().map(|p| p.0[0] as f32 / 65535.0).collect(),
+        }
+    }
+
+    /// Encodes the heights as a 16-bit grayscale PNG, at full resolution.
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let pixels: Vec<u16> = self.heights.iter().map(|h| (h.clamp(0.0, 1.0) * 65535.0).round() as u16).collect();
+        let buffer = image::ImageBuffer::<image::Luma<u16>, Vec<u16>>::from_raw(self.width as u32, self.height as u32, pixels)
+            .ok_or_else(|| "Heightmap size doesn't match its data".to_string())?;
+
+        let mut png_bytes: Vec<u8> = Vec::new();
+        image::DynamicImage::ImageLuma16(buffer)
+            .write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png)
+            .map_err(|e| format!("Couldn't encode heightmap: {}", e))?;
+        Ok(png_bytes)
+    }
+
+    /// Bilinear height at a pixel position, clamped to the edges.
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let x = x.clamp(0.0, (self.width - 1) as f32);
+        let y = y.clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+        let at = |x: usize, y: usize| self.heights[y * self.width + x];
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * tx;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * tx;
+        top + (bottom - top) * ty
+    }
+
+    /// Pixel position under a world XZ point. Landscapes are centered on their component position.
+    pub fn world_to_pixel(&self, landscape_position: [f32; 3], world_x: f32, world_z: f32) -> [f32; 2] {
+        [
+            (world_x - landscape_position[0]) / HEIGHTMAP_WORLD_UNITS_PER_PIXEL + self.width as f32 / 2.0,
+            (world_z - landscape_position[2]) / HEIGHTMAP_WORLD_UNITS_PER_PIXEL + self.height as f32 / 2.0,
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SculptMode {
+    Raise,
+    Lower,
+    Smooth,
+    Flatten,
+}
+
+impl SculptMode {
+    pub const ALL: [SculptMode; 4] = [SculptMode::Raise, SculptMode::Lower, SculptMode::Smooth, SculptMode::Flatten];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SculptMode::Raise => "Raise",
+            SculptMode::Lower => "Lower",
+            SculptMode::Smooth => "Smooth",
+            SculptMode::Flatten => "Flatten",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.label() == label)
+    }
+}
+
+/// A brush stroke step, in heightmap pixels and 0-1 heights.
+#[derive(Clone, Copy, Debug)]
+pub struct Brush {
+    pub mode: SculptMode,
+    pub radius: f32,
+    /// Height change per step at the center for raise/lower, blend amount for smooth and flatten
+    pub strength: f32,
+    pub falloff: FalloffType,
+    /// Fraction of the radius, from the edge inwards, that fades out. The rest gets the full effect
+    pub edge: f32,
+    /// Height flatten pulls towards
+    pub target: f32,
+}
+
+/// The falloff names tool calls and the sculpt toolbar use, the same as `TerrainFeature`'s.
+pub const FALLOFF_LABELS: [&str; 3] = ["Linear", "Smooth", "Gaussian"];
+
+/// Parses a falloff name, anything unknown is `Smooth`.
+pub fn falloff_from_label(label: &str) -> FalloffType {
+    match label {
+        "Linear" => FalloffType::Linear,
+        "Gaussian" => FalloffType::Gaussian,
+        _ => FalloffType::Smooth,
+    }
+}
+
+/// How much of a brush applies at `t`, the 0-1 distance across its fading edge.
+pub fn falloff_weight(falloff: &FalloffType, t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match falloff {
+        FalloffType::Linear => 1.0 - t,
+        FalloffType::Smooth => 1.0 - t * t * (3.0 - 2.0 * t),
+        FalloffType::Gaussian => (-4.0 * t * t).exp() * (1.0 - t),
+    }
+}
+
+/// Applies one step of `brush` centered on pixel `center`. Returns whether anything changed.
+pub fn apply_brush(field: &mut HeightField, center: [f32; 2], brush: &Brush) -> bool {
+    if brush.radius <= 0.0 {
+        return false;
+    }
+
+    let inner = brush.radius * (1.0 - brush.edge.clamp(0.0, 1.0));
+    let min_x = (center[0] - brush.radius).floor().max(0.0) as usize;
+    let max_x = ((center[0] + brush.radius).ceil() as isize).min(field.width as isize - 1);
+    let min_y = (center[1] - brush.radius).floor().max(0.0) as usize;
+    let max_y = ((center[1] + brush.radius).ceil() as isize).min(field.height as isize - 1);
+    if max_x < min_x as isize || max_y < min_y as isize {
+        return false;
+    }
+    let (max_x, max_y) = (max_x as usize, max_y as usize);
+
+    // Smoothing reads the neighbours as they were before this step. Only the brush's box and a
+    // one-pixel border around it are copied, never the whole map
+    let window_x = min_x.saturating_sub(1);
+    let window_y = min_y.saturating_sub(1);
+    let window_width = (max_x + 1).min(field.width - 1) - window_x + 1;
+    let window_height = (max_y + 1).min(field.height - 1) - window_y + 1;
+    let source: Option<Vec<f32>> = (brush.mode == SculptMode::Smooth).then(|| {
+        (window_y..window_y + window_height)
+            .flat_map(|y| field.heights[y * field.width + window_x..y * field.width + window_x + window_width].iter().copied())
+            .collect()
+    });
+    let mut changed = false;
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let distance = ((x as f32 - center[0]).powi(2) + (y as f32 - center[1]).powi(2)).sqrt();
+            if distance > brush.radius {
+                continue;
+            }
+            let weight = if distance <= inner {
+                1.0
+            } else {
+                falloff_weight(&brush.falloff, (distance - inner) / (brush.radius - inner).max(f32::EPSILON))
+            };
+
+            let index = y * field.width + x;
+            let current = field.heights[index];
+            let next = match brush.mode {
+                SculptMode::Raise => current + brush.strength * weight,
+                SculptMode::Lower => current - brush.strength * weight,
+                SculptMode::Flatten => current + (brush.target - current) * (brush.strength * weight).min(1.0),
+                SculptMode::Smooth => {
+                    let source = source.as_ref().unwrap();
+                    let mut sum = 0.0;
+                    let mut count = 0.0;
+                    for ny in y.saturating_sub(1)..=(y + 1).min(field.height - 1) {
+                        for nx in x.saturating_sub(1)..=(x + 1).min(field.width - 1) {
+                            sum += source[(ny - window_y) * window_width + (nx - window_x)];
+                            count += 1.0;
+                        }
+                    }
+                    current + (sum / count - current) * (brush.strength * weight).min(1.0)
+                }
+            }
+            .clamp(0.0, 1.0);
+
+            if next != current {
+                field.heights[index] = next;
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// A landscape's component id and position, asset id and heightmap file name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LandscapeHeightmapInfo {
+    pub component_id: String,
+    pub position: [f32; 3],
+    pub asset_id: String,
+    pub file_name: String,
+}
+
+/// Finds a landscape by component id, or the first landscape in the level when no id is given.
+pub fn landscape_heightmap_info(saved_state: &SavedState, component_id: Option<&str>) -> Option<LandscapeHeightmapInfo> {
+    let components = saved_state.levels.as_ref()?.get(0)?.components.as_ref()?;
+    let component = match component_id {
+        Some(target_id) => components.iter().find(|c| c.id == target_id)?,
+        None => components.iter().find(|c| c.kind == Some(ComponentKind::Landscape))?,
+    };
+    let landscape_data = saved_state.landscapes.as_ref()?.iter().find(|l| l.id == component.asset_id)?;
+    let heightmap_file = landscape_data.heightmap.as_ref()?;
+
+    Some(LandscapeHeightmapInfo {
+        component_id: component.id.clone(),
+        position: component.generic_properties.position,
+        asset_id: component.asset_id.clone(),
+        file_name: heightmap_file.fileName.clone(),
+    })
+}
+
+/// Component id of the landscape under a canvas position: the one the cursor ray hits, or else the one
+/// whose area contains the terrain point under the cursor.
+pub fn landscape_at(editor: &Editor, x: f32, y: f32) -> Option<String> {
+    let components = editor.saved_state.as_ref()?.levels.as_ref()?.get(0)?.components.as_ref()?;
+    let mut landscapes = components.iter().filter(|c| c.kind == Some(ComponentKind::Landscape));
+
+    if let Some(component_id) = editor.pick_component(EntropyPosition { x, y }) {
+        if landscapes.clone().any(|c| c.id == component_id) {
+            return Some(component_id);
+        }
+    }
+
+    let point = editor.pick_landscape_point(EntropyPosition { x, y })?;
+    landscapes
+        .find(|c| {
+            selection_bounds::world_bounds(editor, c).is_some_and(|(min, max)| {
+                (min[0]..=max[0]).contains(&point[0]) && (min[2]..=max[2]).contains(&point[2])
+            })
+        })
+        .map(|c| c.id.clone())
+}
+
+thread_local! {
+    // Full-resolution heights by landscape asset id, filled when a heightmap is generated or first edited
+    static HEIGHT_FIELDS: RefCell<HashMap<String, HeightField>> = RefCell::new(HashMap::new());
+}
+
+pub fn store(asset_id: &str, field: HeightField) {
+    HEIGHT_FIELDS.with(|fields| fields.borrow_mut().insert(asset_id.to_string(), field));
+}
+
+pub fn get(asset_id: &str) -> Option<HeightField> {
+    HEIGHT_FIELDS.with(|fields| fields.borrow().get(asset_id).cloned())
+}
+
+/// Runs `edit` on the stored heights for `asset_id`, if they've been loaded.
+pub fn with_field_mut<R>(asset_id: &str, edit: impl FnOnce(&mut HeightField) -> R) -> Option<R> {
+    HEIGHT_FIELDS.with(|fields| fields.borrow_mut().get_mut(asset_id).map(edit))
+}
+
+/// Forgets every stored heightmap, e.g. when another project is opened.
+pub fn clear() {
+    HEIGHT_FIELDS.with(|fields| fields.borrow_mut().clear());
+}
+
+fn heightmap_url(project_path: &str, info: &LandscapeHeightmapInfo) -> String {
+    format!(
+        "{}/api/landscape-map?projectPath={}&landscapeAssetId={}&type=heightmap&filename={}",
+        get_api_url(),
+        js_sys::encode_uri_component(project_path),
+        js_sys::encode_uri_component(&info.asset_id),
+        js_sys::encode_uri_component(&info.file_name),
+    )
+}
+
+/// The landscape's heights, from memory or fetched from the project's saved heightmap.
+pub async fn load(project_path: &str, info: &LandscapeHeightmapInfo) -> Result<HeightField, String> {
+    if let Some(field) = get(&info.asset_id) {
+        return Ok(field);
+    }
+
+    let response = Request::get(&heightmap_url(project_path, info))
+        .send()
+        .await
+        .map_err(|e| format!("Couldn't fetch heightmap: {}", e))?;
+    if !response.ok() {
+        return Err(format!("Couldn't fetch heightmap: HTTP {}", response.status()));
+    }
+    let bytes = response.binary().await.map_err(|e| e.to_string())?;
+    let img = image::load_from_memory(&bytes).map_err(|e| format!("Couldn't decode heightmap: {}", e))?;
+
+    let field = HeightField::from_image(&img.to_luma16());
+    store(&info.asset_id, field.clone());
+    Ok(field)
+}
+
+/// Saves the heights over the landscape's heightmap file through `/api/save-heightmap`, resolving once the
+/// server has answered.
+pub async fn upload(project_path: &str, info: &LandscapeHeightmapInfo, field: &HeightField) -> Result<(), String> {
+    let png_bytes = field.to_png()?;
+
+    let form_data = web_sys::FormData::new().map_err(|_| "Couldn't create form data".to_string())?;
+    form_data.append_with_str("projectPath", project_path).unwrap();
+    form_data.append_with_str("landscapeAssetId", &info.asset_id).unwrap();
+    form_data.append_with_str("filename", &info.file_name).unwrap();
+
+    let uint8_array = js_sys::Uint8Array::from(&png_bytes[..]);
+    let blob_parts = js_sys::Array::new();
+    blob_parts.push(&uint8_array);
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&blob_parts).map_err(|_| "Couldn't create blob".to_string())?;
+    form_data.append_with_blob("file", &blob).unwrap();
+
+    let url = format!("{}/api/save-heightmap", get_api_url());
+    let response = Request::post(&url)
+        .body(form_data)
+        .map_err(|e| format!("Couldn't save heightmap: {}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Couldn't save heightmap: {}", e))?;
+    if !response.ok() {
+        return Err(format!("Couldn't save heightmap: HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Rebuilds the landscape's preview mesh from `field`, replacing its old mesh and leaving other landscapes alone.
+pub fn rebuild_landscape(editor: &mut Editor, field: &HeightField, info: &LandscapeHeightmapInfo) {
+    let landscape_data = generate_landscape_data(
+        field.width,
+        field.height,
+        field.heights.clone(),
+        field.width as f32 * HEIGHTMAP_WORLD_UNITS_PER_PIXEL,
+        field.height as f32 * HEIGHTMAP_WORLD_UNITS_PER_PIXEL,
+        HEIGHTMAP_HEIGHT_SCALE,
+    );
+
+    if let (Some(renderer_state), Some(gpu_resources), Some(camera)) =
+        (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref(), editor.camera.as_ref())
+    {
+        renderer_state.landscapes.retain(|l| l.id != info.component_id);
+        renderer_state.terrain_managers.retain(|t| t.landscape_id != info.component_id);
+        renderer_state.add_landscape(
+            &gpu_resources.device,
+            &gpu_resources.queue,
+            &info.component_id,
+            &landscape_data,
+            info.position,
+            camera,
+        );
+    }
+}
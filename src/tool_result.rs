@@ -0,0 +1,34 @@
+//! What a tool call reports back to the chat backend: whether it worked, a message for the model and any
+//! data it produced. Every tool handler returns one.
+
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a tool call, reported back alongside the tool call id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ToolResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl ToolResult {
+    pub fn ok() -> Self {
+        ToolResult { success: true, message: None, data: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        ToolResult { success: false, message: Some(message.into()), data: None }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
@@ -0,0 +1,85 @@
+//! Rain and snow for a level: the falling particles, a darker sky while it's coming down,
+//! and wet or snow-covered ground. Particles drift with the grass wind, the level's only wind setting.
+
+use entropy_engine::core::editor::Editor;
+use entropy_engine::helpers::saved_data::{ComponentData, ComponentKind, ProceduralSkyConfig, WeatherConfig, WeatherKind};
+
+/// Wind used when the level has no grass to take it from, the same as new grass gets.
+const DEFAULT_WIND_STRENGTH: f32 = 2.5;
+/// How much of the sky's brightness full-intensity weather takes away.
+const MAX_SKY_DARKENING: f32 = 0.6;
+/// Overcast gray the sky colors fade towards.
+const OVERCAST: [f32; 3] = [0.45, 0.47, 0.5];
+
+pub fn kind_from_label(label: &str) -> Option<WeatherKind> {
+    match label.to_lowercase().as_str() {
+        "none" => Some(WeatherKind::None),
+        "rain" => Some(WeatherKind::Rain),
+        "snow" => Some(WeatherKind::Snow),
+        _ => None,
+    }
+}
+
+/// The level's wind strength, from the grass among its components.
+pub fn wind_strength(components: &[ComponentData]) -> f32 {
+    components.iter()
+        .find(|c| c.kind == Some(ComponentKind::ProceduralGrass))
+        .and_then(|grass| grass.procedural_grass_properties.as_ref())
+        .map(|props| props.wind_strength)
+        .unwrap_or(DEFAULT_WIND_STRENGTH)
+}
+
+/// How much the weather dims the sky, 0 for clear.
+fn darkening(weather: &WeatherConfig) -> f32 {
+    if weather.kind == WeatherKind::None || !weather.darken_sky {
+        0.0
+    } else {
+        weather.intensity.clamp(0.0, 1.0) * MAX_SKY_DARKENING
+    }
+}
+
+/// `sky` as it looks under `weather`: grayer, dimmer, with a weaker sun.
+pub fn darken_sky(sky: &ProceduralSkyConfig, weather: &WeatherConfig) -> ProceduralSkyConfig {
+    let amount = darkening(weather);
+    let overcast = |color: [f32; 3]| {
+        [0, 1, 2].map(|i| (color[i] + (OVERCAST[i] - color[i]) * amount) * (1.0 - amount * 0.5))
+    };
+    ProceduralSkyConfig {
+        horizon_color: overcast(sky.horizon_color),
+        zenith_color: overcast(sky.zenith_color),
+        sun_intensity: sky.sun_intensity * (1.0 - amount),
+        ..sky.clone()
+    }
+}
+
+/// Pushes the level's weather into the preview: particles, ground and, unless the day/night cycle
+/// is driving it, the sky. Clears everything when the weather is none.
+pub fn apply(editor: &mut Editor) {
+    let Some(level) = editor.saved_state.as_ref()
+        .and_then(|saved_state| saved_state.levels.as_ref())
+        .and_then(|levels| levels.get(0))
+    else {
+        return;
+    };
+    let weather = level.weather.clone().unwrap_or_default();
+    // Sideways push on the particles along +X, the direction the grass sways
+    let wind_strength = wind_strength(level.components.as_deref().unwrap_or_default());
+    let wind = [wind_strength * weather.wind_coupling.clamp(0.0, 1.0), 0.0, 0.0];
+    let cycle_running = level.day_night_cycle.as_ref().is_some_and(|config| config.enabled);
+    let sky = darken_sky(&level.procedural_sky.clone().unwrap_or_default(), &weather);
+
+    let intensity = weather.intensity.clamp(0.0, 1.0);
+    let (wetness, snow_cover) = match weather.kind {
+        WeatherKind::Rain => (intensity, 0.0),
+        WeatherKind::Snow => (0.0, intensity),
+        WeatherKind::None => (0.0, 0.0),
+    };
+
+    if let (Some(renderer_state), Some(gpu_resources)) = (editor.renderer_state.as_mut(), editor.gpu_resources.as_ref()) {
+        renderer_state.update_weather(&gpu_resources.device, &gpu_resources.queue, &weather, wind);
+        renderer_state.update_ground_weather(&gpu_resources.queue, wetness, snow_cover);
+        if !cycle_running {
+            renderer_state.update_procedural_sky(&gpu_resources.queue, &sky);
+        }
+    }
+}